@@ -0,0 +1,185 @@
+//! The derive macro for the `KeyedValue` trait of `multi-key-map`.
+//!
+//! Use it through the `derive` feature of `multi-key-map`, which re-exports it as
+//! `multi_key_map::MultiKeys`.
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{quote, quote_spanned};
+use syn::spanned::Spanned;
+use syn::{parse_macro_input, Data, DeriveInput, Error, Fields, Index, Member, Type};
+
+/// Derives `KeyedValue` for a struct from its fields marked `#[multi_key(key)]` and
+/// `#[multi_key(keys)]`.
+#[proc_macro_derive(MultiKeys, attributes(multi_key))]
+pub fn derive_multi_keys(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(&input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+/// How a marked field gives its keys.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    /// The field is one key.
+    Key,
+    /// The field is a collection of keys.
+    Keys,
+}
+
+/// A field marked with `#[multi_key(...)]`.
+struct KeyField<'a> {
+    member: Member,
+    ty: &'a Type,
+    kind: Kind,
+}
+
+fn expand(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let Data::Struct(data) = &input.data else {
+        return Err(Error::new(
+            input.ident.span(),
+            "`MultiKeys` can only be derived for structs",
+        ));
+    };
+    let key_type = parse_key_type(input)?;
+    let fields = parse_fields(&data.fields)?;
+    if fields.is_empty() {
+        return Err(Error::new(
+            input.ident.span(),
+            "`MultiKeys` needs a field marked `#[multi_key(key)]` or `#[multi_key(keys)]`",
+        ));
+    }
+
+    let key = match &key_type {
+        Some(ty) => quote!(#ty),
+        None => quote!(__MultiKey),
+    };
+    let pushes = fields.iter().map(|field| {
+        let KeyField { member, ty, kind } = field;
+        // Span the conversions on the field type, so errors point at the field
+        match kind {
+            Kind::Key => quote_spanned! {ty.span()=>
+                keys.push(::core::convert::Into::<#key>::into(
+                    ::core::clone::Clone::clone(&self.#member),
+                ));
+            },
+            Kind::Keys => quote_spanned! {ty.span()=>
+                keys.extend(
+                    ::core::iter::IntoIterator::into_iter(
+                        ::core::clone::Clone::clone(&self.#member),
+                    )
+                    .map(::core::convert::Into::<#key>::into),
+                );
+            },
+        }
+    });
+
+    let ident = &input.ident;
+    let (_, type_generics, _) = input.generics.split_for_impl();
+    let mut generics = input.generics.clone();
+    if key_type.is_none() {
+        generics.params.push(syn::parse_quote!(__MultiKey));
+        let where_clause = generics.make_where_clause();
+        for KeyField { ty, kind, .. } in &fields {
+            where_clause.predicates.push(match kind {
+                Kind::Key => syn::parse_quote! {
+                    #ty: ::core::clone::Clone + ::core::convert::Into<__MultiKey>
+                },
+                Kind::Keys => syn::parse_quote! {
+                    #ty: ::core::clone::Clone + ::core::iter::IntoIterator<
+                        Item: ::core::convert::Into<__MultiKey>
+                    >
+                },
+            });
+        }
+    }
+    let (impl_generics, _, where_clause) = generics.split_for_impl();
+    Ok(quote! {
+        #[automatically_derived]
+        impl #impl_generics ::multi_key_map::KeyedValue<#key> for #ident #type_generics
+        #where_clause
+        {
+            fn keys(&self) -> ::std::vec::Vec<#key> {
+                let mut keys = ::std::vec::Vec::new();
+                #(#pushes)*
+                keys
+            }
+        }
+    })
+}
+
+/// Parses the key type of the struct attribute `#[multi_key(key_type = T)]`, if any.
+fn parse_key_type(input: &DeriveInput) -> syn::Result<Option<Type>> {
+    let mut key_type = None;
+    for attr in input
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("multi_key"))
+    {
+        attr.parse_nested_meta(|meta| {
+            if !meta.path.is_ident("key_type") {
+                return Err(meta.error("expected `key_type = ...`"));
+            }
+            if key_type.is_some() {
+                return Err(meta.error("the key type is already given"));
+            }
+            key_type = Some(meta.value()?.parse()?);
+            Ok(())
+        })?;
+    }
+    Ok(key_type)
+}
+
+/// Collects the marked fields, in declaration order.
+fn parse_fields(fields: &Fields) -> syn::Result<Vec<KeyField<'_>>> {
+    let mut marked = Vec::new();
+    let mut errors: Option<Error> = None;
+    for (position, field) in fields.iter().enumerate() {
+        let mut kind = None;
+        for attr in field
+            .attrs
+            .iter()
+            .filter(|attr| attr.path().is_ident("multi_key"))
+        {
+            let result = attr.parse_nested_meta(|meta| {
+                let parsed = if meta.path.is_ident("key") {
+                    Kind::Key
+                } else if meta.path.is_ident("keys") {
+                    Kind::Keys
+                } else {
+                    return Err(meta.error("expected `key` or `keys`"));
+                };
+                if kind.is_some() {
+                    return Err(meta.error("a field may be marked `key` or `keys` only once"));
+                }
+                kind = Some(parsed);
+                Ok(())
+            });
+            if let Err(error) = result {
+                match &mut errors {
+                    Some(errors) => errors.combine(error),
+                    None => errors = Some(error),
+                }
+            }
+        }
+        if let Some(kind) = kind {
+            let member = match &field.ident {
+                Some(ident) => Member::Named(ident.clone()),
+                None => Member::Unnamed(Index {
+                    index: position as u32,
+                    span: field.span(),
+                }),
+            };
+            marked.push(KeyField {
+                member,
+                ty: &field.ty,
+                kind,
+            });
+        }
+    }
+    match errors {
+        Some(errors) => Err(errors),
+        None => Ok(marked),
+    }
+}
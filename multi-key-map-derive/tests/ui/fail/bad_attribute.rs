@@ -0,0 +1,11 @@
+use multi_key_map::MultiKeys;
+
+#[derive(MultiKeys)]
+struct User {
+    #[multi_key(primary)]
+    id: String,
+    #[multi_key(key, keys)]
+    emails: Vec<String>,
+}
+
+fn main() {}
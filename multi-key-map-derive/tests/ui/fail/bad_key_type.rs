@@ -0,0 +1,12 @@
+use multi_key_map::MultiKeys;
+
+#[derive(MultiKeys)]
+#[multi_key(key_type = String)]
+struct User {
+    #[multi_key(key)]
+    id: u32,
+    #[multi_key(keys)]
+    emails: Vec<Vec<u8>>,
+}
+
+fn main() {}
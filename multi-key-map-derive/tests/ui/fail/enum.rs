@@ -0,0 +1,8 @@
+use multi_key_map::MultiKeys;
+
+#[derive(MultiKeys)]
+enum Id {
+    Name(String),
+}
+
+fn main() {}
@@ -0,0 +1,12 @@
+use multi_key_map::{MultiKeyMap, MultiKeys};
+
+#[derive(MultiKeys)]
+struct User {
+    #[multi_key(key)]
+    id: u32,
+}
+
+fn main() {
+    let mut map: MultiKeyMap<String, User> = MultiKeyMap::new();
+    map.insert_keyed(User { id: 1 }).unwrap();
+}
@@ -0,0 +1,8 @@
+use multi_key_map::MultiKeys;
+
+#[derive(MultiKeys)]
+struct User {
+    id: String,
+}
+
+fn main() {}
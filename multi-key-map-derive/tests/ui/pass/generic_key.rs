@@ -0,0 +1,21 @@
+use multi_key_map::{KeyedValue, MultiKeyMap, MultiKeys};
+
+#[derive(MultiKeys)]
+struct Host(#[multi_key(key)] &'static str, #[multi_key(keys)] Vec<&'static str>);
+
+#[derive(MultiKeys)]
+struct Wrapper<T: Clone> {
+    #[multi_key(key)]
+    id: T,
+}
+
+fn main() {
+    let host = Host("web1", vec!["10.0.0.1", "www"]);
+    assert_eq!(KeyedValue::<String>::keys(&host), ["web1", "10.0.0.1", "www"]);
+
+    let mut map: MultiKeyMap<&str, Host> = MultiKeyMap::new();
+    assert_eq!(map.insert_keyed(host), Ok(3));
+    assert_eq!(map.get(&"www").unwrap().0, "web1");
+
+    assert_eq!(KeyedValue::<u64>::keys(&Wrapper { id: 7u32 }), [7]);
+}
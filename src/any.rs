@@ -0,0 +1,142 @@
+use crate::MultiKeyMap;
+use std::any::Any;
+use std::hash::Hash;
+
+/// A type-erased value that [`MultiKeyMap`] can downcast, implemented for `dyn Any`,
+/// `dyn Any + Send` and `dyn Any + Send + Sync`.
+///
+/// Maps whose values are boxes of these types gain [`get_as`](MultiKeyMap::get_as),
+/// [`get_mut_as`](MultiKeyMap::get_mut_as) and [`remove_as`](MultiKeyMap::remove_as). This trait
+/// is sealed and cannot be implemented outside of this crate.
+pub trait AnyValue: sealed::Sealed {
+    /// Returns the value as a `&dyn Any`.
+    fn as_any(&self) -> &dyn Any;
+
+    /// Returns the value as a `&mut dyn Any`.
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+
+    /// Converts the box into a `Box<dyn Any>`.
+    fn into_any(self: Box<Self>) -> Box<dyn Any>;
+}
+
+mod sealed {
+    use std::any::Any;
+
+    pub trait Sealed {}
+
+    impl Sealed for dyn Any {}
+    impl Sealed for dyn Any + Send {}
+    impl Sealed for dyn Any + Send + Sync {}
+}
+
+macro_rules! impl_any_value {
+    ($($ty:ty),*) => {
+        $(
+            impl AnyValue for $ty {
+                fn as_any(&self) -> &dyn Any {
+                    self
+                }
+
+                fn as_any_mut(&mut self) -> &mut dyn Any {
+                    self
+                }
+
+                fn into_any(self: Box<Self>) -> Box<dyn Any> {
+                    self
+                }
+            }
+        )*
+    };
+}
+
+impl_any_value!(dyn Any, dyn Any + Send, dyn Any + Send + Sync);
+
+impl<K: Eq + Hash, A: AnyValue + ?Sized> MultiKeyMap<K, Box<A>> {
+    /// Retrieves a reference to a value by its key, downcast to `T`.
+    ///
+    /// Returns `None` if the key does not exist or the value is not a `T`.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key to lookup.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyMap;
+    /// use std::any::Any;
+    ///
+    /// let mut services: MultiKeyMap<&str, Box<dyn Any + Send>> = MultiKeyMap::new();
+    /// services.insert("port", Box::new(8080u16));
+    /// services.insert_alias(&"port", "http.port");
+    /// services.insert("name", Box::new(String::from("api")));
+    ///
+    /// assert_eq!(services.get_as::<u16>(&"http.port"), Some(&8080));
+    /// assert_eq!(services.get_as::<String>(&"name").map(String::as_str), Some("api"));
+    /// assert_eq!(services.get_as::<u32>(&"port"), None);
+    /// assert_eq!(services.get_as::<u16>(&"missing"), None);
+    /// ```
+    pub fn get_as<T: Any>(&self, key: &K) -> Option<&T> {
+        self.get(key)?.as_any().downcast_ref()
+    }
+
+    /// Retrieves a mutable reference to a value by its key, downcast to `T`.
+    ///
+    /// Returns `None` if the key does not exist or the value is not a `T`.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key to lookup.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyMap;
+    /// use std::any::Any;
+    ///
+    /// let mut services: MultiKeyMap<&str, Box<dyn Any>> = MultiKeyMap::new();
+    /// services.insert("counter", Box::new(1u64));
+    /// services.insert_alias(&"counter", "hits");
+    ///
+    /// *services.get_mut_as::<u64>(&"hits").unwrap() += 1;
+    /// assert!(services.get_mut_as::<i64>(&"hits").is_none());
+    /// assert_eq!(services.get_as::<u64>(&"counter"), Some(&2));
+    /// ```
+    pub fn get_mut_as<T: Any>(&mut self, key: &K) -> Option<&mut T> {
+        self.get_mut(key)?.as_any_mut().downcast_mut()
+    }
+
+    /// Removes a value of type `T` by its key and all its aliases.
+    ///
+    /// Returns the value if it was present and is a `T`. If the value is of another type, the
+    /// group is left untouched and `None` is returned.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key of the value to remove.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyMap;
+    /// use std::any::Any;
+    ///
+    /// let mut services: MultiKeyMap<&str, Box<dyn Any + Send + Sync>> = MultiKeyMap::new();
+    /// services.insert("port", Box::new(8080u16));
+    /// services.insert_alias(&"port", "http.port");
+    ///
+    /// // The wrong type leaves the group in place
+    /// assert!(services.remove_as::<String>(&"port").is_none());
+    /// assert_eq!(services.get_as::<u16>(&"http.port"), Some(&8080));
+    ///
+    /// assert_eq!(services.remove_as::<u16>(&"http.port"), Some(Box::new(8080)));
+    /// assert!(!services.contains_key(&"port"));
+    /// assert!(services.remove_as::<u16>(&"port").is_none());
+    /// ```
+    pub fn remove_as<T: Any>(&mut self, key: &K) -> Option<Box<T>> {
+        if !self.get(key)?.as_any().is::<T>() {
+            return None;
+        }
+        self.remove(key)?.into_any().downcast().ok()
+    }
+}
@@ -0,0 +1,538 @@
+use crate::small::GroupKeys;
+use crate::MultiKeyMap;
+use rkyv::bytecheck::{CheckBytes, Verify};
+use rkyv::collections::swiss_table::{ArchivedHashMap, HashMapResolver};
+use rkyv::munge::munge;
+use rkyv::rancor::{Fallible, Source};
+use rkyv::ser::{Allocator, Writer};
+use rkyv::vec::{ArchivedVec, VecResolver};
+use rkyv::{Archive, Archived, Deserialize, Place, Portable, Serialize};
+use std::borrow::Borrow;
+use std::error::Error;
+use std::fmt::{self, Debug, Display};
+use std::hash::Hash;
+
+/// The archived form of a [`MultiKeyMap`], which can be read in place without deserializing.
+///
+/// The groups are stored in order, each as its list of keys and its value, alongside a hash map
+/// from every key to the position of its group. Validating an archive with
+/// [`rkyv::access`] checks that every group has keys and that every key is indexed to the group
+/// listing it, so archives from untrusted sources can be read safely and deserialize to a
+/// consistent map.
+///
+/// Archives have no room for [soft aliases](MultiKeyMap::insert_soft_alias) or
+/// [disabled keys](MultiKeyMap::disable_alias), so archiving a map holding any fails with an
+/// error rather than losing them.
+///
+/// This type is only available with the `rkyv` feature.
+///
+/// # Examples
+///
+/// ```
+/// use multi_key_map::{ArchivedMultiKeyMap, MultiKeyMap};
+/// use rkyv::rancor::Error;
+/// use rkyv::string::ArchivedString;
+///
+/// let mut map = MultiKeyMap::new();
+/// map.insert("one".to_string(), 1u32);
+/// map.insert_alias(&"one".to_string(), "uno".to_string());
+/// map.insert("two".to_string(), 2);
+///
+/// type Archived = ArchivedMultiKeyMap<ArchivedString, rkyv::Archived<u32>>;
+/// let bytes = rkyv::to_bytes::<Error>(&map).unwrap();
+/// let archived = rkyv::access::<Archived, Error>(&bytes).unwrap();
+/// assert_eq!(archived.get("uno").map(|value| value.to_native()), Some(1));
+/// assert!(archived.are_aliases("one", "uno"));
+/// assert_eq!(archived.aliases("two").map(<[_]>::len), Some(1));
+/// assert_eq!(archived.get("three"), None);
+///
+/// let restored: MultiKeyMap<String, u32> = rkyv::deserialize::<_, Error>(archived).unwrap();
+/// assert_eq!(restored, map);
+///
+/// // Invalid archives are rejected
+/// let mut corrupt = bytes.to_vec();
+/// let end = corrupt.len();
+/// corrupt[end - 4..].copy_from_slice(&[0xff; 4]);
+/// assert!(rkyv::access::<Archived, Error>(&corrupt).is_err());
+///
+/// // Soft aliases and disabled keys cannot be archived
+/// map.insert_soft_alias(&"two".to_string(), "latest".to_string());
+/// assert!(rkyv::to_bytes::<Error>(&map).is_err());
+/// map.remove_soft_alias(&"latest".to_string());
+/// map.disable_alias(&"uno".to_string());
+/// assert!(rkyv::to_bytes::<Error>(&map).is_err());
+/// ```
+///
+/// Archives whose groups do not match the index of their keys are rejected as well:
+///
+/// ```
+/// use multi_key_map::ArchivedMultiKeyMap;
+/// use rkyv::rancor::Error;
+/// use rkyv::string::ArchivedString;
+/// use std::collections::HashMap;
+///
+/// // The same layout as an archived map, with any contents
+/// #[derive(rkyv::Archive, rkyv::Serialize)]
+/// struct Raw {
+///     positions: HashMap<String, u32>,
+///     keys: Vec<Vec<String>>,
+///     values: Vec<u32>,
+/// }
+///
+/// type Archived = ArchivedMultiKeyMap<ArchivedString, rkyv::Archived<u32>>;
+/// let access = |positions: &[(&str, u32)], keys: &[&[&str]]| {
+///     let raw = Raw {
+///         positions: positions.iter().map(|&(key, position)| (key.into(), position)).collect(),
+///         keys: keys.iter().map(|keys| keys.iter().map(|&key| key.into()).collect()).collect(),
+///         values: (0..keys.len() as u32).collect(),
+///     };
+///     let bytes = rkyv::to_bytes::<Error>(&raw).unwrap();
+///     rkyv::access::<Archived, Error>(&bytes).map(|_| ()).map_err(|error| error.to_string())
+/// };
+/// assert_eq!(access(&[("a", 0), ("b", 1)], &[&["a"], &["b"]]), Ok(()));
+/// assert_eq!(
+///     access(&[("a", 0)], &[&["a"], &[]]).unwrap_err(),
+///     "archived group 1 has no keys"
+/// );
+/// assert_eq!(
+///     access(&[("a", 0)], &[&["a"], &["a"]]).unwrap_err(),
+///     "archived group 1 lists a key indexed to another group"
+/// );
+/// assert_eq!(
+///     access(&[("a", 0), ("b", 0)], &[&["a"]]).unwrap_err(),
+///     "archived multi-key map indexes keys no group lists"
+/// );
+/// ```
+#[derive(Portable, CheckBytes)]
+#[bytecheck(crate = rkyv::bytecheck, verify)]
+#[rkyv(crate = rkyv)]
+#[repr(C)]
+pub struct ArchivedMultiKeyMap<K, V> {
+    /// The position of the group of every key.
+    positions: ArchivedHashMap<K, Archived<u32>>,
+    /// The keys of every group.
+    keys: ArchivedVec<ArchivedVec<K>>,
+    /// The value of every group.
+    values: ArchivedVec<V>,
+}
+
+impl<K, V> ArchivedMultiKeyMap<K, V> {
+    /// Retrieves a reference to a value by its key.
+    ///
+    /// Returns `None` if the key does not exist.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key to lookup.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::{ArchivedMultiKeyMap, MultiKeyMap};
+    /// use rkyv::rancor::Error;
+    /// use rkyv::Archived;
+    ///
+    /// let mut map = MultiKeyMap::new();
+    /// map.insert(1u64, 10u64);
+    /// map.insert_alias(&1, 2);
+    ///
+    /// let bytes = rkyv::to_bytes::<Error>(&map).unwrap();
+    /// let archived =
+    ///     rkyv::access::<ArchivedMultiKeyMap<Archived<u64>, Archived<u64>>, Error>(&bytes)
+    ///         .unwrap();
+    /// assert_eq!(archived.get(&Archived::<u64>::from_native(2)), Some(&10.into()));
+    /// assert_eq!(archived.get(&Archived::<u64>::from_native(3)), None);
+    /// ```
+    pub fn get<Q: Hash + Eq + ?Sized>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q> + Hash + Eq,
+    {
+        self.values.get(self.position(key)?)
+    }
+
+    /// Returns all keys of the group `key` belongs to, in no particular order.
+    ///
+    /// Returns `None` if the key does not exist.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - A key of the group.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::{ArchivedMultiKeyMap, MultiKeyMap};
+    /// use rkyv::rancor::Error;
+    /// use rkyv::string::ArchivedString;
+    ///
+    /// let mut map = MultiKeyMap::new();
+    /// map.insert("a".to_string(), ());
+    /// map.insert_alias(&"a".to_string(), "b".to_string());
+    ///
+    /// let bytes = rkyv::to_bytes::<Error>(&map).unwrap();
+    /// let archived = rkyv::access::<ArchivedMultiKeyMap<ArchivedString, ()>, Error>(&bytes)
+    ///     .unwrap();
+    /// let aliases = archived.aliases("b").unwrap();
+    /// let mut aliases: Vec<&str> = aliases.iter().map(|key| key.as_str()).collect();
+    /// aliases.sort();
+    /// assert_eq!(aliases, ["a", "b"]);
+    /// assert!(archived.aliases("c").is_none());
+    /// ```
+    pub fn aliases<Q: Hash + Eq + ?Sized>(&self, key: &Q) -> Option<&[K]>
+    where
+        K: Borrow<Q> + Hash + Eq,
+    {
+        self.keys
+            .get(self.position(key)?)
+            .map(|keys| keys.as_slice())
+    }
+
+    /// Returns `true` if both keys exist and belong to the same group.
+    ///
+    /// # Arguments
+    ///
+    /// * `key1` - The first key.
+    /// * `key2` - The second key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::{ArchivedMultiKeyMap, MultiKeyMap};
+    /// use rkyv::rancor::Error;
+    /// use rkyv::string::ArchivedString;
+    ///
+    /// let mut map = MultiKeyMap::new();
+    /// map.insert("a".to_string(), ());
+    /// map.insert_alias(&"a".to_string(), "b".to_string());
+    /// map.insert("c".to_string(), ());
+    ///
+    /// let bytes = rkyv::to_bytes::<Error>(&map).unwrap();
+    /// let archived = rkyv::access::<ArchivedMultiKeyMap<ArchivedString, ()>, Error>(&bytes)
+    ///     .unwrap();
+    /// assert!(archived.are_aliases("a", "b"));
+    /// assert!(!archived.are_aliases("a", "c"));
+    /// assert!(!archived.are_aliases("a", "d"));
+    /// ```
+    pub fn are_aliases<Q: Hash + Eq + ?Sized>(&self, key1: &Q, key2: &Q) -> bool
+    where
+        K: Borrow<Q> + Hash + Eq,
+    {
+        match (self.position(key1), self.position(key2)) {
+            (Some(position1), Some(position2)) => position1 == position2,
+            _ => false,
+        }
+    }
+
+    /// Returns `true` if the map contains a value for the specified key.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key to check.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::{ArchivedMultiKeyMap, MultiKeyMap};
+    /// use rkyv::rancor::Error;
+    /// use rkyv::string::ArchivedString;
+    ///
+    /// let mut map = MultiKeyMap::new();
+    /// map.insert("a".to_string(), ());
+    ///
+    /// let bytes = rkyv::to_bytes::<Error>(&map).unwrap();
+    /// let archived = rkyv::access::<ArchivedMultiKeyMap<ArchivedString, ()>, Error>(&bytes)
+    ///     .unwrap();
+    /// assert!(archived.contains_key("a"));
+    /// assert!(!archived.contains_key("b"));
+    /// ```
+    pub fn contains_key<Q: Hash + Eq + ?Sized>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q> + Hash + Eq,
+    {
+        self.positions.contains_key(key)
+    }
+
+    /// Returns an iterator over the keys and value of every group.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::{ArchivedMultiKeyMap, MultiKeyMap};
+    /// use rkyv::rancor::Error;
+    ///
+    /// let mut map = MultiKeyMap::new();
+    /// map.insert(1u8, 1u8);
+    /// map.insert_alias(&1, 2);
+    /// map.insert(3, 3);
+    ///
+    /// let bytes = rkyv::to_bytes::<Error>(&map).unwrap();
+    /// let archived = rkyv::access::<ArchivedMultiKeyMap<u8, u8>, Error>(&bytes).unwrap();
+    /// let sizes: Vec<(usize, u8)> =
+    ///     archived.groups().map(|(keys, value)| (keys.len(), *value)).collect();
+    /// assert_eq!(sizes, [(2, 1), (1, 3)]);
+    /// ```
+    pub fn groups(&self) -> impl Iterator<Item = (&[K], &V)> {
+        self.keys
+            .iter()
+            .map(|keys| keys.as_slice())
+            .zip(self.values.iter())
+    }
+
+    /// Returns the number of groups in the map.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::{ArchivedMultiKeyMap, MultiKeyMap};
+    /// use rkyv::rancor::Error;
+    ///
+    /// let mut map = MultiKeyMap::new();
+    /// map.insert(1u8, ());
+    /// map.insert_alias(&1, 2);
+    ///
+    /// let bytes = rkyv::to_bytes::<Error>(&map).unwrap();
+    /// let archived = rkyv::access::<ArchivedMultiKeyMap<u8, ()>, Error>(&bytes).unwrap();
+    /// assert_eq!(archived.len(), 1);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Returns the number of keys in the map, counting every alias.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::{ArchivedMultiKeyMap, MultiKeyMap};
+    /// use rkyv::rancor::Error;
+    ///
+    /// let mut map = MultiKeyMap::new();
+    /// map.insert(1u8, ());
+    /// map.insert_alias(&1, 2);
+    ///
+    /// let bytes = rkyv::to_bytes::<Error>(&map).unwrap();
+    /// let archived = rkyv::access::<ArchivedMultiKeyMap<u8, ()>, Error>(&bytes).unwrap();
+    /// assert_eq!(archived.key_count(), 2);
+    /// ```
+    pub fn key_count(&self) -> usize {
+        self.positions.len()
+    }
+
+    /// Returns `true` if the map contains no values.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::{ArchivedMultiKeyMap, MultiKeyMap};
+    /// use rkyv::rancor::Error;
+    ///
+    /// let map: MultiKeyMap<u8, ()> = MultiKeyMap::new();
+    /// let bytes = rkyv::to_bytes::<Error>(&map).unwrap();
+    /// let archived = rkyv::access::<ArchivedMultiKeyMap<u8, ()>, Error>(&bytes).unwrap();
+    /// assert!(archived.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    fn position<Q: Hash + Eq + ?Sized>(&self, key: &Q) -> Option<usize>
+    where
+        K: Borrow<Q> + Hash + Eq,
+    {
+        self.positions
+            .get(key)
+            .map(|position| position.to_native() as usize)
+    }
+}
+
+impl<K: Debug, V: Debug> Debug for ArchivedMultiKeyMap<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.groups()).finish()
+    }
+}
+
+/// The error of a map whose groups cannot be archived, or of an archive whose groups do not
+/// line up.
+#[derive(Debug)]
+struct InvalidGroups(String);
+
+impl Display for InvalidGroups {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl Error for InvalidGroups {}
+
+/// Fails with an [`InvalidGroups`] error holding `message`.
+fn invalid<E: Source>(message: impl Into<String>) -> Result<(), E> {
+    Err(E::new(InvalidGroups(message.into())))
+}
+
+unsafe impl<K, V, C> Verify<C> for ArchivedMultiKeyMap<K, V>
+where
+    K: Hash + Eq,
+    C: Fallible + ?Sized,
+    C::Error: Source,
+{
+    fn verify(&self, _context: &mut C) -> Result<(), C::Error> {
+        let len = self.values.len();
+        if self.keys.len() != len
+            || self
+                .positions
+                .values()
+                .any(|position| position.to_native() as usize >= len)
+        {
+            return invalid("archived multi-key map refers to a group out of bounds");
+        }
+        let mut listed = 0;
+        for (position, keys) in self.keys.iter().enumerate() {
+            if keys.is_empty() {
+                return invalid(format!("archived group {position} has no keys"));
+            }
+            for key in keys.iter() {
+                if self.position(key) != Some(position) {
+                    return invalid(format!(
+                        "archived group {position} lists a key indexed to another group"
+                    ));
+                }
+            }
+            listed += keys.len();
+        }
+        if listed != self.positions.len() {
+            return invalid("archived multi-key map indexes keys no group lists");
+        }
+        Ok(())
+    }
+}
+
+/// The resolver of a [`MultiKeyMap`] archived as an [`ArchivedMultiKeyMap`].
+pub struct MultiKeyMapResolver {
+    positions: HashMapResolver,
+    keys: VecResolver,
+    values: VecResolver,
+}
+
+impl<K, V> Archive for MultiKeyMap<K, V>
+where
+    K: Archive,
+    K::Archived: Hash + Eq,
+    V: Archive,
+{
+    type Archived = ArchivedMultiKeyMap<K::Archived, V::Archived>;
+    type Resolver = MultiKeyMapResolver;
+
+    fn resolve(&self, resolver: Self::Resolver, out: Place<Self::Archived>) {
+        munge!(let ArchivedMultiKeyMap { positions, keys, values } = out);
+        ArchivedHashMap::resolve_from_len(
+            self.key_index.len(),
+            (7, 8),
+            resolver.positions,
+            positions,
+        );
+        ArchivedVec::resolve_from_len(self.len, resolver.keys, keys);
+        ArchivedVec::resolve_from_len(self.len, resolver.values, values);
+    }
+}
+
+impl<K, V, S> Serialize<S> for MultiKeyMap<K, V>
+where
+    K: Serialize<S> + Hash + Eq,
+    K::Archived: Hash + Eq,
+    V: Serialize<S>,
+    S: Fallible + Writer + Allocator + ?Sized,
+    S::Error: Source,
+{
+    fn serialize(&self, serializer: &mut S) -> Result<Self::Resolver, S::Error> {
+        if let Some(error) = self.serialization_error() {
+            invalid(error)?;
+        }
+        if u32::try_from(self.len).is_err() {
+            invalid("too many groups to archive a multi-key map")?;
+        }
+        let groups = || self.slots.iter().filter(|slot| slot.value.is_some());
+        let positions = groups()
+            .enumerate()
+            .flat_map(|(position, slot)| slot.keys.iter().map(move |key| (key, position as u32)));
+        let positions = ArchivedHashMap::<K::Archived, Archived<u32>>::serialize_from_iter::<
+            _,
+            _,
+            _,
+            K,
+            u32,
+            _,
+        >(
+            ExactLen::new(positions, self.key_index.len()),
+            (7, 8),
+            serializer,
+        )?;
+        let keys =
+            ArchivedVec::<ArchivedVec<K::Archived>>::serialize_from_iter::<GroupKeys<K>, _, _>(
+                ExactLen::new(groups().map(|slot| &slot.keys), self.len),
+                serializer,
+            )?;
+        let values = ArchivedVec::<V::Archived>::serialize_from_iter::<V, _, _>(
+            ExactLen::new(groups().filter_map(|slot| slot.value.as_ref()), self.len),
+            serializer,
+        )?;
+        Ok(MultiKeyMapResolver {
+            positions,
+            keys,
+            values,
+        })
+    }
+}
+
+impl<K, V, D> Deserialize<MultiKeyMap<K, V>, D> for ArchivedMultiKeyMap<K::Archived, V::Archived>
+where
+    K: Archive + Eq + Hash,
+    K::Archived: Deserialize<K, D>,
+    V: Archive,
+    V::Archived: Deserialize<V, D>,
+    D: Fallible + ?Sized,
+    D::Error: Source,
+{
+    fn deserialize(&self, deserializer: &mut D) -> Result<MultiKeyMap<K, V>, D::Error> {
+        let mut map = MultiKeyMap::new();
+        for (keys, value) in self.groups() {
+            let keys = keys
+                .iter()
+                .map(|key| key.deserialize(deserializer))
+                .collect::<Result<Vec<K>, _>>()?;
+            let value = value.deserialize(deserializer)?;
+            if let Err(message) = map.push_checked_group(keys, value) {
+                invalid(message)?;
+            }
+        }
+        Ok(map)
+    }
+}
+
+/// An iterator of a known length, for serializers that require an [`ExactSizeIterator`].
+#[derive(Clone)]
+struct ExactLen<I> {
+    iter: I,
+    len: usize,
+}
+
+impl<I> ExactLen<I> {
+    fn new(iter: I, len: usize) -> Self {
+        ExactLen { iter, len }
+    }
+}
+
+impl<I: Iterator> Iterator for ExactLen<I> {
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let item = self.iter.next()?;
+        self.len -= 1;
+        Some(item)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<I: Iterator> ExactSizeIterator for ExactLen<I> {}
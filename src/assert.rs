@@ -0,0 +1,163 @@
+use crate::MultiKeyMap;
+use std::collections::HashSet;
+use std::fmt::{Debug, Write};
+use std::hash::Hash;
+
+impl<K: Eq + Hash + Debug, V: PartialEq + Debug> MultiKeyMap<K, V> {
+    /// Asserts that the map holds exactly the `expected` groups, each given as its keys, in any
+    /// order, and its value.
+    ///
+    /// On failure this panics with a diff of the groups rather than the whole map: the expected
+    /// groups none of whose keys exist, the groups of the map sharing no key with an expected
+    /// group, the groups whose keys differ, with the missing and extra keys, and the groups
+    /// whose values differ. An expected group is compared with the group of its first key that
+    /// exists. Every list is sorted by the [`Debug`] representation of the keys, so the message
+    /// is the same from run to run. [`assert_multi_key_map!`](crate::assert_multi_key_map) is
+    /// shorthand for this method.
+    ///
+    /// # Arguments
+    ///
+    /// * `expected` - The keys and value of every group the map should hold.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the groups of the map are not exactly the expected ones.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyMap;
+    /// use std::panic::{self, AssertUnwindSafe};
+    ///
+    /// let mut map = MultiKeyMap::new();
+    /// map.insert_with_keys(["a", "b"], 1).unwrap();
+    /// map.insert_with_keys(["c", "d", "e"], 2).unwrap();
+    /// map.insert("f", 3);
+    /// map.insert("g", 4);
+    /// map.assert_groups(&[(&["b", "a"], 1), (&["g"], 4), (&["c", "d", "e"], 2), (&["f"], 3)]);
+    ///
+    /// panic::set_hook(Box::new(|_| {}));
+    /// let failure = |expected: &[(&[&'static str], i32)]| {
+    ///     let result = panic::catch_unwind(AssertUnwindSafe(|| map.assert_groups(expected)));
+    ///     *result.unwrap_err().downcast::<String>().unwrap()
+    /// };
+    ///
+    /// let expected: &[(&[&str], i32)] =
+    ///     &[(&["a", "b"], 10), (&["e", "d", "x"], 2), (&["f"], 3), (&["y", "z"], 5)];
+    /// assert_eq!(
+    ///     failure(expected),
+    ///     "the map does not hold the expected groups
+    /// missing groups:
+    ///     [\"y\", \"z\"] => 5
+    /// unexpected groups:
+    ///     [\"g\"] => 4
+    /// groups with other keys:
+    ///     [\"d\", \"e\", \"x\"]: missing [\"x\"], extra [\"c\"]
+    /// groups with other values:
+    ///     [\"a\", \"b\"]: expected 10, found 1"
+    /// );
+    ///
+    /// // An expected group split across two groups of the map is compared with the first
+    /// let expected: &[(&[&str], i32)] = &[(&["a", "b", "f"], 1), (&["c", "d", "e"], 2)];
+    /// assert_eq!(
+    ///     failure(expected),
+    ///     "the map does not hold the expected groups
+    /// unexpected groups:
+    ///     [\"f\"] => 3
+    ///     [\"g\"] => 4
+    /// groups with other keys:
+    ///     [\"a\", \"b\", \"f\"]: missing [\"f\"], extra []"
+    /// );
+    /// ```
+    #[track_caller]
+    pub fn assert_groups(&self, expected: &[(&[K], V)]) {
+        let mut missing = Vec::new();
+        let mut other_keys = Vec::new();
+        let mut other_values = Vec::new();
+        let mut compared = HashSet::new();
+        for &(keys, ref value) in expected {
+            let name = sorted_list(keys);
+            let Some(index) = keys.iter().find_map(|key| self.index_of(key)) else {
+                missing.push(format!("{name} => {value:?}"));
+                continue;
+            };
+            compared.insert(index);
+            let slot = &self.slots[index];
+            let absent = keys.iter().filter(|key| self.index_of(key) != Some(index));
+            let extra = slot
+                .keys
+                .iter()
+                .filter(|key| !keys.iter().any(|other| self.same_key(key, other)));
+            let (absent, extra) = (sorted_list(absent), sorted_list(extra));
+            if absent != "[]" || extra != "[]" {
+                other_keys.push(format!("{name}: missing {absent}, extra {extra}"));
+            }
+            let found = self.value(index);
+            if found != value {
+                other_values.push(format!("{name}: expected {value:?}, found {found:?}"));
+            }
+        }
+        let unexpected = self
+            .occupied()
+            .filter(|(index, _)| !compared.contains(index))
+            .map(|(index, value)| format!("{} => {value:?}", sorted_list(&self.slots[index].keys)))
+            .collect();
+
+        let sections = [
+            ("missing groups", missing),
+            ("unexpected groups", unexpected),
+            ("groups with other keys", other_keys),
+            ("groups with other values", other_values),
+        ];
+        if sections.iter().all(|(_, lines)| lines.is_empty()) {
+            return;
+        }
+        let mut message = String::from("the map does not hold the expected groups");
+        for (title, mut lines) in sections {
+            if lines.is_empty() {
+                continue;
+            }
+            lines.sort();
+            write!(message, "\n{title}:").expect("writing to a string succeeds");
+            for line in lines {
+                write!(message, "\n    {line}").expect("writing to a string succeeds");
+            }
+        }
+        panic!("{message}");
+    }
+}
+
+/// Formats `keys` as a list sorted by their [`Debug`] representation.
+fn sorted_list<'a, K: Debug + 'a>(keys: impl IntoIterator<Item = &'a K>) -> String {
+    let mut keys: Vec<String> = keys.into_iter().map(|key| format!("{key:?}")).collect();
+    keys.sort();
+    format!("[{}]", keys.join(", "))
+}
+
+/// Asserts that a [`MultiKeyMap`](crate::MultiKeyMap) holds exactly the listed groups, printing
+/// a diff of the groups on failure.
+///
+/// Each group lists its keys in brackets, in any order, followed by `=>` and its value. This
+/// expands to a call to [`assert_groups`](crate::MultiKeyMap::assert_groups), which describes
+/// the failure message.
+///
+/// # Examples
+///
+/// ```
+/// use multi_key_map::{assert_multi_key_map, MultiKeyMap};
+///
+/// let mut map = MultiKeyMap::new();
+/// map.insert("a", 1);
+/// map.insert_alias(&"a", "b");
+/// map.insert("c", 2);
+/// assert_multi_key_map!(map, [["b", "a"] => 1, ["c"] => 2]);
+///
+/// map.clear();
+/// assert_multi_key_map!(map, []);
+/// ```
+#[macro_export]
+macro_rules! assert_multi_key_map {
+    ($map:expr, [$([$($key:expr),* $(,)?] => $value:expr),* $(,)?] $(,)?) => {
+        $map.assert_groups(&[$((&[$($key),*][..], $value)),*])
+    };
+}
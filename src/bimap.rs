@@ -0,0 +1,533 @@
+use crate::{GroupId, InsertOrAlias, MultiKeyMap};
+use std::collections::HashMap;
+use std::fmt::{self, Debug};
+use std::hash::Hash;
+
+/// A `BiMultiKeyMap` is a [`MultiKeyMap`] that also indexes its groups by value, so finding the
+/// keys of a value takes `O(1)` time instead of a scan.
+///
+/// Groups behave as in a [`MultiKeyMap`], and several groups may hold equal values. Every value
+/// is cloned once into the index. As the index must follow every change of a value, values
+/// cannot be borrowed mutably: they are changed through [`replace`](Self::replace) or
+/// [`update`](Self::update), which update the index too.
+///
+/// # Examples
+///
+/// ```
+/// use multi_key_map::{BiMultiKeyMap, InsertOrAlias};
+///
+/// let mut colors = BiMultiKeyMap::new();
+/// colors.insert("red", 0xff0000);
+/// colors.insert_alias(&"red", "crimson");
+/// colors.insert("lime", 0x00ff00);
+/// assert_eq!(colors.insert_or_alias("rouge", 0xff0000), InsertOrAlias::Aliased(3));
+///
+/// assert!(colors.contains_value(&0x00ff00));
+/// let mut reds: Vec<_> = colors.keys_of_value(&0xff0000).collect();
+/// reds.sort();
+/// assert_eq!(reds, [&"crimson", &"red", &"rouge"]);
+///
+/// colors.replace(&"lime", 0x32cd32);
+/// assert!(!colors.contains_value(&0x00ff00));
+/// assert_eq!(colors.keys_of_value(&0x32cd32).collect::<Vec<_>>(), [&"lime"]);
+/// ```
+pub struct BiMultiKeyMap<K, V> {
+    map: MultiKeyMap<K, V>,
+    /// The groups holding every value, in insertion order. Never holds an empty list.
+    groups: HashMap<V, Vec<GroupId>>,
+}
+
+impl<K: Eq + Hash, V: Eq + Hash + Clone> BiMultiKeyMap<K, V> {
+    /// Creates an empty `BiMultiKeyMap`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::BiMultiKeyMap;
+    ///
+    /// let map: BiMultiKeyMap<&str, u32> = BiMultiKeyMap::new();
+    /// assert!(map.is_empty());
+    /// ```
+    pub fn new() -> Self {
+        BiMultiKeyMap::from(MultiKeyMap::new())
+    }
+
+    /// Retrieves a reference to a value by its key.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key to lookup.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::BiMultiKeyMap;
+    ///
+    /// let mut map = BiMultiKeyMap::new();
+    /// map.insert("key1", "value1");
+    /// assert_eq!(map.get(&"key1"), Some(&"value1"));
+    /// assert_eq!(map.get(&"key2"), None);
+    /// ```
+    pub fn get(&self, key: &K) -> Option<&V> {
+        self.map.get(key)
+    }
+
+    /// Returns `true` if the map contains a value for the specified key.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key to check.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::BiMultiKeyMap;
+    ///
+    /// let mut map = BiMultiKeyMap::new();
+    /// map.insert("key1", "value1");
+    /// assert!(map.contains_key(&"key1"));
+    /// assert!(!map.contains_key(&"key2"));
+    /// ```
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.map.contains_key(key)
+    }
+
+    /// Returns `true` if a group holds a value equal to `value`.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The value to look for.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::BiMultiKeyMap;
+    ///
+    /// let mut map = BiMultiKeyMap::new();
+    /// map.insert("key1", "value1");
+    /// assert!(map.contains_value(&"value1"));
+    /// assert!(!map.contains_value(&"value2"));
+    /// ```
+    pub fn contains_value(&self, value: &V) -> bool {
+        self.groups.contains_key(value)
+    }
+
+    /// Returns the keys of every group holding a value equal to `value`.
+    ///
+    /// The groups are visited in the order they got their value, and the keys of each group in
+    /// their order in the group.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The value to look for.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::BiMultiKeyMap;
+    ///
+    /// let mut map = BiMultiKeyMap::new();
+    /// map.insert("a", 1);
+    /// map.insert_alias(&"a", "b");
+    /// map.insert("c", 2);
+    /// map.insert("d", 1);
+    /// assert_eq!(map.keys_of_value(&1).collect::<Vec<_>>(), [&"a", &"b", &"d"]);
+    /// assert_eq!(map.keys_of_value(&3).count(), 0);
+    /// ```
+    pub fn keys_of_value(&self, value: &V) -> impl Iterator<Item = &K> + '_ {
+        self.groups
+            .get(value)
+            .into_iter()
+            .flatten()
+            .flat_map(|&id| &self.map.slots[id.index].keys)
+    }
+
+    /// Inserts a value with the given key, as [`MultiKeyMap::insert`] does.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key to insert.
+    /// * `value` - The value to insert.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::BiMultiKeyMap;
+    ///
+    /// let mut map = BiMultiKeyMap::new();
+    /// map.insert("key1", "value1");
+    /// map.insert("key1", "value2");
+    /// assert!(!map.contains_value(&"value1"));
+    /// assert_eq!(map.get(&"key1"), Some(&"value2"));
+    /// ```
+    pub fn insert(&mut self, key: K, value: V) {
+        self.forget_if_last(&key);
+        let indexed = value.clone();
+        let index = self.map.insert_group(vec![key], value);
+        self.map.check_mutation();
+        self.groups
+            .entry(indexed)
+            .or_default()
+            .push(self.map.id_at(index));
+    }
+
+    /// Binds `key` to the first group holding a value equal to `value`, or inserts a new group
+    /// if none does, as [`MultiKeyMap::insert_or_alias`] does, in `O(1)` time.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key to bind.
+    /// * `value` - The value to find or insert.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::{BiMultiKeyMap, InsertOrAlias};
+    ///
+    /// let mut map = BiMultiKeyMap::new();
+    /// assert_eq!(map.insert_or_alias("a", "apple"), InsertOrAlias::Inserted);
+    /// assert_eq!(map.insert_or_alias("b", "banana"), InsertOrAlias::Inserted);
+    /// assert_eq!(map.insert_or_alias("c", "apple"), InsertOrAlias::Aliased(2));
+    /// assert_eq!(map.len(), 2);
+    /// ```
+    pub fn insert_or_alias(&mut self, key: K, value: V) -> InsertOrAlias {
+        let existing = self.groups.get(&value).and_then(|ids| {
+            ids.iter()
+                .copied()
+                .find(|id| self.map.has_room(id.index, 1))
+        });
+        let Some(id) = existing else {
+            self.insert(key, value);
+            return InsertOrAlias::Inserted;
+        };
+        if self.map.group_id(&key) != Some(id) {
+            self.forget_if_last(&key);
+        }
+        let count = self
+            .map
+            .insert_alias_by_id(id, key)
+            .expect("indexed group exists and has room");
+        InsertOrAlias::Aliased(count)
+    }
+
+    /// Adds a new alias key for the value of `key`, as [`MultiKeyMap::insert_alias`] does.
+    ///
+    /// Returns the reference count if the alias is successfully added.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The original key.
+    /// * `alias` - The alias key to add.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::BiMultiKeyMap;
+    ///
+    /// let mut map = BiMultiKeyMap::new();
+    /// map.insert("key1", "value1");
+    /// map.insert("key2", "value2");
+    /// assert_eq!(map.insert_alias(&"key1", "key2"), Some(2));
+    /// assert!(!map.contains_value(&"value2"));
+    /// assert_eq!(map.insert_alias(&"key3", "alias3"), None);
+    /// ```
+    pub fn insert_alias(&mut self, key: &K, alias: K) -> Option<usize> {
+        let index = self.map.index_of(key)?;
+        // Only forget the group of `alias` if it is about to lose its last key
+        if self.map.index_of(&alias) != Some(index) && self.map.has_room(index, 1) {
+            self.forget_if_last(&alias);
+        }
+        self.map.insert_alias(key, alias)
+    }
+
+    /// Removes an alias, removing its group if it was the last key, as
+    /// [`MultiKeyMap::remove_alias`] does.
+    ///
+    /// Returns the number of keys left in the group, or `None` if the alias does not exist.
+    ///
+    /// # Arguments
+    ///
+    /// * `alias` - The key to remove.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::BiMultiKeyMap;
+    ///
+    /// let mut map = BiMultiKeyMap::new();
+    /// map.insert("key1", "value1");
+    /// map.insert_alias(&"key1", "alias1");
+    /// assert_eq!(map.remove_alias(&"key1"), Some(1));
+    /// assert!(map.contains_value(&"value1"));
+    /// assert_eq!(map.remove_alias(&"alias1"), Some(0));
+    /// assert!(!map.contains_value(&"value1"));
+    /// ```
+    pub fn remove_alias(&mut self, alias: &K) -> Option<usize> {
+        self.forget_if_last(alias);
+        self.map.remove_alias(alias)
+    }
+
+    /// Removes the value of `key` and all of its keys, returning the value if the key existed.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - Any key of the value to remove.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::BiMultiKeyMap;
+    ///
+    /// let mut map = BiMultiKeyMap::new();
+    /// map.insert("key1", "value1");
+    /// assert_eq!(map.remove(&"key1"), Some("value1"));
+    /// assert!(!map.contains_value(&"value1"));
+    /// assert_eq!(map.remove(&"key1"), None);
+    /// ```
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        let id = self.map.group_id(key)?;
+        let value = self.map.remove(key)?;
+        forget(&mut self.groups, &value, id);
+        Some(value)
+    }
+
+    /// Replaces the value of `key`, returning the previous value, or `None` if the key does not
+    /// exist.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - Any key of the group.
+    /// * `value` - The new value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::BiMultiKeyMap;
+    ///
+    /// let mut map = BiMultiKeyMap::new();
+    /// map.insert("key1", "value1");
+    /// assert_eq!(map.replace(&"key1", "value2"), Some("value1"));
+    /// assert!(!map.contains_value(&"value1"));
+    /// assert!(map.contains_value(&"value2"));
+    /// assert_eq!(map.replace(&"key2", "value3"), None);
+    /// ```
+    pub fn replace(&mut self, key: &K, value: V) -> Option<V> {
+        let id = self.map.group_id(key)?;
+        let indexed = value.clone();
+        let previous = self.map.replace(key, value)?;
+        forget(&mut self.groups, &previous, id);
+        self.groups.entry(indexed).or_default().push(id);
+        Some(previous)
+    }
+
+    /// Applies `f` to the value of `key` and updates the index, returning the result of `f`, or
+    /// `None` if the key does not exist.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - Any key of the group.
+    /// * `f` - The function to apply to the value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::BiMultiKeyMap;
+    ///
+    /// let mut map = BiMultiKeyMap::new();
+    /// map.insert("counter", 1);
+    /// assert_eq!(map.update(&"counter", |value| { *value += 1; *value }), Some(2));
+    /// assert!(map.contains_value(&2));
+    /// assert!(!map.contains_value(&1));
+    /// ```
+    pub fn update<T>(&mut self, key: &K, f: impl FnOnce(&mut V) -> T) -> Option<T> {
+        let id = self.map.group_id(key)?;
+        let index = id.index;
+        let previous = self.map.slots[index]
+            .value
+            .clone()
+            .expect("found group is occupied");
+        forget(&mut self.groups, &previous, id);
+        let result = f(self.map.value_mut(index));
+        let value = self.map.slots[index]
+            .value
+            .clone()
+            .expect("found group is occupied");
+        self.groups.entry(value).or_default().push(id);
+        Some(result)
+    }
+
+    /// Returns the number of groups in the map.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::BiMultiKeyMap;
+    ///
+    /// let mut map = BiMultiKeyMap::new();
+    /// map.insert("key1", "value1");
+    /// map.insert_alias(&"key1", "alias1");
+    /// assert_eq!(map.len(), 1);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    /// Returns `true` if the map contains no values.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::BiMultiKeyMap;
+    ///
+    /// let map: BiMultiKeyMap<&str, &str> = BiMultiKeyMap::new();
+    /// assert!(map.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    /// Returns a reference to the underlying map.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::BiMultiKeyMap;
+    ///
+    /// let mut map = BiMultiKeyMap::new();
+    /// map.insert("key1", "value1");
+    /// map.insert_alias(&"key1", "alias1");
+    /// assert!(map.as_map().are_aliases(&"key1", &"alias1"));
+    /// ```
+    pub fn as_map(&self) -> &MultiKeyMap<K, V> {
+        &self.map
+    }
+
+    /// Consumes the map, returning the underlying map and dropping the value index.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::BiMultiKeyMap;
+    ///
+    /// let mut map = BiMultiKeyMap::new();
+    /// map.insert("key1", "value1");
+    /// let inner = map.into_map();
+    /// assert_eq!(inner.get(&"key1"), Some(&"value1"));
+    /// ```
+    pub fn into_map(self) -> MultiKeyMap<K, V> {
+        self.map
+    }
+
+    /// Verifies the internal consistency of the map and that the value index matches a scan of
+    /// the groups, panicking with a description of the first violation found.
+    ///
+    /// This is intended for tests and is linear in the size of the map.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::BiMultiKeyMap;
+    /// use rand::rngs::SmallRng;
+    /// use rand::{Rng, SeedableRng};
+    ///
+    /// let mut rng = SmallRng::seed_from_u64(7);
+    /// let mut map = BiMultiKeyMap::new();
+    /// for _ in 0..2000 {
+    ///     let (key, other) = (rng.random_range(0..50), rng.random_range(0..50));
+    ///     let value = rng.random_range(0..10);
+    ///     match rng.random_range(0..7) {
+    ///         0 => map.insert(key, value),
+    ///         1 => drop(map.insert_or_alias(key, value)),
+    ///         2 => drop(map.insert_alias(&key, other)),
+    ///         3 => drop(map.remove_alias(&key)),
+    ///         4 => drop(map.remove(&key)),
+    ///         5 => drop(map.replace(&key, value)),
+    ///         _ => drop(map.update(&key, |v| *v = (*v + 1) % 10)),
+    ///     }
+    ///     map.check_invariants();
+    /// }
+    /// ```
+    pub fn check_invariants(&self) {
+        self.map.check_invariants();
+        let mut scanned: HashMap<&V, Vec<GroupId>> = HashMap::new();
+        for (index, value) in self.map.occupied() {
+            scanned
+                .entry(value)
+                .or_default()
+                .push(self.map.id_at(index));
+        }
+        assert_eq!(
+            scanned.len(),
+            self.groups.len(),
+            "the index holds {} values but the groups hold {}",
+            self.groups.len(),
+            scanned.len()
+        );
+        for (value, ids) in &self.groups {
+            let mut ids = ids.clone();
+            ids.sort_by_key(|id| id.index);
+            assert!(
+                scanned.get(value) == Some(&ids),
+                "the indexed groups of a value differ from the groups holding it"
+            );
+        }
+    }
+
+    /// Forgets the group of `key` in the index if `key` is its last key, as the group is about
+    /// to be removed.
+    fn forget_if_last(&mut self, key: &K) {
+        if self.map.alias_count(key) == Some(1) {
+            let id = self.map.group_id(key).expect("counted key exists");
+            let value = self.map.get(key).expect("counted key exists");
+            forget(&mut self.groups, value, id);
+        }
+    }
+}
+
+/// Forgets that the group `id` holds `value`.
+fn forget<V: Eq + Hash>(groups: &mut HashMap<V, Vec<GroupId>>, value: &V, id: GroupId) {
+    let ids = groups.get_mut(value).expect("held value is indexed");
+    ids.retain(|&other| other != id);
+    if ids.is_empty() {
+        groups.remove(value);
+    }
+}
+
+impl<K: Eq + Hash, V: Eq + Hash + Clone> Default for BiMultiKeyMap<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K, V: Eq + Hash + Clone> From<MultiKeyMap<K, V>> for BiMultiKeyMap<K, V> {
+    /// Indexes the values of `map`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::{BiMultiKeyMap, MultiKeyMap};
+    ///
+    /// let mut map = MultiKeyMap::new();
+    /// map.insert("key1", "value1");
+    /// let map = BiMultiKeyMap::from(map);
+    /// assert!(map.contains_value(&"value1"));
+    /// ```
+    fn from(map: MultiKeyMap<K, V>) -> Self {
+        let mut groups: HashMap<V, Vec<GroupId>> = HashMap::new();
+        for (index, slot) in map.slots.iter().enumerate() {
+            if let Some(value) = &slot.value {
+                groups.entry(value.clone()).or_default().push(GroupId {
+                    index,
+                    generation: slot.generation,
+                });
+            }
+        }
+        BiMultiKeyMap { map, groups }
+    }
+}
+
+impl<K: Debug, V: Debug> Debug for BiMultiKeyMap<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.map.fmt(f)
+    }
+}
@@ -0,0 +1,100 @@
+use crate::MultiKeyMap;
+use borsh::io::{Error, ErrorKind, Read, Result, Write};
+use borsh::{BorshDeserialize, BorshSerialize};
+use std::hash::Hash;
+
+/// Serializes the map as a length-prefixed sequence of groups, each written as its
+/// length-prefixed keys followed by its value.
+///
+/// The keys of each group are sorted and the groups are sorted by their smallest key, so equal
+/// maps have identical encodings, as borsh requires. The smallest key of every group therefore
+/// becomes its [primary key](MultiKeyMap::primary_of) when deserialized.
+///
+/// The encoding has no room for [soft aliases](MultiKeyMap::insert_soft_alias) or
+/// [disabled keys](MultiKeyMap::disable_alias), so serializing a map holding any fails with an
+/// [`ErrorKind::InvalidInput`] error rather than losing them.
+///
+/// This implementation is only available with the `borsh` feature.
+///
+/// # Examples
+///
+/// ```
+/// use multi_key_map::MultiKeyMap;
+///
+/// let mut map = MultiKeyMap::new();
+/// map.insert("c".to_string(), 2u8);
+/// map.insert("b".to_string(), 1);
+/// map.insert_alias(&"b".to_string(), "a".to_string());
+///
+/// let bytes = borsh::to_vec(&map).unwrap();
+/// #[rustfmt::skip]
+/// assert_eq!(
+///     bytes,
+///     [
+///         2, 0, 0, 0,
+///         2, 0, 0, 0, 1, 0, 0, 0, b'a', 1, 0, 0, 0, b'b', 1,
+///         1, 0, 0, 0, 1, 0, 0, 0, b'c', 2,
+///     ]
+/// );
+///
+/// map.insert_soft_alias(&"c".to_string(), "latest".to_string());
+/// let error = borsh::to_vec(&map).unwrap_err();
+/// assert_eq!(error.to_string(), "a map with soft aliases cannot be serialized");
+/// ```
+impl<K, V> BorshSerialize for MultiKeyMap<K, V>
+where
+    K: Eq + Hash + Ord + BorshSerialize,
+    V: BorshSerialize,
+{
+    fn serialize<W: Write>(&self, writer: &mut W) -> Result<()> {
+        if let Some(error) = self.serialization_error() {
+            return Err(Error::new(ErrorKind::InvalidInput, error));
+        }
+        self.sorted_groups().serialize(writer)
+    }
+}
+
+/// Deserializes a map from the encoding written by its [`BorshSerialize`] implementation.
+///
+/// Every group must have at least one key, and no key may appear twice. Input breaking either
+/// rule is rejected with an [`ErrorKind::InvalidData`] error naming the offending groups.
+///
+/// This implementation is only available with the `borsh` feature.
+///
+/// # Examples
+///
+/// ```
+/// use multi_key_map::MultiKeyMap;
+///
+/// let mut map = MultiKeyMap::new();
+/// map.insert(1u32, "one".to_string());
+/// map.insert_alias(&1, 10);
+/// map.insert(2, "two".to_string());
+///
+/// let bytes = borsh::to_vec(&map).unwrap();
+/// let restored: MultiKeyMap<u32, String> = borsh::from_slice(&bytes).unwrap();
+/// assert_eq!(restored, map);
+///
+/// let shared = borsh::to_vec(&vec![(vec![1u32], 1u8), (vec![2, 1], 2)]).unwrap();
+/// let error = borsh::from_slice::<MultiKeyMap<u32, u8>>(&shared).unwrap_err();
+/// assert_eq!(error.to_string(), "a key appears in both group 0 and group 1");
+///
+/// let empty = borsh::to_vec(&vec![(Vec::<u32>::new(), 1u8)]).unwrap();
+/// let error = borsh::from_slice::<MultiKeyMap<u32, u8>>(&empty).unwrap_err();
+/// assert_eq!(error.to_string(), "group 0 has no keys");
+/// ```
+impl<K, V> BorshDeserialize for MultiKeyMap<K, V>
+where
+    K: Eq + Hash + BorshDeserialize,
+    V: BorshDeserialize,
+{
+    fn deserialize_reader<R: Read>(reader: &mut R) -> Result<Self> {
+        let groups = Vec::<(Vec<K>, V)>::deserialize_reader(reader)?;
+        let mut map = MultiKeyMap::new();
+        for (keys, value) in groups {
+            map.push_checked_group(keys, value)
+                .map_err(|message| Error::new(ErrorKind::InvalidData, message))?;
+        }
+        Ok(map)
+    }
+}
@@ -0,0 +1,333 @@
+use crate::MultiKeyMap;
+use std::fmt::{self, Debug};
+use std::hash::Hash;
+
+/// A `BoxedMultiKeyMap` is a [`MultiKeyMap`] for unsized values such as `str`, `[u8]` or trait
+/// objects, stored boxed internally.
+///
+/// Lookups return `&V` rather than `&Box<V>`, and values can be inserted from anything that
+/// converts into a `Box<V>`, such as a `&str` or `String` for `str` and a `Vec<u8>` for `[u8]`.
+///
+/// # Examples
+///
+/// ```
+/// use multi_key_map::BoxedMultiKeyMap;
+/// use std::fmt::Debug;
+///
+/// let mut names: BoxedMultiKeyMap<u32, str> = BoxedMultiKeyMap::new();
+/// names.insert(1, "one");
+/// names.insert_alias(&1, 100);
+/// assert_eq!(names.get(&100), Some("one"));
+/// assert_eq!(names.remove(&1).as_deref(), Some("one"));
+/// assert!(!names.contains_key(&100));
+///
+/// let mut debug: BoxedMultiKeyMap<&str, dyn Debug> = BoxedMultiKeyMap::new();
+/// debug.insert_boxed("list", Box::new(vec![1, 2]));
+/// debug.insert_alias(&"list", "alias");
+/// assert_eq!(format!("{:?}", debug.get(&"alias").unwrap()), "[1, 2]");
+/// ```
+pub struct BoxedMultiKeyMap<K, V: ?Sized> {
+    map: MultiKeyMap<K, Box<V>>,
+}
+
+impl<K: Eq + Hash, V: ?Sized> BoxedMultiKeyMap<K, V> {
+    /// Creates an empty `BoxedMultiKeyMap`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::BoxedMultiKeyMap;
+    ///
+    /// let map: BoxedMultiKeyMap<&str, [u8]> = BoxedMultiKeyMap::new();
+    /// ```
+    pub fn new() -> Self {
+        BoxedMultiKeyMap {
+            map: MultiKeyMap::new(),
+        }
+    }
+
+    /// Retrieves a reference to a value by its key.
+    ///
+    /// Returns `None` if the key does not exist.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key to lookup.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::BoxedMultiKeyMap;
+    ///
+    /// let mut map: BoxedMultiKeyMap<_, [u8]> = BoxedMultiKeyMap::new();
+    /// map.insert("key1", vec![1, 2, 3]);
+    /// assert_eq!(map.get(&"key1"), Some(&[1, 2, 3][..]));
+    /// assert_eq!(map.get(&"key2"), None);
+    /// ```
+    pub fn get(&self, key: &K) -> Option<&V> {
+        self.map.get(key).map(|value| &**value)
+    }
+
+    /// Retrieves a mutable reference to a value by its key.
+    ///
+    /// Returns `None` if the key does not exist.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key to lookup.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::BoxedMultiKeyMap;
+    ///
+    /// let mut map: BoxedMultiKeyMap<_, [u8]> = BoxedMultiKeyMap::new();
+    /// map.insert("key1", vec![1, 2, 3]);
+    /// map.insert_alias(&"key1", "alias1");
+    /// map.get_mut(&"alias1").unwrap()[0] = 9;
+    /// assert_eq!(map.get(&"key1"), Some(&[9, 2, 3][..]));
+    /// ```
+    pub fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        self.map.get_mut(key).map(|value| &mut **value)
+    }
+
+    /// Inserts a value with the given key, boxing it.
+    ///
+    /// If the key already exists, it is moved to the new value, as with
+    /// [`MultiKeyMap::insert`].
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key to insert.
+    /// * `value` - The value to insert, converted into a `Box<V>`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::BoxedMultiKeyMap;
+    ///
+    /// let mut map: BoxedMultiKeyMap<_, str> = BoxedMultiKeyMap::new();
+    /// map.insert("key1", "borrowed");
+    /// map.insert("key2", String::from("owned"));
+    /// assert_eq!(map.get(&"key2"), Some("owned"));
+    /// ```
+    pub fn insert(&mut self, key: K, value: impl Into<Box<V>>) {
+        self.map.insert(key, value.into());
+    }
+
+    /// Inserts an already boxed value with the given key.
+    ///
+    /// This is the way to insert trait objects, whose boxes do not convert implicitly.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key to insert.
+    /// * `value` - The boxed value to insert.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::BoxedMultiKeyMap;
+    /// use std::fmt::Debug;
+    ///
+    /// let mut map: BoxedMultiKeyMap<_, dyn Debug> = BoxedMultiKeyMap::new();
+    /// map.insert_boxed("number", Box::new(1));
+    /// map.insert_boxed("text", Box::new("one"));
+    /// assert_eq!(format!("{:?}", map.get(&"text").unwrap()), "\"one\"");
+    /// ```
+    pub fn insert_boxed(&mut self, key: K, value: Box<V>) {
+        self.map.insert(key, value);
+    }
+
+    /// Adds a new alias key for the element at `key`.
+    ///
+    /// Returns the reference count if the alias is successfully added.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The original key.
+    /// * `alias` - The alias key to add.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::BoxedMultiKeyMap;
+    ///
+    /// let mut map: BoxedMultiKeyMap<_, str> = BoxedMultiKeyMap::new();
+    /// map.insert("key1", "value1");
+    /// assert_eq!(map.insert_alias(&"key1", "alias1"), Some(2));
+    /// assert_eq!(map.insert_alias(&"key2", "alias2"), None);
+    /// assert_eq!(map.get(&"alias1"), Some("value1"));
+    /// ```
+    pub fn insert_alias(&mut self, key: &K, alias: K) -> Option<usize> {
+        self.map.insert_alias(key, alias)
+    }
+
+    /// Removes an alias key.
+    ///
+    /// Returns the reference count if the alias is successfully removed.
+    /// If the last alias is removed, the value is also removed.
+    ///
+    /// # Arguments
+    ///
+    /// * `alias` - The alias key to remove.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::BoxedMultiKeyMap;
+    ///
+    /// let mut map: BoxedMultiKeyMap<_, [u8]> = BoxedMultiKeyMap::new();
+    /// map.insert("key1", vec![1]);
+    /// map.insert_alias(&"key1", "alias1");
+    /// assert_eq!(map.remove_alias(&"key1"), Some(1));
+    /// assert_eq!(map.get(&"alias1"), Some(&[1][..]));
+    /// assert_eq!(map.remove_alias(&"alias1"), Some(0));
+    /// assert!(map.is_empty());
+    /// ```
+    pub fn remove_alias(&mut self, alias: &K) -> Option<usize> {
+        self.map.remove_alias(alias)
+    }
+
+    /// Removes a value by its key and all its aliases.
+    ///
+    /// Returns the boxed value if it was present.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key of the value to remove.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::BoxedMultiKeyMap;
+    /// use std::fmt::Debug;
+    ///
+    /// let mut map: BoxedMultiKeyMap<_, dyn Debug> = BoxedMultiKeyMap::new();
+    /// map.insert_boxed("key1", Box::new('x'));
+    /// map.insert_alias(&"key1", "alias1");
+    /// let value = map.remove(&"alias1").unwrap();
+    /// assert_eq!(format!("{:?}", value), "'x'");
+    /// assert!(!map.contains_key(&"key1"));
+    /// assert!(map.remove(&"key1").is_none());
+    /// ```
+    pub fn remove(&mut self, key: &K) -> Option<Box<V>> {
+        self.map.remove(key)
+    }
+
+    /// Returns `true` if the map contains a value for the specified key.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key to check.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::BoxedMultiKeyMap;
+    ///
+    /// let mut map: BoxedMultiKeyMap<_, str> = BoxedMultiKeyMap::new();
+    /// map.insert("key1", "value1");
+    /// assert!(map.contains_key(&"key1"));
+    /// assert!(!map.contains_key(&"key2"));
+    /// ```
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.map.contains_key(key)
+    }
+
+    /// Returns the underlying map of boxes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::BoxedMultiKeyMap;
+    ///
+    /// let mut map: BoxedMultiKeyMap<_, str> = BoxedMultiKeyMap::new();
+    /// map.insert("key1", "value1");
+    /// map.insert_alias(&"key1", "alias1");
+    /// assert!(map.as_map().are_aliases(&"key1", &"alias1"));
+    /// ```
+    pub fn as_map(&self) -> &MultiKeyMap<K, Box<V>> {
+        &self.map
+    }
+
+    /// Consumes the map, returning the underlying map of boxes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::BoxedMultiKeyMap;
+    ///
+    /// let mut map: BoxedMultiKeyMap<_, str> = BoxedMultiKeyMap::new();
+    /// map.insert("key1", "value1");
+    /// let inner = map.into_map();
+    /// assert_eq!(inner.get(&"key1").map(|value| &**value), Some("value1"));
+    /// ```
+    pub fn into_map(self) -> MultiKeyMap<K, Box<V>> {
+        self.map
+    }
+
+    /// Returns the number of values in the map.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::BoxedMultiKeyMap;
+    ///
+    /// let mut map: BoxedMultiKeyMap<_, str> = BoxedMultiKeyMap::new();
+    /// map.insert("key1", "value1");
+    /// map.insert_alias(&"key1", "alias1");
+    /// assert_eq!(map.len(), 1);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    /// Returns the number of keys in the map, counting every alias.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::BoxedMultiKeyMap;
+    ///
+    /// let mut map: BoxedMultiKeyMap<_, str> = BoxedMultiKeyMap::new();
+    /// map.insert("key1", "value1");
+    /// map.insert_alias(&"key1", "alias1");
+    /// assert_eq!(map.key_count(), 2);
+    /// ```
+    pub fn key_count(&self) -> usize {
+        self.map.key_count()
+    }
+
+    /// Returns `true` if the map contains no values.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::BoxedMultiKeyMap;
+    ///
+    /// let map: BoxedMultiKeyMap<&str, str> = BoxedMultiKeyMap::new();
+    /// assert!(map.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+}
+
+impl<K: Eq + Hash, V: ?Sized> Default for BoxedMultiKeyMap<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Eq + Hash, V: ?Sized> From<MultiKeyMap<K, Box<V>>> for BoxedMultiKeyMap<K, V> {
+    fn from(map: MultiKeyMap<K, Box<V>>) -> Self {
+        BoxedMultiKeyMap { map }
+    }
+}
+
+impl<K: Debug, V: ?Sized + Debug> Debug for BoxedMultiKeyMap<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.map.fmt(f)
+    }
+}
@@ -0,0 +1,528 @@
+use crate::slab::Slab;
+use std::collections::BTreeMap;
+use std::fmt::{self, Debug};
+use std::ops::RangeBounds;
+
+/// A `MultiKeyBTreeMap` allows multiple keys to point to a single value, keeping its keys sorted.
+///
+/// It offers the core API of [`MultiKeyMap`](crate::MultiKeyMap) for keys that are `Ord`
+/// rather than `Hash`. Keys and groups are iterated in key order, so iteration is
+/// deterministic. Operations over a whole group, such as [`remove`](Self::remove) and
+/// [`aliases`](Self::aliases), are linear in the number of keys.
+pub struct MultiKeyBTreeMap<K, V> {
+    key_map: BTreeMap<K, usize>,
+    values: Slab<V>,
+}
+
+impl<K: Ord, V> MultiKeyBTreeMap<K, V> {
+    /// Creates an empty `MultiKeyBTreeMap`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyBTreeMap;
+    ///
+    /// let map: MultiKeyBTreeMap<&str, &str> = MultiKeyBTreeMap::new();
+    /// ```
+    pub fn new() -> Self {
+        MultiKeyBTreeMap {
+            key_map: BTreeMap::new(),
+            values: Slab::new(),
+        }
+    }
+
+    /// Retrieves a reference to a value by its key.
+    ///
+    /// Returns `None` if the key does not exist.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key to lookup.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyBTreeMap;
+    ///
+    /// let mut map = MultiKeyBTreeMap::new();
+    /// map.insert("key1", "value1");
+    /// assert_eq!(map.get(&"key1"), Some(&"value1"));
+    /// assert_eq!(map.get(&"key2"), None);
+    /// ```
+    pub fn get(&self, key: &K) -> Option<&V> {
+        self.key_map.get(key).map(|&index| self.values.get(index))
+    }
+
+    /// Retrieves a mutable reference to a value by its key.
+    ///
+    /// Returns `None` if the key does not exist.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key to lookup.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyBTreeMap;
+    ///
+    /// let mut map = MultiKeyBTreeMap::new();
+    /// map.insert("key1", "value1");
+    /// map.insert_alias(&"key1", "alias1");
+    /// if let Some(value) = map.get_mut(&"alias1") {
+    ///     *value = "value2";
+    /// }
+    /// assert_eq!(map.get(&"key1"), Some(&"value2"));
+    /// ```
+    pub fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        let index = *self.key_map.get(key)?;
+        Some(self.values.get_mut(index))
+    }
+
+    /// Inserts a value with the given key.
+    ///
+    /// If the key already exists, it is moved to the new value, and its old group is removed if
+    /// it has no other keys.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key to insert.
+    /// * `value` - The value to insert.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyBTreeMap;
+    ///
+    /// let mut map = MultiKeyBTreeMap::new();
+    /// map.insert("key1", "value1");
+    /// map.insert("key1", "value2");
+    /// assert_eq!(map.get(&"key1"), Some(&"value2"));
+    /// assert_eq!(map.len(), 1);
+    /// ```
+    pub fn insert(&mut self, key: K, value: V) {
+        let index = self.values.insert(value);
+        self.bind(key, index);
+    }
+
+    /// Adds a new alias key for the element at `key`.
+    ///
+    /// Returns the reference count if the alias is successfully added.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The original key.
+    /// * `alias` - The alias key to add.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyBTreeMap;
+    ///
+    /// let mut map = MultiKeyBTreeMap::new();
+    /// map.insert("key1", "value1");
+    /// assert_eq!(map.insert_alias(&"key1", "alias1"), Some(2));
+    /// assert_eq!(map.insert_alias(&"key1", "key1"), None);
+    /// assert_eq!(map.insert_alias(&"key2", "alias2"), None);
+    /// ```
+    pub fn insert_alias(&mut self, key: &K, alias: K) -> Option<usize> {
+        if key == &alias {
+            // Do not allow aliasing the same key
+            return None;
+        }
+        let index = *self.key_map.get(key)?;
+        self.bind(alias, index);
+        Some(self.values.ref_count(index))
+    }
+
+    /// Removes an alias key.
+    ///
+    /// Returns the reference count if the alias is successfully removed.
+    /// If the last alias is removed, the value is also removed.
+    ///
+    /// # Arguments
+    ///
+    /// * `alias` - The alias key to remove.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyBTreeMap;
+    ///
+    /// let mut map = MultiKeyBTreeMap::new();
+    /// map.insert("key1", "value1");
+    /// map.insert_alias(&"key1", "alias1");
+    /// assert_eq!(map.remove_alias(&"alias1"), Some(1));
+    /// assert_eq!(map.remove_alias(&"key1"), Some(0));
+    /// assert!(map.is_empty());
+    /// ```
+    pub fn remove_alias(&mut self, alias: &K) -> Option<usize> {
+        let index = self.key_map.remove(alias)?;
+        Some(self.values.drop_ref(index))
+    }
+
+    /// Removes a value by its key and all its aliases.
+    ///
+    /// Returns the value if it was present.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key of the value to remove.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyBTreeMap;
+    ///
+    /// let mut map = MultiKeyBTreeMap::new();
+    /// map.insert("key1", "value1");
+    /// map.insert_alias(&"key1", "alias1");
+    /// assert_eq!(map.remove(&"alias1"), Some("value1"));
+    /// assert!(!map.contains_key(&"key1"));
+    /// assert!(map.is_empty());
+    /// ```
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        let index = *self.key_map.get(key)?;
+        self.key_map.retain(|_, &mut other| other != index);
+        Some(self.values.remove(index))
+    }
+
+    /// Retrieves all aliases of the value that `key` points to, in key order.
+    ///
+    /// Returns `None` if the key does not exist.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - A key of the group.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyBTreeMap;
+    ///
+    /// let mut map = MultiKeyBTreeMap::new();
+    /// map.insert("m", 1);
+    /// map.insert_alias(&"m", "z");
+    /// map.insert_alias(&"m", "a");
+    /// map.insert("b", 2);
+    /// assert_eq!(map.aliases(&"z"), Some(vec!["a", "m", "z"]));
+    /// assert_eq!(map.aliases(&"b"), Some(vec!["b"]));
+    /// assert_eq!(map.aliases(&"c"), None);
+    /// ```
+    pub fn aliases(&self, key: &K) -> Option<Vec<K>>
+    where
+        K: Clone,
+    {
+        let index = *self.key_map.get(key)?;
+        Some(
+            self.key_map
+                .iter()
+                .filter(|&(_, &other)| other == index)
+                .map(|(key, _)| key.clone())
+                .collect(),
+        )
+    }
+
+    /// Checks if two keys are aliases of each other.
+    ///
+    /// # Arguments
+    ///
+    /// * `key1` - The first key.
+    /// * `key2` - The second key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyBTreeMap;
+    ///
+    /// let mut map = MultiKeyBTreeMap::new();
+    /// map.insert("key1", "value1");
+    /// map.insert_alias(&"key1", "alias1");
+    /// map.insert("key2", "value1");
+    /// assert!(map.are_aliases(&"key1", &"alias1"));
+    /// assert!(!map.are_aliases(&"key1", &"key2"));
+    /// assert!(!map.are_aliases(&"key1", &"key3"));
+    /// ```
+    pub fn are_aliases(&self, key1: &K, key2: &K) -> bool {
+        match (self.key_map.get(key1), self.key_map.get(key2)) {
+            (Some(index1), Some(index2)) => index1 == index2,
+            _ => false,
+        }
+    }
+
+    /// Returns `true` if the map contains a value for the specified key.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key to check.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyBTreeMap;
+    ///
+    /// let mut map = MultiKeyBTreeMap::new();
+    /// map.insert("key1", "value1");
+    /// assert!(map.contains_key(&"key1"));
+    /// assert!(!map.contains_key(&"key2"));
+    /// ```
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.key_map.contains_key(key)
+    }
+
+    /// Returns an iterator over all keys, in key order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyBTreeMap;
+    ///
+    /// let mut map = MultiKeyBTreeMap::new();
+    /// map.insert("c", 1);
+    /// map.insert("a", 2);
+    /// map.insert_alias(&"c", "b");
+    /// let keys: Vec<_> = map.keys().copied().collect();
+    /// assert_eq!(keys, vec!["a", "b", "c"]);
+    /// ```
+    pub fn keys(&self) -> impl DoubleEndedIterator<Item = &K> + '_ {
+        self.key_map.keys()
+    }
+
+    /// Returns an iterator over every key and the value it points to, in key order.
+    ///
+    /// A value is yielded once for each of its keys.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyBTreeMap;
+    ///
+    /// let mut map = MultiKeyBTreeMap::new();
+    /// map.insert("c", 1);
+    /// map.insert("a", 2);
+    /// map.insert_alias(&"c", "b");
+    /// let entries: Vec<_> = map.iter().collect();
+    /// assert_eq!(entries, vec![(&"a", &2), (&"b", &1), (&"c", &1)]);
+    /// ```
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = (&K, &V)> + '_ {
+        self.key_map
+            .iter()
+            .map(|(key, &index)| (key, self.values.get(index)))
+    }
+
+    /// Returns an iterator over the groups, each given as its keys and its value.
+    ///
+    /// Keys within a group are in key order, and groups are ordered by their smallest key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyBTreeMap;
+    ///
+    /// let mut map = MultiKeyBTreeMap::new();
+    /// map.insert("d", 1);
+    /// map.insert("b", 2);
+    /// map.insert_alias(&"d", "a");
+    /// map.insert_alias(&"b", "c");
+    /// let groups: Vec<_> = map.groups().collect();
+    /// assert_eq!(groups, vec![(vec![&"a", &"d"], &1), (vec![&"b", &"c"], &2)]);
+    /// ```
+    pub fn groups(&self) -> impl Iterator<Item = (Vec<&K>, &V)> + '_ {
+        self.collect_groups(self.key_map.iter())
+    }
+
+    /// Returns an iterator over the keys within `range` and the values they point to, in key
+    /// order.
+    ///
+    /// A value is yielded once for each of its keys within the range.
+    ///
+    /// # Arguments
+    ///
+    /// * `range` - The range of keys to visit.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the start of the range is greater than its end, or if both bounds are excluded
+    /// and equal, like [`BTreeMap::range`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyBTreeMap;
+    ///
+    /// let mut map = MultiKeyBTreeMap::new();
+    /// map.insert(10, "a");
+    /// map.insert(20, "b");
+    /// map.insert_alias(&10, 30);
+    /// map.insert(40, "c");
+    ///
+    /// let entries: Vec<_> = map.range(15..=30).collect();
+    /// assert_eq!(entries, vec![(&20, &"b"), (&30, &"a")]);
+    /// let entries: Vec<_> = map.range(..20).collect();
+    /// assert_eq!(entries, vec![(&10, &"a")]);
+    /// assert_eq!(map.range(21..30).count(), 0);
+    /// assert_eq!(map.range(50..).count(), 0);
+    /// ```
+    pub fn range<R: RangeBounds<K>>(
+        &self,
+        range: R,
+    ) -> impl DoubleEndedIterator<Item = (&K, &V)> + '_ {
+        self.key_map
+            .range(range)
+            .map(|(key, &index)| (key, self.values.get(index)))
+    }
+
+    /// Returns an iterator over the groups with a key within `range`, each given as its keys
+    /// within the range and its value.
+    ///
+    /// A group is yielded once however many of its keys fall within the range; its keys outside
+    /// the range are left out. Groups are ordered by their smallest key within the range.
+    ///
+    /// # Arguments
+    ///
+    /// * `range` - The range of keys to visit.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the start of the range is greater than its end, or if both bounds are excluded
+    /// and equal, like [`BTreeMap::range`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyBTreeMap;
+    ///
+    /// let mut map = MultiKeyBTreeMap::new();
+    /// map.insert(10, "a");
+    /// map.insert(20, "b");
+    /// map.insert_alias(&10, 30);
+    /// map.insert_alias(&10, 50);
+    /// map.insert(40, "c");
+    ///
+    /// // The group of "a" is clipped: 10 falls outside the range, 30 and 50 inside
+    /// let groups: Vec<_> = map.groups_in_range(15..).collect();
+    /// assert_eq!(
+    ///     groups,
+    ///     vec![(vec![&20], &"b"), (vec![&30, &50], &"a"), (vec![&40], &"c")]
+    /// );
+    /// let groups: Vec<_> = map.groups_in_range(..=30).collect();
+    /// assert_eq!(groups, vec![(vec![&10, &30], &"a"), (vec![&20], &"b")]);
+    /// assert_eq!(map.groups_in_range(31..40).count(), 0);
+    /// ```
+    pub fn groups_in_range<R: RangeBounds<K>>(
+        &self,
+        range: R,
+    ) -> impl Iterator<Item = (Vec<&K>, &V)> + '_ {
+        self.collect_groups(self.key_map.range(range))
+    }
+
+    /// Returns the number of groups in the map.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyBTreeMap;
+    ///
+    /// let mut map = MultiKeyBTreeMap::new();
+    /// map.insert("key1", "value1");
+    /// map.insert_alias(&"key1", "alias1");
+    /// assert_eq!(map.len(), 1);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Returns the number of keys in the map, counting every alias.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyBTreeMap;
+    ///
+    /// let mut map = MultiKeyBTreeMap::new();
+    /// map.insert("key1", "value1");
+    /// map.insert_alias(&"key1", "alias1");
+    /// assert_eq!(map.key_count(), 2);
+    /// ```
+    pub fn key_count(&self) -> usize {
+        self.key_map.len()
+    }
+
+    /// Returns `true` if the map contains no values.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyBTreeMap;
+    ///
+    /// let mut map = MultiKeyBTreeMap::new();
+    /// assert!(map.is_empty());
+    /// map.insert("key1", "value1");
+    /// assert!(!map.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.values.len() == 0
+    }
+
+    /// Gathers the keys of `entries` by group, ordering groups by their first key.
+    fn collect_groups<'a>(
+        &'a self,
+        entries: impl Iterator<Item = (&'a K, &'a usize)>,
+    ) -> impl Iterator<Item = (Vec<&'a K>, &'a V)> + 'a {
+        let mut position: Vec<Option<usize>> = vec![None; self.values.index_bound()];
+        let mut groups: Vec<(Vec<&K>, &V)> = Vec::new();
+        for (key, &index) in entries {
+            match position[index] {
+                Some(position) => groups[position].0.push(key),
+                None => {
+                    position[index] = Some(groups.len());
+                    groups.push((vec![key], self.values.get(index)));
+                }
+            }
+        }
+        groups.into_iter()
+    }
+
+    /// Binds `key` to the value at `index`, removing the group it leaves if that group has no
+    /// other keys.
+    fn bind(&mut self, key: K, index: usize) {
+        self.values.add_ref(index);
+        if let Some(previous) = self.key_map.insert(key, index) {
+            self.values.drop_ref(previous);
+        }
+    }
+}
+
+impl<K: Ord, V> Default for MultiKeyBTreeMap<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Ord + Debug, V: Debug> Debug for MultiKeyBTreeMap<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut debug_struct = f.debug_struct("MultiKeyBTreeMap");
+        for (keys, value) in self.groups() {
+            debug_struct.field(&format!("{:?}", keys), value);
+        }
+        debug_struct.finish()
+    }
+}
+
+impl<K: Ord, V: PartialEq> PartialEq for MultiKeyBTreeMap<K, V> {
+    /// Two maps are equal if they have the same keys and every key points to an equal value.
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.iter().eq(other.iter())
+    }
+}
+
+impl<K: Ord, V: Eq> Eq for MultiKeyBTreeMap<K, V> {}
+
+impl<K: Clone, V: Clone> Clone for MultiKeyBTreeMap<K, V> {
+    fn clone(&self) -> Self {
+        MultiKeyBTreeMap {
+            key_map: self.key_map.clone(),
+            values: self.values.clone(),
+        }
+    }
+}
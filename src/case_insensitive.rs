@@ -0,0 +1,128 @@
+use std::fmt::{self, Debug, Display};
+use std::hash::{Hash, Hasher};
+
+/// A string key that compares and hashes ignoring case, while keeping its original spelling.
+///
+/// A [`MultiKeyMap`](crate::MultiKeyMap) keyed by `CaseInsensitive<K>` finds a key whatever the
+/// case it is looked up with, and [`aliases`](crate::MultiKeyMap::aliases) still returns the
+/// spellings the keys were inserted with. Adding an alias to the group it already belongs to keeps
+/// its stored spelling, while binding it to another group, as
+/// [`insert`](crate::MultiKeyMap::insert) does, stores the new one.
+///
+/// By default only ASCII letters are folded, so `"ÄRGER"` and `"ärger"` are different keys. With
+/// the `unicode-case` feature every character is folded to its Unicode lowercase form, which is
+/// slower but matches non-ASCII letters too.
+///
+/// # Examples
+///
+/// ```
+/// use multi_key_map::{CaseInsensitive, MultiKeyMap};
+///
+/// let mut cities = MultiKeyMap::new();
+/// cities.insert(CaseInsensitive("Berlin"), 3_850_000);
+/// assert_eq!(cities.insert_alias(&CaseInsensitive("BERLIN"), CaseInsensitive("BER")), Some(2));
+/// assert_eq!(cities.get(&CaseInsensitive("berlin")), Some(&3_850_000));
+/// assert_eq!(cities.get(&CaseInsensitive("Ber")), Some(&3_850_000));
+///
+/// let aliases = cities.aliases(&CaseInsensitive("bErLiN")).unwrap();
+/// let spellings: Vec<&str> = aliases.iter().map(|key| key.0).collect();
+/// assert_eq!(spellings, ["Berlin", "BER"]);
+///
+/// // An alias spelled differently is the same key
+/// assert_eq!(cities.insert_alias(&CaseInsensitive("berlin"), CaseInsensitive("ber")), Some(2));
+/// assert_eq!(cities.remove_alias(&CaseInsensitive("bEr")), Some(1));
+/// assert_eq!(cities.remove(&CaseInsensitive("BERLIN")), Some(3_850_000));
+/// assert!(cities.is_empty());
+/// ```
+#[derive(Clone, Copy, Default)]
+pub struct CaseInsensitive<K>(pub K);
+
+impl<K> CaseInsensitive<K> {
+    /// Returns the wrapped key, in its original spelling.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::CaseInsensitive;
+    ///
+    /// assert_eq!(CaseInsensitive(String::from("Berlin")).into_inner(), "Berlin");
+    /// ```
+    pub fn into_inner(self) -> K {
+        self.0
+    }
+}
+
+impl<K: AsRef<str>> CaseInsensitive<K> {
+    /// Returns the key as a string, in its original spelling.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::CaseInsensitive;
+    ///
+    /// assert_eq!(CaseInsensitive(String::from("Berlin")).as_str(), "Berlin");
+    /// ```
+    pub fn as_str(&self) -> &str {
+        self.0.as_ref()
+    }
+}
+
+impl<K: AsRef<str>> PartialEq for CaseInsensitive<K> {
+    /// Compares the keys ignoring case.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::CaseInsensitive;
+    ///
+    /// assert_eq!(CaseInsensitive("Berlin"), CaseInsensitive("bErLIN"));
+    /// assert_ne!(CaseInsensitive("Berlin"), CaseInsensitive("Bern"));
+    /// ```
+    fn eq(&self, other: &Self) -> bool {
+        #[cfg(not(feature = "unicode-case"))]
+        return self.as_str().eq_ignore_ascii_case(other.as_str());
+        #[cfg(feature = "unicode-case")]
+        return folded(self.as_str()).eq(folded(other.as_str()));
+    }
+}
+
+impl<K: AsRef<str>> Eq for CaseInsensitive<K> {}
+
+impl<K: AsRef<str>> Hash for CaseInsensitive<K> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        #[cfg(not(feature = "unicode-case"))]
+        for byte in self.as_str().bytes() {
+            state.write_u8(byte.to_ascii_lowercase());
+        }
+        #[cfg(feature = "unicode-case")]
+        for c in folded(self.as_str()) {
+            state.write_u32(c.into());
+        }
+        // Mark the end of the key, as `str` does, so consecutive keys hash unambiguously
+        state.write_u8(0xff);
+    }
+}
+
+impl<K: Debug> Debug for CaseInsensitive<K> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Debug::fmt(&self.0, f)
+    }
+}
+
+impl<K: Display> Display for CaseInsensitive<K> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Display::fmt(&self.0, f)
+    }
+}
+
+impl<K> From<K> for CaseInsensitive<K> {
+    fn from(key: K) -> Self {
+        CaseInsensitive(key)
+    }
+}
+
+/// Returns the characters of `s` folded to lowercase.
+#[cfg(feature = "unicode-case")]
+fn folded(s: &str) -> impl Iterator<Item = char> + '_ {
+    s.chars().flat_map(char::to_lowercase)
+}
@@ -0,0 +1,279 @@
+use crate::{MultiKeyMap, Slot};
+use std::hash::Hash;
+
+/// A change to a [`MultiKeyMap`] recorded while [tracking changes](MultiKeyMap::track_changes).
+///
+/// Groups are identified by their primary key after the change, so a log can be replayed against
+/// a copy of the map held elsewhere.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Change<K> {
+    /// A group was inserted with the given keys.
+    GroupCreated {
+        /// The keys of the new group, its primary key first.
+        keys: Vec<K>,
+    },
+    /// A key was added to an existing group.
+    AliasAdded {
+        /// The key added.
+        alias: K,
+        /// The primary key of the group.
+        group: K,
+    },
+    /// A key was removed from a group that still has other keys.
+    AliasRemoved {
+        /// The key removed.
+        alias: K,
+        /// The primary key of the group after the removal.
+        group: K,
+    },
+    /// A key was renamed, staying in its group.
+    KeyRenamed {
+        /// The key before the change.
+        old: K,
+        /// The key after the change.
+        new: K,
+    },
+    /// The value of a group was replaced through [`MultiKeyMap::replace`] or
+    /// [`MultiKeyMap::apply`].
+    ValueReplaced {
+        /// The primary key of the group.
+        group: K,
+    },
+    /// A group was removed along with the given keys.
+    GroupRemoved {
+        /// The keys the group had when it was removed.
+        keys: Vec<K>,
+    },
+}
+
+/// The changes recorded since they were last taken.
+pub(crate) struct ChangeLog<K> {
+    changes: Vec<Change<K>>,
+    /// Clones the keys of the changes, kept here so that recording does not require `K: Clone`.
+    clone: fn(&K) -> K,
+}
+
+impl<K> ChangeLog<K> {
+    /// Returns the number of changes recorded.
+    pub(crate) fn len(&self) -> usize {
+        self.changes.len()
+    }
+
+    /// Discards the changes recorded after the first `len`.
+    pub(crate) fn truncate(&mut self, len: usize) {
+        self.changes.truncate(len);
+    }
+
+    /// Records the group in `slot` as created.
+    pub(crate) fn group_created<V>(&mut self, slot: &Slot<K, V>) {
+        let keys = slot.keys.iter().map(self.clone).collect();
+        self.changes.push(Change::GroupCreated { keys });
+    }
+
+    /// Records a group with `keys` as removed.
+    pub(crate) fn group_removed(&mut self, keys: &[K]) {
+        let keys = keys.iter().map(self.clone).collect();
+        self.changes.push(Change::GroupRemoved { keys });
+    }
+
+    /// Records the last key of the group in `slot` as added.
+    pub(crate) fn alias_added<V>(&mut self, slot: &Slot<K, V>) {
+        self.aliases_added(slot, 1);
+    }
+
+    /// Records `alias` as removed from the group in `slot`.
+    pub(crate) fn alias_removed<V>(&mut self, alias: &K, slot: &Slot<K, V>) {
+        self.changes.push(Change::AliasRemoved {
+            alias: (self.clone)(alias),
+            group: (self.clone)(&slot.keys[0]),
+        });
+    }
+
+    /// Records the last `count` keys of the group in `slot` as added, in order.
+    pub(crate) fn aliases_added<V>(&mut self, slot: &Slot<K, V>, count: usize) {
+        let group = &slot.keys[0];
+        for alias in &slot.keys[slot.keys.len() - count..] {
+            self.changes.push(Change::AliasAdded {
+                alias: (self.clone)(alias),
+                group: (self.clone)(group),
+            });
+        }
+    }
+
+    /// Records the key `old` as renamed to `new`.
+    pub(crate) fn key_renamed(&mut self, old: &K, new: &K) {
+        self.changes.push(Change::KeyRenamed {
+            old: (self.clone)(old),
+            new: (self.clone)(new),
+        });
+    }
+
+    /// Records the value of the group in `slot` as replaced.
+    pub(crate) fn value_replaced<V>(&mut self, slot: &Slot<K, V>) {
+        let group = (self.clone)(&slot.keys[0]);
+        self.changes.push(Change::ValueReplaced { group });
+    }
+}
+
+impl<K: Eq + Hash, V> MultiKeyMap<K, V> {
+    /// Turns recording of the changes made to the map on or off.
+    ///
+    /// While tracking, the map records a [`Change`] for every key or group added or removed: the
+    /// changes reported to [hooks](Self::set_on_group_inserted), and those of the bulk
+    /// operations hooks are not told about, such as [`retain`](Self::retain),
+    /// [`retain_keys`](Self::retain_keys), [`dedup_values`](Self::dedup_values),
+    /// [`append`](Self::append) or [`split_off`](Self::split_off). A group merged into another
+    /// is recorded as removed, followed by its keys added to the other group. Keys renamed by
+    /// [`rekey_group`](Self::rekey_group) and [`apply_renames`](Self::apply_renames) are
+    /// recorded as [`Change::KeyRenamed`], while
+    /// [`set_key_normalizer`](Self::set_key_normalizer) records every group as removed and
+    /// created again under its normalized keys.
+    ///
+    /// Values are only recorded when replaced through [`replace`](Self::replace) or
+    /// [`apply`](Self::apply), not when modified in place, through
+    /// [`get_mut`](Self::get_mut) for instance. Changes leaving every key in its group are not
+    /// recorded either: [disabling](Self::disable_alias) keys,
+    /// [setting the primary key](Self::set_primary), [soft aliases](Self::insert_soft_alias),
+    /// [`compact`](Self::compact), and keys moving inside a group as others are removed.
+    ///
+    /// Turning tracking off discards the changes not taken yet; turning it on while already
+    /// tracking keeps them. Clones of the map do not track changes.
+    ///
+    /// # Arguments
+    ///
+    /// * `enabled` - Whether to record changes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyMap;
+    ///
+    /// let mut map = MultiKeyMap::new();
+    /// map.insert("a", 1);
+    /// assert!(!map.is_tracking_changes());
+    /// assert!(map.take_changes().is_empty());
+    ///
+    /// map.track_changes(true);
+    /// map.insert("b", 2);
+    /// map.track_changes(false);
+    /// map.track_changes(true);
+    /// assert!(map.take_changes().is_empty());
+    /// ```
+    ///
+    /// Bulk operations record their changes too:
+    ///
+    /// ```
+    /// use multi_key_map::{Change, MergePolicy, MultiKeyMap};
+    ///
+    /// let mut map = MultiKeyMap::new();
+    /// map.insert_with_keys(["a", "a1"], 1).unwrap();
+    /// map.insert("b", 2);
+    /// map.insert("c", 1);
+    /// map.track_changes(true);
+    ///
+    /// map.retain(|keys, _| keys != ["b"]);
+    /// map.rekey_group(&"a", |key| if *key == "a" { "x" } else { "x1" }).unwrap();
+    /// map.dedup_values();
+    /// map.retain_keys(|key| *key != "x1");
+    /// let mut other = MultiKeyMap::new();
+    /// other.insert_with_keys(["d", "c"], 4).unwrap();
+    /// map.append(other, MergePolicy::Replace);
+    /// map.split_off(&["d"]);
+    /// assert_eq!(
+    ///     map.take_changes(),
+    ///     [
+    ///         Change::GroupRemoved { keys: vec!["b"] },
+    ///         Change::KeyRenamed { old: "a", new: "x" },
+    ///         Change::KeyRenamed { old: "a1", new: "x1" },
+    ///         Change::GroupRemoved { keys: vec!["c"] },
+    ///         Change::AliasAdded { alias: "c", group: "x" },
+    ///         Change::AliasRemoved { alias: "x1", group: "x" },
+    ///         Change::AliasRemoved { alias: "c", group: "x" },
+    ///         Change::GroupCreated { keys: vec!["d", "c"] },
+    ///         Change::GroupRemoved { keys: vec!["d", "c"] },
+    ///     ]
+    /// );
+    ///
+    /// map.set_key_normalizer(|key: &&str| key.trim());
+    /// assert_eq!(
+    ///     map.take_changes(),
+    ///     [
+    ///         Change::GroupRemoved { keys: vec!["x"] },
+    ///         Change::GroupCreated { keys: vec!["x"] },
+    ///     ]
+    /// );
+    /// ```
+    pub fn track_changes(&mut self, enabled: bool)
+    where
+        K: Clone,
+    {
+        let log = &mut self.hooks.changes;
+        if !enabled {
+            *log = None;
+        } else if log.is_none() {
+            *log = Some(ChangeLog {
+                changes: Vec::new(),
+                clone: K::clone,
+            });
+        }
+    }
+
+    /// Returns `true` if the map records the changes made to it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyMap;
+    ///
+    /// let mut map = MultiKeyMap::<&str, u32>::new();
+    /// map.track_changes(true);
+    /// assert!(map.is_tracking_changes());
+    /// ```
+    pub fn is_tracking_changes(&self) -> bool {
+        self.hooks.changes.is_some()
+    }
+
+    /// Returns the changes recorded since tracking was turned on or changes were last taken, in
+    /// the order they were made, and clears them.
+    ///
+    /// Returns an empty list if the map does not [track changes](Self::track_changes).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::{Change, MultiKeyMap};
+    ///
+    /// let mut map = MultiKeyMap::new();
+    /// map.insert("a", 1);
+    /// map.track_changes(true);
+    ///
+    /// map.insert_alias(&"a", "b");
+    /// map.insert_with_keys(["c", "d"], 2).unwrap();
+    /// map.replace(&"d", 3);
+    /// map.remove_alias(&"c");
+    /// map.insert_alias(&"d", "a");
+    /// map.remove(&"b");
+    /// assert_eq!(
+    ///     map.take_changes(),
+    ///     [
+    ///         Change::AliasAdded { alias: "b", group: "a" },
+    ///         Change::GroupCreated { keys: vec!["c", "d"] },
+    ///         Change::ValueReplaced { group: "c" },
+    ///         Change::AliasRemoved { alias: "c", group: "d" },
+    ///         Change::AliasRemoved { alias: "a", group: "b" },
+    ///         Change::AliasAdded { alias: "a", group: "d" },
+    ///         Change::GroupRemoved { keys: vec!["b"] },
+    ///     ]
+    /// );
+    /// assert!(map.take_changes().is_empty());
+    ///
+    /// map.clear();
+    /// assert_eq!(map.take_changes(), [Change::GroupRemoved { keys: vec!["d", "a"] }]);
+    /// ```
+    pub fn take_changes(&mut self) -> Vec<Change<K>> {
+        match &mut self.hooks.changes {
+            Some(log) => std::mem::take(&mut log.changes),
+            None => Vec::new(),
+        }
+    }
+}
@@ -0,0 +1,628 @@
+use parking_lot::{RwLock, RwLockReadGuard, RwLockWriteGuard};
+use std::collections::HashMap;
+use std::fmt::{self, Debug};
+use std::hash::{BuildHasher, Hash, RandomState};
+use std::ops::Deref;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// A `ConcurrentMultiKeyMap` allows multiple keys to point to a single value and can be shared
+/// between threads.
+///
+/// Keys are routed to their group through a table sharded by key, and groups are sharded by an
+/// id assigned on creation, so the aliases of a group may live in different shards. Each shard
+/// has its own lock, and operations only lock the shards they touch: route shards first, then
+/// group shards, each in ascending order, so operations never deadlock.
+///
+/// Operations are linearizable, but aggregate queries such as [`len`](Self::len) visit the
+/// shards one after another and may miss concurrent changes. The guards returned by
+/// [`get`](Self::get) hold a lock, and calling the map while holding one may deadlock.
+///
+/// This type is only available with the `concurrent` feature.
+///
+/// # Examples
+///
+/// ```
+/// use multi_key_map::ConcurrentMultiKeyMap;
+/// use std::sync::Arc;
+/// use std::thread;
+///
+/// let map = Arc::new(ConcurrentMultiKeyMap::new());
+/// map.insert("key1", 1);
+/// let handles: Vec<_> = (0..4)
+///     .map(|_| {
+///         let map = Arc::clone(&map);
+///         thread::spawn(move || *map.get(&"key1").unwrap())
+///     })
+///     .collect();
+/// for handle in handles {
+///     assert_eq!(handle.join().unwrap(), 1);
+/// }
+/// ```
+pub struct ConcurrentMultiKeyMap<K, V> {
+    hasher: RandomState,
+    /// The id of the group of every key, sharded by key.
+    routes: Box<[RwLock<HashMap<K, u64>>]>,
+    /// Groups by id, sharded by id.
+    groups: Box<[RwLock<GroupShard<K, V>>]>,
+    next_id: AtomicU64,
+}
+
+/// The groups of one shard, by id.
+type GroupShard<K, V> = HashMap<u64, Group<K, V>>;
+
+struct Group<K, V> {
+    keys: Vec<K>,
+    value: V,
+}
+
+impl<K: Eq + Hash + Clone, V> ConcurrentMultiKeyMap<K, V> {
+    /// Creates an empty `ConcurrentMultiKeyMap` with a number of shards suited to the machine.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::ConcurrentMultiKeyMap;
+    ///
+    /// let map: ConcurrentMultiKeyMap<&str, &str> = ConcurrentMultiKeyMap::new();
+    /// ```
+    pub fn new() -> Self {
+        let parallelism = std::thread::available_parallelism().map_or(1, |n| n.get());
+        Self::with_shards((parallelism * 4).next_power_of_two())
+    }
+
+    /// Creates an empty `ConcurrentMultiKeyMap` with `shards` route shards and as many group
+    /// shards.
+    ///
+    /// # Arguments
+    ///
+    /// * `shards` - The number of shards.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `shards` is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::ConcurrentMultiKeyMap;
+    ///
+    /// let map: ConcurrentMultiKeyMap<&str, &str> = ConcurrentMultiKeyMap::with_shards(8);
+    /// ```
+    pub fn with_shards(shards: usize) -> Self {
+        assert!(shards > 0, "a concurrent map needs at least one shard");
+        ConcurrentMultiKeyMap {
+            hasher: RandomState::new(),
+            routes: (0..shards).map(|_| RwLock::new(HashMap::new())).collect(),
+            groups: (0..shards).map(|_| RwLock::new(HashMap::new())).collect(),
+            next_id: AtomicU64::new(0),
+        }
+    }
+
+    /// Retrieves a guard dereferencing to the value `key` points to.
+    ///
+    /// Returns `None` if the key does not exist. The guard holds a read lock on the shard of the
+    /// group, so writers to that shard wait until it is dropped.
+    ///
+    /// Calling any other method of the map while holding the guard may deadlock, as a read lock
+    /// cannot be taken again while a writer waits for it: a thread holding a guard that calls
+    /// [`get`](Self::get), [`update`](Self::update) or [`insert`](Self::insert) for a key of the
+    /// same shard waits for the writer, which waits for the guard. Drop the guard first, or use
+    /// [`get_cloned`](Self::get_cloned), which does not hold any lock once it returns.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key to lookup.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::ConcurrentMultiKeyMap;
+    ///
+    /// let map = ConcurrentMultiKeyMap::new();
+    /// map.insert("key1", "value1");
+    /// assert_eq!(map.get(&"key1").as_deref(), Some(&"value1"));
+    /// assert!(map.get(&"key2").is_none());
+    /// ```
+    pub fn get(&self, key: &K) -> Option<impl Deref<Target = V> + '_> {
+        let routes = self.routes[self.route_shard(key)].read();
+        let id = *routes.get(key)?;
+        // Lock the group before releasing the route, so the key cannot move in between
+        let groups = self.groups[self.group_shard(id)].read();
+        drop(routes);
+        Some(RwLockReadGuard::map(groups, |groups| &groups[&id].value))
+    }
+
+    /// Returns a clone of the value `key` points to, or `None` if the key does not exist.
+    ///
+    /// Unlike [`get`](Self::get), this releases its locks before returning, so the map can be
+    /// used freely while holding the result.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key to lookup.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::ConcurrentMultiKeyMap;
+    ///
+    /// let map = ConcurrentMultiKeyMap::new();
+    /// map.insert("key1", String::from("value1"));
+    /// map.insert_alias(&"key1", "alias1");
+    /// let value = map.get_cloned(&"alias1").unwrap();
+    /// map.update(&"key1", |value| value.push('!'));
+    /// assert_eq!(value, "value1");
+    /// assert_eq!(map.get_cloned(&"key1").as_deref(), Some("value1!"));
+    /// assert_eq!(map.get_cloned(&"key2"), None);
+    /// ```
+    pub fn get_cloned(&self, key: &K) -> Option<V>
+    where
+        V: Clone,
+    {
+        self.get(key).map(|value| value.clone())
+    }
+
+    /// Updates the value `key` points to with `f`, returning its result.
+    ///
+    /// Returns `None` if the key does not exist.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key to lookup.
+    /// * `f` - The function to apply to the value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::ConcurrentMultiKeyMap;
+    ///
+    /// let map = ConcurrentMultiKeyMap::new();
+    /// map.insert("key1", 1);
+    /// map.insert_alias(&"key1", "alias1");
+    /// assert_eq!(map.update(&"alias1", |value| { *value += 1; *value }), Some(2));
+    /// assert_eq!(*map.get(&"key1").unwrap(), 2);
+    /// assert_eq!(map.update(&"key2", |value| *value), None);
+    /// ```
+    pub fn update<T>(&self, key: &K, f: impl FnOnce(&mut V) -> T) -> Option<T> {
+        let routes = self.routes[self.route_shard(key)].read();
+        let id = *routes.get(key)?;
+        let mut groups = self.groups[self.group_shard(id)].write();
+        drop(routes);
+        let group = groups.get_mut(&id).expect("routed group exists");
+        Some(f(&mut group.value))
+    }
+
+    /// Inserts a value with the given key.
+    ///
+    /// If the key already exists, it is moved to the new value, and its old group is removed if
+    /// it has no other keys.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key to insert.
+    /// * `value` - The value to insert.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::ConcurrentMultiKeyMap;
+    ///
+    /// let map = ConcurrentMultiKeyMap::new();
+    /// map.insert("key1", "value1");
+    /// map.insert("key1", "value2");
+    /// assert_eq!(map.get(&"key1").as_deref(), Some(&"value2"));
+    /// assert_eq!(map.len(), 1);
+    /// ```
+    pub fn insert(&self, key: K, value: V) {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let route_shard = self.route_shard(&key);
+        let mut routes = self.routes[route_shard].write();
+        let previous = routes.insert(key.clone(), id);
+        let mut group_shards = vec![self.group_shard(id)];
+        group_shards.extend(previous.map(|previous| self.group_shard(previous)));
+        let mut groups = ShardGuards::lock(&self.groups, group_shards);
+        if let Some(previous) = previous {
+            Self::unlink(groups.get(self.group_shard(previous)), previous, &key);
+        }
+        groups.get(self.group_shard(id)).insert(
+            id,
+            Group {
+                keys: vec![key],
+                value,
+            },
+        );
+    }
+
+    /// Adds a new alias key for the element at `key`.
+    ///
+    /// Returns the reference count if the alias is successfully added.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The original key.
+    /// * `alias` - The alias key to add.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::ConcurrentMultiKeyMap;
+    ///
+    /// let map = ConcurrentMultiKeyMap::new();
+    /// map.insert("key1", "value1");
+    /// assert_eq!(map.insert_alias(&"key1", "alias1"), Some(2));
+    /// assert_eq!(map.insert_alias(&"key1", "alias1"), Some(2));
+    /// assert_eq!(map.insert_alias(&"key1", "key1"), None);
+    /// assert_eq!(map.insert_alias(&"key2", "alias2"), None);
+    /// ```
+    pub fn insert_alias(&self, key: &K, alias: K) -> Option<usize> {
+        if key == &alias {
+            // Do not allow aliasing the same key
+            return None;
+        }
+        let (key_shard, alias_shard) = (self.route_shard(key), self.route_shard(&alias));
+        let mut routes = ShardGuards::lock(&self.routes, vec![key_shard, alias_shard]);
+        let id = *routes.get(key_shard).get(key)?;
+        let previous = routes.get(alias_shard).insert(alias.clone(), id);
+        let mut group_shards = vec![self.group_shard(id)];
+        group_shards.extend(previous.map(|previous| self.group_shard(previous)));
+        let mut groups = ShardGuards::lock(&self.groups, group_shards);
+        match previous {
+            Some(previous) if previous == id => {}
+            Some(previous) => {
+                Self::unlink(groups.get(self.group_shard(previous)), previous, &alias);
+                Self::link(groups.get(self.group_shard(id)), id, alias);
+            }
+            None => Self::link(groups.get(self.group_shard(id)), id, alias),
+        }
+        Some(groups.get(self.group_shard(id))[&id].keys.len())
+    }
+
+    /// Removes an alias key.
+    ///
+    /// Returns the reference count if the alias is successfully removed.
+    /// If the last alias is removed, the value is also removed.
+    ///
+    /// # Arguments
+    ///
+    /// * `alias` - The alias key to remove.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::ConcurrentMultiKeyMap;
+    ///
+    /// let map = ConcurrentMultiKeyMap::new();
+    /// map.insert("key1", "value1");
+    /// map.insert_alias(&"key1", "alias1");
+    /// assert_eq!(map.remove_alias(&"alias1"), Some(1));
+    /// assert_eq!(map.remove_alias(&"key1"), Some(0));
+    /// assert!(map.is_empty());
+    /// ```
+    pub fn remove_alias(&self, alias: &K) -> Option<usize> {
+        let mut routes = self.routes[self.route_shard(alias)].write();
+        let id = routes.remove(alias)?;
+        let mut groups = self.groups[self.group_shard(id)].write();
+        Some(Self::unlink(&mut groups, id, alias))
+    }
+
+    /// Removes a value by its key and all its aliases.
+    ///
+    /// Returns the value if it was present.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key of the value to remove.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::ConcurrentMultiKeyMap;
+    ///
+    /// let map = ConcurrentMultiKeyMap::new();
+    /// map.insert("key1", "value1");
+    /// map.insert_alias(&"key1", "alias1");
+    /// map.insert_alias(&"key1", "alias2");
+    /// assert_eq!(map.remove(&"alias1"), Some("value1"));
+    /// assert_eq!(map.remove(&"key1"), None);
+    /// assert!(map.get(&"alias2").is_none());
+    /// assert_eq!(map.key_count(), 0);
+    /// ```
+    pub fn remove(&self, key: &K) -> Option<V> {
+        let key_shard = self.route_shard(key);
+        loop {
+            // Find the route shards of the group's keys, then lock them all in order
+            let (id, route_shards) = {
+                let routes = self.routes[key_shard].read();
+                let id = *routes.get(key)?;
+                let groups = self.groups[self.group_shard(id)].read();
+                let keys = &groups[&id].keys;
+                (id, keys.iter().map(|key| self.route_shard(key)).collect())
+            };
+            let mut routes = ShardGuards::lock(&self.routes, route_shards);
+            // The key and the group may have changed while no lock was held
+            match routes.get(key_shard).get(key) {
+                Some(&current) if current == id => {}
+                Some(_) => continue,
+                None => return None,
+            }
+            let mut groups = self.groups[self.group_shard(id)].write();
+            let group = &groups[&id];
+            if !group
+                .keys
+                .iter()
+                .all(|key| routes.contains(self.route_shard(key)))
+            {
+                continue;
+            }
+            let group = groups.remove(&id).expect("routed group exists");
+            for key in &group.keys {
+                routes.get(self.route_shard(key)).remove(key);
+            }
+            return Some(group.value);
+        }
+    }
+
+    /// Retrieves all aliases of the value that `key` points to.
+    ///
+    /// Returns `None` if the key does not exist.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - A key of the group.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::ConcurrentMultiKeyMap;
+    ///
+    /// let map = ConcurrentMultiKeyMap::new();
+    /// map.insert("key1", "value1");
+    /// map.insert_alias(&"key1", "alias1");
+    /// let mut aliases = map.aliases(&"alias1").unwrap();
+    /// aliases.sort();
+    /// assert_eq!(aliases, vec!["alias1", "key1"]);
+    /// assert_eq!(map.aliases(&"key2"), None);
+    /// ```
+    pub fn aliases(&self, key: &K) -> Option<Vec<K>> {
+        let routes = self.routes[self.route_shard(key)].read();
+        let id = *routes.get(key)?;
+        let groups = self.groups[self.group_shard(id)].read();
+        Some(groups[&id].keys.clone())
+    }
+
+    /// Returns `true` if the map contains a value for the specified key.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key to check.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::ConcurrentMultiKeyMap;
+    ///
+    /// let map = ConcurrentMultiKeyMap::new();
+    /// map.insert("key1", "value1");
+    /// assert!(map.contains_key(&"key1"));
+    /// assert!(!map.contains_key(&"key2"));
+    /// ```
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.routes[self.route_shard(key)].read().contains_key(key)
+    }
+
+    /// Returns the number of groups in the map.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::ConcurrentMultiKeyMap;
+    ///
+    /// let map = ConcurrentMultiKeyMap::new();
+    /// map.insert("key1", "value1");
+    /// map.insert_alias(&"key1", "alias1");
+    /// assert_eq!(map.len(), 1);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.groups.iter().map(|shard| shard.read().len()).sum()
+    }
+
+    /// Returns the number of keys in the map, counting every alias.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::ConcurrentMultiKeyMap;
+    ///
+    /// let map = ConcurrentMultiKeyMap::new();
+    /// map.insert("key1", "value1");
+    /// map.insert_alias(&"key1", "alias1");
+    /// assert_eq!(map.key_count(), 2);
+    /// ```
+    pub fn key_count(&self) -> usize {
+        self.routes.iter().map(|shard| shard.read().len()).sum()
+    }
+
+    /// Returns `true` if the map contains no values.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::ConcurrentMultiKeyMap;
+    ///
+    /// let map: ConcurrentMultiKeyMap<&str, &str> = ConcurrentMultiKeyMap::new();
+    /// assert!(map.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.groups.iter().all(|shard| shard.read().is_empty())
+    }
+
+    /// Verifies the internal consistency of the map, panicking with a description of the first
+    /// violation found.
+    ///
+    /// This locks every shard, so it sees a consistent state even while other threads operate on
+    /// the map. It is intended for tests and is linear in the size of the map.
+    ///
+    /// # Examples
+    ///
+    /// Hammering alias operations on overlapping groups from several threads:
+    ///
+    /// ```
+    /// use multi_key_map::ConcurrentMultiKeyMap;
+    /// use std::sync::Arc;
+    /// use std::thread;
+    ///
+    /// let map = Arc::new(ConcurrentMultiKeyMap::with_shards(4));
+    /// for key in 0..16u64 {
+    ///     map.insert(key, key);
+    /// }
+    /// let handles: Vec<_> = (0..8u64)
+    ///     .map(|thread| {
+    ///         let map = Arc::clone(&map);
+    ///         thread::spawn(move || {
+    ///             let mut state = thread * 7919 + 1;
+    ///             for _ in 0..5_000 {
+    ///                 state ^= state << 13;
+    ///                 state ^= state >> 7;
+    ///                 state ^= state << 17;
+    ///                 let (a, b) = (state % 64, (state >> 8) % 64);
+    ///                 match (state >> 16) % 6 {
+    ///                     0 | 1 => {
+    ///                         map.insert_alias(&a, b);
+    ///                     }
+    ///                     2 => {
+    ///                         map.remove_alias(&a);
+    ///                     }
+    ///                     3 => {
+    ///                         map.remove(&a);
+    ///                     }
+    ///                     4 => map.insert(a, b),
+    ///                     _ => {
+    ///                         if let Some(aliases) = map.aliases(&a) {
+    ///                             assert!(aliases.contains(&a));
+    ///                         }
+    ///                     }
+    ///                 }
+    ///             }
+    ///         })
+    ///     })
+    ///     .collect();
+    /// for handle in handles {
+    ///     handle.join().unwrap();
+    /// }
+    /// map.check_invariants();
+    /// ```
+    pub fn check_invariants(&self) {
+        let routes: Vec<_> = self.routes.iter().map(|shard| shard.read()).collect();
+        let groups: Vec<_> = self.groups.iter().map(|shard| shard.read()).collect();
+        for (shard, routes) in routes.iter().enumerate() {
+            for (key, id) in routes.iter() {
+                assert_eq!(
+                    self.route_shard(key),
+                    shard,
+                    "a key is routed in the wrong shard"
+                );
+                let group = groups[self.group_shard(*id)].get(id);
+                assert!(
+                    group.is_some_and(|group| group.keys.contains(key)),
+                    "a key is routed to group {id}, which does not list it"
+                );
+            }
+        }
+        for (shard, groups) in groups.iter().enumerate() {
+            for (id, group) in groups.iter() {
+                assert_eq!(
+                    self.group_shard(*id),
+                    shard,
+                    "group {id} is in the wrong shard"
+                );
+                assert!(!group.keys.is_empty(), "group {id} has no keys");
+                for key in &group.keys {
+                    assert_eq!(
+                        routes[self.route_shard(key)].get(key),
+                        Some(id),
+                        "group {id} lists a key routed elsewhere"
+                    );
+                }
+            }
+        }
+    }
+
+    /// Adds `key` to the keys of the group `id`.
+    fn link(groups: &mut GroupShard<K, V>, id: u64, key: K) {
+        groups
+            .get_mut(&id)
+            .expect("routed group exists")
+            .keys
+            .push(key);
+    }
+
+    /// Removes `key` from the keys of the group `id`, removing the group once it has no keys.
+    /// Returns the number of remaining keys.
+    fn unlink(groups: &mut GroupShard<K, V>, id: u64, key: &K) -> usize {
+        let keys = &mut groups.get_mut(&id).expect("routed group exists").keys;
+        if let Some(position) = keys.iter().position(|k| k == key) {
+            keys.swap_remove(position);
+        }
+        let remaining = keys.len();
+        if remaining == 0 {
+            groups.remove(&id);
+        }
+        remaining
+    }
+
+    fn route_shard(&self, key: &K) -> usize {
+        (self.hasher.hash_one(key) % self.routes.len() as u64) as usize
+    }
+
+    fn group_shard(&self, id: u64) -> usize {
+        (id % self.groups.len() as u64) as usize
+    }
+}
+
+impl<K: Eq + Hash + Clone, V> Default for ConcurrentMultiKeyMap<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Debug, V: Debug> Debug for ConcurrentMultiKeyMap<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut debug_struct = f.debug_struct("ConcurrentMultiKeyMap");
+        for shard in self.groups.iter() {
+            for group in shard.read().values() {
+                debug_struct.field(&format!("{:?}", group.keys), &group.value);
+            }
+        }
+        debug_struct.finish()
+    }
+}
+
+/// Write guards on a set of shards, acquired in ascending order.
+struct ShardGuards<'a, T> {
+    guards: Vec<(usize, RwLockWriteGuard<'a, T>)>,
+}
+
+impl<'a, T> ShardGuards<'a, T> {
+    fn lock(shards: &'a [RwLock<T>], mut indices: Vec<usize>) -> Self {
+        indices.sort_unstable();
+        indices.dedup();
+        ShardGuards {
+            guards: indices
+                .into_iter()
+                .map(|index| (index, shards[index].write()))
+                .collect(),
+        }
+    }
+
+    fn contains(&self, index: usize) -> bool {
+        self.guards.iter().any(|&(locked, _)| locked == index)
+    }
+
+    fn get(&mut self, index: usize) -> &mut T {
+        self.guards
+            .iter_mut()
+            .find(|(locked, _)| *locked == index)
+            .map(|(_, guard)| &mut **guard)
+            .expect("shard is locked")
+    }
+}
@@ -0,0 +1,27 @@
+use crate::MultiKeyMap;
+use std::hash::Hash;
+
+impl<K: Eq + Hash, V> MultiKeyMap<K, V> {
+    /// Makes the recorded number of groups disagree with the slots, so tests can check that the
+    /// next modification of the map reports the corruption.
+    pub(crate) fn corrupt_len_for_testing(&mut self) {
+        self.len += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::panic::{self, AssertUnwindSafe};
+
+    #[test]
+    fn modification_reports_corrupted_len() {
+        let mut map = MultiKeyMap::new();
+        map.insert("key1", "value1");
+        map.corrupt_len_for_testing();
+
+        let error = panic::catch_unwind(AssertUnwindSafe(|| map.insert("key2", "value2")));
+        let message = error.unwrap_err().downcast::<String>().unwrap();
+        assert!(message.contains("length 3 does not match the 2 occupied slots"));
+    }
+}
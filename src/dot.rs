@@ -0,0 +1,163 @@
+use crate::MultiKeyMap;
+use std::fmt::{Debug, Write};
+use std::hash::Hash;
+
+/// The appearance of a value node in the output of [`MultiKeyMap::to_dot_with`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NodeStyle {
+    /// The text shown in the node.
+    pub label: String,
+    /// The fill color of the node, as any color Graphviz accepts, or `None` for no fill.
+    pub fill_color: Option<String>,
+}
+
+impl NodeStyle {
+    /// Creates a style showing `label` without a fill color.
+    ///
+    /// # Arguments
+    ///
+    /// * `label` - The text shown in the node.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::NodeStyle;
+    ///
+    /// let style = NodeStyle::new("value");
+    /// assert_eq!(style.fill_color, None);
+    /// ```
+    pub fn new(label: impl Into<String>) -> Self {
+        NodeStyle {
+            label: label.into(),
+            fill_color: None,
+        }
+    }
+}
+
+impl<K: Eq + Hash, V> MultiKeyMap<K, V> {
+    /// Renders the alias structure of the map as a Graphviz graph in the DOT language.
+    ///
+    /// Every group becomes a cluster holding a box for its value, labelled with its `Debug`
+    /// representation, and an ellipse for each of its keys with an edge to the value. Groups are
+    /// listed in storage order and keys in the order of [`aliases`](Self::aliases), so the
+    /// output only changes when the map does.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyMap;
+    ///
+    /// let mut map = MultiKeyMap::new();
+    /// map.insert("a", 1);
+    /// map.insert_alias(&"a", "b");
+    /// map.insert("c", 2);
+    ///
+    /// let dot = map.to_dot();
+    /// assert_eq!(
+    ///     dot,
+    ///     r#"digraph {
+    ///     subgraph cluster_0 {
+    ///         "g0" [shape=box, label="1"];
+    ///         "g0k0" [label="\"a\""];
+    ///         "g0k0" -> "g0";
+    ///         "g0k1" [label="\"b\""];
+    ///         "g0k1" -> "g0";
+    ///     }
+    ///     subgraph cluster_1 {
+    ///         "g1" [shape=box, label="2"];
+    ///         "g1k0" [label="\"c\""];
+    ///         "g1k0" -> "g1";
+    ///     }
+    /// }
+    /// "#
+    /// );
+    /// assert_eq!(dot.matches('{').count(), dot.matches('}').count());
+    /// ```
+    pub fn to_dot(&self) -> String
+    where
+        K: Debug,
+        V: Debug,
+    {
+        self.to_dot_with(|_, value| NodeStyle::new(format!("{value:?}")))
+    }
+
+    /// Renders the alias structure of the map as a Graphviz graph, styling the value nodes with
+    /// `style`.
+    ///
+    /// The output has the same structure as that of [`to_dot`](Self::to_dot); `style` receives
+    /// the keys and the value of every group and returns the appearance of its value node.
+    ///
+    /// # Arguments
+    ///
+    /// * `style` - A function that gives the style of the value node of a group.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::{MultiKeyMap, NodeStyle};
+    ///
+    /// let mut map = MultiKeyMap::new();
+    /// map.insert(1, "a \"b\"");
+    /// map.insert_alias(&1, 2);
+    /// map.insert(3, "single");
+    ///
+    /// let dot = map.to_dot_with(|keys, value| NodeStyle {
+    ///     label: value.to_string(),
+    ///     fill_color: (keys.len() > 1).then(|| "gold".to_string()),
+    /// });
+    /// let shared = r#""g0" [shape=box, label="a \"b\"", style=filled, fillcolor="gold"];"#;
+    /// assert!(dot.contains(shared));
+    /// assert!(dot.contains(r#""g1" [shape=box, label="single"];"#));
+    /// assert!(dot.starts_with("digraph {\n") && dot.ends_with("}\n"));
+    /// ```
+    pub fn to_dot_with(&self, mut style: impl FnMut(&[K], &V) -> NodeStyle) -> String
+    where
+        K: Debug,
+    {
+        let mut dot = String::from("digraph {\n");
+        for (group, (index, value)) in self.occupied().enumerate() {
+            let keys = &self.slots[index].keys;
+            let style = style(keys, value);
+            writeln!(dot, "    subgraph cluster_{group} {{").expect("writing to a string succeeds");
+            write!(
+                dot,
+                "        \"g{group}\" [shape=box, label={}",
+                quote(&style.label)
+            )
+            .expect("writing to a string succeeds");
+            if let Some(color) = &style.fill_color {
+                write!(dot, ", style=filled, fillcolor={}", quote(color))
+                    .expect("writing to a string succeeds");
+            }
+            dot.push_str("];\n");
+            for (position, key) in keys.iter().enumerate() {
+                let label = quote(&format!("{key:?}"));
+                writeln!(dot, "        \"g{group}k{position}\" [label={label}];")
+                    .expect("writing to a string succeeds");
+                writeln!(dot, "        \"g{group}k{position}\" -> \"g{group}\";")
+                    .expect("writing to a string succeeds");
+            }
+            dot.push_str("    }\n");
+        }
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+/// Quotes `text` as a DOT string.
+fn quote(text: &str) -> String {
+    let mut quoted = String::with_capacity(text.len() + 2);
+    quoted.push('"');
+    for c in text.chars() {
+        match c {
+            '"' | '\\' => {
+                quoted.push('\\');
+                quoted.push(c);
+            }
+            '\n' => quoted.push_str("\\n"),
+            _ => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
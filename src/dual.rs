@@ -0,0 +1,603 @@
+use crate::slab::Slab;
+use hashbrown::HashTable;
+use std::fmt::{self, Debug};
+use std::hash::{BuildHasher, Hash, RandomState};
+
+/// A `DualKeyMap` addresses every value by one key of type `K1` and any number of keys of type
+/// `K2`.
+///
+/// Each entry has exactly one first key, which owns it, and a list of second keys. Removing an
+/// entry through either kind of key removes all of its keys from both indexes, while
+/// [`remove_second`](Self::remove_second) only unbinds a single second key and leaves the entry
+/// in place. Like in [`MultiKeyMap`](crate::MultiKeyMap), every key is stored exactly once.
+///
+/// # Examples
+///
+/// ```
+/// use multi_key_map::DualKeyMap;
+///
+/// let mut map = DualKeyMap::new();
+/// map.insert(1u64, ["alice".to_string(), "al".to_string()], "Alice");
+/// map.insert(2u64, ["bob".to_string()], "Bob");
+///
+/// assert_eq!(map.get_by_first(&1), Some(&"Alice"));
+/// assert_eq!(map.get_by_second(&"al".to_string()), Some(&"Alice"));
+/// assert_eq!(map.first_of(&"bob".to_string()), Some(&2));
+///
+/// assert_eq!(map.remove_by_second(&"alice".to_string()), Some("Alice"));
+/// assert!(!map.contains_first(&1));
+/// assert!(!map.contains_second(&"al".to_string()));
+/// assert_eq!(map.remove_by_first(&2), Some("Bob"));
+/// assert!(!map.contains_second(&"bob".to_string()));
+/// assert!(map.is_empty());
+/// ```
+pub struct DualKeyMap<K1, K2, V> {
+    /// Locates the entry of every first key.
+    firsts: HashTable<FirstEntry>,
+    /// Locates every second key within the keys of its entry.
+    seconds: HashTable<SecondEntry>,
+    hasher: RandomState,
+    entries: Slab<Entry<K1, K2, V>>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct FirstEntry {
+    hash: u64,
+    index: usize,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct SecondEntry {
+    hash: u64,
+    index: usize,
+    position: usize,
+}
+
+#[derive(Clone)]
+struct Entry<K1, K2, V> {
+    first: K1,
+    seconds: Vec<K2>,
+    value: V,
+}
+
+impl<K1: Eq + Hash, K2: Eq + Hash, V> DualKeyMap<K1, K2, V> {
+    /// Creates an empty `DualKeyMap`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::DualKeyMap;
+    ///
+    /// let map: DualKeyMap<u64, String, &str> = DualKeyMap::new();
+    /// ```
+    pub fn new() -> Self {
+        DualKeyMap {
+            firsts: HashTable::new(),
+            seconds: HashTable::new(),
+            hasher: RandomState::new(),
+            entries: Slab::new(),
+        }
+    }
+
+    /// Retrieves a reference to a value by its first key.
+    ///
+    /// Returns `None` if the key does not exist.
+    ///
+    /// # Arguments
+    ///
+    /// * `first` - The first key to lookup.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::DualKeyMap;
+    ///
+    /// let mut map = DualKeyMap::new();
+    /// map.insert(1, ["one"], "value1");
+    /// assert_eq!(map.get_by_first(&1), Some(&"value1"));
+    /// assert_eq!(map.get_by_first(&2), None);
+    /// ```
+    pub fn get_by_first(&self, first: &K1) -> Option<&V> {
+        let index = self.index_of_first(first)?;
+        Some(&self.entries.get(index).value)
+    }
+
+    /// Retrieves a reference to a value by one of its second keys.
+    ///
+    /// Returns `None` if the key does not exist.
+    ///
+    /// # Arguments
+    ///
+    /// * `second` - The second key to lookup.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::DualKeyMap;
+    ///
+    /// let mut map = DualKeyMap::new();
+    /// map.insert(1, ["one", "uno"], "value1");
+    /// assert_eq!(map.get_by_second(&"uno"), Some(&"value1"));
+    /// assert_eq!(map.get_by_second(&"two"), None);
+    /// ```
+    pub fn get_by_second(&self, second: &K2) -> Option<&V> {
+        let entry = self.find_second(second)?;
+        Some(&self.entries.get(entry.index).value)
+    }
+
+    /// Retrieves a mutable reference to a value by its first key.
+    ///
+    /// Returns `None` if the key does not exist.
+    ///
+    /// # Arguments
+    ///
+    /// * `first` - The first key to lookup.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::DualKeyMap;
+    ///
+    /// let mut map = DualKeyMap::new();
+    /// map.insert(1, ["one"], "value1");
+    /// if let Some(value) = map.get_mut_by_first(&1) {
+    ///     *value = "value2";
+    /// }
+    /// assert_eq!(map.get_by_second(&"one"), Some(&"value2"));
+    /// ```
+    pub fn get_mut_by_first(&mut self, first: &K1) -> Option<&mut V> {
+        let index = self.index_of_first(first)?;
+        Some(&mut self.entries.get_mut(index).value)
+    }
+
+    /// Retrieves a mutable reference to a value by one of its second keys.
+    ///
+    /// Returns `None` if the key does not exist.
+    ///
+    /// # Arguments
+    ///
+    /// * `second` - The second key to lookup.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::DualKeyMap;
+    ///
+    /// let mut map = DualKeyMap::new();
+    /// map.insert(1, ["one"], "value1");
+    /// if let Some(value) = map.get_mut_by_second(&"one") {
+    ///     *value = "value2";
+    /// }
+    /// assert_eq!(map.get_by_first(&1), Some(&"value2"));
+    /// ```
+    pub fn get_mut_by_second(&mut self, second: &K2) -> Option<&mut V> {
+        let index = self.find_second(second)?.index;
+        Some(&mut self.entries.get_mut(index).value)
+    }
+
+    /// Inserts a value with its first key and its second keys.
+    ///
+    /// If the first key already exists, its entry is removed with all of its second keys and its
+    /// value is returned. A second key that already belongs to another entry is moved to the new
+    /// one; the other entry stays, since its first key still points to it.
+    ///
+    /// # Arguments
+    ///
+    /// * `first` - The first key of the value.
+    /// * `seconds` - The second keys of the value.
+    /// * `value` - The value to insert.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::DualKeyMap;
+    ///
+    /// let mut map = DualKeyMap::new();
+    /// assert_eq!(map.insert(1, ["one", "uno"], "value1"), None);
+    /// assert_eq!(map.insert(2, ["two", "uno"], "value2"), None);
+    /// assert_eq!(map.get_by_second(&"uno"), Some(&"value2"));
+    /// assert_eq!(map.aliases_second(&1), Some(&["one"][..]));
+    ///
+    /// assert_eq!(map.insert(1, [], "value3"), Some("value1"));
+    /// assert!(!map.contains_second(&"one"));
+    /// assert_eq!(map.second_count(), 2);
+    /// ```
+    pub fn insert(
+        &mut self,
+        first: K1,
+        seconds: impl IntoIterator<Item = K2>,
+        value: V,
+    ) -> Option<V> {
+        let previous = self.remove_by_first(&first);
+        let hash = self.hasher.hash_one(&first);
+        let index = self.entries.insert(Entry {
+            first,
+            seconds: Vec::new(),
+            value,
+        });
+        self.firsts
+            .insert_unique(hash, FirstEntry { hash, index }, |entry| entry.hash);
+        for second in seconds {
+            self.bind_second(second, index);
+        }
+        previous
+    }
+
+    /// Adds a second key to the entry of `first`.
+    ///
+    /// A second key that already belongs to another entry is moved. Returns the number of second
+    /// keys of the entry if `first` exists.
+    ///
+    /// # Arguments
+    ///
+    /// * `first` - The first key of the entry.
+    /// * `second` - The second key to add.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::DualKeyMap;
+    ///
+    /// let mut map = DualKeyMap::new();
+    /// map.insert(1, ["one"], "value1");
+    /// assert_eq!(map.insert_second(&1, "uno"), Some(2));
+    /// assert_eq!(map.insert_second(&1, "uno"), Some(2));
+    /// assert_eq!(map.insert_second(&2, "two"), None);
+    /// assert_eq!(map.get_by_second(&"uno"), Some(&"value1"));
+    /// ```
+    pub fn insert_second(&mut self, first: &K1, second: K2) -> Option<usize> {
+        let index = self.index_of_first(first)?;
+        self.bind_second(second, index);
+        Some(self.entries.get(index).seconds.len())
+    }
+
+    /// Removes a single second key, leaving its entry in place.
+    ///
+    /// Returns the number of second keys left in the entry if the key existed.
+    ///
+    /// # Arguments
+    ///
+    /// * `second` - The second key to remove.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::DualKeyMap;
+    ///
+    /// let mut map = DualKeyMap::new();
+    /// map.insert(1, ["one"], "value1");
+    /// assert_eq!(map.remove_second(&"one"), Some(0));
+    /// assert_eq!(map.remove_second(&"one"), None);
+    /// assert_eq!(map.get_by_first(&1), Some(&"value1"));
+    /// ```
+    pub fn remove_second(&mut self, second: &K2) -> Option<usize> {
+        let entry = *self.find_second(second)?;
+        self.unlink_second(entry.index, entry.position);
+        Some(self.entries.get(entry.index).seconds.len())
+    }
+
+    /// Removes a value by its first key, along with all of its second keys.
+    ///
+    /// Returns the value if it was present.
+    ///
+    /// # Arguments
+    ///
+    /// * `first` - The first key of the value to remove.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::DualKeyMap;
+    ///
+    /// let mut map = DualKeyMap::new();
+    /// map.insert(1, ["one", "uno"], "value1");
+    /// assert_eq!(map.remove_by_first(&1), Some("value1"));
+    /// assert_eq!(map.remove_by_first(&1), None);
+    /// assert_eq!(map.get_by_second(&"uno"), None);
+    /// assert_eq!(map.second_count(), 0);
+    /// ```
+    pub fn remove_by_first(&mut self, first: &K1) -> Option<V> {
+        let index = self.index_of_first(first)?;
+        Some(self.remove_entry(index))
+    }
+
+    /// Removes a value by one of its second keys, along with its first key and all of its second
+    /// keys.
+    ///
+    /// Returns the value if it was present.
+    ///
+    /// # Arguments
+    ///
+    /// * `second` - A second key of the value to remove.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::DualKeyMap;
+    ///
+    /// let mut map = DualKeyMap::new();
+    /// map.insert(1, ["one", "uno"], "value1");
+    /// map.insert(2, ["two"], "value2");
+    /// assert_eq!(map.remove_by_second(&"one"), Some("value1"));
+    /// assert_eq!(map.get_by_first(&1), None);
+    /// assert_eq!(map.get_by_second(&"uno"), None);
+    /// assert_eq!(map.get_by_second(&"two"), Some(&"value2"));
+    /// assert_eq!((map.len(), map.second_count()), (1, 1));
+    /// ```
+    pub fn remove_by_second(&mut self, second: &K2) -> Option<V> {
+        let index = self.find_second(second)?.index;
+        Some(self.remove_entry(index))
+    }
+
+    /// Returns the second keys of the entry of `first`, in the order they were added, except
+    /// that removing a second key moves the last one into its place.
+    ///
+    /// Returns `None` if the first key does not exist.
+    ///
+    /// # Arguments
+    ///
+    /// * `first` - The first key of the entry.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::DualKeyMap;
+    ///
+    /// let mut map = DualKeyMap::new();
+    /// map.insert(1, ["one", "uno"], "value1");
+    /// map.insert(2, [], "value2");
+    /// assert_eq!(map.aliases_second(&1), Some(&["one", "uno"][..]));
+    /// assert_eq!(map.aliases_second(&2), Some(&[][..]));
+    /// assert_eq!(map.aliases_second(&3), None);
+    /// ```
+    pub fn aliases_second(&self, first: &K1) -> Option<&[K2]> {
+        let index = self.index_of_first(first)?;
+        Some(&self.entries.get(index).seconds)
+    }
+
+    /// Returns the first key of the entry a second key belongs to.
+    ///
+    /// Returns `None` if the second key does not exist.
+    ///
+    /// # Arguments
+    ///
+    /// * `second` - A second key of the entry.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::DualKeyMap;
+    ///
+    /// let mut map = DualKeyMap::new();
+    /// map.insert(1, ["one", "uno"], "value1");
+    /// assert_eq!(map.first_of(&"uno"), Some(&1));
+    /// assert_eq!(map.first_of(&"two"), None);
+    /// ```
+    pub fn first_of(&self, second: &K2) -> Option<&K1> {
+        let entry = self.find_second(second)?;
+        Some(&self.entries.get(entry.index).first)
+    }
+
+    /// Returns `true` if the map contains a value for the specified first key.
+    ///
+    /// # Arguments
+    ///
+    /// * `first` - The first key to check.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::DualKeyMap;
+    ///
+    /// let mut map = DualKeyMap::new();
+    /// map.insert(1, ["one"], "value1");
+    /// assert!(map.contains_first(&1));
+    /// assert!(!map.contains_first(&2));
+    /// ```
+    pub fn contains_first(&self, first: &K1) -> bool {
+        self.index_of_first(first).is_some()
+    }
+
+    /// Returns `true` if the map contains a value for the specified second key.
+    ///
+    /// # Arguments
+    ///
+    /// * `second` - The second key to check.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::DualKeyMap;
+    ///
+    /// let mut map = DualKeyMap::new();
+    /// map.insert(1, ["one"], "value1");
+    /// assert!(map.contains_second(&"one"));
+    /// assert!(!map.contains_second(&"two"));
+    /// ```
+    pub fn contains_second(&self, second: &K2) -> bool {
+        self.find_second(second).is_some()
+    }
+
+    /// Returns an iterator over the entries, each given as its first key, its second keys and its
+    /// value, in no particular order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::DualKeyMap;
+    ///
+    /// let mut map = DualKeyMap::new();
+    /// map.insert(1, ["one", "uno"], "value1");
+    /// map.insert(2, ["two"], "value2");
+    /// let mut entries: Vec<_> = map.iter().collect();
+    /// entries.sort();
+    /// assert_eq!(
+    ///     entries,
+    ///     vec![(&1, &["one", "uno"][..], &"value1"), (&2, &["two"][..], &"value2")]
+    /// );
+    /// ```
+    pub fn iter(&self) -> impl Iterator<Item = (&K1, &[K2], &V)> + '_ {
+        self.firsts.iter().map(|entry| {
+            let entry = self.entries.get(entry.index);
+            (&entry.first, &entry.seconds[..], &entry.value)
+        })
+    }
+
+    /// Returns the number of values in the map, which is also the number of first keys.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::DualKeyMap;
+    ///
+    /// let mut map = DualKeyMap::new();
+    /// map.insert(1, ["one", "uno"], "value1");
+    /// assert_eq!(map.len(), 1);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns the number of second keys in the map.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::DualKeyMap;
+    ///
+    /// let mut map = DualKeyMap::new();
+    /// map.insert(1, ["one", "uno"], "value1");
+    /// assert_eq!(map.second_count(), 2);
+    /// ```
+    pub fn second_count(&self) -> usize {
+        self.seconds.len()
+    }
+
+    /// Returns `true` if the map contains no values.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::DualKeyMap;
+    ///
+    /// let mut map: DualKeyMap<u64, &str, &str> = DualKeyMap::new();
+    /// assert!(map.is_empty());
+    /// map.insert(1, [], "value1");
+    /// assert!(!map.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.entries.len() == 0
+    }
+
+    fn index_of_first(&self, first: &K1) -> Option<usize> {
+        let hash = self.hasher.hash_one(first);
+        self.firsts
+            .find(hash, |entry| {
+                entry.hash == hash && self.entries.get(entry.index).first == *first
+            })
+            .map(|entry| entry.index)
+    }
+
+    fn find_second(&self, second: &K2) -> Option<&SecondEntry> {
+        let hash = self.hasher.hash_one(second);
+        self.seconds.find(hash, |entry| {
+            entry.hash == hash && self.entries.get(entry.index).seconds[entry.position] == *second
+        })
+    }
+
+    /// Binds `second` to the entry at `index`, unbinding it from the entry it belonged to.
+    fn bind_second(&mut self, second: K2, index: usize) {
+        if let Some(entry) = self.find_second(&second).copied() {
+            if entry.index == index {
+                return;
+            }
+            self.unlink_second(entry.index, entry.position);
+        }
+        let hash = self.hasher.hash_one(&second);
+        let seconds = &mut self.entries.get_mut(index).seconds;
+        let entry = SecondEntry {
+            hash,
+            index,
+            position: seconds.len(),
+        };
+        seconds.push(second);
+        self.seconds.insert_unique(hash, entry, |entry| entry.hash);
+    }
+
+    /// Unbinds the second key at `position` in the entry at `index` and returns it.
+    ///
+    /// The last second key of the entry takes its position.
+    fn unlink_second(&mut self, index: usize, position: usize) -> K2 {
+        let hash = self
+            .hasher
+            .hash_one(&self.entries.get(index).seconds[position]);
+        self.seconds
+            .find_entry(hash, |entry| {
+                entry.index == index && entry.position == position
+            })
+            .expect("listed second key is indexed")
+            .remove();
+        let seconds = &mut self.entries.get_mut(index).seconds;
+        let second = seconds.swap_remove(position);
+        if let Some(moved) = seconds.get(position) {
+            let last = seconds.len();
+            let hash = self.hasher.hash_one(moved);
+            self.seconds
+                .find_mut(hash, |entry| entry.index == index && entry.position == last)
+                .expect("listed second key is indexed")
+                .position = position;
+        }
+        second
+    }
+
+    /// Removes the entry at `index` and all of its keys from both indexes, returning its value.
+    fn remove_entry(&mut self, index: usize) -> V {
+        let entry = self.entries.remove(index);
+        self.firsts
+            .find_entry(self.hasher.hash_one(&entry.first), |first| {
+                first.index == index
+            })
+            .expect("first key is indexed")
+            .remove();
+        for (position, second) in entry.seconds.iter().enumerate() {
+            self.seconds
+                .find_entry(self.hasher.hash_one(second), |entry| {
+                    entry.index == index && entry.position == position
+                })
+                .expect("listed second key is indexed")
+                .remove();
+        }
+        entry.value
+    }
+}
+
+impl<K1: Eq + Hash, K2: Eq + Hash, V> Default for DualKeyMap<K1, K2, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K1: Debug, K2: Debug, V: Debug> Debug for DualKeyMap<K1, K2, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut debug_struct = f.debug_struct("DualKeyMap");
+        for entry in self.firsts.iter() {
+            let entry = self.entries.get(entry.index);
+            debug_struct.field(
+                &format!("{:?} {:?}", entry.first, entry.seconds),
+                &entry.value,
+            );
+        }
+        debug_struct.finish()
+    }
+}
+
+impl<K1: Clone, K2: Clone, V: Clone> Clone for DualKeyMap<K1, K2, V> {
+    fn clone(&self) -> Self {
+        DualKeyMap {
+            firsts: self.firsts.clone(),
+            seconds: self.seconds.clone(),
+            hasher: self.hasher.clone(),
+            entries: self.entries.clone(),
+        }
+    }
+}
@@ -0,0 +1,315 @@
+#[cfg(feature = "borsh")]
+use crate::LoadError;
+use std::error;
+use std::fmt::{self, Debug, Display};
+
+/// Any error returned by a [`MultiKeyMap`](crate::MultiKeyMap).
+///
+/// Every specific error converts into this type, so code handling errors of several methods can
+/// propagate them all with `?`. Its [`Display`] output is that of the specific error.
+///
+/// # Examples
+///
+/// ```
+/// use multi_key_map::{AliasLimitExceeded, CollisionPolicy, Error, MultiKeyMap};
+///
+/// fn build() -> Result<MultiKeyMap<usize, u32>, Error<usize>> {
+///     let mut map = MultiKeyMap::with_alias_limit(2);
+///     map.insert("one", 1);
+///     map.try_insert_alias(&"one", "uno")?;
+///     map.insert("two", 2);
+///     Ok(map.map_keys(|key| key.len(), CollisionPolicy::Error)?)
+/// }
+///
+/// let error = build().unwrap_err();
+/// assert_eq!(error.to_string(), "key 3 is claimed by more than one group");
+/// assert!(matches!(error, Error::KeyCollision(ref collision) if collision.key == 3));
+///
+/// let error = Error::<&str>::from(AliasLimitExceeded { limit: 2 });
+/// assert_eq!(error.to_string(), "a group may have at most 2 keys");
+/// assert!(matches!(error, Error::AliasLimitExceeded(AliasLimitExceeded { limit: 2 })));
+/// ```
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error<K> {
+    /// Keys of different groups converted to the same key.
+    KeyCollision(KeyCollisionError<K>),
+    /// Binding keys would take a group past the alias limit of the map.
+    AliasLimitExceeded(AliasLimitExceeded),
+    /// Inserting the keys extracted from a value failed.
+    InsertKeys(InsertKeysError<K>),
+    /// Removing an alias would remove the last key of its group.
+    LastKey(LastKeyError),
+    /// Rewriting the keys of a group failed.
+    Rekey(RekeyError<K>),
+    /// Renaming a batch of keys failed.
+    RenameBatch(RenameBatchError<K>),
+    /// Loading a saved map failed.
+    ///
+    /// This variant is only available with the `borsh` feature.
+    #[cfg(feature = "borsh")]
+    Load(LoadError),
+}
+
+impl<K: Debug> Display for Error<K> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::KeyCollision(error) => Display::fmt(error, f),
+            Error::AliasLimitExceeded(error) => Display::fmt(error, f),
+            Error::InsertKeys(error) => Display::fmt(error, f),
+            Error::LastKey(error) => Display::fmt(error, f),
+            Error::Rekey(error) => Display::fmt(error, f),
+            Error::RenameBatch(error) => Display::fmt(error, f),
+            #[cfg(feature = "borsh")]
+            Error::Load(error) => Display::fmt(error, f),
+        }
+    }
+}
+
+impl<K: Debug> error::Error for Error<K> {
+    /// Returns the source of the specific error.
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Error::KeyCollision(_)
+            | Error::AliasLimitExceeded(_)
+            | Error::InsertKeys(_)
+            | Error::LastKey(_)
+            | Error::Rekey(_)
+            | Error::RenameBatch(_) => None,
+            #[cfg(feature = "borsh")]
+            Error::Load(error) => error.source(),
+        }
+    }
+}
+
+impl<K> From<KeyCollisionError<K>> for Error<K> {
+    fn from(error: KeyCollisionError<K>) -> Self {
+        Error::KeyCollision(error)
+    }
+}
+
+impl<K> From<AliasLimitExceeded> for Error<K> {
+    fn from(error: AliasLimitExceeded) -> Self {
+        Error::AliasLimitExceeded(error)
+    }
+}
+
+impl<K> From<InsertKeysError<K>> for Error<K> {
+    fn from(error: InsertKeysError<K>) -> Self {
+        Error::InsertKeys(error)
+    }
+}
+
+impl<K> From<LastKeyError> for Error<K> {
+    fn from(error: LastKeyError) -> Self {
+        Error::LastKey(error)
+    }
+}
+
+impl<K> From<RekeyError<K>> for Error<K> {
+    fn from(error: RekeyError<K>) -> Self {
+        Error::Rekey(error)
+    }
+}
+
+impl<K> From<RenameBatchError<K>> for Error<K> {
+    fn from(error: RenameBatchError<K>) -> Self {
+        Error::RenameBatch(error)
+    }
+}
+
+#[cfg(feature = "borsh")]
+impl<K> From<LoadError> for Error<K> {
+    /// Wraps `error`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::{Error, LoadError, MultiKeyMap};
+    /// use std::error::Error as _;
+    ///
+    /// let error: Error<String> = MultiKeyMap::<String, u32>::read_from(&b"MKMP"[..])
+    ///     .unwrap_err()
+    ///     .into();
+    /// assert!(matches!(error, Error::Load(LoadError::NotAMap)));
+    /// assert_eq!(error.to_string(), "the input is not a saved map");
+    /// assert!(error.source().is_none());
+    ///
+    /// let error = Error::<String>::from(LoadError::Io(std::io::ErrorKind::Other.into()));
+    /// assert_eq!(error.source().unwrap().to_string(), "other error");
+    /// ```
+    fn from(error: LoadError) -> Self {
+        Error::Load(error)
+    }
+}
+
+/// The error returned by [`MultiKeyMap::map_keys`](crate::MultiKeyMap::map_keys) when keys of
+/// different groups collide.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyCollisionError<K> {
+    /// The key that more than one group converted to.
+    pub key: K,
+}
+
+impl<K: Debug> Display for KeyCollisionError<K> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "key {:?} is claimed by more than one group", self.key)
+    }
+}
+
+impl<K: Debug> error::Error for KeyCollisionError<K> {}
+
+/// The error returned when binding keys would take a group of a
+/// [`MultiKeyMap`](crate::MultiKeyMap) past its alias limit, as set by
+/// [`MultiKeyMap::with_alias_limit`](crate::MultiKeyMap::with_alias_limit).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AliasLimitExceeded {
+    /// The maximum number of keys of a group.
+    pub limit: usize,
+}
+
+impl Display for AliasLimitExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "a group may have at most {} keys", self.limit)
+    }
+}
+
+impl error::Error for AliasLimitExceeded {}
+
+/// The error returned by [`MultiKeyMap::insert_keyed`](crate::MultiKeyMap::insert_keyed) and
+/// [`MultiKeyMap::refresh_keys`](crate::MultiKeyMap::refresh_keys) when the keys extracted from a
+/// value cannot be bound to its group.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InsertKeysError<K> {
+    /// The value has no keys.
+    NoKeys,
+    /// The key already belongs to another group.
+    KeyTaken(K),
+    /// The keys do not fit the alias limit of the map.
+    AliasLimitExceeded(AliasLimitExceeded),
+}
+
+impl<K: Debug> Display for InsertKeysError<K> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InsertKeysError::NoKeys => f.write_str("the value has no keys"),
+            InsertKeysError::KeyTaken(key) => {
+                write!(f, "key {key:?} already belongs to another group")
+            }
+            InsertKeysError::AliasLimitExceeded(error) => Display::fmt(error, f),
+        }
+    }
+}
+
+impl<K: Debug> error::Error for InsertKeysError<K> {}
+
+impl<K> From<AliasLimitExceeded> for InsertKeysError<K> {
+    fn from(error: AliasLimitExceeded) -> Self {
+        InsertKeysError::AliasLimitExceeded(error)
+    }
+}
+
+/// The error returned by
+/// [`MultiKeyMap::remove_alias_keep_value`](crate::MultiKeyMap::remove_alias_keep_value) when the
+/// alias is the only key of its group.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LastKeyError;
+
+impl Display for LastKeyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("the last key of a group can only be removed with its value")
+    }
+}
+
+impl error::Error for LastKeyError {}
+
+/// The error returned by [`MultiKeyMap::rekey_group`](crate::MultiKeyMap::rekey_group) when the
+/// keys of a group cannot be rewritten.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RekeyError<K> {
+    /// The key does not exist.
+    NotFound,
+    /// The new key already belongs to another group.
+    KeyTaken(K),
+    /// More than one key of the group was rewritten to this key.
+    DuplicateKey(K),
+}
+
+impl<K: Debug> Display for RekeyError<K> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RekeyError::NotFound => f.write_str("the key does not exist"),
+            RekeyError::KeyTaken(key) => {
+                write!(f, "key {key:?} already belongs to another group")
+            }
+            RekeyError::DuplicateKey(key) => {
+                write!(f, "more than one key of the group was rewritten to {key:?}")
+            }
+        }
+    }
+}
+
+impl<K: Debug> error::Error for RekeyError<K> {}
+
+/// The error returned by [`MultiKeyMap::apply_renames`](crate::MultiKeyMap::apply_renames) when
+/// the renames cannot all apply.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RenameBatchError<K> {
+    /// The new name is a key that exists and is not renamed itself.
+    TargetTaken(K),
+    /// More than one key is renamed to this key.
+    DuplicateTarget(K),
+    /// This key of the renames is the same key as another one once normalized.
+    DuplicateSource(K),
+}
+
+impl<K: Debug> Display for RenameBatchError<K> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RenameBatchError::TargetTaken(key) => {
+                write!(f, "key {key:?} already exists and is not renamed")
+            }
+            RenameBatchError::DuplicateTarget(key) => {
+                write!(f, "more than one key is renamed to {key:?}")
+            }
+            RenameBatchError::DuplicateSource(key) => {
+                write!(f, "key {key:?} is renamed more than once")
+            }
+        }
+    }
+}
+
+impl<K: Debug> error::Error for RenameBatchError<K> {}
+
+/// The pairs [`MultiKeyMap::try_extend`](crate::MultiKeyMap::try_extend) did not insert, and why.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExtendReport<K, V> {
+    /// The number of pairs inserted.
+    pub inserted: usize,
+    /// The pairs not inserted, in the order of the batch, with the reason of each.
+    pub rejected: Vec<(K, V, RejectReason)>,
+}
+
+impl<K, V> Display for ExtendReport<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} of {} pairs were rejected",
+            self.rejected.len(),
+            self.inserted + self.rejected.len()
+        )
+    }
+}
+
+impl<K: Debug, V: Debug> error::Error for ExtendReport<K, V> {}
+
+/// Why [`MultiKeyMap::try_extend`](crate::MultiKeyMap::try_extend) did not insert a pair.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RejectReason {
+    /// The key was already bound in the map.
+    KeyBound,
+    /// An earlier pair of the batch has the same key.
+    DuplicateKey,
+    /// The pair has no conflict, but another pair of an atomic batch has one.
+    BatchRejected,
+}
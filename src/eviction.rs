@@ -0,0 +1,241 @@
+use crate::GroupId;
+use std::collections::{BTreeMap, HashMap};
+
+/// Marks the end of the recency list.
+const NIL: usize = usize::MAX;
+
+/// Decides which group a [`MultiKeyLruMap`](crate::MultiKeyLruMap) evicts when it grows beyond
+/// its capacity.
+///
+/// The map reports the life of every group to its policy, identified by an opaque [`GroupId`]:
+/// its insertion, every access, and its removal, including the removal of the victims the
+/// policy picks. A policy never sees keys or values.
+///
+/// # Examples
+///
+/// A policy evicting the oldest group, however often it is used:
+///
+/// ```
+/// use multi_key_map::{EvictionPolicy, GroupId, MultiKeyLruMap};
+/// use std::collections::VecDeque;
+///
+/// #[derive(Default)]
+/// struct Fifo(VecDeque<GroupId>);
+///
+/// impl EvictionPolicy for Fifo {
+///     fn on_insert(&mut self, id: GroupId) {
+///         self.0.push_back(id);
+///     }
+///
+///     fn on_access(&mut self, _id: GroupId) {}
+///
+///     fn on_remove(&mut self, id: GroupId) {
+///         self.0.retain(|&other| other != id);
+///     }
+///
+///     fn pick_victim(&mut self) -> Option<GroupId> {
+///         self.0.front().copied()
+///     }
+/// }
+///
+/// let mut cache = MultiKeyLruMap::with_policy(2, Fifo::default());
+/// cache.insert("a", 1);
+/// cache.insert("b", 2);
+/// cache.get(&"a");
+/// assert_eq!(cache.insert("c", 3), Some((vec!["a"], 1)));
+/// ```
+pub trait EvictionPolicy {
+    /// Records a new group.
+    fn on_insert(&mut self, id: GroupId);
+
+    /// Records an access to the group `id`.
+    fn on_access(&mut self, id: GroupId);
+
+    /// Forgets the group `id`, which has been removed from the map.
+    fn on_remove(&mut self, id: GroupId);
+
+    /// Returns the group to evict next, without forgetting it.
+    ///
+    /// The group must be one the policy was told about and not told to forget. The policy must
+    /// pick a group as long as it knows of any.
+    fn pick_victim(&mut self) -> Option<GroupId>;
+}
+
+/// The policy evicting the least recently used group.
+///
+/// The groups are kept in a list threaded through a table indexed like the storage of the map, so
+/// every operation is `O(1)`.
+#[derive(Debug, Clone)]
+pub struct Lru {
+    /// The neighbours of every known group in the recency list, indexed like the slots.
+    links: Vec<Link>,
+    /// The most recently used slot.
+    head: usize,
+    /// The least recently used slot.
+    tail: usize,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Link {
+    /// The more recently used neighbour.
+    prev: usize,
+    /// The less recently used neighbour.
+    next: usize,
+    /// The generation of the group in the slot.
+    generation: u64,
+}
+
+impl Lru {
+    /// Creates a policy knowing of no group.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::{Lru, MultiKeyLruMap};
+    ///
+    /// let mut cache = MultiKeyLruMap::with_policy(1, Lru::new());
+    /// cache.insert("a", 1);
+    /// assert_eq!(cache.insert("b", 2), Some((vec!["a"], 1)));
+    /// ```
+    pub fn new() -> Self {
+        Lru {
+            links: Vec::new(),
+            head: NIL,
+            tail: NIL,
+        }
+    }
+
+    /// Puts the group `id`, which is not in the recency list, at its front.
+    fn link_front(&mut self, id: GroupId) {
+        let index = id.index;
+        if self.links.len() <= index {
+            self.links.resize(
+                index + 1,
+                Link {
+                    prev: NIL,
+                    next: NIL,
+                    generation: 0,
+                },
+            );
+        }
+        self.links[index] = Link {
+            prev: NIL,
+            next: self.head,
+            generation: id.generation,
+        };
+        match self.head {
+            NIL => self.tail = index,
+            head => self.links[head].prev = index,
+        }
+        self.head = index;
+    }
+
+    /// Takes the slot at `index` out of the recency list.
+    fn unlink(&mut self, index: usize) {
+        let Link { prev, next, .. } = self.links[index];
+        match prev {
+            NIL => self.head = next,
+            prev => self.links[prev].next = next,
+        }
+        match next {
+            NIL => self.tail = prev,
+            next => self.links[next].prev = prev,
+        }
+    }
+}
+
+impl Default for Lru {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl EvictionPolicy for Lru {
+    fn on_insert(&mut self, id: GroupId) {
+        self.link_front(id);
+    }
+
+    fn on_access(&mut self, id: GroupId) {
+        if self.head != id.index {
+            self.unlink(id.index);
+            self.link_front(id);
+        }
+    }
+
+    fn on_remove(&mut self, id: GroupId) {
+        self.unlink(id.index);
+    }
+
+    fn pick_victim(&mut self) -> Option<GroupId> {
+        (self.tail != NIL).then(|| GroupId {
+            index: self.tail,
+            generation: self.links[self.tail].generation,
+        })
+    }
+}
+
+/// The policy evicting the least frequently used group, and the least recently used one among
+/// groups used equally often.
+///
+/// Every insertion and access counts as a use. Accesses and evictions take `O(log n)` time.
+#[derive(Debug, Clone, Default)]
+pub struct Lfu {
+    /// The use count and latest use of every known group.
+    uses: HashMap<GroupId, (u64, u64)>,
+    /// The known groups, ordered by use count and then latest use.
+    order: BTreeMap<(u64, u64), GroupId>,
+    /// The number of uses recorded, which orders the latest uses.
+    clock: u64,
+}
+
+impl Lfu {
+    /// Creates a policy knowing of no group.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::{Lfu, MultiKeyLruMap};
+    ///
+    /// let mut cache = MultiKeyLruMap::with_policy(2, Lfu::new());
+    /// cache.insert("a", 1);
+    /// cache.insert("b", 2);
+    /// cache.get(&"a");
+    /// cache.get(&"a");
+    /// cache.get(&"b");
+    /// assert_eq!(cache.insert("c", 3), Some((vec!["b"], 2)));
+    /// ```
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records a use of the group `id`, which was used `count` times before.
+    fn record(&mut self, id: GroupId, count: u64) {
+        self.clock += 1;
+        let rank = (count + 1, self.clock);
+        self.uses.insert(id, rank);
+        self.order.insert(rank, id);
+    }
+}
+
+impl EvictionPolicy for Lfu {
+    fn on_insert(&mut self, id: GroupId) {
+        self.record(id, 0);
+    }
+
+    fn on_access(&mut self, id: GroupId) {
+        if let Some(rank) = self.uses.remove(&id) {
+            self.order.remove(&rank);
+            self.record(id, rank.0);
+        }
+    }
+
+    fn on_remove(&mut self, id: GroupId) {
+        if let Some(rank) = self.uses.remove(&id) {
+            self.order.remove(&rank);
+        }
+    }
+
+    fn pick_victim(&mut self) -> Option<GroupId> {
+        self.order.first_key_value().map(|(_, &id)| id)
+    }
+}
@@ -0,0 +1,235 @@
+use crate::MultiKeyMap;
+use std::fmt::{self, Debug, Display};
+use std::hash::Hash;
+
+/// The number of sibling keys a [`LookupTrace`] lists at most.
+const SIBLING_SAMPLE: usize = 8;
+
+/// How a lookup of a key in a [`MultiKeyMap`] resolved, as returned by [`MultiKeyMap::explain`].
+///
+/// Its [`Display`] output is a summary over a few lines, meant to be pasted into bug reports.
+///
+/// # Examples
+///
+/// ```
+/// use multi_key_map::MultiKeyMap;
+///
+/// let mut map = MultiKeyMap::new();
+/// map.insert_with_keys(["a", "b", "c"], 1).unwrap();
+///
+/// let trace = map.explain(&"b");
+/// assert!(trace.found() && trace.is_consistent());
+/// assert_eq!(
+///     trace.to_string(),
+///     "lookup of \"b\": found in slot 0\n\
+///      group: 3 keys, siblings \"a\", \"c\"\n\
+///      invariants: hold"
+/// );
+/// assert_eq!(map.explain(&"x").to_string(), "lookup of \"x\": not found");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LookupTrace<K> {
+    /// The key looked up, as given.
+    pub key: K,
+    /// The index of the slot the key resolves to, or `None` if it was not found.
+    pub slot: Option<usize>,
+    /// The number of keys of the group, including the key itself, or `0` if it was not found.
+    pub group_size: usize,
+    /// The other keys of the group, in slot order, at most eight of them.
+    pub siblings: Vec<K>,
+    /// The broken invariants around the key and its slot, empty if they all hold.
+    pub problems: Vec<String>,
+}
+
+impl<K> LookupTrace<K> {
+    /// Returns `true` if the key was found.
+    pub fn found(&self) -> bool {
+        self.slot.is_some()
+    }
+
+    /// Returns `true` if no broken invariant was found around the key and its slot.
+    pub fn is_consistent(&self) -> bool {
+        self.problems.is_empty()
+    }
+}
+
+impl<K: Debug> Display for LookupTrace<K> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "lookup of {:?}: ", self.key)?;
+        match self.slot {
+            Some(slot) => {
+                writeln!(f, "found in slot {slot}")?;
+                let noun = if self.group_size == 1 { "key" } else { "keys" };
+                write!(f, "group: {} {noun}, siblings ", self.group_size)?;
+                if self.siblings.is_empty() {
+                    f.write_str("none")?;
+                }
+                for (position, sibling) in self.siblings.iter().enumerate() {
+                    if position > 0 {
+                        f.write_str(", ")?;
+                    }
+                    write!(f, "{sibling:?}")?;
+                }
+                let unlisted = self.group_size.saturating_sub(self.siblings.len() + 1);
+                if unlisted > 0 {
+                    write!(f, " and {unlisted} more")?;
+                }
+                f.write_str("\ninvariants: ")?;
+                if self.problems.is_empty() {
+                    return f.write_str("hold");
+                }
+            }
+            None => {
+                f.write_str("not found")?;
+                if self.problems.is_empty() {
+                    return Ok(());
+                }
+                f.write_str("\ninvariants: ")?;
+            }
+        }
+        f.write_str("violated")?;
+        for problem in &self.problems {
+            write!(f, "\n  - {problem}")?;
+        }
+        Ok(())
+    }
+}
+
+impl<K: Eq + Hash + Clone, V> MultiKeyMap<K, V> {
+    /// Looks up `key` and reports how it resolved, checking the invariants around its slot on
+    /// the way.
+    ///
+    /// Unlike the other lookups, this does not trust the key index: entries that point past the
+    /// slots or their keys are reported instead of panicking. A key that is found has its slot
+    /// checked for holding a value and for every one of its keys being indexed at its position.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key to lookup.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyMap;
+    ///
+    /// let mut map = MultiKeyMap::new();
+    /// map.insert(100, 0);
+    /// map.insert_with_keys(0..12, 1).unwrap();
+    /// map.remove_alias(&3);
+    ///
+    /// let trace = map.explain(&0);
+    /// assert_eq!((trace.slot, trace.group_size, trace.siblings.len()), (Some(1), 11, 8));
+    /// assert_eq!(
+    ///     trace.to_string(),
+    ///     "lookup of 0: found in slot 1\n\
+    ///      group: 11 keys, siblings 1, 2, 11, 4, 5, 6, 7, 8 and 2 more\n\
+    ///      invariants: hold"
+    /// );
+    /// ```
+    pub fn explain(&self, key: &K) -> LookupTrace<K> {
+        let normalized: K;
+        let probe = match &self.normalizer {
+            Some(normalize) => {
+                normalized = normalize(key);
+                &normalized
+            }
+            None => key,
+        };
+        let hash = self.hash(probe);
+        let mut problems = Vec::new();
+        let mut found = None;
+        for entry in self.key_index.iter_hash(hash) {
+            if entry.hash != hash {
+                continue;
+            }
+            let Some(slot) = self.slots.get(entry.slot) else {
+                problems.push(format!(
+                    "an index entry with the hash of the key points to slot {}, past the {} slots",
+                    entry.slot,
+                    self.slots.len()
+                ));
+                continue;
+            };
+            match slot.keys.get(entry.position) {
+                Some(stored) if stored == probe => found = Some(*entry),
+                Some(_) => {}
+                None => problems.push(format!(
+                    "an index entry with the hash of the key points to key {} of slot {}, which \
+                     has {} keys",
+                    entry.position,
+                    entry.slot,
+                    slot.keys.len()
+                )),
+            }
+        }
+
+        let Some(entry) = found else {
+            return LookupTrace {
+                key: key.clone(),
+                slot: None,
+                group_size: 0,
+                siblings: Vec::new(),
+                problems,
+            };
+        };
+        let slot = &self.slots[entry.slot];
+        if slot.value.is_none() {
+            problems.push(format!("slot {} lists keys but holds no value", entry.slot));
+        }
+        for (position, sibling) in slot.keys.iter().enumerate() {
+            let indexed = self.key_index.find(self.hash(sibling), |other| {
+                other.slot == entry.slot && other.position == position
+            });
+            if indexed.is_none() {
+                problems.push(format!(
+                    "key {position} of slot {} is not indexed",
+                    entry.slot
+                ));
+            }
+        }
+        LookupTrace {
+            key: key.clone(),
+            slot: Some(entry.slot),
+            group_size: slot.keys.len(),
+            siblings: slot
+                .keys
+                .iter()
+                .enumerate()
+                .filter(|&(position, _)| position != entry.position)
+                .map(|(_, sibling)| sibling.clone())
+                .take(SIBLING_SAMPLE)
+                .collect(),
+            problems,
+        }
+    }
+
+    /// Drops the value of the group of `key` while leaving its keys in place, so tests can check
+    /// that [`explain`](Self::explain) reports the corruption.
+    #[cfg(test)]
+    pub(crate) fn vacate_for_testing(&mut self, key: &K) {
+        if let Some(index) = self.index_of(key) {
+            self.slots[index].value = None;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::MultiKeyMap;
+
+    #[test]
+    fn explain_reports_vacated_slot() {
+        let mut map = MultiKeyMap::new();
+        map.insert("solo", 1);
+        map.vacate_for_testing(&"solo");
+        let trace = map.explain(&"solo");
+        assert!(trace.found() && !trace.is_consistent());
+        assert_eq!(
+            trace.to_string(),
+            "lookup of \"solo\": found in slot 0\n\
+             group: 1 key, siblings none\n\
+             invariants: violated\n  \
+             - slot 0 lists keys but holds no value"
+        );
+    }
+}
@@ -0,0 +1,395 @@
+use crate::MultiKeyMap;
+use hashbrown::HashTable;
+use std::fmt::{self, Debug};
+use std::hash::{BuildHasher, Hash, RandomState};
+use std::sync::Arc;
+
+/// An immutable snapshot of a [`MultiKeyMap`], laid out for lookups.
+///
+/// Created with [`MultiKeyMap::freeze`]. The keys of all groups are stored contiguously, group
+/// after group, and a frozen map cannot be modified at all, so it can be shared freely between
+/// threads. Cloning only bumps a reference count. [`thaw`](Self::thaw) turns it back into a
+/// `MultiKeyMap`.
+///
+/// # Examples
+///
+/// ```
+/// use multi_key_map::{FrozenMultiKeyMap, MultiKeyMap};
+/// use std::thread;
+///
+/// fn assert_send_sync<T: Send + Sync>() {}
+/// assert_send_sync::<FrozenMultiKeyMap<String, Vec<u8>>>();
+///
+/// let mut map = MultiKeyMap::new();
+/// map.insert("key1".to_string(), 1);
+/// map.insert_alias(&"key1".to_string(), "alias1".to_string());
+/// let frozen = map.freeze();
+///
+/// let handles: Vec<_> = (0..4)
+///     .map(|_| {
+///         let frozen = frozen.clone();
+///         thread::spawn(move || *frozen.get(&"alias1".to_string()).unwrap())
+///     })
+///     .collect();
+/// for handle in handles {
+///     assert_eq!(handle.join().unwrap(), 1);
+/// }
+/// ```
+pub struct FrozenMultiKeyMap<K, V> {
+    inner: Arc<Inner<K, V>>,
+}
+
+#[derive(Clone)]
+struct Inner<K, V> {
+    /// Locates every key in `keys` by its position.
+    key_index: HashTable<usize>,
+    hasher: RandomState,
+    keys: Box<[K]>,
+    /// The group of every key in `keys`.
+    key_groups: Box<[usize]>,
+    /// Whether every key in `keys` is disabled.
+    disabled: Box<[bool]>,
+    /// The start of the keys of every group in `keys`, followed by the number of keys.
+    group_starts: Box<[usize]>,
+    values: Box<[V]>,
+    /// Every soft alias with the key it points to.
+    soft_links: HashTable<(K, K)>,
+}
+
+impl<K: Eq + Hash, V> FrozenMultiKeyMap<K, V> {
+    /// Builds a frozen map from groups of keys, the positions of their disabled keys and their
+    /// values, and from soft aliases with the keys they point to.
+    pub(crate) fn from_groups(
+        groups: impl Iterator<Item = (Vec<K>, Vec<usize>, V)>,
+        links: impl Iterator<Item = (K, K)>,
+    ) -> Self {
+        let hasher = RandomState::new();
+        let mut keys = Vec::new();
+        let mut key_groups = Vec::new();
+        let mut disabled = Vec::new();
+        let mut group_starts = vec![0];
+        let mut values = Vec::new();
+        for (group, (group_keys, disabled_positions, value)) in groups.enumerate() {
+            key_groups.extend(std::iter::repeat_n(group, group_keys.len()));
+            disabled.resize(keys.len() + group_keys.len(), false);
+            for position in disabled_positions {
+                disabled[keys.len() + position] = true;
+            }
+            keys.extend(group_keys);
+            group_starts.push(keys.len());
+            values.push(value);
+        }
+        let mut key_index = HashTable::with_capacity(keys.len());
+        for (position, key) in keys.iter().enumerate() {
+            key_index.insert_unique(hasher.hash_one(key), position, |&position| {
+                hasher.hash_one(&keys[position])
+            });
+        }
+        let mut soft_links = HashTable::new();
+        for (link, target) in links {
+            soft_links.insert_unique(hasher.hash_one(&link), (link, target), |(link, _)| {
+                hasher.hash_one(link)
+            });
+        }
+        FrozenMultiKeyMap {
+            inner: Arc::new(Inner {
+                key_index,
+                hasher,
+                keys: keys.into_boxed_slice(),
+                key_groups: key_groups.into_boxed_slice(),
+                disabled: disabled.into_boxed_slice(),
+                group_starts: group_starts.into_boxed_slice(),
+                values: values.into_boxed_slice(),
+                soft_links,
+            }),
+        }
+    }
+
+    /// Retrieves a reference to a value by its key.
+    ///
+    /// Returns `None` if the key does not exist.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key to lookup.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyMap;
+    ///
+    /// let mut map = MultiKeyMap::new();
+    /// for i in 0..100 {
+    ///     map.insert(i, i * 10);
+    ///     map.insert_alias(&i, i + 1000);
+    /// }
+    /// let frozen = map.clone().freeze();
+    /// for key in map.keys() {
+    ///     assert_eq!(frozen.get(key), map.get(key));
+    /// }
+    /// assert_eq!(frozen.get(&-1), None);
+    /// ```
+    pub fn get(&self, key: &K) -> Option<&V> {
+        self.resolve_group(key)
+            .map(|group| &self.inner.values[group])
+    }
+
+    /// Returns the keys of the group `key` belongs to, in no particular order.
+    ///
+    /// Returns `None` if the key does not exist.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - A key of the group.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyMap;
+    ///
+    /// let mut map = MultiKeyMap::new();
+    /// map.insert("a", 1);
+    /// map.insert_alias(&"a", "b");
+    /// map.insert("c", 2);
+    /// let frozen = map.freeze();
+    /// let mut aliases = frozen.aliases(&"b").unwrap().to_vec();
+    /// aliases.sort();
+    /// assert_eq!(aliases, vec!["a", "b"]);
+    /// assert_eq!(frozen.aliases(&"c"), Some(&["c"][..]));
+    /// assert_eq!(frozen.aliases(&"d"), None);
+    /// ```
+    pub fn aliases(&self, key: &K) -> Option<&[K]> {
+        self.group_of(key).map(|group| self.group_keys(group))
+    }
+
+    /// Checks if two keys are aliases of each other.
+    ///
+    /// # Arguments
+    ///
+    /// * `key1` - The first key.
+    /// * `key2` - The second key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyMap;
+    ///
+    /// let mut map = MultiKeyMap::new();
+    /// map.insert("key1", "value1");
+    /// map.insert_alias(&"key1", "alias1");
+    /// map.insert("key2", "value1");
+    /// let frozen = map.freeze();
+    /// assert!(frozen.are_aliases(&"key1", &"alias1"));
+    /// assert!(!frozen.are_aliases(&"key1", &"key2"));
+    /// assert!(!frozen.are_aliases(&"key1", &"key3"));
+    /// ```
+    pub fn are_aliases(&self, key1: &K, key2: &K) -> bool {
+        match (self.group_of(key1), self.group_of(key2)) {
+            (Some(group1), Some(group2)) => group1 == group2,
+            _ => false,
+        }
+    }
+
+    /// Returns `true` if the map contains a value for the specified key.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key to check.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyMap;
+    ///
+    /// let mut map = MultiKeyMap::new();
+    /// map.insert("key1", "value1");
+    /// let frozen = map.freeze();
+    /// assert!(frozen.contains_key(&"key1"));
+    /// assert!(!frozen.contains_key(&"key2"));
+    /// ```
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.resolve_group(key).is_some()
+    }
+
+    /// Returns an iterator over the groups, each given as its keys and its value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyMap;
+    ///
+    /// let mut map = MultiKeyMap::new();
+    /// map.insert("a", 1);
+    /// map.insert_alias(&"a", "b");
+    /// map.insert("c", 2);
+    /// let frozen = map.freeze();
+    /// let mut groups: Vec<_> =
+    ///     frozen.groups().map(|(keys, value)| (keys.len(), *value)).collect();
+    /// groups.sort();
+    /// assert_eq!(groups, vec![(1, 2), (2, 1)]);
+    /// ```
+    pub fn groups(&self) -> impl Iterator<Item = (&[K], &V)> + '_ {
+        self.inner
+            .values
+            .iter()
+            .enumerate()
+            .map(|(group, value)| (self.group_keys(group), value))
+    }
+
+    /// Returns the number of groups in the map.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyMap;
+    ///
+    /// let mut map = MultiKeyMap::new();
+    /// map.insert("key1", "value1");
+    /// map.insert_alias(&"key1", "alias1");
+    /// assert_eq!(map.freeze().len(), 1);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.inner.values.len()
+    }
+
+    /// Returns the number of keys in the map, counting every alias.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyMap;
+    ///
+    /// let mut map = MultiKeyMap::new();
+    /// map.insert("key1", "value1");
+    /// map.insert_alias(&"key1", "alias1");
+    /// assert_eq!(map.freeze().key_count(), 2);
+    /// ```
+    pub fn key_count(&self) -> usize {
+        self.inner.keys.len()
+    }
+
+    /// Returns `true` if the map contains no values.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyMap;
+    ///
+    /// let map: MultiKeyMap<&str, &str> = MultiKeyMap::new();
+    /// assert!(map.freeze().is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.inner.values.is_empty()
+    }
+
+    /// Converts the frozen map back into a mutable [`MultiKeyMap`].
+    ///
+    /// The keys and values are moved if this is the only handle to the snapshot, and cloned
+    /// otherwise.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyMap;
+    ///
+    /// let mut map = MultiKeyMap::new();
+    /// map.insert("a", 1);
+    /// map.insert_alias(&"a", "b");
+    /// let frozen = map.clone().freeze();
+    /// let shared = frozen.clone();
+    ///
+    /// let mut thawed = frozen.thaw();
+    /// assert_eq!(thawed, map);
+    /// thawed.remove_alias(&"b");
+    /// assert_eq!(shared.get(&"b"), Some(&1));
+    /// ```
+    pub fn thaw(self) -> MultiKeyMap<K, V>
+    where
+        K: Clone,
+        V: Clone,
+    {
+        let inner = Arc::try_unwrap(self.inner).unwrap_or_else(|inner| (*inner).clone());
+        let mut map = MultiKeyMap::new();
+        let mut keys = inner.keys.into_vec();
+        for (value, window) in inner
+            .values
+            .into_vec()
+            .into_iter()
+            .zip(inner.group_starts.windows(2))
+            .rev()
+        {
+            let index = map.push_group(keys.drain(window[0]..), value);
+            for (position, _) in inner.disabled[window[0]..window[1]]
+                .iter()
+                .enumerate()
+                .filter(|(_, &disabled)| disabled)
+            {
+                map.disable_at(index, position);
+            }
+        }
+        for (link, target) in inner.soft_links {
+            map.set_soft_link(link, target);
+        }
+        map
+    }
+
+    /// Returns the group `key` belongs to.
+    fn group_of(&self, key: &K) -> Option<usize> {
+        self.position_of(key)
+            .map(|position| self.inner.key_groups[position])
+    }
+
+    /// Returns the position of `key` in `keys`.
+    fn position_of(&self, key: &K) -> Option<usize> {
+        let inner = &*self.inner;
+        let hash = inner.hasher.hash_one(key);
+        inner
+            .key_index
+            .find(hash, |&position| inner.keys[position] == *key)
+            .copied()
+    }
+
+    /// Returns the group a value lookup of `key` resolves to: that of the key, or that of the
+    /// key a soft alias leads to, unless that key is disabled.
+    fn resolve_group(&self, key: &K) -> Option<usize> {
+        let inner = &*self.inner;
+        let mut key = key;
+        // A chain longer than the number of soft aliases visits one of them twice: it is a cycle
+        for _ in 0..=inner.soft_links.len() {
+            if let Some(position) = self.position_of(key) {
+                return (!inner.disabled[position]).then(|| inner.key_groups[position]);
+            }
+            key = inner
+                .soft_links
+                .find(inner.hasher.hash_one(key), |(link, _)| link == key)
+                .map(|(_, target)| target)?;
+        }
+        None
+    }
+
+    fn group_keys(&self, group: usize) -> &[K] {
+        let starts = &self.inner.group_starts;
+        &self.inner.keys[starts[group]..starts[group + 1]]
+    }
+}
+
+impl<K, V> Clone for FrozenMultiKeyMap<K, V> {
+    /// Returns another handle to the same snapshot, without copying it.
+    fn clone(&self) -> Self {
+        FrozenMultiKeyMap {
+            inner: Arc::clone(&self.inner),
+        }
+    }
+}
+
+impl<K: Debug, V: Debug> Debug for FrozenMultiKeyMap<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let inner = &*self.inner;
+        let mut debug_struct = f.debug_struct("FrozenMultiKeyMap");
+        for (value, window) in inner.values.iter().zip(inner.group_starts.windows(2)) {
+            let keys = &inner.keys[window[0]..window[1]];
+            debug_struct.field(&format!("{:?}", keys), value);
+        }
+        debug_struct.finish()
+    }
+}
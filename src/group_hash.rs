@@ -0,0 +1,118 @@
+use crate::{DeterministicState, MultiKeyMap};
+use std::hash::{BuildHasher, Hash, Hasher};
+
+impl<K: Eq + Hash, V> MultiKeyMap<K, V> {
+    /// Returns a hash of the group `key` belongs to, covering its set of keys and its value.
+    ///
+    /// The hash is computed with a [`DeterministicState`] seeded with `0`, whatever the hasher
+    /// of the map, and only depends on the keys and value of the group: not on the order of
+    /// its keys, which one is primary, where the group is stored or what other groups exist.
+    /// Hashes are thus comparable across clones, rebuilt maps and processes running the same
+    /// build, and tell whether a group changed since a hash was last taken. Use
+    /// [`group_hash_with_seed`](Self::group_hash_with_seed) for another seed.
+    ///
+    /// The key is resolved as by [`get`](Self::get): a soft alias leads to its target, and a
+    /// disabled key is not found. The hash covers every key of the group, disabled or not, but
+    /// not the soft aliases leading to it.
+    ///
+    /// Returns `None` if the key does not exist or is disabled.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - Any key of the group.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyMap;
+    ///
+    /// let mut map = MultiKeyMap::new();
+    /// map.insert_with_keys(["user-7", "ada@example.com"], "Ada").unwrap();
+    /// map.insert("user-8", "Grace");
+    /// let synced = map.group_hash(&"user-7").unwrap();
+    /// assert_eq!(map.group_hash(&"ada@example.com"), Some(synced));
+    /// assert_eq!(map.group_hash(&"user-9"), None);
+    ///
+    /// // Keys resolve as they do for `get`
+    /// map.insert_soft_alias(&"user-7", "admin");
+    /// assert_eq!(map.group_hash(&"admin"), Some(synced));
+    /// map.disable_alias(&"ada@example.com");
+    /// assert_eq!(map.get(&"ada@example.com"), None);
+    /// assert_eq!(map.group_hash(&"ada@example.com"), None);
+    /// assert_eq!(map.group_hash(&"user-7"), Some(synced));
+    /// map.enable_alias(&"ada@example.com");
+    ///
+    /// // Clones and maps rebuilt in another order, with another hasher, agree
+    /// assert_eq!(map.clone().group_hash(&"user-7"), Some(synced));
+    /// let mut rebuilt = MultiKeyMap::with_seed(99);
+    /// rebuilt.insert("user-1", "Alan");
+    /// rebuilt.insert_with_keys(["ada@example.com", "user-7"], "Ada").unwrap();
+    /// assert_eq!(rebuilt.group_hash(&"user-7"), Some(synced));
+    ///
+    /// // Other groups and the layout of the map do not matter
+    /// map.remove(&"user-8");
+    /// map.insert("user-10", "Edsger");
+    /// map.compact();
+    /// map.set_primary(&"ada@example.com");
+    /// assert_eq!(map.group_hash(&"user-7"), Some(synced));
+    ///
+    /// // The keys and the value of the group do
+    /// map.insert_alias(&"user-7", "ada");
+    /// let aliased = map.group_hash(&"user-7").unwrap();
+    /// assert_ne!(aliased, synced);
+    /// map.remove_alias(&"ada");
+    /// assert_eq!(map.group_hash(&"user-7"), Some(synced));
+    /// *map.get_mut(&"user-7").unwrap() = "Ada Lovelace";
+    /// assert_ne!(map.group_hash(&"user-7"), Some(synced));
+    /// assert_ne!(map.group_hash(&"user-7"), Some(aliased));
+    /// ```
+    pub fn group_hash(&self, key: &K) -> Option<u64>
+    where
+        V: Hash,
+    {
+        self.group_hash_with_seed(key, 0)
+    }
+
+    /// Returns a hash of the group `key` belongs to, covering its set of keys and its value,
+    /// computed with a [`DeterministicState`] seeded with `seed`.
+    ///
+    /// This is [`group_hash`](Self::group_hash) with another seed; hashes only compare equal
+    /// when taken with the same seed.
+    ///
+    /// Returns `None` if the key does not exist or is disabled.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - Any key of the group.
+    /// * `seed` - The seed of the hasher.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyMap;
+    ///
+    /// let mut map = MultiKeyMap::new();
+    /// map.insert_with_keys(["a", "b"], 1).unwrap();
+    /// assert_eq!(map.group_hash_with_seed(&"a", 0), map.group_hash(&"b"));
+    /// assert_eq!(map.group_hash_with_seed(&"a", 7), map.group_hash_with_seed(&"b", 7));
+    /// assert_ne!(map.group_hash_with_seed(&"a", 7), map.group_hash(&"a"));
+    /// assert_eq!(map.group_hash_with_seed(&"c", 7), None);
+    /// ```
+    pub fn group_hash_with_seed(&self, key: &K, seed: u64) -> Option<u64>
+    where
+        V: Hash,
+    {
+        let index = self.resolve_index(key)?;
+        let state = DeterministicState::with_seed(seed);
+        let keys = &self.slots[index].keys;
+        // Summing the hashes of the keys makes the result independent of their order
+        let key_set = keys
+            .iter()
+            .fold(0u64, |sum, key| sum.wrapping_add(state.hash_one(key)));
+        let mut hasher = state.build_hasher();
+        hasher.write_u64(key_set);
+        hasher.write_u64(keys.len() as u64);
+        self.value(index).hash(&mut hasher);
+        Some(hasher.finish())
+    }
+}
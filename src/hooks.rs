@@ -0,0 +1,275 @@
+use crate::changes::ChangeLog;
+#[cfg(feature = "tracing")]
+use crate::trace::KeyWriter;
+use crate::{MultiKeyMap, Slot};
+use std::hash::Hash;
+
+type GroupHook<K, V> = Box<dyn FnMut(&[K], &V) + Send + Sync>;
+type AliasHook<K, V> = Box<dyn FnMut(AliasChange, &K, &[K], &V) + Send + Sync>;
+
+/// How the keys of a group changed, as reported to the hook set with
+/// [`MultiKeyMap::set_on_alias_changed`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AliasChange {
+    /// The key was added to the group.
+    Added,
+    /// The key was removed from the group, which still has other keys.
+    Removed,
+}
+
+/// The callbacks and change log of a [`MultiKeyMap`], which are not cloned along with the map.
+pub(crate) struct Hooks<K, V> {
+    group_inserted: Option<GroupHook<K, V>>,
+    group_removed: Option<GroupHook<K, V>>,
+    alias_changed: Option<AliasHook<K, V>>,
+    pub(crate) changes: Option<ChangeLog<K>>,
+    #[cfg(feature = "tracing")]
+    pub(crate) key_writer: Option<KeyWriter<K>>,
+}
+
+impl<K, V> Hooks<K, V> {
+    pub(crate) const fn new() -> Self {
+        Hooks {
+            group_inserted: None,
+            group_removed: None,
+            alias_changed: None,
+            changes: None,
+            #[cfg(feature = "tracing")]
+            key_writer: None,
+        }
+    }
+}
+
+impl<K, V> Default for Hooks<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K, V> Hooks<K, V> {
+    /// Reports the group in `slot` as inserted.
+    pub(crate) fn group_inserted(&mut self, slot: &Slot<K, V>) {
+        if let Some(log) = &mut self.changes {
+            log.group_created(slot);
+        }
+        if let (Some(hook), Some(value)) = (&mut self.group_inserted, &slot.value) {
+            hook(&slot.keys, value);
+        }
+    }
+
+    /// Reports a group with `keys` and `value` as removed.
+    pub(crate) fn group_removed(&mut self, keys: &[K], value: &V) {
+        if let Some(log) = &mut self.changes {
+            log.group_removed(keys);
+        }
+        if let Some(hook) = &mut self.group_removed {
+            hook(keys, value);
+        }
+    }
+
+    /// Reports every occupied slot of `slots` as a removed group.
+    pub(crate) fn groups_removed(&mut self, slots: impl Iterator<Item = Slot<K, V>>) {
+        for slot in slots {
+            if let Some(value) = &slot.value {
+                self.group_removed(&slot.keys, value);
+            }
+        }
+    }
+
+    /// Reports the last key of the group in `slot` as added.
+    pub(crate) fn alias_added(&mut self, slot: &Slot<K, V>) {
+        if let Some(log) = &mut self.changes {
+            log.alias_added(slot);
+        }
+        if let (Some(hook), Some(value)) = (&mut self.alias_changed, &slot.value) {
+            let alias = slot.keys.last().expect("added alias is listed");
+            hook(AliasChange::Added, alias, &slot.keys, value);
+        }
+    }
+
+    /// Reports `alias` as removed from the group in `slot`.
+    pub(crate) fn alias_removed(&mut self, alias: &K, slot: &Slot<K, V>) {
+        if let Some(log) = &mut self.changes {
+            log.alias_removed(alias, slot);
+        }
+        if let (Some(hook), Some(value)) = (&mut self.alias_changed, &slot.value) {
+            hook(AliasChange::Removed, alias, &slot.keys, value);
+        }
+    }
+
+    /// Reports the value of the group in `slot` as replaced.
+    pub(crate) fn value_replaced(&mut self, slot: &Slot<K, V>) {
+        if let Some(log) = &mut self.changes {
+            log.value_replaced(slot);
+        }
+    }
+
+    /// Returns the change log, for the bulk operations that record their changes without
+    /// reporting them to the hooks.
+    pub(crate) fn log(&mut self) -> Option<&mut ChangeLog<K>> {
+        self.changes.as_mut()
+    }
+}
+
+impl<K: Eq + Hash, V> MultiKeyMap<K, V> {
+    /// Sets a callback invoked with the keys and value of every group inserted from then on.
+    ///
+    /// Hooks report the changes made by the methods inserting or removing single keys or groups:
+    /// [`insert`](Self::insert), [`insert_or_alias`](Self::insert_or_alias),
+    /// [`insert_with_keys`](Self::insert_with_keys), [`insert_alias`](Self::insert_alias) and its
+    /// variants, [`remove_alias`](Self::remove_alias), [`remove`](Self::remove) and
+    /// [`clear`](Self::clear). Bulk operations such as [`retain`](Self::retain) or
+    /// [`append`](Self::append) are not reported, though the [change log](Self::track_changes)
+    /// records them. A key moved out of another group is reported as removed from that group
+    /// first.
+    ///
+    /// Hooks only receive the keys and value concerned, never the map, so they cannot observe it
+    /// midway through an operation. They are not carried over by [`Clone`], and do not show in
+    /// the [`Debug`](std::fmt::Debug) output.
+    ///
+    /// # Arguments
+    ///
+    /// * `hook` - The callback, replacing any previous one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyMap;
+    /// use std::sync::{Arc, Mutex};
+    ///
+    /// let log = Arc::new(Mutex::new(Vec::new()));
+    /// let mut map = MultiKeyMap::new();
+    /// let inserted = Arc::clone(&log);
+    /// map.set_on_group_inserted(move |keys: &[&str], value: &u32| {
+    ///     inserted.lock().unwrap().push(format!("+{keys:?}={value}"));
+    /// });
+    /// map.insert("a", 1);
+    /// map.insert_with_keys(["b", "c"], 2).unwrap();
+    /// assert_eq!(*log.lock().unwrap(), ["+[\"a\"]=1", "+[\"b\", \"c\"]=2"]);
+    /// ```
+    pub fn set_on_group_inserted(&mut self, hook: impl FnMut(&[K], &V) + Send + Sync + 'static) {
+        self.hooks.group_inserted = Some(Box::new(hook));
+    }
+
+    /// Sets a callback invoked with the keys and value of every group removed from then on,
+    /// including groups removed because their last key was removed or moved to another group.
+    ///
+    /// See [`set_on_group_inserted`](Self::set_on_group_inserted) for the methods reporting
+    /// changes.
+    ///
+    /// # Arguments
+    ///
+    /// * `hook` - The callback, replacing any previous one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyMap;
+    /// use std::sync::{Arc, Mutex};
+    ///
+    /// let log = Arc::new(Mutex::new(Vec::new()));
+    /// let mut map = MultiKeyMap::new();
+    /// let removed = Arc::clone(&log);
+    /// map.set_on_group_removed(move |keys: &[&str], value: &u32| {
+    ///     removed.lock().unwrap().push(format!("-{keys:?}={value}"));
+    /// });
+    /// map.insert("a", 1);
+    /// map.insert_alias(&"a", "b");
+    /// map.insert("c", 2);
+    /// map.insert("d", 3);
+    ///
+    /// map.remove(&"a");
+    /// map.insert("e", 4);
+    /// map.insert_alias(&"e", "c");
+    /// map.clear();
+    /// assert_eq!(
+    ///     *log.lock().unwrap(),
+    ///     ["-[\"a\", \"b\"]=1", "-[\"c\"]=2", "-[\"e\", \"c\"]=4", "-[\"d\"]=3"]
+    /// );
+    /// ```
+    pub fn set_on_group_removed(&mut self, hook: impl FnMut(&[K], &V) + Send + Sync + 'static) {
+        self.hooks.group_removed = Some(Box::new(hook));
+    }
+
+    /// Sets a callback invoked whenever a key is added to or removed from a group that exists
+    /// before and after the change.
+    ///
+    /// The callback receives the kind of change, the key, and the keys and value of the group
+    /// after the change. Adding the first key of a group or removing its last key is reported as
+    /// an inserted or removed group instead. See
+    /// [`set_on_group_inserted`](Self::set_on_group_inserted) for the methods reporting changes.
+    ///
+    /// # Arguments
+    ///
+    /// * `hook` - The callback, replacing any previous one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::{AliasChange, MultiKeyMap};
+    /// use std::sync::{Arc, Mutex};
+    ///
+    /// let log = Arc::new(Mutex::new(Vec::new()));
+    /// let mut map = MultiKeyMap::new();
+    /// let events = Arc::clone(&log);
+    /// map.set_on_alias_changed(move |change, alias: &&str, keys: &[&str], value: &u32| {
+    ///     events.lock().unwrap().push(format!("{change:?} {alias} {keys:?}={value}"));
+    /// });
+    /// let groups = Arc::clone(&log);
+    /// map.set_on_group_removed(move |keys: &[&str], value: &u32| {
+    ///     groups.lock().unwrap().push(format!("-{keys:?}={value}"));
+    /// });
+    ///
+    /// map.insert("a", 1);
+    /// map.insert_alias(&"a", "b");
+    /// map.insert("c", 2);
+    /// map.insert_alias(&"c", "b");
+    /// map.remove_alias(&"a");
+    /// map.remove_alias(&"c");
+    /// assert_eq!(
+    ///     *log.lock().unwrap(),
+    ///     [
+    ///         "Added b [\"a\", \"b\"]=1",
+    ///         "Removed b [\"a\"]=1",
+    ///         "Added b [\"c\", \"b\"]=2",
+    ///         "-[\"a\"]=1",
+    ///         "Removed c [\"b\"]=2",
+    ///     ]
+    /// );
+    /// ```
+    pub fn set_on_alias_changed(
+        &mut self,
+        hook: impl FnMut(AliasChange, &K, &[K], &V) + Send + Sync + 'static,
+    ) {
+        self.hooks.alias_changed = Some(Box::new(hook));
+    }
+
+    /// Removes every callback set on the map.
+    ///
+    /// [Change tracking](Self::track_changes) is not affected.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyMap;
+    /// use std::sync::atomic::{AtomicUsize, Ordering};
+    /// use std::sync::Arc;
+    ///
+    /// let count = Arc::new(AtomicUsize::new(0));
+    /// let mut map = MultiKeyMap::new();
+    /// let inserted = Arc::clone(&count);
+    /// map.set_on_group_inserted(move |_: &[&str], _: &u32| {
+    ///     inserted.fetch_add(1, Ordering::Relaxed);
+    /// });
+    /// map.insert("a", 1);
+    /// map.clear_hooks();
+    /// map.insert("b", 2);
+    /// assert_eq!(count.load(Ordering::Relaxed), 1);
+    /// ```
+    pub fn clear_hooks(&mut self) {
+        self.hooks = Hooks {
+            changes: self.hooks.changes.take(),
+            ..Hooks::default()
+        };
+    }
+}
@@ -0,0 +1,751 @@
+use hashbrown::HashTable;
+use std::fmt::{self, Debug};
+use std::hash::{BuildHasher, Hash, RandomState};
+
+/// A `MultiKeyIndexMap` allows multiple keys to point to a single value, remembering the order
+/// in which groups were created and keys were added.
+///
+/// Groups are iterated in the order they were created, and the keys of a group in the order they
+/// were added to it, so "first defined wins" logic can rely on iteration order.
+///
+/// # Removals
+///
+/// Removing a key from a group shifts the later keys of that group down, preserving their order.
+/// Removing a whole group, whether with [`swap_remove`](Self::swap_remove) or because its last
+/// key was removed or rebound, moves the last group into its place, like
+/// [`Vec::swap_remove`]. This keeps removals proportional to the size of the groups involved
+/// but perturbs the order of the last group. [`shift_remove`](Self::shift_remove) preserves the
+/// order of the remaining groups instead.
+pub struct MultiKeyIndexMap<K, V> {
+    /// Locates every key in the `keys` of its group.
+    key_index: HashTable<KeyEntry>,
+    hasher: RandomState,
+    groups: Vec<Group<K, V>>,
+}
+
+#[derive(Clone)]
+struct Group<K, V> {
+    /// The keys of the group, in the order they were added.
+    keys: Vec<K>,
+    value: V,
+}
+
+/// The position of a key in the `keys` of a group.
+#[derive(Debug, Clone, Copy)]
+struct KeyEntry {
+    hash: u64,
+    group: usize,
+    position: usize,
+}
+
+impl<K: Eq + Hash, V> MultiKeyIndexMap<K, V> {
+    /// Creates an empty `MultiKeyIndexMap`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyIndexMap;
+    ///
+    /// let map: MultiKeyIndexMap<&str, &str> = MultiKeyIndexMap::new();
+    /// ```
+    pub fn new() -> Self {
+        MultiKeyIndexMap {
+            key_index: HashTable::new(),
+            hasher: RandomState::new(),
+            groups: Vec::new(),
+        }
+    }
+
+    /// Retrieves a reference to a value by its key.
+    ///
+    /// Returns `None` if the key does not exist.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key to lookup.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyIndexMap;
+    ///
+    /// let mut map = MultiKeyIndexMap::new();
+    /// map.insert("key1", "value1");
+    /// assert_eq!(map.get(&"key1"), Some(&"value1"));
+    /// assert_eq!(map.get(&"key2"), None);
+    /// ```
+    pub fn get(&self, key: &K) -> Option<&V> {
+        self.find(key).map(|entry| &self.groups[entry.group].value)
+    }
+
+    /// Retrieves a mutable reference to a value by its key.
+    ///
+    /// Returns `None` if the key does not exist.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key to lookup.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyIndexMap;
+    ///
+    /// let mut map = MultiKeyIndexMap::new();
+    /// map.insert("key1", "value1");
+    /// map.insert_alias(&"key1", "alias1");
+    /// if let Some(value) = map.get_mut(&"alias1") {
+    ///     *value = "value2";
+    /// }
+    /// assert_eq!(map.get(&"key1"), Some(&"value2"));
+    /// ```
+    pub fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        let group = self.find(key)?.group;
+        Some(&mut self.groups[group].value)
+    }
+
+    /// Inserts a value with the given key as a new group after all existing ones.
+    ///
+    /// If the key already exists, it is moved to the new group, and its old group is removed if
+    /// it has no other keys.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key to insert.
+    /// * `value` - The value to insert.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyIndexMap;
+    ///
+    /// let mut map = MultiKeyIndexMap::new();
+    /// map.insert("b", 1);
+    /// map.insert("a", 2);
+    /// map.insert("c", 3);
+    /// let keys: Vec<_> = map.keys().copied().collect();
+    /// assert_eq!(keys, vec!["b", "a", "c"]);
+    ///
+    /// map.insert("b", 4);
+    /// let keys: Vec<_> = map.keys().copied().collect();
+    /// assert_eq!(keys, vec!["c", "a", "b"]);
+    /// ```
+    pub fn insert(&mut self, key: K, value: V) {
+        self.unbind(&key);
+        self.groups.push(Group {
+            keys: Vec::new(),
+            value,
+        });
+        self.link(key, self.groups.len() - 1);
+    }
+
+    /// Adds a new alias key for the element at `key`, after the existing keys of its group.
+    ///
+    /// Returns the reference count if the alias is successfully added.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The original key.
+    /// * `alias` - The alias key to add.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyIndexMap;
+    ///
+    /// let mut map = MultiKeyIndexMap::new();
+    /// map.insert("key1", "value1");
+    /// assert_eq!(map.insert_alias(&"key1", "alias2"), Some(2));
+    /// assert_eq!(map.insert_alias(&"key1", "alias1"), Some(3));
+    /// assert_eq!(map.aliases(&"key1"), Some(&["key1", "alias2", "alias1"][..]));
+    /// assert_eq!(map.insert_alias(&"key2", "alias3"), None);
+    ///
+    /// // Taking the only key of a group removes that group, moving the last group into its place
+    /// map.insert("key2", "value2");
+    /// map.insert("key3", "value3");
+    /// assert_eq!(map.insert_alias(&"key3", "key2"), Some(2));
+    /// let keys: Vec<_> = map.keys().copied().collect();
+    /// assert_eq!(keys, vec!["key1", "alias2", "alias1", "key3", "key2"]);
+    /// ```
+    pub fn insert_alias(&mut self, key: &K, alias: K) -> Option<usize> {
+        if key == &alias {
+            // Do not allow aliasing the same key
+            return None;
+        }
+        let mut group = self.find(key)?.group;
+        match self.find(&alias) {
+            // Already in the group, so keep its position
+            Some(entry) if entry.group == group => return Some(self.groups[group].keys.len()),
+            Some(_) => {
+                if let Some(removed) = self.unbind(&alias) {
+                    // The group may have been the last one, moved into the removed group's place
+                    if group == self.groups.len() {
+                        group = removed;
+                    }
+                }
+            }
+            None => {}
+        }
+        self.link(alias, group);
+        Some(self.groups[group].keys.len())
+    }
+
+    /// Removes an alias key, shifting the later keys of its group down.
+    ///
+    /// Returns the reference count if the alias is successfully removed.
+    /// If the last alias is removed, the group is removed and the last group takes its place.
+    ///
+    /// # Arguments
+    ///
+    /// * `alias` - The alias key to remove.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyIndexMap;
+    ///
+    /// let mut map = MultiKeyIndexMap::new();
+    /// map.insert("a", 1);
+    /// map.insert_alias(&"a", "b");
+    /// map.insert_alias(&"a", "c");
+    /// map.insert("d", 2);
+    /// map.insert("e", 3);
+    ///
+    /// assert_eq!(map.remove_alias(&"b"), Some(2));
+    /// assert_eq!(map.aliases(&"a"), Some(&["a", "c"][..]));
+    ///
+    /// // Removing the last key of a group moves the last group into its place
+    /// assert_eq!(map.remove_alias(&"d"), Some(0));
+    /// let keys: Vec<_> = map.keys().copied().collect();
+    /// assert_eq!(keys, vec!["a", "c", "e"]);
+    /// ```
+    pub fn remove_alias(&mut self, alias: &K) -> Option<usize> {
+        let entry = *self.find(alias)?;
+        self.unlink(entry.group, entry.position);
+        let remaining_references = self.groups[entry.group].keys.len();
+        if remaining_references == 0 {
+            self.swap_remove_group(entry.group);
+        }
+        Some(remaining_references)
+    }
+
+    /// Removes a value by its key and all its aliases, moving the last group into its place.
+    ///
+    /// Returns the value if it was present.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key of the value to remove.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyIndexMap;
+    ///
+    /// let mut map = MultiKeyIndexMap::new();
+    /// map.insert("a", 1);
+    /// map.insert("b", 2);
+    /// map.insert_alias(&"b", "c");
+    /// map.insert("d", 3);
+    /// map.insert_alias(&"d", "e");
+    ///
+    /// assert_eq!(map.swap_remove(&"a"), Some(1));
+    /// assert_eq!(map.swap_remove(&"a"), None);
+    /// let keys: Vec<_> = map.keys().copied().collect();
+    /// assert_eq!(keys, vec!["d", "e", "b", "c"]);
+    /// assert_eq!(map.get(&"e"), Some(&3));
+    /// ```
+    pub fn swap_remove(&mut self, key: &K) -> Option<V> {
+        let group = self.find(key)?.group;
+        Some(self.swap_remove_group(group).value)
+    }
+
+    /// Returns the keys of the group `key` belongs to, in the order they were added.
+    ///
+    /// Returns `None` if the key does not exist.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - A key of the group.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyIndexMap;
+    ///
+    /// let mut map = MultiKeyIndexMap::new();
+    /// map.insert("m", 1);
+    /// map.insert_alias(&"m", "z");
+    /// map.insert_alias(&"z", "a");
+    /// assert_eq!(map.aliases(&"a"), Some(&["m", "z", "a"][..]));
+    /// assert_eq!(map.aliases(&"b"), None);
+    /// ```
+    pub fn aliases(&self, key: &K) -> Option<&[K]> {
+        self.find(key)
+            .map(|entry| &self.groups[entry.group].keys[..])
+    }
+
+    /// Checks if two keys are aliases of each other.
+    ///
+    /// # Arguments
+    ///
+    /// * `key1` - The first key.
+    /// * `key2` - The second key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyIndexMap;
+    ///
+    /// let mut map = MultiKeyIndexMap::new();
+    /// map.insert("key1", "value1");
+    /// map.insert_alias(&"key1", "alias1");
+    /// map.insert("key2", "value1");
+    /// assert!(map.are_aliases(&"key1", &"alias1"));
+    /// assert!(!map.are_aliases(&"key1", &"key2"));
+    /// assert!(!map.are_aliases(&"key1", &"key3"));
+    /// ```
+    pub fn are_aliases(&self, key1: &K, key2: &K) -> bool {
+        match (self.find(key1), self.find(key2)) {
+            (Some(entry1), Some(entry2)) => entry1.group == entry2.group,
+            _ => false,
+        }
+    }
+
+    /// Returns `true` if the map contains a value for the specified key.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key to check.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyIndexMap;
+    ///
+    /// let mut map = MultiKeyIndexMap::new();
+    /// map.insert("key1", "value1");
+    /// assert!(map.contains_key(&"key1"));
+    /// assert!(!map.contains_key(&"key2"));
+    /// ```
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.find(key).is_some()
+    }
+
+    /// Returns an iterator over all keys, group by group in the order groups were created.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyIndexMap;
+    ///
+    /// let mut map = MultiKeyIndexMap::new();
+    /// map.insert("c", 1);
+    /// map.insert("a", 2);
+    /// map.insert_alias(&"c", "b");
+    /// let keys: Vec<_> = map.keys().copied().collect();
+    /// assert_eq!(keys, vec!["c", "b", "a"]);
+    /// ```
+    pub fn keys(&self) -> impl DoubleEndedIterator<Item = &K> + '_ {
+        self.groups.iter().flat_map(|group| &group.keys)
+    }
+
+    /// Returns an iterator over every key and the value it points to, in the order of
+    /// [`keys`](Self::keys).
+    ///
+    /// A value is yielded once for each of its keys.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyIndexMap;
+    ///
+    /// let mut map = MultiKeyIndexMap::new();
+    /// map.insert("c", 1);
+    /// map.insert("a", 2);
+    /// map.insert_alias(&"c", "b");
+    /// let entries: Vec<_> = map.iter().collect();
+    /// assert_eq!(entries, vec![(&"c", &1), (&"b", &1), (&"a", &2)]);
+    /// ```
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = (&K, &V)> + '_ {
+        self.groups
+            .iter()
+            .flat_map(|group| group.keys.iter().map(move |key| (key, &group.value)))
+    }
+
+    /// Returns an iterator over the groups in the order they were created, each given as its
+    /// keys and its value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyIndexMap;
+    ///
+    /// let mut map = MultiKeyIndexMap::new();
+    /// map.insert("d", 1);
+    /// map.insert("b", 2);
+    /// map.insert_alias(&"d", "a");
+    /// let groups: Vec<_> = map.groups().collect();
+    /// assert_eq!(groups, vec![(&["d", "a"][..], &1), (&["b"][..], &2)]);
+    /// ```
+    pub fn groups(&self) -> impl DoubleEndedIterator<Item = (&[K], &V)> + '_ {
+        self.groups
+            .iter()
+            .map(|group| (group.keys.as_slice(), &group.value))
+    }
+
+    /// Returns the number of groups in the map.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyIndexMap;
+    ///
+    /// let mut map = MultiKeyIndexMap::new();
+    /// map.insert("key1", "value1");
+    /// map.insert_alias(&"key1", "alias1");
+    /// assert_eq!(map.len(), 1);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.groups.len()
+    }
+
+    /// Returns the number of keys in the map, counting every alias.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyIndexMap;
+    ///
+    /// let mut map = MultiKeyIndexMap::new();
+    /// map.insert("key1", "value1");
+    /// map.insert_alias(&"key1", "alias1");
+    /// assert_eq!(map.key_count(), 2);
+    /// ```
+    pub fn key_count(&self) -> usize {
+        self.key_index.len()
+    }
+
+    /// Returns `true` if the map contains no values.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyIndexMap;
+    ///
+    /// let mut map = MultiKeyIndexMap::new();
+    /// assert!(map.is_empty());
+    /// map.insert("key1", "value1");
+    /// assert!(!map.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.groups.is_empty()
+    }
+
+    /// Returns the group at `index` in iteration order, given as its keys and its value.
+    ///
+    /// Returns `None` if `index` is out of bounds.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - The position of the group.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyIndexMap;
+    ///
+    /// let mut map = MultiKeyIndexMap::new();
+    /// map.insert("a", 1);
+    /// map.insert_alias(&"a", "b");
+    /// map.insert("c", 2);
+    /// assert_eq!(map.get_index(0), Some((&["a", "b"][..], &1)));
+    /// assert_eq!(map.get_index(1), Some((&["c"][..], &2)));
+    /// assert_eq!(map.get_index(2), None);
+    /// ```
+    pub fn get_index(&self, index: usize) -> Option<(&[K], &V)> {
+        self.groups
+            .get(index)
+            .map(|group| (group.keys.as_slice(), &group.value))
+    }
+
+    /// Returns the position in iteration order of the group `key` belongs to.
+    ///
+    /// Returns `None` if the key does not exist.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - A key of the group.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyIndexMap;
+    ///
+    /// let mut map = MultiKeyIndexMap::new();
+    /// map.insert("a", 1);
+    /// map.insert("c", 2);
+    /// map.insert_alias(&"c", "d");
+    /// assert_eq!(map.get_index_of(&"a"), Some(0));
+    /// assert_eq!(map.get_index_of(&"d"), Some(1));
+    /// assert_eq!(map.get_index_of(&"e"), None);
+    /// ```
+    pub fn get_index_of(&self, key: &K) -> Option<usize> {
+        self.find(key).map(|entry| entry.group)
+    }
+
+    /// Removes a value by its key and all its aliases, shifting the later groups down.
+    ///
+    /// Unlike [`swap_remove`](Self::swap_remove), this preserves the order of the remaining
+    /// groups, at the cost of touching the keys of every later group.
+    ///
+    /// Returns the value if it was present.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key of the value to remove.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyIndexMap;
+    ///
+    /// let mut map = MultiKeyIndexMap::new();
+    /// map.insert("a", 1);
+    /// map.insert("b", 2);
+    /// map.insert_alias(&"b", "c");
+    /// map.insert("d", 3);
+    /// map.insert("e", 4);
+    ///
+    /// assert_eq!(map.shift_remove(&"c"), Some(2));
+    /// assert_eq!(map.shift_remove(&"c"), None);
+    /// let keys: Vec<_> = map.keys().copied().collect();
+    /// assert_eq!(keys, vec!["a", "d", "e"]);
+    /// assert_eq!(map.get_index_of(&"e"), Some(2));
+    ///
+    /// // First and last groups
+    /// assert_eq!(map.shift_remove(&"a"), Some(1));
+    /// assert_eq!(map.shift_remove(&"e"), Some(4));
+    /// assert_eq!(map.get_index(0), Some((&["d"][..], &3)));
+    /// assert_eq!(map.get(&"d"), Some(&3));
+    /// ```
+    pub fn shift_remove(&mut self, key: &K) -> Option<V> {
+        let group = self.find(key)?.group;
+        Some(self.shift_remove_group(group).value)
+    }
+
+    /// Moves the group at position `from` to position `to`, shifting the groups in between.
+    ///
+    /// # Arguments
+    ///
+    /// * `from` - The current position of the group.
+    /// * `to` - The position of the group afterwards.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `from` or `to` is out of bounds.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyIndexMap;
+    ///
+    /// let mut map = MultiKeyIndexMap::new();
+    /// map.insert("a", 1);
+    /// map.insert("b", 2);
+    /// map.insert_alias(&"b", "x");
+    /// map.insert("c", 3);
+    /// map.insert("d", 4);
+    ///
+    /// map.move_index(0, 3);
+    /// let keys: Vec<_> = map.keys().copied().collect();
+    /// assert_eq!(keys, vec!["b", "x", "c", "d", "a"]);
+    ///
+    /// map.move_index(3, 0);
+    /// let keys: Vec<_> = map.keys().copied().collect();
+    /// assert_eq!(keys, vec!["a", "b", "x", "c", "d"]);
+    ///
+    /// map.move_index(1, 2);
+    /// assert_eq!(map.get_index_of(&"x"), Some(2));
+    /// assert_eq!(map.get_index_of(&"c"), Some(1));
+    /// assert_eq!(map.get(&"x"), Some(&2));
+    /// assert!(map.are_aliases(&"b", &"x"));
+    ///
+    /// map.move_index(2, 2);
+    /// assert_eq!(map.get_index_of(&"x"), Some(2));
+    /// ```
+    ///
+    /// ```should_panic
+    /// use multi_key_map::MultiKeyIndexMap;
+    ///
+    /// let mut map = MultiKeyIndexMap::new();
+    /// map.insert("a", 1);
+    /// map.move_index(0, 1);
+    /// ```
+    pub fn move_index(&mut self, from: usize, to: usize) {
+        let len = self.groups.len();
+        assert!(
+            from < len && to < len,
+            "cannot move group {from} to {to} in a map of {len} groups"
+        );
+        if from == to {
+            return;
+        }
+        // Park the moved group's keys on an unused group index while the others shift
+        let parked = usize::MAX;
+        self.repoint(from, from, parked);
+        if from < to {
+            for later in from + 1..=to {
+                self.repoint(later, later, later - 1);
+            }
+            self.groups[from..=to].rotate_left(1);
+        } else {
+            for earlier in (to..from).rev() {
+                self.repoint(earlier, earlier, earlier + 1);
+            }
+            self.groups[to..=from].rotate_right(1);
+        }
+        self.repoint(to, parked, to);
+    }
+
+    /// Unbinds `key`, removing its group if that group has no other keys.
+    ///
+    /// Returns the index of the removed group, if any.
+    fn unbind(&mut self, key: &K) -> Option<usize> {
+        let entry = *self.find(key)?;
+        self.unlink(entry.group, entry.position);
+        if !self.groups[entry.group].keys.is_empty() {
+            return None;
+        }
+        self.swap_remove_group(entry.group);
+        Some(entry.group)
+    }
+
+    /// Appends `key`, which must not be bound yet, to the keys of the group at `group`.
+    fn link(&mut self, key: K, group: usize) {
+        let hash = self.hasher.hash_one(&key);
+        let keys = &mut self.groups[group].keys;
+        let entry = KeyEntry {
+            hash,
+            group,
+            position: keys.len(),
+        };
+        keys.push(key);
+        self.key_index
+            .insert_unique(hash, entry, |entry| entry.hash);
+    }
+
+    /// Unbinds the key at `position` in the group at `group`, shifting the later keys down.
+    fn unlink(&mut self, group: usize, position: usize) -> K {
+        let keys = &self.groups[group].keys;
+        self.key_index
+            .find_entry(self.hasher.hash_one(&keys[position]), |entry| {
+                entry.group == group && entry.position == position
+            })
+            .expect("listed key is indexed")
+            .remove();
+        for (later, key) in keys.iter().enumerate().skip(position + 1) {
+            self.key_index
+                .find_mut(self.hasher.hash_one(key), |entry| {
+                    entry.group == group && entry.position == later
+                })
+                .expect("listed key is indexed")
+                .position = later - 1;
+        }
+        self.groups[group].keys.remove(position)
+    }
+
+    /// Removes the group at `group` with all of its keys, moving the last group into its place.
+    fn swap_remove_group(&mut self, group: usize) -> Group<K, V> {
+        self.unlink_group(group);
+        let last = self.groups.len() - 1;
+        if group != last {
+            self.repoint(last, last, group);
+        }
+        self.groups.swap_remove(group)
+    }
+
+    /// Removes the group at `group` with all of its keys, shifting the later groups down.
+    fn shift_remove_group(&mut self, group: usize) -> Group<K, V> {
+        self.unlink_group(group);
+        for later in group + 1..self.groups.len() {
+            self.repoint(later, later, later - 1);
+        }
+        self.groups.remove(group)
+    }
+
+    /// Removes the index entries of every key of the group at `group`.
+    fn unlink_group(&mut self, group: usize) {
+        for (position, key) in self.groups[group].keys.iter().enumerate() {
+            self.key_index
+                .find_entry(self.hasher.hash_one(key), |entry| {
+                    entry.group == group && entry.position == position
+                })
+                .expect("listed key is indexed")
+                .remove();
+        }
+    }
+
+    /// Points the index entries of the keys stored in `groups[stored]`, currently recorded as
+    /// belonging to group `current`, to group `new`.
+    ///
+    /// Callers must ensure no other key of group `current` shares a position with these keys.
+    fn repoint(&mut self, stored: usize, current: usize, new: usize) {
+        for (position, key) in self.groups[stored].keys.iter().enumerate() {
+            self.key_index
+                .find_mut(self.hasher.hash_one(key), |entry| {
+                    entry.group == current && entry.position == position
+                })
+                .expect("listed key is indexed")
+                .group = new;
+        }
+    }
+
+    /// Returns the index entry of `key`.
+    fn find(&self, key: &K) -> Option<&KeyEntry> {
+        let hash = self.hasher.hash_one(key);
+        self.key_index.find(hash, |entry| {
+            entry.hash == hash && self.groups[entry.group].keys[entry.position] == *key
+        })
+    }
+}
+
+impl<K: Eq + Hash, V> Default for MultiKeyIndexMap<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Debug, V: Debug> Debug for MultiKeyIndexMap<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut debug_struct = f.debug_struct("MultiKeyIndexMap");
+        for group in &self.groups {
+            debug_struct.field(&format!("{:?}", group.keys), &group.value);
+        }
+        debug_struct.finish()
+    }
+}
+
+impl<K: Eq + Hash, V: PartialEq> PartialEq for MultiKeyIndexMap<K, V> {
+    /// Two maps are equal if they have the same keys and every key points to an equal value,
+    /// regardless of order.
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len()
+            && self.key_count() == other.key_count()
+            && self
+                .iter()
+                .all(|(key, value)| other.get(key) == Some(value))
+    }
+}
+
+impl<K: Eq + Hash, V: Eq> Eq for MultiKeyIndexMap<K, V> {}
+
+impl<K: Clone, V: Clone> Clone for MultiKeyIndexMap<K, V> {
+    fn clone(&self) -> Self {
+        MultiKeyIndexMap {
+            key_index: self.key_index.clone(),
+            hasher: self.hasher.clone(),
+            groups: self.groups.clone(),
+        }
+    }
+}
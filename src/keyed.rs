@@ -0,0 +1,189 @@
+use crate::{InsertKeysError, MultiKeyMap};
+use std::hash::Hash;
+
+/// A value that knows its own keys, such as a record listing its identifiers.
+///
+/// [`MultiKeyMap::insert_keyed`] binds the keys of such a value as one group, and
+/// [`MultiKeyMap::refresh_keys`] brings the group in line with the value after it changed.
+///
+/// # Examples
+///
+/// ```
+/// use multi_key_map::{KeyedValue, MultiKeyMap};
+///
+/// struct User {
+///     id: String,
+///     email: String,
+/// }
+///
+/// impl KeyedValue<String> for User {
+///     fn keys(&self) -> Vec<String> {
+///         vec![self.id.clone(), self.email.clone()]
+///     }
+/// }
+///
+/// let mut users = MultiKeyMap::new();
+/// users
+///     .insert_keyed(User { id: "u1".into(), email: "ada@example.com".into() })
+///     .unwrap();
+/// assert_eq!(users.get(&"ada@example.com".to_string()).unwrap().id, "u1");
+/// ```
+pub trait KeyedValue<K> {
+    /// Returns the keys of the value, the first being the primary key of its group.
+    ///
+    /// Repeated keys count once.
+    fn keys(&self) -> Vec<K>;
+}
+
+impl<K: Eq + Hash, V: KeyedValue<K>> MultiKeyMap<K, V> {
+    /// Inserts `value` as a new group bound to the keys it returns from
+    /// [`KeyedValue::keys`], and returns the number of keys of the group.
+    ///
+    /// The first key is the primary key. Nothing is inserted if any key fails to bind.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The value to insert.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`InsertKeysError::NoKeys`] if the value has no keys,
+    /// [`InsertKeysError::KeyTaken`] with the first key that already exists, and
+    /// [`InsertKeysError::AliasLimitExceeded`] if the keys do not fit the
+    /// [alias limit](Self::with_alias_limit). The value is dropped in every case.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::{InsertKeysError, KeyedValue, MultiKeyMap};
+    ///
+    /// struct Host(Vec<&'static str>);
+    ///
+    /// impl KeyedValue<&'static str> for Host {
+    ///     fn keys(&self) -> Vec<&'static str> {
+    ///         self.0.clone()
+    ///     }
+    /// }
+    ///
+    /// let mut hosts = MultiKeyMap::new();
+    /// assert_eq!(hosts.insert_keyed(Host(vec!["web1", "10.0.0.1", "web1"])), Ok(2));
+    /// assert_eq!(hosts.primary_of(&"10.0.0.1"), Some(&"web1"));
+    ///
+    /// // A key of another group is a conflict, and nothing is inserted
+    /// let error = hosts.insert_keyed(Host(vec!["web2", "10.0.0.1"])).unwrap_err();
+    /// assert_eq!(error, InsertKeysError::KeyTaken("10.0.0.1"));
+    /// assert!(!hosts.contains_key(&"web2"));
+    /// assert_eq!(hosts.insert_keyed(Host(vec![])), Err(InsertKeysError::NoKeys));
+    /// assert_eq!(hosts.len(), 1);
+    /// ```
+    pub fn insert_keyed(&mut self, value: V) -> Result<usize, InsertKeysError<K>> {
+        let mut keys = value.keys();
+        if keys.is_empty() {
+            return Err(InsertKeysError::NoKeys);
+        }
+        if let Some(position) = keys.iter().position(|key| self.contains_key(key)) {
+            return Err(InsertKeysError::KeyTaken(keys.swap_remove(position)));
+        }
+        self.check_group_size(&keys)?;
+        let index = self.insert_group(keys, value);
+        self.check_mutation();
+        Ok(self.slots[index].keys.len())
+    }
+
+    /// Extracts the keys of the value of `key` again, after the value was mutated in place, and
+    /// rebinds its group to them. Returns the number of keys of the group, or `None` if the key
+    /// does not exist.
+    ///
+    /// Keys the value no longer returns are removed from the group, and keys it now returns are
+    /// added as aliases, so `key` itself may be gone afterwards. The group is left unchanged if
+    /// any key fails to bind.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - Any key of the group, as bound before the refresh.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`InsertKeysError::NoKeys`] if the value has no keys left,
+    /// [`InsertKeysError::KeyTaken`] with the first key that belongs to another group, and
+    /// [`InsertKeysError::AliasLimitExceeded`] if the keys do not fit the
+    /// [alias limit](Self::with_alias_limit).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::{InsertKeysError, KeyedValue, MultiKeyMap};
+    ///
+    /// struct Host(Vec<&'static str>);
+    ///
+    /// impl KeyedValue<&'static str> for Host {
+    ///     fn keys(&self) -> Vec<&'static str> {
+    ///         self.0.clone()
+    ///     }
+    /// }
+    ///
+    /// let mut hosts = MultiKeyMap::new();
+    /// hosts.insert_keyed(Host(vec!["web1", "10.0.0.1"])).unwrap();
+    /// hosts.insert_keyed(Host(vec!["web2"])).unwrap();
+    ///
+    /// // The address changed and a name was added
+    /// hosts.get_mut(&"web1").unwrap().0 = vec!["web1", "10.0.0.9", "www"];
+    /// assert_eq!(hosts.refresh_keys(&"web1"), Ok(Some(3)));
+    /// assert!(!hosts.contains_key(&"10.0.0.1"));
+    /// assert_eq!(hosts.get(&"www").unwrap().0[1], "10.0.0.9");
+    ///
+    /// // Claiming the key of another group leaves the group as it was
+    /// hosts.get_mut(&"www").unwrap().0 = vec!["web2"];
+    /// assert_eq!(hosts.refresh_keys(&"www"), Err(InsertKeysError::KeyTaken("web2")));
+    /// assert_eq!(hosts.aliases(&"www").unwrap().len(), 3);
+    ///
+    /// // The key used to refresh may itself be dropped
+    /// hosts.get_mut(&"www").unwrap().0 = vec!["web3"];
+    /// assert_eq!(hosts.refresh_keys(&"www"), Ok(Some(1)));
+    /// assert!(!hosts.contains_key(&"www"));
+    /// assert_eq!(hosts.primary_of(&"web3"), Some(&"web3"));
+    /// assert_eq!(hosts.refresh_keys(&"www"), Ok(None));
+    /// ```
+    pub fn refresh_keys(&mut self, key: &K) -> Result<Option<usize>, InsertKeysError<K>> {
+        let Some(index) = self.index_of(key) else {
+            return Ok(None);
+        };
+        let mut keys = self.slots[index]
+            .value
+            .as_ref()
+            .expect("bound slot is occupied")
+            .keys();
+        if keys.is_empty() {
+            return Err(InsertKeysError::NoKeys);
+        }
+        let taken = keys
+            .iter()
+            .position(|key| matches!(self.index_of(key), Some(other) if other != index));
+        if let Some(position) = taken {
+            return Err(InsertKeysError::KeyTaken(keys.swap_remove(position)));
+        }
+        self.check_group_size(&keys)?;
+        let bound = self.slots[index].keys.len();
+        let mut kept = vec![false; bound];
+        for key in keys {
+            match self.find(&key) {
+                Some(entry) => {
+                    // Keys added by this refresh lie past the bound ones
+                    if entry.position < bound {
+                        kept[entry.position] = true;
+                    }
+                }
+                None => self.join_hooked(key, index),
+            }
+        }
+        // Going backwards, a key moved into a removed position was already visited
+        for position in (0..bound).rev() {
+            if !kept[position] {
+                let alias = self.unlink_at(index, position);
+                self.hooks.alias_removed(&alias, &self.slots[index]);
+            }
+        }
+        self.check_mutation();
+        Ok(Some(self.slots[index].keys.len()))
+    }
+}
@@ -1,85 +1,420 @@
-use std::collections::{hash_map::Keys, HashMap};
+use hashbrown::HashTable;
+use hooks::Hooks;
+use normalize::Normalizer;
+use revision::Revision;
+use seeded::MapState;
+use small::{GroupKeys, KeyIndex, Slots};
+use stats::Counters;
+use std::collections::{HashMap, HashSet};
 use std::fmt::{self, Debug};
-use std::hash::Hash;
+use std::hash::{BuildHasher, Hash};
+use value_index::ValueIndex;
+
+mod any;
+#[cfg(feature = "rkyv")]
+mod archive;
+mod assert;
+mod bimap;
+#[cfg(feature = "borsh")]
+mod borsh_impl;
+mod boxed;
+mod btree;
+mod case_insensitive;
+mod changes;
+#[cfg(feature = "concurrent")]
+mod concurrent;
+#[cfg(all(test, feature = "debug-invariants", debug_assertions))]
+mod debug_invariants;
+mod dot;
+mod dual;
+mod error;
+mod eviction;
+mod explain;
+mod frozen;
+mod group_hash;
+mod hooks;
+mod index_map;
+mod keyed;
+mod loading;
+mod locked;
+mod lookup;
+mod lru;
+mod multi_value;
+mod namespaced;
+mod normalize;
+#[cfg(feature = "borsh")]
+mod persist;
+#[cfg(feature = "persistent")]
+mod persistent;
+#[cfg(feature = "quickcheck")]
+mod quickcheck_impl;
+#[cfg(feature = "rayon")]
+mod rayon_impl;
+mod report;
+mod revision;
+#[cfg(feature = "rand")]
+mod sample;
+#[cfg(feature = "schemars")]
+mod schema;
+mod scoped;
+mod scrub;
+mod seeded;
+#[cfg(feature = "serde")]
+pub mod serde_flat;
+#[cfg(feature = "serde")]
+pub mod serde_flat_ungrouped;
+#[cfg(feature = "serde")]
+mod serde_impl;
+mod set;
+mod slab;
+mod small;
+mod soft;
+mod static_map;
+mod stats;
+#[cfg(feature = "sync")]
+pub mod sync;
+mod trace;
+mod transaction;
+mod ttl;
+mod undo;
+mod value_index;
+mod view;
+mod weak;
+
+pub use any::AnyValue;
+#[cfg(feature = "rkyv")]
+pub use archive::{ArchivedMultiKeyMap, MultiKeyMapResolver};
+pub use bimap::BiMultiKeyMap;
+pub use boxed::BoxedMultiKeyMap;
+pub use btree::MultiKeyBTreeMap;
+pub use case_insensitive::CaseInsensitive;
+pub use changes::Change;
+#[cfg(feature = "concurrent")]
+pub use concurrent::ConcurrentMultiKeyMap;
+pub use dot::NodeStyle;
+pub use dual::DualKeyMap;
+pub use error::{
+    AliasLimitExceeded, Error, ExtendReport, InsertKeysError, KeyCollisionError, LastKeyError,
+    RejectReason, RekeyError, RenameBatchError,
+};
+pub use eviction::{EvictionPolicy, Lfu, Lru};
+pub use explain::LookupTrace;
+pub use frozen::FrozenMultiKeyMap;
+pub use hooks::AliasChange;
+pub use index_map::MultiKeyIndexMap;
+pub use keyed::KeyedValue;
+pub use locked::SyncMultiKeyMap;
+pub use lookup::KeyLookup;
+pub use lru::MultiKeyLruMap;
+/// Derives [`KeyedValue`] for a struct from its fields marked `#[multi_key(key)]` and
+/// `#[multi_key(keys)]`.
+///
+/// The keys are returned in the order of the fields, so the first marked field gives the primary
+/// key. A `key` field is cloned and converted into the key type with [`Into`], and a `keys` field
+/// is cloned and iterated, converting every item. The struct attribute
+/// `#[multi_key(key_type = T)]` implements `KeyedValue<T>` only, instead of `KeyedValue<K>` for
+/// every key type `K` the fields convert into.
+///
+/// This macro is only available with the `derive` feature.
+///
+/// # Examples
+///
+/// ```
+/// use multi_key_map::{MultiKeyMap, MultiKeys};
+///
+/// #[derive(MultiKeys)]
+/// #[multi_key(key_type = String)]
+/// struct User {
+///     #[multi_key(key)]
+///     id: &'static str,
+///     #[multi_key(keys)]
+///     emails: Vec<String>,
+///     name: String,
+/// }
+///
+/// let mut users = MultiKeyMap::new();
+/// let user = User {
+///     id: "u1",
+///     emails: vec!["ada@example.com".into(), "ada@work.example".into()],
+///     name: "Ada".into(),
+/// };
+/// assert_eq!(users.insert_keyed(user), Ok(3));
+/// assert_eq!(users.get(&"ada@work.example".into()).unwrap().name, "Ada");
+/// assert_eq!(users.primary_of(&"ada@example.com".into()), Some(&"u1".into()));
+///
+/// users.get_mut(&"u1".into()).unwrap().emails.pop();
+/// assert_eq!(users.refresh_keys(&"u1".into()), Ok(Some(2)));
+/// assert!(!users.contains_key(&"ada@work.example".into()));
+/// ```
+#[cfg(feature = "derive")]
+pub use multi_key_map_derive::MultiKeys;
+pub use namespaced::NamespacedMultiKeyMap;
+#[cfg(feature = "borsh")]
+pub use persist::LoadError;
+#[cfg(feature = "persistent")]
+pub use persistent::PersistentMultiKeyMap;
+pub use report::MapReport;
+pub use scoped::ScopedMultiKeyMap;
+pub use seeded::DeterministicState;
+pub use set::MultiKeySet;
+pub use static_map::StaticMultiKeyMap;
+#[cfg(feature = "stats")]
+pub use stats::MapStats;
+pub use ttl::{Clock, MultiKeyTtlMap, SystemClock};
+pub use undo::UndoableMultiKeyMap;
+pub use view::MultiKeyMapView;
 
 /// A `MultiKeyMap` allows multiple keys to point to a single value.
+///
+/// Every key is stored exactly once, in the list of keys of its group, and looked up through a
+/// hash index of positions in those lists. Group-wide operations such as
+/// [`aliases`](Self::aliases) and [`remove`](Self::remove) therefore only touch the keys of that
+/// group, and the core API does not require `K: Clone`.
+///
+/// # Examples
+///
+/// ```
+/// use multi_key_map::{CollisionPolicy, MultiKeyMap};
+///
+/// #[derive(Debug, PartialEq, Eq, Hash)]
+/// struct Name(String);
+///
+/// let mut map = MultiKeyMap::new();
+/// map.insert(Name("key1".to_string()), 1);
+/// assert_eq!(map.insert_alias(&Name("key1".to_string()), Name("alias1".to_string())), Some(2));
+/// map.insert(Name("key2".to_string()), 2);
+/// assert_eq!(map.get(&Name("alias1".to_string())), Some(&1));
+/// *map.get_mut(&Name("key2".to_string())).unwrap() += 1;
+/// assert!(map.are_aliases(&Name("key1".to_string()), &Name("alias1".to_string())));
+/// assert_eq!(map.alias_count(&Name("key1".to_string())), Some(2));
+/// assert_eq!(map.keys().count(), 3);
+///
+/// let id = map.group_id(&Name("key2".to_string())).unwrap();
+/// assert_eq!(map.get_by_id(id), Some(&3));
+/// let mut other = map.split_off([&Name("key2".to_string())]);
+/// other.insert(Name("key3".to_string()), 4);
+/// map.append_with(other, |a, b| a + b);
+/// map.compact();
+/// map.check_invariants();
+///
+/// assert_eq!(map.remove_alias(&Name("key1".to_string())), Some(1));
+/// map.retain_keys(|key| key.0 != "key2");
+/// assert_eq!(map.remove(&Name("alias1".to_string())), Some(1));
+/// let map = map.map_keys(|key| key.0.len(), CollisionPolicy::Error);
+/// assert_eq!(format!("{:?}", map.unwrap()), "MultiKeyMap { [4]: 4 }");
+/// ```
+///
+/// # Small maps
+///
+/// A map of up to 2 groups of up to 2 keys each stores them inline and does not allocate. Up to
+/// 4 keys are indexed inline too, and found by comparing their hashes in turn rather than
+/// through a hash table. Growing past these sizes moves the storage to the heap, and removing
+/// keys moves the key index back inline once at most 2 keys remain; the slots and the keys of a
+/// group move back on [`shrink_to_fit`](Self::shrink_to_fit). This is transparent: only
+/// [`keys_capacity`](Self::keys_capacity) and [`values_capacity`](Self::values_capacity) tell
+/// the representations apart.
+///
+/// ```
+/// use multi_key_map::MultiKeyMap;
+///
+/// let mut map = MultiKeyMap::new();
+/// map.insert(1, "a");
+/// map.insert_alias(&1, 2);
+/// map.insert(3, "b");
+/// assert_eq!((map.keys_capacity(), map.values_capacity()), (4, 2));
+///
+/// for key in 4..10 {
+///     map.insert(key, "c");
+/// }
+/// assert!(map.keys_capacity() > 4 && map.values_capacity() > 2);
+///
+/// for key in 3..10 {
+///     map.remove(&key);
+/// }
+/// assert_eq!(map.keys_capacity(), 4);
+/// map.shrink_to_fit();
+/// assert_eq!(map.values_capacity(), 2);
+/// assert_eq!(map.get(&2), Some(&"a"));
+/// ```
 pub struct MultiKeyMap<K, V> {
-    key_map: HashMap<K, usize>,
-    values: Vec<V>,
+    /// Locates every key in the `keys` of its slot.
+    key_index: KeyIndex,
+    hasher: MapState,
+    slots: Slots<K, V>,
+    /// Indices of the vacant slots, reused by later insertions.
+    free: Vec<usize>,
+    /// Number of occupied slots.
+    len: usize,
+    /// Generation of newly pushed slots, above that of every slot dropped from `slots`.
+    next_generation: u64,
+    /// The most keys a group may reach through the alias-adding methods.
+    alias_limit: Option<usize>,
+    counters: Counters,
+    hooks: Hooks<K, V>,
+    /// The revision of the latest modification of the map.
+    revision: Revision,
+    /// Applied to every key before it is stored or looked up.
+    normalizer: Option<Normalizer<K>>,
+    /// Soft aliases and the keys they point to, both normalized. No soft alias is also bound
+    /// in `key_index`.
+    soft_links: HashTable<(K, K)>,
+    /// The values by hash, built by the first [`insert_or_alias`](Self::insert_or_alias).
+    value_index: Option<ValueIndex>,
+}
+
+/// Storage for the value of a group. Slots never move, so removals leave the others in place.
+#[derive(Clone)]
+struct Slot<K, V> {
+    /// Incremented whenever the slot is vacated, so `GroupId`s of its former group go stale.
+    generation: u64,
+    value: Option<V>,
+    /// The keys pointing to the slot. The first one is the primary key of the group; the order
+    /// of the others is unspecified.
+    keys: GroupKeys<K>,
+    /// The revision of the map when the group was last modified. Kept across compaction.
+    revision: Revision,
+}
+
+/// The position of a key in the `keys` of a slot.
+#[derive(Debug, Clone, Copy)]
+struct KeyEntry {
+    hash: u64,
+    slot: usize,
+    position: usize,
+    /// Set while the key is disabled, hiding it from value lookups.
+    disabled: bool,
+}
+
+/// An opaque handle to a group of a [`MultiKeyMap`], obtained from [`MultiKeyMap::group_id`].
+///
+/// A `GroupId` resolves its group without hashing a key. It becomes stale once its group is
+/// removed, after which the `*_by_id` methods return `None` instead of a wrong group, even if the
+/// group's storage has been reused. Removing other groups does not affect it. Ids are only
+/// meaningful for the map that issued them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct GroupId {
+    index: usize,
+    generation: u64,
 }
 
-impl<K: Eq + Hash + Clone, V> MultiKeyMap<K, V> {
+/// The outcome of [`MultiKeyMap::insert_or_alias`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InsertOrAlias {
+    /// No equal value existed, so a new group was created.
+    Inserted,
+    /// The key was bound to an existing group holding an equal value.
+    ///
+    /// Contains the reference count of that group after the insertion.
+    Aliased(usize),
+}
+
+impl<K: Eq + Hash, V> MultiKeyMap<K, V> {
     /// Creates an empty `MultiKeyMap`.
     ///
+    /// This is a `const fn`, so it can initialize a `static` directly. Nothing is allocated,
+    /// and the random state of the hasher is only created when the first key is hashed.
+    ///
     /// # Examples
     ///
     /// ```
     /// use multi_key_map::MultiKeyMap;
+    /// use std::sync::Mutex;
     ///
     /// let map: MultiKeyMap<&str, &str> = MultiKeyMap::new();
+    ///
+    /// type Handler = fn(&str) -> String;
+    ///
+    /// static REGISTRY: Mutex<MultiKeyMap<&'static str, Handler>> =
+    ///     Mutex::new(MultiKeyMap::new());
+    ///
+    /// REGISTRY.lock().unwrap().insert("greet", |name| format!("Hello, {name}!"));
+    /// REGISTRY.lock().unwrap().insert_alias(&"greet", "hi");
+    /// let registry = REGISTRY.lock().unwrap();
+    /// assert_eq!(registry.get(&"hi").unwrap()("Ada"), "Hello, Ada!");
+    /// assert_eq!(registry.len(), 1);
     /// ```
-    pub fn new() -> Self {
+    pub const fn new() -> Self {
         MultiKeyMap {
-            key_map: HashMap::new(),
-            values: Vec::new(),
+            key_index: KeyIndex::new(),
+            hasher: MapState::new(),
+            slots: Slots::new_const(),
+            free: Vec::new(),
+            len: 0,
+            next_generation: 0,
+            alias_limit: None,
+            counters: Counters::new(),
+            hooks: Hooks::new(),
+            revision: Revision::new(),
+            normalizer: None,
+            soft_links: HashTable::new(),
+            value_index: None,
         }
     }
 
-    /// Retrieves a reference to a value by its key.
+    /// Creates an empty `MultiKeyMap` whose groups may have at most `limit` keys, counting their
+    /// first key.
     ///
-    /// Returns `None` if the key does not exist.
+    /// The limit applies to the methods adding aliases to an existing group, and to
+    /// [`insert_with_keys`](Self::insert_with_keys). Plain inserts always succeed, and so do
+    /// merges of whole maps such as [`append`](Self::append).
     ///
     /// # Arguments
     ///
-    /// * `key` - The key to lookup.
+    /// * `limit` - The maximum number of keys of a group.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `limit` is zero.
     ///
     /// # Examples
     ///
     /// ```
-    /// use multi_key_map::MultiKeyMap;
+    /// use multi_key_map::{AliasLimitExceeded, MultiKeyMap};
     ///
-    /// let mut map = MultiKeyMap::new();
-    /// map.insert("key1", "value1");
-    /// assert_eq!(map.get(&"key1"), Some(&"value1"));
+    /// let mut map = MultiKeyMap::with_alias_limit(3);
+    /// map.insert("a", 1);
+    /// assert_eq!(map.try_insert_alias(&"a", "b"), Ok(Some(2)));
+    /// assert_eq!(map.try_insert_alias(&"a", "c"), Ok(Some(3)));
+    /// assert_eq!(map.try_insert_alias(&"a", "d"), Err(AliasLimitExceeded { limit: 3 }));
+    /// assert!(!map.contains_key(&"d"));
+    ///
+    /// // Other groups are not affected by a full one
+    /// map.insert("x", 2);
+    /// assert_eq!(map.try_insert_alias(&"x", "y"), Ok(Some(2)));
     /// ```
-    pub fn get(&self, key: &K) -> Option<&V> {
-        self.key_map
-            .get(key)
-            .and_then(|&index| self.values.get(index))
+    pub fn with_alias_limit(limit: usize) -> Self {
+        let mut map = Self::new();
+        map.set_alias_limit(Some(limit));
+        map
     }
 
-    /// Retrieves a mutable reference to a value by its key.
-    ///
-    /// Returns `None` if the key does not exist.
-    ///
-    /// # Arguments
-    ///
-    /// * `key` - The key to lookup.
+    /// Returns the maximum number of keys of a group, if the map has a limit.
     ///
     /// # Examples
     ///
     /// ```
     /// use multi_key_map::MultiKeyMap;
     ///
-    /// let mut map = MultiKeyMap::new();
-    /// map.insert("key1", "value1");
-    /// if let Some(value) = map.get_mut(&"key1") {
-    ///     *value = "value2";
-    /// }
-    /// assert_eq!(map.get(&"key1"), Some(&"value2"));
+    /// let map: MultiKeyMap<&str, &str> = MultiKeyMap::with_alias_limit(4);
+    /// assert_eq!(map.alias_limit(), Some(4));
+    /// assert_eq!(MultiKeyMap::<&str, &str>::new().alias_limit(), None);
     /// ```
-    pub fn get_mut(&mut self, key: &K) -> Option<&mut V> {
-        self.key_map
-            .get(key)
-            .and_then(|index| self.values.get_mut(*index))
+    pub fn alias_limit(&self) -> Option<usize> {
+        self.alias_limit
     }
 
-    /// Inserts a value with the given key.
+    /// Sets or removes the maximum number of keys of a group.
+    ///
+    /// Lowering the limit does not trim existing groups: a group already above the new limit
+    /// keeps its keys, but cannot gain new ones until it is back under the limit.
     ///
     /// # Arguments
     ///
-    /// * `key` - The key to insert.
-    /// * `value` - The value to insert.
+    /// * `limit` - The maximum number of keys of a group, or `None` for no limit.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `limit` is `Some(0)`.
     ///
     /// # Examples
     ///
@@ -87,22 +422,32 @@ impl<K: Eq + Hash + Clone, V> MultiKeyMap<K, V> {
     /// use multi_key_map::MultiKeyMap;
     ///
     /// let mut map = MultiKeyMap::new();
-    /// map.insert("key1", "value1");
+    /// map.insert("a", 1);
+    /// map.insert_alias(&"a", "b");
+    /// map.insert_alias(&"a", "c");
+    ///
+    /// map.set_alias_limit(Some(2));
+    /// assert_eq!(map.alias_count(&"a"), Some(3));
+    /// assert!(map.try_insert_alias(&"a", "d").is_err());
+    /// map.remove_alias(&"c");
+    /// map.remove_alias(&"b");
+    /// assert_eq!(map.try_insert_alias(&"a", "d"), Ok(Some(2)));
+    ///
+    /// map.set_alias_limit(None);
+    /// assert_eq!(map.try_insert_alias(&"a", "e"), Ok(Some(3)));
     /// ```
-    pub fn insert(&mut self, key: K, value: V) {
-        let index = self.values.len();
-        self.values.push(value);
-        self.key_map.insert(key, index);
+    pub fn set_alias_limit(&mut self, limit: Option<usize>) {
+        assert!(limit != Some(0), "a group needs room for at least one key");
+        self.alias_limit = limit;
     }
 
-    /// Adds a new alias key for the element at `key`.
+    /// Retrieves a reference to a value by its key.
     ///
-    /// Returns the reference count if the alias is successfully added.
+    /// Returns `None` if the key does not exist.
     ///
     /// # Arguments
     ///
-    /// * `key` - The original key.
-    /// * `alias` - The alias key to add.
+    /// * `key` - The key to lookup.
     ///
     /// # Examples
     ///
@@ -111,29 +456,24 @@ impl<K: Eq + Hash + Clone, V> MultiKeyMap<K, V> {
     ///
     /// let mut map = MultiKeyMap::new();
     /// map.insert("key1", "value1");
-    /// assert_eq!(map.insert_alias(&"key1", "alias1"), Some(2));
+    /// assert_eq!(map.get(&"key1"), Some(&"value1"));
     /// ```
-    pub fn insert_alias(&mut self, key: &K, alias: K) -> Option<usize> {
-        if key == &alias {
-            // Do not allow aliasing the same key
-            return None;
-        }
-        if let Some(&index) = self.key_map.get(key) {
-            self.key_map.insert(alias, index);
-            Some(self.count_references(index))
-        } else {
-            None
-        }
+    pub fn get(&self, key: &K) -> Option<&V> {
+        let index = self.resolve_index(key);
+        self.counters.lookup(index.is_some());
+        index.map(|index| self.value(index))
     }
 
-    /// Removes an alias key.
+    /// Retrieves the keys of the group of `key`, including the key itself, together with its
+    /// value.
     ///
-    /// Returns the reference count if the alias is successfully removed.
-    /// If the last alias is removed, the value is also removed.
+    /// The key is looked up once, and the keys are borrowed from the group, in the order of
+    /// [`aliases`](Self::aliases), without cloning any of them. Returns `None` if the key does not
+    /// exist.
     ///
     /// # Arguments
     ///
-    /// * `alias` - The alias key to remove.
+    /// * `key` - Any key of the group.
     ///
     /// # Examples
     ///
@@ -141,44 +481,34 @@ impl<K: Eq + Hash + Clone, V> MultiKeyMap<K, V> {
     /// use multi_key_map::MultiKeyMap;
     ///
     /// let mut map = MultiKeyMap::new();
-    /// map.insert("key1", "value1");
-    /// map.insert_alias(&"key1", "alias1");
-    /// assert_eq!(map.remove_alias(&"alias1"), Some(1));
+    /// map.insert_with_keys(["nyc", "new york", "big apple"], "NY").unwrap();
+    /// map.insert_with_keys(["sf", "san francisco"], "CA").unwrap();
+    /// map.remove_alias(&"nyc");
+    /// map.remove(&"sf");
+    /// map.insert_with_keys(["la", "los angeles"], "CA").unwrap();
+    ///
+    /// for key in ["new york", "big apple", "la", "los angeles"] {
+    ///     let (keys, value) = map.get_with_aliases(&key).unwrap();
+    ///     assert_eq!(keys, map.aliases(&key).unwrap().iter().collect::<Vec<_>>());
+    ///     assert_eq!(Some(value), map.get(&key));
+    /// }
+    /// assert_eq!(map.get_with_aliases(&"nyc"), None);
+    /// assert_eq!(map.get_with_aliases(&"sf"), None);
     /// ```
-    pub fn remove_alias(&mut self, alias: &K) -> Option<usize> {
-        if let Some(&index) = self.key_map.get(alias) {
-            self.key_map.remove(alias);
-            let remaining_references = self.count_references(index);
-            if remaining_references == 0 {
-                self.values.swap_remove(index);
-                // Update the indices for the remaining values
-                if index != self.values.len() {
-                    // Last index is swapped to the removed index
-                    let last_value_keys = self
-                        .key_map
-                        .iter()
-                        .filter(|(_, &v)| v == self.values.len())
-                        .map(|(k, _)| k.clone())
-                        .collect::<Vec<_>>();
-                    // Update the index for the keys
-                    for k in last_value_keys {
-                        self.key_map.insert(k, index);
-                    }
-                }
-            }
-            Some(remaining_references)
-        } else {
-            None
-        }
+    pub fn get_with_aliases(&self, key: &K) -> Option<(Vec<&K>, &V)> {
+        let index = self.resolve_index(key);
+        self.counters.lookup(index.is_some());
+        let index = index?;
+        Some((self.slots[index].keys.iter().collect(), self.value(index)))
     }
 
-    /// Removes a value by its key and all its aliases.
+    /// Retrieves a mutable reference to a value by its key.
     ///
-    /// Returns the value if it was present.
+    /// Returns `None` if the key does not exist.
     ///
     /// # Arguments
     ///
-    /// * `key` - The key to remove.
+    /// * `key` - The key to lookup.
     ///
     /// # Examples
     ///
@@ -187,45 +517,27 @@ impl<K: Eq + Hash + Clone, V> MultiKeyMap<K, V> {
     ///
     /// let mut map = MultiKeyMap::new();
     /// map.insert("key1", "value1");
-    /// assert_eq!(map.remove(&"key1"), Some("value1"));
-    /// assert_eq!(map.get(&"key1"), None);
+    /// if let Some(value) = map.get_mut(&"key1") {
+    ///     *value = "value2";
+    /// }
+    /// assert_eq!(map.get(&"key1"), Some(&"value2"));
     /// ```
-    pub fn remove(&mut self, key: &K) -> Option<V> {
-        if let Some(&index) = self.key_map.get(key) {
-            let value = self.values.swap_remove(index);
-            let keys_to_remove: Vec<K> = self
-                .key_map
-                .iter()
-                .filter_map(|(k, &v)| if v == index { Some(k.clone()) } else { None })
-                .collect();
-            for k in keys_to_remove {
-                self.key_map.remove(&k);
-            }
-            if index != self.values.len() {
-                // Last index is swapped to the removed index
-                let last_value_keys = self
-                    .key_map
-                    .iter()
-                    .filter(|(_, &v)| v == self.values.len())
-                    .map(|(k, _)| k.clone())
-                    .collect::<Vec<_>>();
-                // Update the index for the keys
-                for k in last_value_keys {
-                    self.key_map.insert(k, index);
-                }
-            }
-            Some(value)
-        } else {
-            None
-        }
+    pub fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        let index = self.resolve_index(key);
+        self.counters.lookup(index.is_some());
+        Some(self.value_mut(index?))
     }
-    /// Retrieves all aliases (including the key itself) for a given key.
+
+    /// Replaces the value of the group of `key`, keeping all of its keys.
     ///
-    /// Returns a vector of all keys associated with the value of the specified key.
+    /// The key is resolved like [`get`](Self::get) resolves it. Returns the previous value, or
+    /// `None`, leaving the map unchanged, if the key does not exist or is
+    /// [disabled](Self::disable_alias).
     ///
     /// # Arguments
     ///
-    /// * `key` - The key to retrieve aliases for.
+    /// * `key` - Any key of the group.
+    /// * `value` - The new value.
     ///
     /// # Examples
     ///
@@ -235,28 +547,32 @@ impl<K: Eq + Hash + Clone, V> MultiKeyMap<K, V> {
     /// let mut map = MultiKeyMap::new();
     /// map.insert("key1", "value1");
     /// map.insert_alias(&"key1", "alias1");
-    /// let mut aliases = map.aliases(&"key1").unwrap();
-    /// aliases.sort();
+    /// assert_eq!(map.replace(&"alias1", "value2"), Some("value1"));
+    /// assert_eq!(map.get(&"key1"), Some(&"value2"));
+    /// assert_eq!(map.replace(&"key2", "value3"), None);
     ///
-    /// assert_eq!(aliases, vec!["alias1", "key1"]);
+    /// map.disable_alias(&"alias1");
+    /// assert_eq!(map.replace(&"alias1", "value4"), None);
+    /// assert_eq!(map.get(&"key1"), Some(&"value2"));
     /// ```
-    pub fn aliases(&self, key: &K) -> Option<Vec<K>> {
-        self.key_map.get(key).map(|&index| {
-            self.key_map
-                .iter()
-                .filter_map(|(k, &v)| if v == index { Some(k.clone()) } else { None })
-                .collect()
-        })
+    pub fn replace(&mut self, key: &K, value: V) -> Option<V> {
+        let index = self.resolve_index(key)?;
+        let previous = std::mem::replace(self.value_mut(index), value);
+        self.hooks.value_replaced(&self.slots[index]);
+        Some(previous)
     }
 
-    /// Checks if two keys point to the same value.
+    /// Applies `f` to the value of every key in `keys`, once per group, and returns the number
+    /// of groups modified.
     ///
-    /// Returns `true` if both keys point to the same value, otherwise returns `false`.
+    /// Keys that do not exist are skipped, and keys of a group already modified by this call,
+    /// such as aliases of an earlier key, do not call `f` again. The groups are modified in the
+    /// order their first key appears in `keys`.
     ///
     /// # Arguments
     ///
-    /// * `key1` - The first key to check.
-    /// * `key2` - The second key to check.
+    /// * `keys` - The keys of the values to modify.
+    /// * `f` - The function to apply to each value.
     ///
     /// # Examples
     ///
@@ -264,22 +580,47 @@ impl<K: Eq + Hash + Clone, V> MultiKeyMap<K, V> {
     /// use multi_key_map::MultiKeyMap;
     ///
     /// let mut map = MultiKeyMap::new();
-    /// map.insert("key1", "value1");
-    /// map.insert_alias(&"key1", "key2");
-    /// assert!(map.are_aliases(&"key1", &"key2"));
-    /// assert!(!map.are_aliases(&"key1", &"key3"));
+    /// map.insert("a", 0);
+    /// map.insert_alias(&"a", "alias");
+    /// map.insert("b", 0);
+    /// map.insert("c", 0);
+    ///
+    /// let mut calls = 0;
+    /// let modified = map.modify_many(&["a", "alias", "b", "missing", "a"], |dirty| {
+    ///     calls += 1;
+    ///     *dirty += 1;
+    /// });
+    /// assert_eq!(modified, 2);
+    /// assert_eq!(calls, 2);
+    /// assert_eq!(map.get(&"alias"), Some(&1));
+    /// assert_eq!(map.get(&"b"), Some(&1));
+    /// assert_eq!(map.get(&"c"), Some(&0));
     /// ```
-    pub fn are_aliases(&self, key1: &K, key2: &K) -> bool {
-        if let (Some(&index1), Some(&index2)) = (self.key_map.get(key1), self.key_map.get(key2)) {
-            index1 == index2
-        } else {
-            false
+    pub fn modify_many<'a>(
+        &mut self,
+        keys: impl IntoIterator<Item = &'a K>,
+        mut f: impl FnMut(&mut V),
+    ) -> usize
+    where
+        K: 'a,
+    {
+        let mut modified = HashSet::new();
+        for key in keys {
+            let index = self.resolve_index(key);
+            self.counters.lookup(index.is_some());
+            if let Some(index) = index.filter(|&index| modified.insert(index)) {
+                f(self.value_mut(index));
+            }
         }
+        modified.len()
     }
 
-    /// Retrieves all keys in the map.
+    /// Inserts a value with the given key.
     ///
-    /// Returns a vector of keys.
+    /// # Arguments
+    ///
+    /// * `key` - The key to insert.
+    /// * `value` - The value to insert.
     ///
     /// # Examples
     ///
@@ -288,36 +629,101 @@ impl<K: Eq + Hash + Clone, V> MultiKeyMap<K, V> {
     ///
     /// let mut map = MultiKeyMap::new();
     /// map.insert("key1", "value1");
-    /// map.insert_alias(&"key1", "alias1");
-    /// let mut keys: Vec<_> = map.keys().cloned().collect();
-    /// keys.sort();
-    /// assert_eq!(keys, vec!["alias1", "key1"]);
     /// ```
-    pub fn keys(&self) -> Keys<'_, K, usize> {
-        self.key_map.keys()
+    pub fn insert(&mut self, key: K, value: V) {
+        let index = self.alloc(value);
+        self.bind_hooked(key, index);
+        self.counters.insert();
+        self.hooks.group_inserted(&self.slots[index]);
+        self.trace_key("insert", &self.slots[index].keys[0], 1);
+        self.check_mutation();
     }
 
-    /// Checks if a key exists in the map.
+    /// Inserts a value with the given key, reusing an existing group if an equal value exists.
+    ///
+    /// If a value equal to `value` is already present, `key` is bound as a new alias of that
+    /// value's group and `value` is dropped. Otherwise a new group is created, as it is when the
+    /// equal value's group is at the [alias limit](Self::with_alias_limit).
+    ///
+    /// Equal values are found through an index of the values by hash, which the first call
+    /// builds and later calls bring up to date with the groups changed since, so interning
+    /// values one after another takes amortized constant time per value.
     ///
     /// # Arguments
     ///
-    /// * `key` - The key to check.
+    /// * `key` - The key to insert.
+    /// * `value` - The value to insert.
     ///
     /// # Examples
     ///
     /// ```
-    /// use multi_key_map::MultiKeyMap;
+    /// use multi_key_map::{InsertOrAlias, MultiKeyMap};
     ///
     /// let mut map = MultiKeyMap::new();
-    /// map.insert("key1", "value1");
-    /// assert!(map.contains_key(&"key1"));
-    /// assert!(!map.contains_key(&"key2"));
+    /// assert_eq!(map.insert_or_alias("a", "apple"), InsertOrAlias::Inserted);
+    /// assert_eq!(map.insert_or_alias("b", "banana"), InsertOrAlias::Inserted);
+    /// assert_eq!(map.insert_or_alias("c", "apple"), InsertOrAlias::Aliased(2));
+    /// assert_eq!(map.insert_or_alias("d", "cherry"), InsertOrAlias::Inserted);
+    /// assert_eq!(map.insert_or_alias("e", "apple"), InsertOrAlias::Aliased(3));
+    /// assert_eq!(map.insert_or_alias("f", "banana"), InsertOrAlias::Aliased(2));
+    ///
+    /// assert_eq!(map.len(), 3);
+    /// assert!(map.are_aliases(&"a", &"e"));
+    /// assert!(map.are_aliases(&"b", &"f"));
+    ///
+    /// // Values changed or removed in between are taken into account
+    /// use std::collections::HashSet;
+    ///
+    /// let mut map = MultiKeyMap::new();
+    /// for key in 0..2000u32 {
+    ///     map.insert_or_alias(key, key % 50);
+    ///     assert_eq!(map.get(&key), Some(&(key % 50)));
+    ///     match key % 7 {
+    ///         0 => drop(map.remove(&(key / 2))),
+    ///         3 => {
+    ///             if let Some(value) = map.get_mut(&(key / 3)) {
+    ///                 *value = 10_000 + key;
+    ///             }
+    ///         }
+    ///         5 => map.compact(),
+    ///         _ => {}
+    ///     }
+    ///     let distinct: HashSet<_> = map.keys().map(|key| map.get(key)).collect();
+    ///     assert_eq!(map.len(), distinct.len());
+    /// }
     /// ```
-    pub fn contains_key(&self, key: &K) -> bool {
-        self.key_map.contains_key(key)
+    pub fn insert_or_alias(&mut self, key: K, value: V) -> InsertOrAlias
+    where
+        V: Eq + Hash,
+    {
+        let mut values = self.value_index.take().unwrap_or_else(ValueIndex::new);
+        values.refresh(&self.slots, |value| self.hasher.hash_one(value));
+        let existing = values.find(self.hasher.hash_one(&value)).find(|&index| {
+            self.slots[index].value.as_ref() == Some(&value) && self.has_room(index, 1)
+        });
+        self.value_index = Some(values);
+        if let Some(index) = existing {
+            self.bind_hooked(key, index);
+            self.counters.alias_inserts(1);
+            self.hooks.alias_added(&self.slots[index]);
+            self.check_mutation();
+            InsertOrAlias::Aliased(self.count_references(index))
+        } else {
+            self.insert(key, value);
+            InsertOrAlias::Inserted
+        }
     }
 
-    /// Returns the number of elements in the map.
+    /// Adds a new alias key for the element at `key`.
+    ///
+    /// Returns the reference count if the alias is successfully added. Returns `None` as well if
+    /// the group is at the [alias limit](Self::with_alias_limit) of the map; use
+    /// [`try_insert_alias`](Self::try_insert_alias) to tell the two cases apart.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The original key.
+    /// * `alias` - The alias key to add.
     ///
     /// # Examples
     ///
@@ -326,134 +732,4201 @@ impl<K: Eq + Hash + Clone, V> MultiKeyMap<K, V> {
     ///
     /// let mut map = MultiKeyMap::new();
     /// map.insert("key1", "value1");
-    /// assert_eq!(map.len(), 1);
+    /// assert_eq!(map.insert_alias(&"key1", "alias1"), Some(2));
     /// ```
-    pub fn len(&self) -> usize {
-        self.values.len()
+    pub fn insert_alias(&mut self, key: &K, alias: K) -> Option<usize> {
+        self.try_insert_alias(key, alias).ok().flatten()
     }
 
-    /// Returns `true` if the map contains no elements.
+    /// Adds a new alias key for the element at `key`, reporting a group at the alias limit as an
+    /// error.
+    ///
+    /// Returns the reference count if the alias is successfully added, and `None` if `key` does
+    /// not exist or equals `alias`. An alias already in the group is accepted even at the limit.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The original key.
+    /// * `alias` - The alias key to add.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AliasLimitExceeded`] without binding `alias` if the group already has as many
+    /// keys as the [alias limit](Self::with_alias_limit) allows.
     ///
     /// # Examples
     ///
     /// ```
-    /// use multi_key_map::MultiKeyMap;
+    /// use multi_key_map::{AliasLimitExceeded, MultiKeyMap};
     ///
-    /// let map: MultiKeyMap<&str, &str> = MultiKeyMap::new();
-    /// assert!(map.is_empty());
+    /// let mut map = MultiKeyMap::with_alias_limit(2);
+    /// map.insert("key1", "value1");
+    /// assert_eq!(map.try_insert_alias(&"key1", "alias1"), Ok(Some(2)));
+    /// assert_eq!(map.try_insert_alias(&"key1", "alias1"), Ok(Some(2)));
+    /// assert_eq!(
+    ///     map.try_insert_alias(&"key1", "alias2"),
+    ///     Err(AliasLimitExceeded { limit: 2 })
+    /// );
+    /// assert_eq!(map.try_insert_alias(&"key2", "alias2"), Ok(None));
     /// ```
-    pub fn is_empty(&self) -> bool {
-        self.values.is_empty()
+    pub fn try_insert_alias(
+        &mut self,
+        key: &K,
+        alias: K,
+    ) -> Result<Option<usize>, AliasLimitExceeded> {
+        if self.same_key(key, &alias) {
+            // Do not allow aliasing the same key
+            return Ok(None);
+        }
+        let Some(index) = self.index_of(key) else {
+            return Ok(None);
+        };
+        if self.index_of(&alias) != Some(index) {
+            self.check_room(index, 1)?;
+            self.bind_hooked(alias, index);
+            self.counters.alias_inserts(1);
+            self.hooks.alias_added(&self.slots[index]);
+            let keys = &self.slots[index].keys;
+            self.trace_key(
+                "insert_alias",
+                keys.last().expect("alias is bound"),
+                keys.len(),
+            );
+            self.check_mutation();
+        }
+        Ok(Some(self.count_references(index)))
     }
 
-    /// Clears the map, removing all key-value pairs.
+    /// Adds several alias keys for the element at `key`, either all of them or none.
+    ///
+    /// Returns the reference count once the aliases are added, or `None` if `key` does not
+    /// exist. Aliases already in the group, and repeated aliases, count once.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The original key.
+    /// * `aliases` - The alias keys to add.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AliasLimitExceeded`] without binding any alias if they would take the group past
+    /// the [alias limit](Self::with_alias_limit).
     ///
     /// # Examples
     ///
     /// ```
-    /// use multi_key_map::MultiKeyMap;
+    /// use multi_key_map::{AliasLimitExceeded, MultiKeyMap};
     ///
-    /// let mut map = MultiKeyMap::new();
-    /// map.insert("key1", "value1");
-    /// map.clear();
-    /// assert!(map.is_empty());
+    /// let mut map = MultiKeyMap::with_alias_limit(3);
+    /// map.insert("a", 1);
+    /// assert_eq!(map.insert_aliases(&"a", ["b", "b", "a"]), Ok(Some(2)));
+    /// assert_eq!(
+    ///     map.insert_aliases(&"a", ["c", "d"]),
+    ///     Err(AliasLimitExceeded { limit: 3 })
+    /// );
+    /// assert!(!map.contains_key(&"c"));
+    /// assert_eq!(map.insert_aliases(&"a", ["c"]), Ok(Some(3)));
+    /// assert_eq!(map.insert_aliases(&"z", ["c"]), Ok(None));
     /// ```
-    pub fn clear(&mut self) {
-        self.key_map.clear();
-        self.values.clear();
-    }
-
-    /// Counts the number of references to a particular value index.
-    fn count_references(&self, index: usize) -> usize {
-        self.key_map.values().filter(|&&i| i == index).count()
+    pub fn insert_aliases(
+        &mut self,
+        key: &K,
+        aliases: impl IntoIterator<Item = K>,
+    ) -> Result<Option<usize>, AliasLimitExceeded> {
+        let Some(index) = self.index_of(key) else {
+            return Ok(None);
+        };
+        let mut seen = HashSet::new();
+        let aliases: Vec<K> = aliases
+            .into_iter()
+            .filter(|alias| self.index_of(alias) != Some(index))
+            .collect();
+        let added = aliases.iter().filter(|&alias| seen.insert(alias)).count();
+        self.check_room(index, added)?;
+        for alias in aliases {
+            if self.bind_hooked(alias, index) {
+                self.hooks.alias_added(&self.slots[index]);
+            }
+        }
+        self.counters.alias_inserts(added);
+        self.check_mutation();
+        Ok(Some(self.count_references(index)))
     }
-}
 
-impl<K: Eq + Hash + Clone + Debug, V: Debug> Debug for MultiKeyMap<K, V> {
-    /// Formats the value using the given formatter.
+    /// Reserves capacity for at least `additional` more aliases of the group of `key`.
     ///
-    /// This trait is used for debugging purposes.
+    /// Both the keys of the group and the index of all keys grow, so that adding the aliases
+    /// does not reallocate. This only affects performance, not the contents of the map.
+    ///
+    /// Returns `false` if `key` does not exist.
     ///
     /// # Arguments
     ///
-    /// * `f` - The formatter to use.
+    /// * `key` - Any key of the group.
+    /// * `additional` - The number of aliases about to be added.
     ///
     /// # Examples
     ///
     /// ```
     /// use multi_key_map::MultiKeyMap;
     ///
-    /// let mut map = MultiKeyMap::new();
-    /// map.insert("key1", "value1");
-    /// println!("{:?}", map);
+    /// let mut reserved = MultiKeyMap::new();
+    /// reserved.insert(0, "value");
+    /// assert!(reserved.reserve_aliases(&0, 300));
+    /// assert!(!reserved.reserve_aliases(&1000, 300));
+    ///
+    /// let mut plain = MultiKeyMap::new();
+    /// plain.insert(0, "value");
+    ///
+    /// for map in [&mut reserved, &mut plain] {
+    ///     map.insert_aliases(&0, 1..=300).unwrap();
+    /// }
+    /// assert_eq!(reserved, plain);
+    /// assert_eq!(reserved.aliases(&0), plain.aliases(&0));
     /// ```
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let mut map: HashMap<usize, Vec<&K>> = HashMap::new();
-        for (key, &index) in &self.key_map {
-            map.entry(index).or_insert_with(Vec::new).push(key);
-        }
-        let mut debug_struct = f.debug_struct("MultiKeyMap");
-        for (index, keys) in map {
-            debug_struct.field(&format!("{:?}", keys), &self.values[index]);
-        }
-        debug_struct.finish()
+    pub fn reserve_aliases(&mut self, key: &K, additional: usize) -> bool {
+        let Some(index) = self.index_of(key) else {
+            return false;
+        };
+        self.slots[index].keys.reserve(additional);
+        self.reserve_keys(additional);
+        true
     }
-}
 
-impl<K: Eq + Hash, V: PartialEq> PartialEq for MultiKeyMap<K, V> {
-    /// Compares two `MultiKeyMap` instances for equality.
+    /// Makes `a` and `b` aliases, creating or merging groups as needed, and returns the number
+    /// of keys of their group.
     ///
-    /// Two `MultiKeyMap` instances are considered equal if they have the same keys and values,
-    /// and each key in one map points to the same value as the corresponding key in the other map.
+    /// * If neither key exists, a new group holding both keys and `V::default()` is inserted.
+    /// * If one key exists, the other one joins its group.
+    /// * If the keys are in different groups, every key of the group of `b` joins the group of
+    ///   `a`, which keeps its value; the value of the group of `b` is dropped.
+    ///
+    /// Applied to a sequence of equivalences, this maintains their equivalence classes, as a
+    /// union-find structure does. Like the other methods merging groups, this method does not
+    /// enforce the [alias limit](Self::with_alias_limit).
     ///
     /// # Arguments
     ///
-    /// * `other` - The other `MultiKeyMap` to compare against.
+    /// * `a` - A key, whose value is kept if both keys exist.
+    /// * `b` - A key equivalent to `a`.
     ///
     /// # Examples
     ///
     /// ```
     /// use multi_key_map::MultiKeyMap;
     ///
-    /// let mut map1 = MultiKeyMap::new();
-    /// map1.insert("key1", "value1");
-    /// map1.insert_alias(&"key1", "alias1");
-    ///
-    /// let mut map2 = MultiKeyMap::new();
-    /// map2.insert("key1", "value1");
-    /// map2.insert_alias(&"key1", "alias1");
-    ///
-    /// assert_eq!(map1, map2);  // Should be true because both maps have the same keys and values.
+    /// let mut map: MultiKeyMap<&str, u32> = MultiKeyMap::new();
+    /// // Neither key exists
+    /// assert_eq!(map.alias_or_insert_default("a", "b"), 2);
+    /// assert_eq!(map.get(&"b"), Some(&0));
+    /// // Only the first key exists
+    /// *map.get_mut(&"a").unwrap() = 1;
+    /// assert_eq!(map.alias_or_insert_default("b", "c"), 3);
+    /// // Only the second key exists
+    /// assert_eq!(map.alias_or_insert_default("d", "a"), 4);
+    /// // Both keys exist in the same group
+    /// assert_eq!(map.alias_or_insert_default("c", "d"), 4);
+    /// // Both keys exist in different groups
+    /// map.insert("x", 9);
+    /// map.insert_alias(&"x", "y");
+    /// assert_eq!(map.alias_or_insert_default("y", "b"), 6);
+    /// assert_eq!(map.get(&"a"), Some(&9));
+    /// assert_eq!(map.len(), 1);
     ///
-    /// map2.remove_alias(&"alias1");
-    /// assert_ne!(map1, map2);  // Should be true because the alias has been removed from map2.
+    /// // Transitive chains end up in one group
+    /// let mut classes: MultiKeyMap<u32, ()> = MultiKeyMap::new();
+    /// for (a, b) in [(1, 2), (3, 4), (5, 6), (2, 3), (6, 7), (4, 7)] {
+    ///     classes.alias_or_insert_default(a, b);
+    /// }
+    /// assert_eq!(classes.len(), 1);
+    /// assert!(classes.are_aliases_all(&[1, 2, 3, 4, 5, 6, 7]));
     /// ```
-    fn eq(&self, other: &Self) -> bool {
-        // Check if both maps have the same number of values
-        if self.values.len() != other.values.len() {
-            return false;
-        }
-        // Check if each key in `self` maps to the same value as the corresponding key in `other`
-        for (key, &index) in &self.key_map {
-            if let Some(&other_index) = other.key_map.get(key) {
-                if self.values[index] != other.values[other_index] {
-                    return false;
+    pub fn alias_or_insert_default(&mut self, a: K, b: K) -> usize
+    where
+        V: Default,
+    {
+        let index = match (self.index_of(&a), self.index_of(&b)) {
+            (Some(index), Some(other)) => {
+                if index != other {
+                    self.merge_hooked(other, index);
                 }
-            } else {
-                return false;
+                index
             }
-        }
-        true
-    }
+            (Some(index), None) => {
+                self.join_hooked(b, index);
+                index
+            }
+            (None, Some(index)) => {
+                self.join_hooked(a, index);
+                index
+            }
+            (None, None) => self.insert_group(vec![a, b], V::default()),
+        };
+        self.check_mutation();
+        self.count_references(index)
+    }
+
+    /// Inserts a value bound to all of `keys` at once.
+    ///
+    /// Keys that already exist are moved to the new value, and their old groups are removed if
+    /// they have no other keys. Repeated keys count once. Returns the reference count of the
+    /// new group.
+    ///
+    /// # Arguments
+    ///
+    /// * `keys` - The keys of the value.
+    /// * `value` - The value to insert.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AliasLimitExceeded`] without inserting anything if there are more keys than the
+    /// [alias limit](Self::with_alias_limit) allows.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `keys` is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::{AliasLimitExceeded, MultiKeyMap};
+    ///
+    /// let mut map = MultiKeyMap::with_alias_limit(2);
+    /// assert_eq!(map.insert_with_keys(["a", "b", "a"], 1), Ok(2));
+    /// assert!(map.are_aliases(&"a", &"b"));
+    /// assert_eq!(
+    ///     map.insert_with_keys(["c", "d", "e"], 2),
+    ///     Err(AliasLimitExceeded { limit: 2 })
+    /// );
+    /// assert!(!map.contains_key(&"c"));
+    /// assert_eq!(map.len(), 1);
+    /// ```
+    pub fn insert_with_keys(
+        &mut self,
+        keys: impl IntoIterator<Item = K>,
+        value: V,
+    ) -> Result<usize, AliasLimitExceeded> {
+        let keys: Vec<K> = keys.into_iter().collect();
+        self.check_group_size(&keys)?;
+        let index = self.insert_group(keys, value);
+        self.check_mutation();
+        Ok(self.count_references(index))
+    }
+
+    /// Removes an alias key.
+    ///
+    /// Returns the reference count if the alias is successfully removed.
+    /// If the last alias is removed, the value is also removed.
+    ///
+    /// Removing the [primary key](Self::primary_of) of a group promotes the key listed last by
+    /// [`aliases`](Self::aliases) to primary.
+    ///
+    /// # Arguments
+    ///
+    /// * `alias` - The alias key to remove.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyMap;
+    ///
+    /// let mut map = MultiKeyMap::new();
+    /// map.insert("key1", "value1");
+    /// map.insert_alias(&"key1", "alias1");
+    /// assert_eq!(map.remove_alias(&"alias1"), Some(1));
+    /// ```
+    pub fn remove_alias(&mut self, alias: &K) -> Option<usize> {
+        let (index, alias) = self.unbind(alias)?;
+        self.counters.removal();
+        let remaining_references = self.count_references(index);
+        if remaining_references == 0 {
+            let value = self.release(index);
+            self.hooks
+                .group_removed(std::slice::from_ref(&alias), &value);
+        } else {
+            self.hooks.alias_removed(&alias, &self.slots[index]);
+        }
+        self.trace_key("remove_alias", &alias, remaining_references);
+        self.check_mutation();
+        Some(remaining_references)
+    }
+
+    /// Removes an alias key, unless it is the only key of its group.
+    ///
+    /// Returns the reference count if the alias is successfully removed, or `None` if it does not
+    /// exist. Unlike [`remove_alias`](Self::remove_alias), this never drops a value: removing a
+    /// group is left to [`remove`](Self::remove).
+    ///
+    /// # Arguments
+    ///
+    /// * `alias` - The alias key to remove.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`LastKeyError`], leaving the map unchanged, if `alias` is the only key of its
+    /// group.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::{LastKeyError, MultiKeyMap};
+    ///
+    /// let mut map = MultiKeyMap::new();
+    /// map.insert("key1", "value1");
+    /// map.insert_alias(&"key1", "alias1");
+    /// assert_eq!(map.remove_alias_keep_value(&"key1"), Ok(Some(1)));
+    /// assert_eq!(map.remove_alias_keep_value(&"alias1"), Err(LastKeyError));
+    /// assert_eq!(map.get(&"alias1"), Some(&"value1"));
+    /// assert_eq!(map.remove_alias_keep_value(&"key1"), Ok(None));
+    /// ```
+    pub fn remove_alias_keep_value(&mut self, alias: &K) -> Result<Option<usize>, LastKeyError> {
+        match self.index_of(alias) {
+            Some(index) if self.count_references(index) == 1 => Err(LastKeyError),
+            Some(_) => Ok(self.remove_alias(alias)),
+            None => Ok(None),
+        }
+    }
+
+    /// Disables `key`, hiding it from value lookups until [`enable_alias`](Self::enable_alias)
+    /// restores it.
+    ///
+    /// A disabled key stays bound to its group: [`get`](Self::get), [`get_mut`](Self::get_mut),
+    /// [`get_with_aliases`](Self::get_with_aliases), [`replace`](Self::replace),
+    /// [`modify_many`](Self::modify_many), [`contains_key`](Self::contains_key),
+    /// [`group_id`](Self::group_id) and loaders such as
+    /// [`get_or_try_insert_with`](Self::get_or_try_insert_with) act as if it did not exist, while
+    /// the other methods still see it, so that it is listed by [`aliases`](Self::aliases) and
+    /// can be removed with [`remove_alias`](Self::remove_alias). Every key of a group may be
+    /// disabled, in which case its value stays in the map. Binding the key to a group again, as
+    /// [`insert`](Self::insert) does, enables it.
+    ///
+    /// Disabled keys stay disabled in clones and in the maps built by
+    /// [`map_keys`](Self::map_keys), [`split_off`](Self::split_off),
+    /// [`partition`](Self::partition) and [`freeze`](Self::freeze). The serialized forms of the
+    /// map cannot hold them, so serializing a map with disabled keys fails.
+    ///
+    /// Returns `true` if the key exists and was enabled.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key to disable.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyMap;
+    ///
+    /// let mut map = MultiKeyMap::new();
+    /// map.insert_with_keys(["account", "api-key-1", "api-key-2"], 7).unwrap();
+    /// assert!(map.disable_alias(&"api-key-1"));
+    /// assert!(!map.disable_alias(&"api-key-1"));
+    /// assert!(!map.disable_alias(&"missing"));
+    ///
+    /// assert_eq!(map.get(&"api-key-1"), None);
+    /// assert!(!map.contains_key(&"api-key-1"));
+    /// assert_eq!(map.get(&"api-key-2"), Some(&7));
+    /// assert!(map.is_alias_disabled(&"api-key-1"));
+    /// assert!(map.aliases(&"account").unwrap().contains(&"api-key-1"));
+    ///
+    /// // Disabling every key keeps the value for when a key is enabled again
+    /// map.disable_alias(&"account");
+    /// map.disable_alias(&"api-key-2");
+    /// assert_eq!(map.len(), 1);
+    /// assert!(map.keys().all(|key| !map.contains_key(key)));
+    /// assert!(map.enable_alias(&"api-key-2"));
+    /// assert_eq!(map.get(&"api-key-2"), Some(&7));
+    ///
+    /// // Copies keep the key disabled
+    /// let (copy, _) = map.clone().partition(|_, _| true);
+    /// assert!(copy.is_alias_disabled(&"api-key-1"));
+    /// assert_eq!(copy.get(&"api-key-1"), None);
+    /// assert_eq!(map.clone().freeze().get(&"api-key-1"), None);
+    ///
+    /// // Disabled keys are removed like any other
+    /// assert_eq!(map.remove_alias(&"api-key-1"), Some(2));
+    /// assert!(!map.is_alias_disabled(&"api-key-1"));
+    /// assert!(map.group_matches(&"api-key-2", &["account", "api-key-2"]));
+    /// map.check_invariants();
+    /// ```
+    pub fn disable_alias(&mut self, key: &K) -> bool {
+        self.set_disabled(key, true)
+    }
+
+    /// Enables `key` again after [`disable_alias`](Self::disable_alias), so that value lookups
+    /// find its group.
+    ///
+    /// Returns `true` if the key exists and was disabled.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key to enable.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyMap;
+    ///
+    /// let mut map = MultiKeyMap::new();
+    /// map.insert("token", 1);
+    /// map.disable_alias(&"token");
+    /// assert_eq!(map.get(&"token"), None);
+    ///
+    /// assert!(map.enable_alias(&"token"));
+    /// assert_eq!(map.get(&"token"), Some(&1));
+    /// assert!(!map.enable_alias(&"token"));
+    /// assert!(!map.enable_alias(&"missing"));
+    /// ```
+    pub fn enable_alias(&mut self, key: &K) -> bool {
+        self.set_disabled(key, false)
+    }
+
+    /// Returns `true` if `key` exists and is disabled.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key to check.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyMap;
+    ///
+    /// let mut map = MultiKeyMap::new();
+    /// map.insert("token", 1);
+    /// assert!(!map.is_alias_disabled(&"token"));
+    /// map.disable_alias(&"token");
+    /// assert!(map.is_alias_disabled(&"token"));
+    ///
+    /// // Inserting the key again binds it anew, enabled
+    /// map.insert("token", 2);
+    /// assert!(!map.is_alias_disabled(&"token"));
+    /// assert_eq!(map.len(), 1);
+    /// ```
+    pub fn is_alias_disabled(&self, key: &K) -> bool {
+        self.find(key).is_some_and(|entry| entry.disabled)
+    }
+
+    /// Removes a value by its key and all its aliases.
+    ///
+    /// Returns the value if it was present.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key to remove.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyMap;
+    ///
+    /// let mut map = MultiKeyMap::new();
+    /// map.insert("key1", "value1");
+    /// assert_eq!(map.remove(&"key1"), Some("value1"));
+    /// assert_eq!(map.get(&"key1"), None);
+    /// ```
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        let index = self.index_of(key)?;
+        let keys = self.unlink_all(index);
+        self.counters.removal();
+        let value = self.release(index);
+        self.hooks.group_removed(&keys, &value);
+        self.trace_key("remove", key, 0);
+        self.check_mutation();
+        Some(value)
+    }
+
+    /// Merges all groups holding equal values into single groups.
+    ///
+    /// The keys of the merged groups are unioned onto the first of the equal values; the
+    /// duplicate values are dropped. Returns the number of groups that were eliminated.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyMap;
+    ///
+    /// let mut map = MultiKeyMap::new();
+    /// map.insert("a", "same");
+    /// map.insert("b", "same");
+    /// map.insert_alias(&"b", "b1");
+    /// map.insert("c", "same");
+    /// map.insert("d", "other");
+    ///
+    /// assert_eq!(map.dedup_values(), 2);
+    /// assert_eq!(map.len(), 2);
+    /// assert!(map.are_aliases(&"a", &"b"));
+    /// assert!(map.are_aliases(&"a", &"b1"));
+    /// assert!(map.are_aliases(&"a", &"c"));
+    /// assert_eq!(map.get(&"c"), Some(&"same"));
+    /// assert_eq!(map.get(&"d"), Some(&"other"));
+    /// assert_eq!(map.dedup_values(), 0);
+    /// ```
+    pub fn dedup_values(&mut self) -> usize
+    where
+        V: Eq + Hash,
+    {
+        // Map every slot to the slot of the first equal value
+        let mut first_seen: HashMap<&V, usize> = HashMap::with_capacity(self.len);
+        let canonical: Vec<usize> = self
+            .slots
+            .iter()
+            .enumerate()
+            .map(|(index, slot)| match &slot.value {
+                Some(value) => *first_seen.entry(value).or_insert(index),
+                None => index,
+            })
+            .collect();
+        drop(first_seen);
+
+        let mut eliminated = 0;
+        for (index, &target) in canonical.iter().enumerate() {
+            if target != index {
+                self.relocate(index, target);
+                self.release(index);
+                eliminated += 1;
+            }
+        }
+        self.check_mutation();
+        eliminated
+    }
+
+    /// Retains only the groups specified by the predicate.
+    ///
+    /// The predicate receives the keys of each group (in unspecified order) and a mutable
+    /// reference to its value. Groups for which it returns `false` are removed together with all
+    /// of their keys. Their slots are vacated for later insertions to reuse, and the remaining
+    /// values stay where they are; see [`compact`](Self::compact) to release the space.
+    ///
+    /// # Arguments
+    ///
+    /// * `f` - The predicate deciding which groups to keep.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyMap;
+    ///
+    /// let mut map = MultiKeyMap::new();
+    /// map.insert("a", 1);
+    /// map.insert_alias(&"a", "a1");
+    /// map.insert("b", 2);
+    /// map.insert("c", 3);
+    /// map.insert_alias(&"c", "c1");
+    /// map.insert("d", 4);
+    ///
+    /// // Bump every value, then keep groups with aliases or with a small value
+    /// map.retain(|keys, value| {
+    ///     *value += 10;
+    ///     keys.len() > 1 || *value < 13
+    /// });
+    ///
+    /// assert_eq!(map.len(), 3);
+    /// assert_eq!(map.get(&"a1"), Some(&11));
+    /// assert_eq!(map.get(&"b"), Some(&12));
+    /// assert_eq!(map.get(&"c1"), Some(&13));
+    /// assert_eq!(map.get(&"d"), None);
+    /// assert!(map.are_aliases(&"c", &"c1"));
+    /// ```
+    pub fn retain(&mut self, mut f: impl FnMut(&[K], &mut V) -> bool) {
+        let keep: Vec<bool> = self
+            .slots
+            .iter_mut()
+            .map(|Slot { value, keys, .. }| value.as_mut().is_some_and(|value| f(keys, value)))
+            .collect();
+        for (index, _) in keep.iter().enumerate().filter(|(_, &kept)| kept) {
+            self.touch(index);
+        }
+        self.retain_groups(&keep);
+        self.check_mutation();
+    }
+
+    /// Rewrites or removes every group in a single pass.
+    ///
+    /// `f` receives the keys of each group and takes its value by move. Returning `Some` stores
+    /// the returned value in the group, which may be the same value, and returning `None` removes
+    /// the group together with all of its keys. Unlike
+    /// [`filter_map_values`](Self::filter_map_values), this works in place, without building a
+    /// new map. If `f` panics, the group whose value it took is removed.
+    ///
+    /// # Arguments
+    ///
+    /// * `f` - The function deciding the new value of each group, or its removal.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyMap;
+    ///
+    /// let mut map = MultiKeyMap::new();
+    /// map.insert_with_keys(["a", "a1"], String::from("keep")).unwrap();
+    /// map.insert_with_keys(["b", "b1", "b2"], String::from("shout")).unwrap();
+    /// map.insert("c", String::from("drop"));
+    /// map.insert("d", String::from("drop"));
+    /// map.remove(&"d");
+    ///
+    /// map.retain_map(|keys, value| match value.as_str() {
+    ///     "drop" => None,
+    ///     "shout" => Some(format!("{} x{}", value.to_uppercase(), keys.len())),
+    ///     _ => Some(value),
+    /// });
+    ///
+    /// map.check_invariants();
+    /// assert_eq!(map.len(), 2);
+    /// assert_eq!(map.get(&"a1").map(String::as_str), Some("keep"));
+    /// assert_eq!(map.get(&"b2").map(String::as_str), Some("SHOUT x3"));
+    /// assert!(map.group_matches(&"b", &["b", "b1", "b2"]));
+    /// assert!(!map.contains_key(&"c"));
+    ///
+    /// // A panic removes the group being rewritten and leaves the others intact
+    /// let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+    ///     map.retain_map(|keys, value| if keys.contains(&"b") { panic!() } else { Some(value) });
+    /// }));
+    /// assert!(result.is_err());
+    /// map.check_invariants();
+    /// assert!(map.contains_key(&"a") && !map.contains_key(&"b1"));
+    /// ```
+    pub fn retain_map(&mut self, mut f: impl FnMut(&[K], V) -> Option<V>) {
+        /// Removes the group at `index`, whose value was taken out, unless forgotten.
+        struct Vacate<'a, K: Eq + Hash, V> {
+            map: &'a mut MultiKeyMap<K, V>,
+            index: usize,
+        }
+
+        impl<K: Eq + Hash, V> Drop for Vacate<'_, K, V> {
+            fn drop(&mut self) {
+                let keys = self.map.unlink_all(self.index);
+                if let Some(log) = self.map.hooks.log() {
+                    log.group_removed(&keys);
+                }
+                self.map.vacate(self.index);
+            }
+        }
+
+        for index in 0..self.slots.len() {
+            let Some(value) = self.slots[index].value.take() else {
+                continue;
+            };
+            let guard = Vacate { map: self, index };
+            if let Some(value) = f(&guard.map.slots[index].keys, value) {
+                std::mem::forget(guard);
+                self.touch(index);
+                self.slots[index].value = Some(value);
+            }
+        }
+        self.check_mutation();
+    }
+
+    /// Retains only the keys specified by the predicate.
+    ///
+    /// Keys for which the predicate returns `false` are removed individually. A group keeps its
+    /// value as long as at least one of its keys survives; groups that lose every key are
+    /// removed together with their value.
+    ///
+    /// # Arguments
+    ///
+    /// * `f` - The predicate deciding which keys to keep.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyMap;
+    ///
+    /// let mut map = MultiKeyMap::new();
+    /// map.insert("id:1", "first");
+    /// map.insert_alias(&"id:1", "old:1");
+    /// map.insert("old:2", "second");
+    /// map.insert_alias(&"old:2", "old:2b");
+    /// map.insert("id:3", "third");
+    ///
+    /// map.retain_keys(|key| !key.starts_with("old:"));
+    ///
+    /// assert_eq!(map.get(&"id:1"), Some(&"first"));
+    /// assert_eq!(map.get(&"old:1"), None);
+    /// assert_eq!(map.get(&"old:2"), None);
+    /// assert_eq!(map.get(&"id:3"), Some(&"third"));
+    /// assert_eq!(map.key_count(), 2);
+    /// assert_eq!(map.group_count(), 2);
+    /// ```
+    pub fn retain_keys(&mut self, mut f: impl FnMut(&K) -> bool) {
+        for index in 0..self.slots.len() {
+            let mut position = 0;
+            while position < self.slots[index].keys.len() {
+                if f(&self.slots[index].keys[position]) {
+                    position += 1;
+                } else {
+                    let key = self.unlink_at(index, position);
+                    self.log_unbound(&key, index);
+                }
+            }
+            if self.slots[index].value.is_some() && self.slots[index].keys.is_empty() {
+                self.release(index);
+            }
+        }
+        self.check_mutation();
+    }
+
+    /// Consumes the map and converts every value, preserving the alias structure.
+    ///
+    /// The keys are moved into the new map as they are, without being rehashed.
+    ///
+    /// # Arguments
+    ///
+    /// * `f` - The conversion applied to each value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyMap;
+    ///
+    /// let mut map = MultiKeyMap::new();
+    /// map.insert("port", "8080");
+    /// map.insert_alias(&"port", "p");
+    /// map.insert("workers", "4");
+    ///
+    /// let parsed = map.map_values(|value| value.parse::<u32>().unwrap());
+    /// assert_eq!(parsed.get(&"p"), Some(&8080));
+    /// assert_eq!(parsed.get(&"workers"), Some(&4));
+    /// assert!(parsed.are_aliases(&"port", &"p"));
+    /// assert_eq!(parsed.len(), 2);
+    /// ```
+    pub fn map_values<U>(self, mut f: impl FnMut(V) -> U) -> MultiKeyMap<K, U> {
+        self.map_slots(|_, value| Some(f(value)))
+    }
+
+    /// Converts every value by reference into a new map with cloned keys.
+    ///
+    /// The alias structure of the new map is identical to this one.
+    ///
+    /// # Arguments
+    ///
+    /// * `f` - The conversion applied to each value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyMap;
+    ///
+    /// let mut map = MultiKeyMap::new();
+    /// map.insert("port", "8080");
+    /// map.insert_alias(&"port", "p");
+    ///
+    /// let lengths = map.map_values_ref(|value| value.len());
+    /// assert_eq!(lengths.get(&"p"), Some(&4));
+    /// assert!(lengths.are_aliases(&"port", &"p"));
+    /// assert_eq!(map.get(&"p"), Some(&"8080"));
+    /// ```
+    pub fn map_values_ref<U>(&self, mut f: impl FnMut(&V) -> U) -> MultiKeyMap<K, U>
+    where
+        K: Clone,
+    {
+        let slots = self
+            .slots
+            .iter()
+            .map(|slot| Slot {
+                generation: slot.generation,
+                value: slot.value.as_ref().map(&mut f),
+                keys: slot.keys.clone(),
+                revision: slot.revision,
+            })
+            .collect();
+        MultiKeyMap {
+            key_index: self.key_index.clone(),
+            hasher: self.hasher.clone(),
+            slots,
+            free: self.free.clone(),
+            len: self.len,
+            next_generation: self.next_generation,
+            alias_limit: self.alias_limit,
+            counters: self.counters.clone(),
+            hooks: Hooks::default(),
+            revision: self.revision,
+            normalizer: self.normalizer.clone(),
+            soft_links: self.soft_links.clone(),
+            value_index: None,
+        }
+    }
+
+    /// Consumes the map, converting every value and dropping the groups mapped to `None`.
+    ///
+    /// The closure receives the keys of each group (in unspecified order) along with its value.
+    /// Returning `None` drops the whole group including all of its keys.
+    ///
+    /// # Arguments
+    ///
+    /// * `f` - The conversion applied to each group.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyMap;
+    ///
+    /// let mut map = MultiKeyMap::new();
+    /// map.insert("port", "8080");
+    /// map.insert_alias(&"port", "p");
+    /// map.insert("workers", "many");
+    /// map.insert_alias(&"workers", "w");
+    /// map.insert("debug:level", "3");
+    ///
+    /// let parsed = map.filter_map_values(|keys, value| {
+    ///     if keys.iter().any(|key| key.starts_with("debug:")) {
+    ///         return None;
+    ///     }
+    ///     value.parse::<u32>().ok()
+    /// });
+    /// assert_eq!(parsed.len(), 1);
+    /// assert_eq!(parsed.get(&"p"), Some(&8080));
+    /// assert!(parsed.are_aliases(&"port", &"p"));
+    /// assert!(!parsed.contains_key(&"workers"));
+    /// assert!(!parsed.contains_key(&"w"));
+    /// assert!(!parsed.contains_key(&"debug:level"));
+    /// ```
+    pub fn filter_map_values<U>(self, f: impl FnMut(&[K], V) -> Option<U>) -> MultiKeyMap<K, U> {
+        self.map_slots(f)
+    }
+
+    /// Consumes the map and converts every key, resolving collisions with `on_collision`.
+    ///
+    /// A collision happens when keys of two different groups convert to the same key. Keys of
+    /// the same group converting to the same key are simply merged into one key and never count
+    /// as a collision. Groups are processed in storage order, so with
+    /// [`CollisionPolicy::KeepFirst`] the group stored first keeps the key; a group that loses
+    /// all of its keys this way is dropped.
+    ///
+    /// Soft aliases are converted as well, together with the keys they point to. A soft alias
+    /// converting to a key bound to a group is dropped.
+    ///
+    /// # Arguments
+    ///
+    /// * `f` - The conversion applied to each key.
+    /// * `on_collision` - How to resolve keys of different groups colliding.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`KeyCollisionError`] with the colliding key if `on_collision` is
+    /// [`CollisionPolicy::Error`] and a collision occurs.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::{CollisionPolicy, MultiKeyMap};
+    ///
+    /// let mut map = MultiKeyMap::new();
+    /// map.insert("Berlin", 1);
+    /// map.insert_alias(&"Berlin", "BERLIN");
+    /// map.insert("berlin", 2);
+    /// map.insert("Paris", 3);
+    ///
+    /// let error = map
+    ///     .map_keys(|key| key.to_lowercase(), CollisionPolicy::Error)
+    ///     .unwrap_err();
+    /// assert_eq!(error.key, "berlin");
+    ///
+    /// // Keys of the same group colliding are harmless
+    /// let mut map = MultiKeyMap::new();
+    /// map.insert("Berlin", 1);
+    /// map.insert_alias(&"Berlin", "BERLIN");
+    /// let map = map.map_keys(|key| key.to_lowercase(), CollisionPolicy::Error).unwrap();
+    /// assert_eq!(map.key_count(), 1);
+    /// assert_eq!(map.get(&"berlin".to_string()), Some(&1));
+    ///
+    /// // Keep the binding of the group stored first
+    /// let mut map = MultiKeyMap::new();
+    /// map.insert("Berlin", 1);
+    /// map.insert("berlin", 2);
+    /// map.insert_alias(&"berlin", "BER");
+    /// let map = map.map_keys(|key| key.to_lowercase(), CollisionPolicy::KeepFirst).unwrap();
+    /// assert_eq!(map.get(&"berlin".to_string()), Some(&1));
+    /// assert_eq!(map.get(&"ber".to_string()), Some(&2));
+    ///
+    /// // Merge the colliding groups, combining their values
+    /// let mut map = MultiKeyMap::new();
+    /// map.insert("Berlin", 1);
+    /// map.insert("berlin", 2);
+    /// map.insert_alias(&"berlin", "BER");
+    /// map.insert("Paris", 3);
+    /// let map = map
+    ///     .map_keys(
+    ///         |key| key.to_lowercase(),
+    ///         CollisionPolicy::Merge(Box::new(|a, b| a + b)),
+    ///     )
+    ///     .unwrap();
+    /// assert_eq!(map.len(), 2);
+    /// assert_eq!(map.get(&"berlin".to_string()), Some(&3));
+    /// assert!(map.are_aliases(&"berlin".to_string(), &"ber".to_string()));
+    /// assert_eq!(map.get(&"paris".to_string()), Some(&3));
+    ///
+    /// // Soft aliases follow the conversion
+    /// let mut map = MultiKeyMap::new();
+    /// map.insert("Berlin", 1);
+    /// map.insert_soft_alias(&"Berlin", "Capital");
+    /// let map = map.map_keys(|key| key.to_lowercase(), CollisionPolicy::Error).unwrap();
+    /// assert_eq!(map.soft_alias_target(&"capital".to_string()), Some(&"berlin".to_string()));
+    /// assert_eq!(map.get(&"capital".to_string()), Some(&1));
+    /// ```
+    pub fn map_keys<J: Eq + Hash>(
+        self,
+        mut f: impl FnMut(K) -> J,
+        mut on_collision: CollisionPolicy<V>,
+    ) -> Result<MultiKeyMap<J, V>, KeyCollisionError<J>> {
+        let mut groups = Vec::with_capacity(self.slots.len());
+        let disabled = self.disabled_positions();
+        let soft_links = self.soft_links;
+        let slots = self
+            .slots
+            .into_iter()
+            .map(|slot| {
+                groups.push(slot.keys);
+                Slot {
+                    generation: slot.generation,
+                    value: slot.value,
+                    keys: GroupKeys::new(),
+                    revision: slot.revision,
+                }
+            })
+            .collect();
+        let mut map = MultiKeyMap {
+            key_index: KeyIndex::with_capacity(self.key_index.len()),
+            hasher: self.hasher,
+            slots,
+            free: self.free,
+            len: self.len,
+            next_generation: self.next_generation,
+            alias_limit: self.alias_limit,
+            counters: self.counters,
+            hooks: Hooks::default(),
+            revision: self.revision,
+            normalizer: None,
+            soft_links: HashTable::new(),
+            value_index: None,
+        };
+
+        // Bind keys in slot order, so the group stored first wins a collision
+        let mut sets = DisjointSets::new(groups.len());
+        for (index, keys) in groups.into_iter().enumerate() {
+            let disabled = disabled.get(&index).map_or(&[][..], Vec::as_slice);
+            for (position, key) in keys.into_iter().map(&mut f).enumerate() {
+                let Some(existing) = map.index_of(&key) else {
+                    map.link(key, index);
+                    if disabled.contains(&position) {
+                        map.disable_at(index, map.slots[index].keys.len() - 1);
+                    }
+                    continue;
+                };
+                if sets.find(existing) == sets.find(index) {
+                    continue;
+                }
+                match on_collision {
+                    CollisionPolicy::Error => return Err(KeyCollisionError { key }),
+                    CollisionPolicy::KeepFirst => {}
+                    CollisionPolicy::Merge(_) => sets.union(existing, index),
+                }
+            }
+        }
+        map.merge_sets(&mut sets, |first, second| match &mut on_collision {
+            CollisionPolicy::Merge(resolve) => resolve(first, second),
+            _ => unreachable!("only the merge policy unites groups"),
+        });
+        for (link, target) in soft_links {
+            map.set_soft_link(f(link), f(target));
+        }
+        Ok(map)
+    }
+
+    /// Consumes the map and splits it into two maps by a predicate on each group.
+    ///
+    /// Groups for which the predicate returns `true` go into the first map, the others into the
+    /// second. Each group moves with all of its keys; nothing is cloned.
+    ///
+    /// # Arguments
+    ///
+    /// * `f` - The predicate receiving the keys (in unspecified order) and value of each group.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyMap;
+    ///
+    /// let mut map = MultiKeyMap::new();
+    /// map.insert("a", "active");
+    /// map.insert_alias(&"a", "a1");
+    /// map.insert("b", "archived");
+    /// map.insert_alias(&"b", "b1");
+    /// map.insert("c", "active");
+    /// let keys: Vec<_> = map.keys().cloned().collect();
+    ///
+    /// let (active, archived) = map.partition(|_, value| *value == "active");
+    /// assert_eq!(active.len(), 2);
+    /// assert_eq!(archived.len(), 1);
+    /// assert!(active.are_aliases(&"a", &"a1"));
+    /// assert!(archived.are_aliases(&"b", &"b1"));
+    /// for key in &keys {
+    ///     assert!(active.contains_key(key) != archived.contains_key(key));
+    /// }
+    /// assert_eq!(active.key_count() + archived.key_count(), keys.len());
+    /// ```
+    pub fn partition(self, mut f: impl FnMut(&[K], &V) -> bool) -> (Self, Self) {
+        let mut left = MultiKeyMap::new();
+        let mut right = MultiKeyMap::new();
+        for (keys, disabled, value) in self.into_groups() {
+            let side = if f(&keys, &value) {
+                &mut left
+            } else {
+                &mut right
+            };
+            let index = side.push_group(keys, value);
+            for position in disabled {
+                side.disable_at(index, position);
+            }
+        }
+        (left, right)
+    }
+
+    /// Removes every group containing any of the given keys and returns them as a new map.
+    ///
+    /// Each group is moved with its complete set of keys, including aliases that were not
+    /// listed. Listing several keys of the same group moves it once, and missing keys are
+    /// ignored.
+    ///
+    /// # Arguments
+    ///
+    /// * `keys` - The keys whose groups should be split off.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyMap;
+    ///
+    /// let mut map = MultiKeyMap::new();
+    /// map.insert("a", 1);
+    /// map.insert_alias(&"a", "a1");
+    /// map.insert_alias(&"a", "a2");
+    /// map.insert("b", 2);
+    /// map.insert("c", 3);
+    /// map.insert_alias(&"c", "c1");
+    ///
+    /// let shard = map.split_off(&["a", "a1", "c", "missing"]);
+    /// assert_eq!(shard.len(), 2);
+    /// assert_eq!(shard.get(&"a2"), Some(&1));
+    /// assert_eq!(shard.get(&"c1"), Some(&3));
+    /// assert!(shard.are_aliases(&"a", &"a2"));
+    /// assert_eq!(shard.key_count(), 5);
+    ///
+    /// assert_eq!(map.len(), 1);
+    /// assert_eq!(map.key_count(), 1);
+    /// assert_eq!(map.get(&"b"), Some(&2));
+    /// assert!(!map.contains_key(&"a2"));
+    /// ```
+    pub fn split_off<'a>(&mut self, keys: impl IntoIterator<Item = &'a K>) -> Self
+    where
+        K: 'a,
+    {
+        let mut take = vec![false; self.slots.len()];
+        for key in keys {
+            if let Some(index) = self.index_of(key) {
+                take[index] = true;
+            }
+        }
+        let taken = self.extract_groups(&take);
+        self.check_mutation();
+        taken
+    }
+
+    /// Returns a new map with clones of every group containing any of the given keys.
+    ///
+    /// Each group is copied with its complete set of keys and appears once, no matter how many
+    /// of its keys were listed. Missing keys are ignored and `self` is left untouched.
+    ///
+    /// # Arguments
+    ///
+    /// * `keys` - The keys whose groups should be copied.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyMap;
+    ///
+    /// let mut map = MultiKeyMap::new();
+    /// map.insert("a", 1);
+    /// map.insert_alias(&"a", "a1");
+    /// map.insert_alias(&"a", "a2");
+    /// map.insert("b", 2);
+    /// map.insert("c", 3);
+    ///
+    /// let subset = map.subset(&["a", "a1", "c", "missing"]);
+    /// assert_eq!(subset.len(), 2);
+    /// assert!(subset.group_matches(&"a", &["a", "a1", "a2"]));
+    /// assert_eq!(subset.get(&"c"), Some(&3));
+    /// assert!(!subset.contains_key(&"b"));
+    ///
+    /// assert_eq!(map.len(), 3);
+    /// assert_eq!(map.key_count(), 5);
+    /// ```
+    pub fn subset<'a>(&self, keys: impl IntoIterator<Item = &'a K>) -> Self
+    where
+        K: 'a + Clone,
+        V: Clone,
+    {
+        let mut take = vec![false; self.slots.len()];
+        for key in keys {
+            if let Some(index) = self.index_of(key) {
+                take[index] = true;
+            }
+        }
+        self.clone_groups(&take)
+    }
+
+    /// Returns clones of the groups of `self` none of whose keys are present in `other`.
+    ///
+    /// Groups are compared as a whole: a group sharing even one key with `other` is left out,
+    /// regardless of how `other` groups that key. A key of `other` is present even while it is
+    /// [disabled](Self::disable_alias), while a [soft alias](Self::insert_soft_alias) of
+    /// `other` is not a key of it.
+    ///
+    /// # Arguments
+    ///
+    /// * `other` - The map whose keys are subtracted.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyMap;
+    ///
+    /// let mut map = MultiKeyMap::new();
+    /// map.insert("a", 1);
+    /// map.insert_alias(&"a", "a1");
+    /// map.insert("b", 2);
+    /// map.insert("c", 3);
+    ///
+    /// let mut other = MultiKeyMap::new();
+    /// other.insert("a1", 10);
+    /// other.insert_alias(&"a1", "c");
+    ///
+    /// let difference = map.difference(&other);
+    /// assert_eq!(difference.len(), 1);
+    /// assert_eq!(difference.get(&"b"), Some(&2));
+    /// assert!(!difference.contains_key(&"a"));
+    ///
+    /// // A disabled key of `other` is subtracted, a soft alias is not
+    /// other.disable_alias(&"c");
+    /// other.insert_soft_alias(&"a1", "b");
+    /// let difference = map.difference(&other);
+    /// assert_eq!(difference.len(), 1);
+    /// assert_eq!(difference.get(&"b"), Some(&2));
+    /// assert_eq!(map.intersection_keys(&other).len(), 2);
+    /// ```
+    pub fn difference(&self, other: &Self) -> Self
+    where
+        K: Clone,
+        V: Clone,
+    {
+        let take: Vec<bool> = self
+            .slots
+            .iter()
+            .map(|slot| !slot.keys.iter().any(|key| other.index_of(key).is_some()))
+            .collect();
+        self.clone_groups(&take)
+    }
+
+    /// Returns clones of the groups of `self` sharing at least one key with `other`.
+    ///
+    /// Groups are compared as a whole: an overlapping group is copied with all of its keys,
+    /// including those absent from `other`, regardless of how `other` groups the shared keys.
+    /// A key of `other` is present even while it is [disabled](Self::disable_alias), while a
+    /// [soft alias](Self::insert_soft_alias) of `other` is not a key of it.
+    ///
+    /// # Arguments
+    ///
+    /// * `other` - The map whose keys select the groups.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::{assert_multi_key_map, MultiKeyMap};
+    ///
+    /// let mut map = MultiKeyMap::new();
+    /// map.insert("a", 1);
+    /// map.insert_alias(&"a", "a1");
+    /// map.insert("b", 2);
+    /// map.insert_alias(&"b", "b1");
+    /// map.insert("c", 3);
+    ///
+    /// // `other` considers `a1` and `b` to be the same value
+    /// let mut other = MultiKeyMap::new();
+    /// other.insert("a1", 10);
+    /// other.insert_alias(&"a1", "b");
+    ///
+    /// let intersection = map.intersection_keys(&other);
+    /// assert_multi_key_map!(intersection, [["a", "a1"] => 1, ["b", "b1"] => 2]);
+    /// ```
+    pub fn intersection_keys(&self, other: &Self) -> Self
+    where
+        K: Clone,
+        V: Clone,
+    {
+        let take: Vec<bool> = self
+            .slots
+            .iter()
+            .map(|slot| slot.keys.iter().any(|key| other.index_of(key).is_some()))
+            .collect();
+        self.clone_groups(&take)
+    }
+
+    /// Moves every group of `other` into `self`, resolving shared keys with `policy`.
+    ///
+    /// A group that loses all of its keys to the other map is dropped together with its value.
+    /// To merge the groups on both sides of a shared key instead, use
+    /// [`append_with`](Self::append_with).
+    ///
+    /// # Arguments
+    ///
+    /// * `other` - The map whose groups are moved into `self`.
+    /// * `policy` - Which side keeps a key present in both maps.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::{assert_multi_key_map, MergePolicy, MultiKeyMap};
+    ///
+    /// let mut map = MultiKeyMap::new();
+    /// map.insert("a", 1);
+    /// map.insert_alias(&"a", "shared");
+    /// map.insert("b", 2);
+    ///
+    /// let mut other = MultiKeyMap::new();
+    /// other.insert("c", 3);
+    /// other.insert_alias(&"c", "shared");
+    /// other.insert("b", 4);
+    ///
+    /// let mut kept = map.clone();
+    /// kept.append(other.clone(), MergePolicy::KeepSelf);
+    /// assert_multi_key_map!(kept, [["a", "shared"] => 1, ["b"] => 2, ["c"] => 3]);
+    ///
+    /// map.append(other, MergePolicy::Replace);
+    /// assert_multi_key_map!(map, [["a"] => 1, ["c", "shared"] => 3, ["b"] => 4]);
+    ///
+    /// // A disabled key is kept or replaced like any other
+    /// let mut map = MultiKeyMap::new();
+    /// map.insert_with_keys(["k", "a"], 1).unwrap();
+    /// map.disable_alias(&"k");
+    /// let mut other = MultiKeyMap::new();
+    /// other.insert("k", 2);
+    ///
+    /// let mut kept = map.clone();
+    /// kept.append(other.clone(), MergePolicy::KeepSelf);
+    /// assert_eq!(kept.get(&"k"), None);
+    /// assert!(kept.enable_alias(&"k"));
+    /// assert_multi_key_map!(kept, [["k", "a"] => 1]);
+    ///
+    /// map.append(other, MergePolicy::Replace);
+    /// assert_multi_key_map!(map, [["a"] => 1, ["k"] => 2]);
+    /// ```
+    pub fn append(&mut self, other: Self, policy: MergePolicy) {
+        let _span = self.trace_bulk("append");
+        self.reserve_keys(other.key_count());
+        self.slots
+            .reserve(other.len.saturating_sub(self.free.len()));
+        if policy == MergePolicy::Replace {
+            let mut affected = false;
+            for key in other.keys() {
+                if let Some((index, key)) = self.unbind(key) {
+                    self.log_unbound(&key, index);
+                    affected = true;
+                }
+            }
+            if affected {
+                let keep: Vec<bool> = self
+                    .slots
+                    .iter()
+                    .map(|slot| !slot.keys.is_empty())
+                    .collect();
+                self.retain_groups(&keep);
+            }
+        }
+        for (keys, _, value) in other.into_groups() {
+            let keys: Vec<K> = keys
+                .into_iter()
+                .filter(|key| self.index_of(key).is_none())
+                .collect();
+            if !keys.is_empty() {
+                self.push_group(keys, value);
+            }
+        }
+        self.check_mutation();
+    }
+
+    /// Moves every group of `other` into `self`, merging groups that share a key.
+    ///
+    /// All groups connected through shared keys, on either side, become a single group holding
+    /// the union of their keys. Their values are combined with `resolve`, which receives values
+    /// of `self` before values of `other`.
+    ///
+    /// # Arguments
+    ///
+    /// * `other` - The map whose groups are moved into `self`.
+    /// * `resolve` - Combines the values of two merged groups.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyMap;
+    ///
+    /// let mut map = MultiKeyMap::new();
+    /// map.insert("a", 1);
+    /// map.insert_alias(&"a", "a1");
+    /// map.insert("b", 2);
+    /// map.insert("c", 4);
+    ///
+    /// let mut other = MultiKeyMap::new();
+    /// other.insert("a1", 10);
+    /// other.insert_alias(&"a1", "x");
+    /// other.insert_alias(&"a1", "b");
+    /// other.insert("d", 20);
+    ///
+    /// map.append_with(other, |mine, theirs| mine + theirs);
+    /// assert_eq!(map.len(), 3);
+    /// assert!(map.group_matches(&"a", &["a", "a1", "x", "b"]));
+    /// assert_eq!(map.get(&"x"), Some(&13));
+    /// assert_eq!(map.get(&"c"), Some(&4));
+    /// assert_eq!(map.get(&"d"), Some(&20));
+    /// ```
+    pub fn append_with(&mut self, other: Self, resolve: impl FnMut(V, V) -> V) {
+        let _span = self.trace_bulk("append_with");
+        self.reserve_keys(other.key_count());
+        self.slots.reserve(other.len);
+        // Append after the existing slots so values of `self` precede those of `other`
+        let mut sets = DisjointSets::new(self.slots.len() + other.len);
+        for (keys, _, value) in other.into_groups() {
+            let index = self.push_slot(value);
+            for key in keys {
+                match self.index_of(&key) {
+                    Some(existing) => sets.union(existing, index),
+                    None => self.link(key, index),
+                }
+            }
+            // Groups merged into others are recorded as removed again by `merge_sets`
+            if let (Some(log), false) = (self.hooks.log(), self.slots[index].keys.is_empty()) {
+                log.group_created(&self.slots[index]);
+            }
+        }
+        self.merge_sets(&mut sets, resolve);
+        self.check_mutation();
+    }
+
+    /// Inserts every key-value pair of `iter` whose key is free, and reports the others.
+    ///
+    /// Unlike [`extend`](Extend::extend), which lets later pairs take the keys of earlier ones,
+    /// no key changes group: a pair is rejected if its key is already bound in the map, or if
+    /// an earlier pair of the batch has the same key. With [`ExtendPolicy::Partial`] the other
+    /// pairs are inserted as new groups, in order; with [`ExtendPolicy::Atomic`] nothing is
+    /// inserted unless every pair can be.
+    ///
+    /// Returns [`ExtendReport`] listing the pairs that were not inserted, in the order of
+    /// `iter`, if any pair was rejected.
+    ///
+    /// # Arguments
+    ///
+    /// * `iter` - The key-value pairs to insert.
+    /// * `policy` - Whether pairs without conflicts are inserted when others are rejected.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::{ExtendPolicy, MultiKeyMap, RejectReason};
+    ///
+    /// let mut map = MultiKeyMap::new();
+    /// map.insert_with_keys(["alice", "a@example.com"], 1).unwrap();
+    ///
+    /// let batch = [("bob", 2), ("a@example.com", 3), ("carol", 4), ("bob", 5)];
+    /// let report = map.try_extend(batch, ExtendPolicy::Partial).unwrap_err();
+    /// assert_eq!(report.to_string(), "2 of 4 pairs were rejected");
+    /// assert_eq!(report.inserted, 2);
+    /// assert_eq!(
+    ///     report.rejected,
+    ///     [
+    ///         ("a@example.com", 3, RejectReason::KeyBound),
+    ///         ("bob", 5, RejectReason::DuplicateKey),
+    ///     ]
+    /// );
+    /// assert_eq!(map.get(&"a@example.com"), Some(&1));
+    /// assert_eq!(map.get(&"bob"), Some(&2));
+    /// assert_eq!(map.get(&"carol"), Some(&4));
+    /// assert_eq!(map.len(), 3);
+    ///
+    /// // Atomically, a single conflict rejects the whole batch
+    /// let report = map
+    ///     .try_extend([("dave", 6), ("carol", 7)], ExtendPolicy::Atomic)
+    ///     .unwrap_err();
+    /// assert_eq!(report.inserted, 0);
+    /// assert_eq!(
+    ///     report.rejected,
+    ///     [
+    ///         ("dave", 6, RejectReason::BatchRejected),
+    ///         ("carol", 7, RejectReason::KeyBound),
+    ///     ]
+    /// );
+    /// assert!(!map.contains_key(&"dave"));
+    ///
+    /// assert_eq!(map.try_extend([("dave", 6)], ExtendPolicy::Atomic), Ok(()));
+    /// assert_eq!(map.get(&"dave"), Some(&6));
+    /// ```
+    pub fn try_extend(
+        &mut self,
+        iter: impl IntoIterator<Item = (K, V)>,
+        policy: ExtendPolicy,
+    ) -> Result<(), ExtendReport<K, V>> {
+        let _span = self.trace_bulk("try_extend");
+        let pairs: Vec<(K, V)> = iter.into_iter().collect();
+        // The position of the first pair of each key in the batch
+        let mut seen = HashTable::with_capacity(pairs.len());
+        let reasons: Vec<Option<RejectReason>> = (0..pairs.len())
+            .map(|position| {
+                let key = &pairs[position].0;
+                if self.find(key).is_some() {
+                    return Some(RejectReason::KeyBound);
+                }
+                let hash = match &self.normalizer {
+                    Some(normalize) => self.hash(&normalize(key)),
+                    None => self.hash(key),
+                };
+                let same = |&(_, earlier): &(u64, usize)| self.same_key(&pairs[earlier].0, key);
+                if seen.find(hash, same).is_some() {
+                    return Some(RejectReason::DuplicateKey);
+                }
+                seen.insert_unique(hash, (hash, position), |&(hash, _)| hash);
+                None
+            })
+            .collect();
+        let rejects = reasons.iter().any(Option::is_some);
+        if !rejects {
+            for (key, value) in pairs {
+                self.insert(key, value);
+            }
+            return Ok(());
+        }
+        let mut report = ExtendReport {
+            inserted: 0,
+            rejected: Vec::new(),
+        };
+        for ((key, value), reason) in pairs.into_iter().zip(reasons) {
+            match (reason, policy) {
+                (Some(reason), _) => report.rejected.push((key, value, reason)),
+                (None, ExtendPolicy::Atomic) => {
+                    report
+                        .rejected
+                        .push((key, value, RejectReason::BatchRejected));
+                }
+                (None, ExtendPolicy::Partial) => {
+                    self.insert(key, value);
+                    report.inserted += 1;
+                }
+            }
+        }
+        Err(report)
+    }
+
+    /// Computes the changes that turn `self` into `other`.
+    ///
+    /// Each group of `other` is matched with a group of `self` it shares keys with; matched
+    /// groups are kept and have their aliases and value adjusted, while unmatched groups are
+    /// removed from or added to `self`. Keys [disabled](Self::disable_alias) in only one of the
+    /// maps are listed to be disabled or enabled. Applying the result to `self` with
+    /// [`apply`](Self::apply) makes it equal to `other`.
+    ///
+    /// # Arguments
+    ///
+    /// * `other` - The target state.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::{assert_multi_key_map, MultiKeyMap};
+    ///
+    /// let mut a = MultiKeyMap::new();
+    /// a.insert("x", 1);
+    /// a.insert_alias(&"x", "x1");
+    /// a.insert("y", 2);
+    /// a.insert("z", 3);
+    /// a.insert_alias(&"z", "z1");
+    ///
+    /// let mut b = MultiKeyMap::new();
+    /// b.insert("x", 1);
+    /// b.insert_alias(&"x", "x2");
+    /// b.insert("z", 30);
+    /// b.insert_alias(&"z", "z1");
+    /// b.insert_alias(&"z", "y");
+    /// b.insert("w", 4);
+    ///
+    /// let changes = a.diff(&b);
+    /// assert_eq!(changes.removed_groups, vec!["y"]);
+    /// assert_eq!(changes.removed_aliases, vec!["x1"]);
+    /// assert_eq!(changes.added_aliases.len(), 2);
+    /// assert_eq!(changes.replaced_values.len(), 1);
+    /// assert_eq!(changes.added_groups, vec![(vec!["w"], 4)]);
+    ///
+    /// a.apply(changes);
+    /// assert_eq!(a, b);
+    /// assert!(a.same_grouping(&b));
+    /// assert_multi_key_map!(a, [["x", "x2"] => 1, ["y", "z", "z1"] => 30, ["w"] => 4]);
+    /// assert!(a.diff(&b).is_empty());
+    ///
+    /// // Groups that split or merge round-trip as well
+    /// let mut c = MultiKeyMap::new();
+    /// c.insert("p", 1);
+    /// c.insert_alias(&"p", "q");
+    /// c.insert_alias(&"p", "r");
+    ///
+    /// let mut d = MultiKeyMap::new();
+    /// d.insert("p", 1);
+    /// d.insert("q", 1);
+    /// d.insert_alias(&"q", "r");
+    /// d.insert_alias(&"q", "s");
+    ///
+    /// let mut split = c.clone();
+    /// split.apply(c.diff(&d));
+    /// assert_eq!(split, d);
+    /// assert!(split.same_grouping(&d));
+    /// assert_multi_key_map!(split, [["p"] => 1, ["q", "r", "s"] => 1]);
+    ///
+    /// let mut merged = d.clone();
+    /// merged.apply(d.diff(&c));
+    /// assert_eq!(merged, c);
+    /// assert!(merged.same_grouping(&c));
+    /// assert_multi_key_map!(merged, [["p", "q", "r"] => 1]);
+    ///
+    /// // So do disabled keys
+    /// let mut disabled = c.clone();
+    /// disabled.disable_alias(&"q");
+    /// let changes = c.diff(&disabled);
+    /// assert_eq!(changes.disabled_keys, vec!["q"]);
+    /// let mut applied = c.clone();
+    /// applied.apply(changes);
+    /// assert_eq!(applied, disabled);
+    ///
+    /// let changes = disabled.diff(&c);
+    /// assert_eq!(changes.enabled_keys, vec!["q"]);
+    /// applied.apply(changes);
+    /// assert_eq!(applied, c);
+    /// ```
+    pub fn diff(&self, other: &Self) -> ChangeSet<K, V>
+    where
+        K: Clone,
+        V: PartialEq + Clone,
+    {
+        let mut changes = ChangeSet::new();
+        let mut matched_to = vec![None; self.slots.len()];
+
+        for (other_index, slot) in other.slots.iter().enumerate() {
+            let keys = &slot.keys;
+            if slot.value.is_none() {
+                continue;
+            }
+            // Match with the unmatched group of `self` holding most of these keys
+            let mut shared: HashMap<usize, usize> = HashMap::new();
+            for key in keys {
+                if let Some(index) = self.index_of(key) {
+                    *shared.entry(index).or_default() += 1;
+                }
+            }
+            let candidate = shared
+                .into_iter()
+                .filter(|&(index, _)| matched_to[index].is_none())
+                .max_by_key(|&(index, count)| (count, std::cmp::Reverse(index)));
+            let value = other.value(other_index);
+            match candidate {
+                Some((index, _)) => {
+                    matched_to[index] = Some(other_index);
+                    let anchor = keys
+                        .iter()
+                        .find(|key| self.index_of(key) == Some(index))
+                        .expect("matched groups share a key");
+                    for key in keys {
+                        let disabled = other.is_alias_disabled(key);
+                        if self.index_of(key) != Some(index) {
+                            changes.added_aliases.push((anchor.clone(), key.clone()));
+                            if disabled {
+                                changes.disabled_keys.push(key.clone());
+                            }
+                        } else if disabled && !self.is_alias_disabled(key) {
+                            changes.disabled_keys.push(key.clone());
+                        } else if !disabled && self.is_alias_disabled(key) {
+                            changes.enabled_keys.push(key.clone());
+                        }
+                    }
+                    if self.value(index) != value {
+                        changes
+                            .replaced_values
+                            .push((anchor.clone(), value.clone()));
+                    }
+                }
+                None => {
+                    changes.added_groups.push((keys.to_vec(), value.clone()));
+                    changes.disabled_keys.extend(
+                        keys.iter()
+                            .filter(|key| other.is_alias_disabled(key))
+                            .cloned(),
+                    );
+                }
+            }
+        }
+
+        for (index, slot) in self.slots.iter().enumerate() {
+            match matched_to[index] {
+                Some(other_index) => changes.removed_aliases.extend(
+                    slot.keys
+                        .iter()
+                        .filter(|key| other.index_of(key) != Some(other_index))
+                        .cloned(),
+                ),
+                None => changes.removed_groups.extend(slot.keys.first().cloned()),
+            }
+        }
+        changes
+    }
+
+    /// Applies a [`ChangeSet`], typically produced by [`diff`](Self::diff).
+    ///
+    /// Groups are removed first, then aliases are removed and added, values are replaced, new
+    /// groups are inserted, and finally keys are disabled and enabled. Keys identify their
+    /// groups even if they are disabled.
+    ///
+    /// # Arguments
+    ///
+    /// * `changes` - The changes to apply.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::{ChangeSet, MultiKeyMap};
+    ///
+    /// let mut map = MultiKeyMap::new();
+    /// map.insert("a", 1);
+    /// map.insert("b", 2);
+    ///
+    /// let mut changes = ChangeSet::new();
+    /// changes.removed_groups.push("b");
+    /// changes.added_aliases.push(("a", "a1"));
+    /// changes.replaced_values.push(("a1", 10));
+    /// changes.added_groups.push((vec!["c", "c1"], 3));
+    /// map.apply(changes);
+    ///
+    /// assert_eq!(map.len(), 2);
+    /// assert_eq!(map.get(&"a"), Some(&10));
+    /// assert!(map.are_aliases(&"c", &"c1"));
+    /// assert!(!map.contains_key(&"b"));
+    /// ```
+    pub fn apply(&mut self, changes: ChangeSet<K, V>) {
+        for key in &changes.removed_groups {
+            self.remove(key);
+        }
+        for key in &changes.removed_aliases {
+            self.remove_alias(key);
+        }
+        for (key, alias) in changes.added_aliases {
+            self.insert_alias(&key, alias);
+        }
+        for (key, value) in changes.replaced_values {
+            // Unlike `replace`, which misses disabled keys, any key of the group identifies it
+            if let Some(index) = self.index_of(&key) {
+                *self.value_mut(index) = value;
+                self.hooks.value_replaced(&self.slots[index]);
+            }
+        }
+        for (keys, value) in changes.added_groups {
+            self.push_group(keys, value);
+        }
+        for key in &changes.disabled_keys {
+            self.set_disabled(key, true);
+        }
+        for key in &changes.enabled_keys {
+            self.set_disabled(key, false);
+        }
+        self.check_mutation();
+    }
+
+    /// Checks if both maps bind the same keys to equal values, regardless of grouping.
+    ///
+    /// Unlike `==`, which also requires both maps to hold the same number of values, two keys
+    /// may be aliases in one map and point to separate but equal values in the other. Maps that
+    /// are equal under `==` are always equal under this relation.
+    ///
+    /// # Arguments
+    ///
+    /// * `other` - The map to compare against.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyMap;
+    ///
+    /// let mut grouped = MultiKeyMap::new();
+    /// grouped.insert("a", 1);
+    /// grouped.insert_alias(&"a", "b");
+    ///
+    /// let mut separate = MultiKeyMap::new();
+    /// separate.insert("a", 1);
+    /// separate.insert("b", 1);
+    ///
+    /// assert!(grouped.eq_ignoring_aliases(&separate));
+    /// assert_ne!(grouped, separate);
+    ///
+    /// separate.insert("c", 1);
+    /// assert!(!grouped.eq_ignoring_aliases(&separate));
+    /// assert!(!separate.eq_ignoring_aliases(&grouped));
+    /// ```
+    pub fn eq_ignoring_aliases(&self, other: &Self) -> bool
+    where
+        V: PartialEq,
+    {
+        self.key_count() == other.key_count()
+            && self.occupied().all(|(index, value)| {
+                self.slots[index].keys.iter().all(|key| {
+                    other
+                        .index_of(key)
+                        .is_some_and(|other_index| value == other.value(other_index))
+                })
+            })
+    }
+
+    /// Checks if both maps have the same keys, grouped the same way, regardless of values.
+    ///
+    /// Two keys are aliases in `self` if and only if they are aliases in `other`. Values are
+    /// never compared.
+    ///
+    /// # Arguments
+    ///
+    /// * `other` - The map to compare against.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyMap;
+    ///
+    /// let mut map = MultiKeyMap::new();
+    /// map.insert("a", 1);
+    /// map.insert_alias(&"a", "b");
+    /// map.insert("c", 2);
+    ///
+    /// let mut other = MultiKeyMap::new();
+    /// other.insert("b", "x");
+    /// other.insert_alias(&"b", "a");
+    /// other.insert("c", "y");
+    /// assert!(map.same_grouping(&other));
+    ///
+    /// // Same keys, different partition
+    /// let mut other = MultiKeyMap::new();
+    /// other.insert("a", "x");
+    /// other.insert_alias(&"a", "c");
+    /// other.insert("b", "y");
+    /// assert!(!map.same_grouping(&other));
+    ///
+    /// // Different keys
+    /// let mut other = MultiKeyMap::new();
+    /// other.insert("a", "x");
+    /// other.insert_alias(&"a", "b");
+    /// other.insert("d", "y");
+    /// assert!(!map.same_grouping(&other));
+    /// ```
+    pub fn same_grouping<U>(&self, other: &MultiKeyMap<K, U>) -> bool {
+        if self.len != other.len || self.key_count() != other.key_count() {
+            return false;
+        }
+        // Map each group of `self` to a group of `other`, and check the mapping is injective
+        let mut backward = vec![None; other.slots.len()];
+        self.slots.iter().enumerate().all(|(index, slot)| {
+            let mut forward = None;
+            slot.keys.iter().all(|key| {
+                let Some(other_index) = other.index_of(key) else {
+                    return false;
+                };
+                *forward.get_or_insert(other_index) == other_index
+                    && *backward[other_index].get_or_insert(index) == index
+            })
+        })
+    }
+
+    /// Checks if the two maps have no keys in common.
+    ///
+    /// Aliases count as keys, and so do [disabled](Self::disable_alias) keys, while
+    /// [soft aliases](Self::insert_soft_alias) do not. The smaller key set is iterated and
+    /// probed against the larger.
+    ///
+    /// # Arguments
+    ///
+    /// * `other` - The map to compare against.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyMap;
+    ///
+    /// let mut map = MultiKeyMap::new();
+    /// map.insert("a", 1);
+    /// map.insert("b", 2);
+    /// map.insert("c", 3);
+    ///
+    /// let mut other = MultiKeyMap::new();
+    /// other.insert("x", 10);
+    /// assert!(map.is_disjoint_keys(&other));
+    /// assert!(other.is_disjoint_keys(&map));
+    ///
+    /// other.insert_alias(&"x", "c");
+    /// assert!(!map.is_disjoint_keys(&other));
+    /// assert!(!other.is_disjoint_keys(&map));
+    ///
+    /// // A disabled key overlaps whichever map is the smaller one
+    /// let mut single = MultiKeyMap::new();
+    /// single.insert("k", 0);
+    /// map.insert_alias(&"a", "k");
+    /// map.disable_alias(&"k");
+    /// assert!(!map.is_disjoint_keys(&single));
+    /// assert!(!single.is_disjoint_keys(&map));
+    ///
+    /// map.enable_alias(&"k");
+    /// single.disable_alias(&"k");
+    /// assert!(!map.is_disjoint_keys(&single));
+    /// assert!(!single.is_disjoint_keys(&map));
+    /// ```
+    pub fn is_disjoint_keys<U>(&self, other: &MultiKeyMap<K, U>) -> bool {
+        self.overlapping_keys(other).next().is_none()
+    }
+
+    /// Returns an iterator over the keys present in both maps.
+    ///
+    /// Aliases count as keys, and so do [disabled](Self::disable_alias) keys, while
+    /// [soft aliases](Self::insert_soft_alias) do not. The smaller key set is iterated and
+    /// probed against the larger; the order of the keys is unspecified.
+    ///
+    /// # Arguments
+    ///
+    /// * `other` - The map to compare against.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyMap;
+    ///
+    /// let mut map = MultiKeyMap::new();
+    /// map.insert("a", 1);
+    /// map.insert_alias(&"a", "a1");
+    /// map.insert("b", 2);
+    /// map.insert("c", 3);
+    ///
+    /// let mut other = MultiKeyMap::new();
+    /// other.insert("a1", 10);
+    /// other.insert_alias(&"a1", "c");
+    ///
+    /// let mut overlap: Vec<_> = map.overlapping_keys(&other).collect();
+    /// overlap.sort();
+    /// assert_eq!(overlap, vec![&"a1", &"c"]);
+    /// let mut overlap: Vec<_> = other.overlapping_keys(&map).collect();
+    /// overlap.sort();
+    /// assert_eq!(overlap, vec![&"a1", &"c"]);
+    ///
+    /// // Disabled keys overlap on either side, soft aliases on neither
+    /// map.disable_alias(&"a1");
+    /// other.disable_alias(&"c");
+    /// other.insert_soft_alias(&"a1", "b");
+    /// for (left, right) in [(&map, &other), (&other, &map)] {
+    ///     let mut overlap: Vec<_> = left.overlapping_keys(right).collect();
+    ///     overlap.sort();
+    ///     assert_eq!(overlap, vec![&"a1", &"c"]);
+    /// }
+    /// ```
+    pub fn overlapping_keys<'a, U>(
+        &'a self,
+        other: &'a MultiKeyMap<K, U>,
+    ) -> impl Iterator<Item = &'a K> {
+        // Probe the larger map with the keys of the smaller one
+        let keys: Box<dyn Iterator<Item = &'a K> + 'a> = if self.key_count() <= other.key_count() {
+            Box::new(self.keys().filter(move |key| other.index_of(key).is_some()))
+        } else {
+            Box::new(other.keys().filter(move |key| self.index_of(key).is_some()))
+        };
+        keys
+    }
+
+    /// Retrieves all aliases (including the key itself) for a given key.
+    ///
+    /// Returns a vector of all keys associated with the value of the specified key.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key to retrieve aliases for.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyMap;
+    ///
+    /// let mut map = MultiKeyMap::new();
+    /// map.insert("key1", "value1");
+    /// map.insert_alias(&"key1", "alias1");
+    /// let mut aliases = map.aliases(&"key1").unwrap();
+    /// aliases.sort();
+    ///
+    /// assert_eq!(aliases, vec!["alias1", "key1"]);
+    /// ```
+    pub fn aliases(&self, key: &K) -> Option<Vec<K>>
+    where
+        K: Clone,
+    {
+        self.index_of(key).map(|index| self.keys_of(index))
+    }
+
+    /// Checks if two keys point to the same value.
+    ///
+    /// Returns `true` if both keys point to the same value, otherwise returns `false`.
+    ///
+    /// # Arguments
+    ///
+    /// * `key1` - The first key to check.
+    /// * `key2` - The second key to check.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyMap;
+    ///
+    /// let mut map = MultiKeyMap::new();
+    /// map.insert("key1", "value1");
+    /// map.insert_alias(&"key1", "key2");
+    /// assert!(map.are_aliases(&"key1", &"key2"));
+    /// assert!(!map.are_aliases(&"key1", &"key3"));
+    /// ```
+    pub fn are_aliases(&self, key1: &K, key2: &K) -> bool {
+        if let (Some(index1), Some(index2)) = (self.index_of(key1), self.index_of(key2)) {
+            index1 == index2
+        } else {
+            false
+        }
+    }
+
+    /// Checks if all of the given keys point to the same value.
+    ///
+    /// Each key is resolved once. Returns `false` as soon as a key is missing or points to a
+    /// different value than the first key. An empty iterator is vacuously `true`, and a single
+    /// key is equivalent to [`contains_key`](Self::contains_key).
+    ///
+    /// # Arguments
+    ///
+    /// * `keys` - The keys to check.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyMap;
+    ///
+    /// let mut map = MultiKeyMap::new();
+    /// map.insert("key1", "value1");
+    /// map.insert_alias(&"key1", "key2");
+    /// map.insert_alias(&"key1", "key3");
+    /// map.insert("other", "value2");
+    ///
+    /// assert!(map.are_aliases_all(&["key1", "key2", "key3"]));
+    /// assert!(!map.are_aliases_all(&["key1", "other", "key3"]));
+    /// assert!(!map.are_aliases_all(&["key1", "missing", "key3"]));
+    ///
+    /// assert!(map.are_aliases_all(&[]));
+    /// assert!(map.are_aliases_all(&["key1"]));
+    /// assert!(!map.are_aliases_all(&["missing"]));
+    /// ```
+    pub fn are_aliases_all<'a>(&self, keys: impl IntoIterator<Item = &'a K>) -> bool
+    where
+        K: 'a,
+    {
+        let mut group = None;
+        for key in keys {
+            match (self.index_of(key), group) {
+                (None, _) => return false,
+                (Some(index), None) => group = Some(index),
+                (Some(index), Some(first)) if index != first => return false,
+                _ => {}
+            }
+        }
+        true
+    }
+
+    /// Checks if the group containing `key` consists of exactly the `expected` keys.
+    ///
+    /// The comparison is order-insensitive and duplicates in `expected` are ignored. Returns
+    /// `false` if `key` does not exist, and stops at the first expected key outside the group.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - A key of the group to check.
+    /// * `expected` - The keys the group is expected to consist of.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyMap;
+    ///
+    /// let mut map = MultiKeyMap::new();
+    /// map.insert("key1", "value1");
+    /// map.insert_alias(&"key1", "key2");
+    /// map.insert("other", "value2");
+    ///
+    /// assert!(map.group_matches(&"key1", &["key2", "key1"]));
+    /// assert!(map.group_matches(&"key1", &["key1", "key2", "key1"]));
+    /// assert!(!map.group_matches(&"key1", &["key1"]));
+    /// assert!(!map.group_matches(&"key1", &["key1", "key2", "key3"]));
+    /// assert!(!map.group_matches(&"key1", &["key1", "key2", "other"]));
+    /// assert!(!map.group_matches(&"missing", &["missing"]));
+    /// ```
+    pub fn group_matches<'a>(&self, key: &K, expected: impl IntoIterator<Item = &'a K>) -> bool
+    where
+        K: 'a,
+    {
+        let Some(index) = self.index_of(key) else {
+            return false;
+        };
+        let mut seen = HashSet::new();
+        for expected_key in expected {
+            if self.index_of(expected_key) != Some(index) {
+                return false;
+            }
+            seen.insert(expected_key);
+        }
+        seen.len() == self.count_references(index)
+    }
+
+    /// Returns a [`GroupId`] for the group of `key`.
+    ///
+    /// The key is resolved like [`get`](Self::get) resolves it, following soft aliases. Returns
+    /// `None` if the key does not exist or is disabled.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - A key of the group.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyMap;
+    ///
+    /// let mut map = MultiKeyMap::new();
+    /// map.insert("key1", "value1");
+    /// map.insert_alias(&"key1", "alias1");
+    /// assert_eq!(map.group_id(&"key1"), map.group_id(&"alias1"));
+    /// assert_eq!(map.group_id(&"missing"), None);
+    ///
+    /// map.disable_alias(&"alias1");
+    /// assert_eq!(map.group_id(&"alias1"), None);
+    /// map.insert_soft_alias(&"key1", "latest");
+    /// assert_eq!(map.group_id(&"latest"), map.group_id(&"key1"));
+    /// ```
+    pub fn group_id(&self, key: &K) -> Option<GroupId> {
+        self.resolve_index(key).map(|index| self.id_at(index))
+    }
+
+    /// Retrieves a reference to the value of the group identified by `id`.
+    ///
+    /// Returns `None` if the id is stale.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The id of the group.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyMap;
+    ///
+    /// let mut map = MultiKeyMap::new();
+    /// map.insert("key1", "value1");
+    /// map.insert("key2", "value2");
+    /// let id1 = map.group_id(&"key1").unwrap();
+    /// let id2 = map.group_id(&"key2").unwrap();
+    /// assert_eq!(map.get_by_id(id2), Some(&"value2"));
+    ///
+    /// // Removing a group invalidates its id, even once its storage is reused
+    /// map.remove(&"key1");
+    /// map.insert("key3", "value3");
+    /// assert_eq!(map.get_by_id(id1), None);
+    /// assert_eq!(map.get_by_id(id2), Some(&"value2"));
+    /// ```
+    pub fn get_by_id(&self, id: GroupId) -> Option<&V> {
+        self.resolve_id(id).map(|index| self.value(index))
+    }
+
+    /// Retrieves a mutable reference to the value of the group identified by `id`.
+    ///
+    /// Returns `None` if the id is stale.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The id of the group.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyMap;
+    ///
+    /// let mut map = MultiKeyMap::new();
+    /// map.insert("key1", "value1");
+    /// let id = map.group_id(&"key1").unwrap();
+    /// *map.get_mut_by_id(id).unwrap() = "value2";
+    /// assert_eq!(map.get(&"key1"), Some(&"value2"));
+    ///
+    /// map.remove(&"key1");
+    /// map.insert("key2", "value3");
+    /// assert_eq!(map.get_mut_by_id(id), None);
+    /// ```
+    pub fn get_mut_by_id(&mut self, id: GroupId) -> Option<&mut V> {
+        let index = self.resolve_id(id)?;
+        Some(self.value_mut(index))
+    }
+
+    /// Retrieves all keys of the group identified by `id`.
+    ///
+    /// Returns `None` if the id is stale.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The id of the group.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyMap;
+    ///
+    /// let mut map = MultiKeyMap::new();
+    /// map.insert("key1", "value1");
+    /// map.insert_alias(&"key1", "alias1");
+    /// let id = map.group_id(&"key1").unwrap();
+    /// let mut aliases = map.aliases_by_id(id).unwrap();
+    /// aliases.sort();
+    /// assert_eq!(aliases, vec!["alias1", "key1"]);
+    ///
+    /// map.remove_alias(&"alias1");
+    /// map.remove_alias(&"key1");
+    /// assert_eq!(map.aliases_by_id(id), None);
+    /// ```
+    pub fn aliases_by_id(&self, id: GroupId) -> Option<Vec<K>>
+    where
+        K: Clone,
+    {
+        self.resolve_id(id).map(|index| self.keys_of(index))
+    }
+
+    /// Adds a new alias key for the group identified by `id`.
+    ///
+    /// Returns the reference count if the alias is successfully added, or `None` if the id is
+    /// stale.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The id of the group.
+    /// * `alias` - The alias key to add.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyMap;
+    ///
+    /// let mut map = MultiKeyMap::new();
+    /// map.insert("key1", "value1");
+    /// let id = map.group_id(&"key1").unwrap();
+    /// assert_eq!(map.insert_alias_by_id(id, "alias1"), Some(2));
+    /// assert!(map.are_aliases(&"key1", &"alias1"));
+    ///
+    /// map.remove(&"key1");
+    /// assert_eq!(map.insert_alias_by_id(id, "alias2"), None);
+    /// assert!(!map.contains_key(&"alias2"));
+    /// ```
+    pub fn insert_alias_by_id(&mut self, id: GroupId, alias: K) -> Option<usize> {
+        let index = self.resolve_id(id)?;
+        if self.index_of(&alias) != Some(index) {
+            self.check_room(index, 1).ok()?;
+            self.bind_hooked(alias, index);
+            self.counters.alias_inserts(1);
+            self.hooks.alias_added(&self.slots[index]);
+            self.check_mutation();
+        }
+        Some(self.count_references(index))
+    }
+
+    /// Returns the number of keys (including `key` itself) bound to the value of `key`.
+    ///
+    /// Returns `None` if the key does not exist. The count is maintained as keys are added and
+    /// removed, so this is a single lookup.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key whose group is counted.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyMap;
+    ///
+    /// let mut map = MultiKeyMap::new();
+    /// map.insert("key1", "value1");
+    /// assert_eq!(map.alias_count(&"key1"), Some(1));
+    ///
+    /// map.insert_alias(&"key1", "alias1");
+    /// map.insert_alias(&"alias1", "alias2");
+    /// assert_eq!(map.alias_count(&"key1"), Some(3));
+    /// assert_eq!(map.alias_count(&"alias2"), Some(3));
+    ///
+    /// map.remove_alias(&"alias1");
+    /// assert_eq!(map.alias_count(&"key1"), Some(2));
+    /// assert_eq!(map.alias_count(&"alias1"), None);
+    ///
+    /// map.remove(&"alias2");
+    /// assert_eq!(map.alias_count(&"key1"), None);
+    /// assert_eq!(map.alias_count(&"alias2"), None);
+    /// ```
+    pub fn alias_count(&self, key: &K) -> Option<usize> {
+        self.index_of(key).map(|index| self.count_references(index))
+    }
+
+    /// Returns the primary key of the group `key` belongs to.
+    ///
+    /// The key passed to [`insert`](Self::insert) is the primary key of its group, and aliases
+    /// added later are not. [`set_primary`](Self::set_primary) designates another one, and
+    /// removing the primary key with [`remove_alias`](Self::remove_alias) promotes the key
+    /// listed last by [`aliases`](Self::aliases). When groups are merged, the merged group keeps
+    /// the primary key of the group its value comes from.
+    ///
+    /// Returns `None` if the key does not exist.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - A key of the group.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyMap;
+    ///
+    /// let mut map = MultiKeyMap::new();
+    /// map.insert("id", 1);
+    /// map.insert_alias(&"id", "alias1");
+    /// map.insert_alias(&"id", "alias2");
+    /// assert_eq!(map.primary_of(&"alias2"), Some(&"id"));
+    /// assert_eq!(map.primary_of(&"other"), None);
+    ///
+    /// map.remove_alias(&"id");
+    /// assert_eq!(map.aliases(&"alias1").unwrap().first(), map.primary_of(&"alias1"));
+    /// assert_eq!(map.primary_of(&"alias1"), Some(&"alias2"));
+    ///
+    /// // Merged groups keep the primary key of the surviving value
+    /// map.insert("other", 1);
+    /// map.dedup_values();
+    /// assert_eq!(map.primary_of(&"other"), Some(&"alias2"));
+    /// ```
+    pub fn primary_of(&self, key: &K) -> Option<&K> {
+        self.index_of(key).map(|index| &self.slots[index].keys[0])
+    }
+
+    /// Makes `key` the primary key of its group.
+    ///
+    /// Returns `false` if the key does not exist.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key to designate.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyMap;
+    ///
+    /// let mut map = MultiKeyMap::new();
+    /// map.insert("old", 1);
+    /// map.insert_alias(&"old", "new");
+    /// assert!(map.set_primary(&"new"));
+    /// assert_eq!(map.primary_of(&"old"), Some(&"new"));
+    /// assert!(!map.set_primary(&"missing"));
+    /// map.check_invariants();
+    /// ```
+    pub fn set_primary(&mut self, key: &K) -> bool {
+        let Some(&KeyEntry { slot, position, .. }) = self.find(key) else {
+            return false;
+        };
+        if position != 0 {
+            self.swap_keys(slot, 0, position);
+            self.check_mutation();
+        }
+        true
+    }
+
+    /// Rewrites every key of the group of `key` with `f`, leaving the other groups untouched.
+    ///
+    /// Each new key takes the position of the key it was computed from, so the primary key of
+    /// the group is rewritten into its new primary key. The old keys are only released once
+    /// every new key is known to be free, and on error the map is left unchanged. A
+    /// [disabled](Self::disable_alias) key stays disabled under its new name, and a new key that
+    /// was a [soft alias](Self::insert_soft_alias) leading into the group replaces it. Like
+    /// [`set_primary`](Self::set_primary), this does not report the changes to hooks.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - Any key of the group.
+    /// * `f` - The function computing the new key from each key of the group.
+    ///
+    /// # Errors
+    ///
+    /// * [`RekeyError::NotFound`] if `key` does not exist.
+    /// * [`RekeyError::KeyTaken`] if a new key already belongs to another group, or is a soft
+    ///   alias leading to another group.
+    /// * [`RekeyError::DuplicateKey`] if two keys of the group are rewritten to the same key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::{MultiKeyMap, RekeyError};
+    ///
+    /// let key = |key: &str| key.to_string();
+    /// let mut map = MultiKeyMap::new();
+    /// map.insert_with_keys([key("user:1"), key("email:a"), key("name:ann")], 1).unwrap();
+    /// map.insert(key("acme/user:2"), 2);
+    ///
+    /// map.rekey_group(&key("email:a"), |old| format!("acme/{old}")).unwrap();
+    /// let aliases = map.aliases(&key("acme/email:a")).unwrap();
+    /// assert_eq!(aliases, ["acme/user:1", "acme/email:a", "acme/name:ann"]);
+    /// assert!(!map.contains_key(&key("user:1")));
+    /// assert_eq!(map.get(&key("acme/user:2")), Some(&2));
+    ///
+    /// // A new key belonging to another group fails without changing anything
+    /// let before = map.clone();
+    /// let error = map.rekey_group(&key("acme/user:1"), |old| old.replace('1', "2"));
+    /// assert_eq!(error, Err(RekeyError::KeyTaken(key("acme/user:2"))));
+    /// assert_eq!(map, before);
+    ///
+    /// // So do two keys of the group rewritten to the same key
+    /// let error = map.rekey_group(&key("acme/user:1"), |old| old[..5].to_string());
+    /// assert_eq!(error, Err(RekeyError::DuplicateKey(key("acme/"))));
+    /// assert_eq!(map, before);
+    ///
+    /// let error = map.rekey_group(&key("missing"), |old| old.clone());
+    /// assert_eq!(error, Err(RekeyError::NotFound));
+    ///
+    /// // Disabled keys stay disabled under their new names
+    /// map.disable_alias(&key("acme/name:ann"));
+    /// map.rekey_group(&key("acme/user:1"), |old| old.to_uppercase()).unwrap();
+    /// assert!(map.is_alias_disabled(&key("ACME/NAME:ANN")));
+    /// assert_eq!(map.get(&key("ACME/NAME:ANN")), None);
+    /// assert_eq!(map.get(&key("ACME/USER:1")), Some(&1));
+    ///
+    /// // A soft alias leading to another group is taken too
+    /// map.insert_soft_alias(&key("acme/user:2"), key("acme/user:1"));
+    /// let before = map.clone();
+    /// let error = map.rekey_group(&key("ACME/USER:1"), |old| old.to_lowercase());
+    /// assert_eq!(error, Err(RekeyError::KeyTaken(key("acme/user:1"))));
+    /// assert_eq!(map, before);
+    /// assert_eq!(map.get(&key("acme/user:1")), Some(&2));
+    /// map.check_invariants();
+    /// ```
+    pub fn rekey_group(
+        &mut self,
+        key: &K,
+        mut f: impl FnMut(&K) -> K,
+    ) -> Result<(), RekeyError<K>> {
+        let index = self.index_of(key).ok_or(RekeyError::NotFound)?;
+        let mut keys: Vec<K> = self.slots[index]
+            .keys
+            .iter()
+            .map(|key| match &self.normalizer {
+                Some(normalize) => normalize(&f(key)),
+                None => f(key),
+            })
+            .collect();
+        let taken = keys.iter().position(|key| {
+            let entry = self
+                .find_normalized(key)
+                .or_else(|| self.soft_link_entry(key));
+            matches!(entry, Some(entry) if entry.slot != index)
+        });
+        if let Some(position) = taken {
+            return Err(RekeyError::KeyTaken(keys.swap_remove(position)));
+        }
+        let mut seen = HashTable::with_capacity(keys.len());
+        for position in 0..keys.len() {
+            let hash = self.hash(&keys[position]);
+            if seen
+                .find(hash, |&other: &usize| keys[other] == keys[position])
+                .is_some()
+            {
+                return Err(RekeyError::DuplicateKey(keys.swap_remove(position)));
+            }
+            seen.insert_unique(hash, position, |&other| self.hash(&keys[other]));
+        }
+        // Rewrite the keys in place, so each keeps its index entry and whether it is disabled.
+        // Unindex them all first, so no key is ever indexed twice when two keys trade places.
+        self.touch(index);
+        let entries: Vec<KeyEntry> = (0..keys.len())
+            .map(|position| {
+                let hash = self.hash(&self.slots[index].keys[position]);
+                self.key_index
+                    .remove(hash, |entry| {
+                        entry.slot == index && entry.position == position
+                    })
+                    .expect("listed key is indexed")
+            })
+            .collect();
+        for (entry, key) in entries.into_iter().zip(keys) {
+            let hash = self.hash(&key);
+            self.remove_soft_link(hash, &key);
+            let old = std::mem::replace(&mut self.slots[index].keys[entry.position], key);
+            let new = &self.slots[index].keys[entry.position];
+            if let (Some(log), false) = (self.hooks.log(), old == *new) {
+                log.key_renamed(&old, new);
+            }
+            self.key_index.insert_unique(KeyEntry { hash, ..entry });
+        }
+        self.check_mutation();
+        Ok(())
+    }
+
+    /// Renames keys all at once, each key of `renames` that exists becoming its value, and
+    /// returns the number of keys renamed.
+    ///
+    /// Renames apply simultaneously, so chains such as `a → b` with `b → c` move every key one
+    /// step, and swaps such as `a → b` with `b → a` exchange the keys. A renamed key keeps its
+    /// group and its position in it, so a renamed primary key stays primary. Keys of `renames`
+    /// that do not exist are ignored. Like [`rekey_group`](Self::rekey_group), this does not
+    /// report the changes to hooks.
+    ///
+    /// # Arguments
+    ///
+    /// * `renames` - The new name of each key to rename.
+    ///
+    /// # Errors
+    ///
+    /// Nothing is renamed if any of these holds, and the error names one of the offending keys:
+    ///
+    /// * [`RenameBatchError::TargetTaken`] if a new name is a key that exists and is not renamed
+    ///   itself.
+    /// * [`RenameBatchError::DuplicateTarget`] if two existing keys are renamed to the same key.
+    /// * [`RenameBatchError::DuplicateSource`] if two keys of `renames` are the same key once
+    ///   normalized by the [key normalizer](Self::set_key_normalizer).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::{assert_multi_key_map, MultiKeyMap, RenameBatchError};
+    /// use std::collections::HashMap;
+    ///
+    /// let mut map = MultiKeyMap::new();
+    /// map.insert_with_keys(["a", "a2"], 1).unwrap();
+    /// map.insert("b", 2);
+    /// map.insert("c", 3);
+    /// map.insert("x", 10);
+    /// map.insert("y", 20);
+    ///
+    /// // A chain moves every key one step, and a swap exchanges the keys
+    /// let renames = HashMap::from([("a", "b"), ("b", "c"), ("c", "d"), ("x", "y"), ("y", "x")]);
+    /// assert_eq!(map.apply_renames(&renames), Ok(5));
+    /// assert_eq!(map.primary_of(&"a2"), Some(&"b"));
+    /// assert_multi_key_map!(
+    ///     map,
+    ///     [["b", "a2"] => 1, ["c"] => 2, ["d"] => 3, ["y"] => 10, ["x"] => 20],
+    /// );
+    ///
+    /// // A new name that exists and stays fails the whole batch
+    /// let before = map.clone();
+    /// let renames = HashMap::from([("x", "z"), ("b", "d")]);
+    /// assert_eq!(map.apply_renames(&renames), Err(RenameBatchError::TargetTaken("d")));
+    /// assert_eq!(map, before);
+    ///
+    /// // So do two keys renamed to the same key
+    /// let renames = HashMap::from([("x", "z"), ("y", "z")]);
+    /// assert_eq!(map.apply_renames(&renames), Err(RenameBatchError::DuplicateTarget("z")));
+    /// assert_eq!(map, before);
+    ///
+    /// // Missing keys are skipped
+    /// assert_eq!(map.apply_renames(&HashMap::from([("gone", "x"), ("x", "z")])), Ok(1));
+    /// assert_eq!(map.get(&"z"), Some(&20));
+    /// map.check_invariants();
+    /// ```
+    pub fn apply_renames(&mut self, renames: &HashMap<K, K>) -> Result<usize, RenameBatchError<K>>
+    where
+        K: Clone,
+    {
+        let normalize = |key: &K| match &self.normalizer {
+            Some(normalize) => normalize(key),
+            None => key.clone(),
+        };
+        let mut moves: Vec<(KeyEntry, K)> = Vec::new();
+        let mut renamed = HashSet::new();
+        for (source, target) in renames {
+            let Some(&entry) = self.find(source) else {
+                continue;
+            };
+            if !renamed.insert((entry.slot, entry.position)) {
+                return Err(RenameBatchError::DuplicateSource(source.clone()));
+            }
+            moves.push((entry, normalize(target)));
+        }
+        let mut targets = HashTable::with_capacity(moves.len());
+        for (position, (_, target)) in moves.iter().enumerate() {
+            let taken = self
+                .find_normalized(target)
+                .is_some_and(|entry| !renamed.contains(&(entry.slot, entry.position)));
+            if taken {
+                return Err(RenameBatchError::TargetTaken(target.clone()));
+            }
+            let hash = self.hash(target);
+            let same = |&(_, other): &(u64, usize)| moves[other].1 == *target;
+            if targets.find(hash, same).is_some() {
+                return Err(RenameBatchError::DuplicateTarget(target.clone()));
+            }
+            targets.insert_unique(hash, (hash, position), |&(hash, _)| hash);
+        }
+
+        // Unindex every renamed key first, so no key is ever indexed twice along a chain or swap
+        for (entry, _) in &moves {
+            self.key_index
+                .remove(entry.hash, |other| {
+                    other.slot == entry.slot && other.position == entry.position
+                })
+                .expect("found key is indexed");
+        }
+        let count = moves.len();
+        for (entry, target) in moves {
+            self.touch(entry.slot);
+            let hash = self.hash(&target);
+            self.remove_soft_link(hash, &target);
+            let old = std::mem::replace(&mut self.slots[entry.slot].keys[entry.position], target);
+            let new = &self.slots[entry.slot].keys[entry.position];
+            if let (Some(log), false) = (self.hooks.log(), old == *new) {
+                log.key_renamed(&old, new);
+            }
+            let entry = KeyEntry { hash, ..entry };
+            self.key_index.insert_unique(entry);
+        }
+        self.check_mutation();
+        Ok(count)
+    }
+
+    /// Retrieves all keys in the map.
+    ///
+    /// Returns a vector of keys.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyMap;
+    ///
+    /// let mut map = MultiKeyMap::new();
+    /// map.insert("key1", "value1");
+    /// map.insert_alias(&"key1", "alias1");
+    /// let mut keys: Vec<_> = map.keys().cloned().collect();
+    /// keys.sort();
+    /// assert_eq!(keys, vec!["alias1", "key1"]);
+    /// ```
+    pub fn keys(&self) -> impl Iterator<Item = &K> + '_ {
+        self.slots.iter().flat_map(|slot| &slot.keys)
+    }
+
+    /// Checks if a key exists in the map.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key to check.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyMap;
+    ///
+    /// let mut map = MultiKeyMap::new();
+    /// map.insert("key1", "value1");
+    /// assert!(map.contains_key(&"key1"));
+    /// assert!(!map.contains_key(&"key2"));
+    /// ```
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.resolve_index(key).is_some()
+    }
+
+    /// Returns the number of values (groups) in the map.
+    ///
+    /// Aliases do not count towards the length; use [`key_count`](Self::key_count) for the
+    /// number of keys. This is the same as [`group_count`](Self::group_count).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyMap;
+    ///
+    /// let mut map = MultiKeyMap::new();
+    /// map.insert("key1", "value1");
+    /// map.insert_alias(&"key1", "alias1");
+    /// assert_eq!(map.len(), 1);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns the number of groups in the map, i.e. the number of distinct values.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyMap;
+    ///
+    /// let mut map = MultiKeyMap::new();
+    /// map.insert("key1", "value1");
+    /// map.insert("key2", "value2");
+    /// map.insert_alias(&"key1", "alias1");
+    /// assert_eq!(map.group_count(), 2);
+    ///
+    /// map.remove_alias(&"alias1");
+    /// assert_eq!(map.group_count(), 2);
+    /// map.remove_alias(&"key2");
+    /// assert_eq!(map.group_count(), 1);
+    /// ```
+    pub fn group_count(&self) -> usize {
+        self.len
+    }
+
+    /// Returns the number of keys in the map, counting every alias.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyMap;
+    ///
+    /// let mut map = MultiKeyMap::new();
+    /// map.insert("key1", "value1");
+    /// map.insert("key2", "value2");
+    /// map.insert_alias(&"key1", "alias1");
+    /// assert_eq!(map.key_count(), 3);
+    ///
+    /// map.remove_alias(&"alias1");
+    /// assert_eq!(map.key_count(), 2);
+    /// map.remove(&"key2");
+    /// assert_eq!(map.key_count(), 1);
+    /// ```
+    pub fn key_count(&self) -> usize {
+        self.key_index.len()
+    }
+
+    /// Returns an iterator over the number of keys in each group.
+    ///
+    /// The sizes are read from the maintained reference counts, without looking at any key.
+    /// The order of the sizes is unspecified.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyMap;
+    ///
+    /// let mut map = MultiKeyMap::new();
+    /// map.insert("a", 1);
+    /// map.insert_alias(&"a", "a1");
+    /// map.insert_alias(&"a", "a2");
+    /// map.insert("b", 2);
+    /// map.insert_alias(&"b", "b1");
+    /// map.insert("c", 3);
+    ///
+    /// let mut sizes: Vec<_> = map.group_sizes().collect();
+    /// sizes.sort();
+    /// assert_eq!(sizes, vec![1, 2, 3]);
+    ///
+    /// map.remove_alias(&"a2");
+    /// map.remove(&"c");
+    /// let mut sizes: Vec<_> = map.group_sizes().collect();
+    /// sizes.sort();
+    /// assert_eq!(sizes, vec![2, 2]);
+    /// ```
+    pub fn group_sizes(&self) -> impl Iterator<Item = usize> + '_ {
+        self.slots
+            .iter()
+            .filter(|slot| slot.value.is_some())
+            .map(|slot| slot.keys.len())
+    }
+
+    /// Returns the number of keys in the largest group.
+    ///
+    /// Returns `None` if the map is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyMap;
+    ///
+    /// let mut map = MultiKeyMap::new();
+    /// assert_eq!(map.max_group_size(), None);
+    ///
+    /// map.insert("a", 1);
+    /// map.insert("b", 2);
+    /// map.insert_alias(&"b", "b1");
+    /// assert_eq!(map.max_group_size(), Some(2));
+    /// ```
+    pub fn max_group_size(&self) -> Option<usize> {
+        self.group_sizes().max()
+    }
+
+    /// Returns `true` if the map contains no elements.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyMap;
+    ///
+    /// let map: MultiKeyMap<&str, &str> = MultiKeyMap::new();
+    /// assert!(map.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Clears the map, removing all key-value pairs.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyMap;
+    ///
+    /// let mut map = MultiKeyMap::new();
+    /// map.insert("key1", "value1");
+    /// map.clear();
+    /// assert!(map.is_empty());
+    /// ```
+    pub fn clear(&mut self) {
+        self.trace_clear(self.len);
+        self.revision = self.revision.next();
+        self.key_index.clear();
+        self.soft_links.clear();
+        self.next_generation = self.fresh_generation();
+        self.free.clear();
+        self.len = 0;
+        self.value_index = None;
+        self.hooks.groups_removed(self.slots.drain(..));
+        self.check_mutation();
+    }
+
+    /// Moves all values to the front of the internal storage and releases the unused memory.
+    ///
+    /// Removals leave vacant slots behind, which later insertions reuse. Compacting is only
+    /// worthwhile after removing many groups. It does not change the contents of the map, but
+    /// invalidates every [`GroupId`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyMap;
+    ///
+    /// let mut map = MultiKeyMap::new();
+    /// for key in 0..100 {
+    ///     map.insert(key, key);
+    /// }
+    /// map.insert_alias(&99, 100);
+    /// let id = map.group_id(&99).unwrap();
+    /// for key in 0..90 {
+    ///     map.remove(&key);
+    /// }
+    ///
+    /// map.compact();
+    /// assert_eq!(map.len(), 10);
+    /// assert_eq!(map.values_capacity(), 10);
+    /// assert_eq!(map.get(&100), Some(&99));
+    /// assert_eq!(map.get_by_id(id), None);
+    /// ```
+    pub fn compact(&mut self) {
+        let generation = self.fresh_generation();
+        let mut new_index = vec![0; self.slots.len()];
+        let mut slots = Slots::with_capacity(self.len);
+        for (index, slot) in std::mem::take(&mut self.slots).into_iter().enumerate() {
+            if let Some(value) = slot.value {
+                new_index[index] = slots.len();
+                slots.push(Slot {
+                    generation,
+                    value: Some(value),
+                    keys: slot.keys,
+                    revision: slot.revision,
+                });
+            }
+        }
+        for entry in self.key_index.iter_mut() {
+            entry.slot = new_index[entry.slot];
+        }
+        self.slots = slots;
+        self.free = Vec::new();
+        self.next_generation = generation;
+        // The slots were renumbered
+        self.value_index = None;
+        self.check_mutation();
+    }
+
+    /// Returns the number of keys the map can hold without reallocating its key index.
+    ///
+    /// A [small map](Self#small-maps) indexes up to 4 keys inline, without allocating.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyMap;
+    ///
+    /// let mut map = MultiKeyMap::new();
+    /// assert_eq!(map.keys_capacity(), 4);
+    /// map.insert("a", 1);
+    /// map.insert_alias(&"a", "b");
+    /// assert_eq!(map.keys_capacity(), 4);
+    /// map.insert_with_keys(["c", "d", "e"], 2).unwrap();
+    /// assert!(map.keys_capacity() >= 5);
+    /// ```
+    pub fn keys_capacity(&self) -> usize {
+        self.key_index.capacity()
+    }
+
+    /// Returns the number of slots for groups the map can hold without reallocating.
+    ///
+    /// Slots vacated by removals count against this capacity until they are reused, or
+    /// dropped by [`shrink_to_fit`](Self::shrink_to_fit) or [`compact`](Self::compact). A
+    /// [small map](Self#small-maps) stores up to 2 slots inline, without allocating.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyMap;
+    ///
+    /// let mut map = MultiKeyMap::new();
+    /// assert_eq!(map.values_capacity(), 2);
+    /// map.insert("a", 1);
+    /// map.insert("b", 2);
+    /// assert_eq!(map.values_capacity(), 2);
+    /// map.insert("c", 3);
+    /// assert!(map.values_capacity() >= 3);
+    /// ```
+    pub fn values_capacity(&self) -> usize {
+        self.slots.capacity()
+    }
+
+    /// Shrinks the capacity of the key index and the slots as much as possible.
+    ///
+    /// Vacant slots after the last occupied one are dropped; vacant slots between occupied ones
+    /// stay, as dropping them would move groups and invalidate their [ids](Self::group_id). Use
+    /// [`compact`](Self::compact) to drop those too. Shrinking never changes the contents of the
+    /// map: every key, value, group id and generation is kept.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyMap;
+    ///
+    /// let mut map = MultiKeyMap::new();
+    /// for i in 0..1000 {
+    ///     map.insert(i, i * 10);
+    ///     map.insert_alias(&i, i + 1000);
+    /// }
+    /// let id = map.group_id(&5).unwrap();
+    /// let stale = map.group_id(&500).unwrap();
+    /// for i in 10..1000 {
+    ///     map.remove(&i);
+    /// }
+    /// map.shrink_to_fit();
+    /// map.check_invariants();
+    ///
+    /// assert!(map.keys_capacity() < 100);
+    /// assert!(map.values_capacity() < 100);
+    /// for i in 0..10 {
+    ///     assert_eq!(map.get(&i), Some(&(i * 10)));
+    ///     assert_eq!(map.get(&(i + 1000)), Some(&(i * 10)));
+    /// }
+    /// assert_eq!(map.get(&10), None);
+    /// assert_eq!(map.get_by_id(id), Some(&50));
+    ///
+    /// // Ids of removed groups never resolve again, even once their slots are reused
+    /// for i in 10..1000 {
+    ///     map.insert(i, 0);
+    /// }
+    /// assert_eq!(map.get_by_id(stale), None);
+    /// ```
+    pub fn shrink_to_fit(&mut self) {
+        self.shrink_to(0);
+    }
+
+    /// Shrinks the capacity of the key index and the slots, keeping room for at least
+    /// `min_keys` keys.
+    ///
+    /// The slots keep room for `min_keys` groups as well, since every group has a key. As with
+    /// [`shrink_to_fit`](Self::shrink_to_fit), vacant slots after the last occupied one are
+    /// dropped, the capacity never drops below what the map holds, and the contents of the map
+    /// never change.
+    ///
+    /// # Arguments
+    ///
+    /// * `min_keys` - The number of keys the map keeps room for.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyMap;
+    ///
+    /// let mut map = MultiKeyMap::new();
+    /// for i in 0..1000 {
+    ///     map.insert(i, i);
+    /// }
+    /// map.retain(|_, value| *value < 10);
+    /// map.shrink_to(100);
+    ///
+    /// assert!(map.keys_capacity() >= 100 && map.keys_capacity() < 1000);
+    /// assert!(map.values_capacity() >= 100 && map.values_capacity() < 1000);
+    /// assert_eq!(map.len(), 10);
+    /// assert!((0..10).all(|i| map.get(&i) == Some(&i)));
+    ///
+    /// // Requests above the current capacity leave it unchanged
+    /// let capacity = map.keys_capacity();
+    /// map.shrink_to(10_000);
+    /// assert_eq!(map.keys_capacity(), capacity);
+    /// ```
+    pub fn shrink_to(&mut self, min_keys: usize) {
+        let used = self
+            .slots
+            .iter()
+            .rposition(|slot| slot.value.is_some())
+            .map_or(0, |index| index + 1);
+        if used < self.slots.len() {
+            // Dropped slots take their generations along, so new slots must start above them
+            self.next_generation = self.fresh_generation();
+            self.slots.truncate(used);
+            self.free.retain(|&index| index < used);
+        }
+        small::shrink_to(&mut self.slots, min_keys);
+        self.free.shrink_to(min_keys);
+        for slot in &mut self.slots {
+            slot.keys.shrink_to_fit();
+        }
+        self.key_index.shrink_to(min_keys);
+        self.check_mutation();
+    }
+
+    /// Returns a snapshot of the operation counters of the map.
+    ///
+    /// This method is only available with the `stats` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::{MapStats, MultiKeyMap};
+    ///
+    /// let mut map = MultiKeyMap::new();
+    /// map.insert("a", 1);
+    /// map.insert_alias(&"a", "b");
+    /// map.insert_alias(&"missing", "c");
+    /// assert_eq!(map.get(&"b"), Some(&1));
+    /// assert_eq!(map.get(&"a"), Some(&1));
+    /// assert_eq!(map.get(&"c"), None);
+    /// *map.get_mut(&"b").unwrap() += 1;
+    /// map.remove_alias(&"b");
+    /// map.remove(&"a");
+    /// map.remove(&"a");
+    ///
+    /// assert_eq!(
+    ///     map.stats(),
+    ///     MapStats {
+    ///         lookups: 4,
+    ///         hits: 3,
+    ///         misses: 1,
+    ///         inserts: 1,
+    ///         alias_inserts: 1,
+    ///         removals: 2,
+    ///     }
+    /// );
+    /// ```
+    #[cfg(feature = "stats")]
+    pub fn stats(&self) -> MapStats {
+        self.counters.snapshot()
+    }
+
+    /// Sets all operation counters of the map back to zero.
+    ///
+    /// This method is only available with the `stats` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::{MapStats, MultiKeyMap};
+    ///
+    /// let mut map = MultiKeyMap::new();
+    /// map.insert("a", 1);
+    /// map.get(&"a");
+    /// map.reset_stats();
+    /// assert_eq!(map.stats(), MapStats::default());
+    /// ```
+    #[cfg(feature = "stats")]
+    pub fn reset_stats(&mut self) {
+        self.counters = Counters::default();
+    }
+
+    /// Converts the map into an immutable [`FrozenMultiKeyMap`] optimized for lookups.
+    ///
+    /// Soft aliases are kept: the frozen map resolves them as [`get`](Self::get) does, and
+    /// [`thaw`](FrozenMultiKeyMap::thaw) restores them.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyMap;
+    ///
+    /// let mut map = MultiKeyMap::new();
+    /// map.insert("key1", "value1");
+    /// map.insert_alias(&"key1", "alias1");
+    /// map.insert("key2", "value2");
+    /// map.insert_soft_alias(&"key2", "latest");
+    /// let frozen = map.clone().freeze();
+    /// for key in map.keys() {
+    ///     assert_eq!(frozen.get(key), map.get(key));
+    ///     assert_eq!(frozen.aliases(key).map(<[_]>::len), map.alias_count(key));
+    /// }
+    /// assert_eq!(frozen.len(), map.len());
+    /// assert_eq!(frozen.get(&"latest"), Some(&"value2"));
+    /// assert_eq!(frozen.thaw(), map);
+    /// ```
+    pub fn freeze(mut self) -> FrozenMultiKeyMap<K, V> {
+        let soft_links = std::mem::take(&mut self.soft_links);
+        FrozenMultiKeyMap::from_groups(self.into_groups(), soft_links.into_iter())
+    }
+
+    /// Verifies the internal consistency of the map, panicking with a description of the first
+    /// violation found.
+    ///
+    /// This is intended for tests and is linear in the size of the map. With the
+    /// `debug-invariants` feature, debug builds also run it at the end of every method changing
+    /// the keys or groups of the map, so corruption is caught by the call that caused it. Release
+    /// builds never do.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyMap;
+    ///
+    /// let mut map = MultiKeyMap::new();
+    /// map.insert("key1", "value1");
+    /// map.insert_alias(&"key1", "alias1");
+    /// map.remove(&"key1");
+    /// map.check_invariants();
+    /// ```
+    pub fn check_invariants(&self) {
+        let occupied = self.occupied().count();
+        assert_eq!(
+            self.len, occupied,
+            "length {} does not match the {} occupied slots",
+            self.len, occupied
+        );
+        let mut vacant = vec![false; self.slots.len()];
+        for &index in &self.free {
+            assert!(
+                self.slots
+                    .get(index)
+                    .is_some_and(|slot| slot.value.is_none()),
+                "free list entry {index} is not a vacant slot"
+            );
+            assert!(!vacant[index], "slot {index} is on the free list twice");
+            vacant[index] = true;
+        }
+        assert_eq!(
+            self.free.len() + self.len,
+            self.slots.len(),
+            "vacant slots are missing from the free list"
+        );
+        for entry in self.key_index.iter() {
+            let index = entry.slot;
+            assert!(
+                self.slots
+                    .get(index)
+                    .is_some_and(|slot| slot.value.is_some()),
+                "a key points to vacant slot {index}"
+            );
+            let key = self.slots[index].keys.get(entry.position);
+            assert!(
+                key.is_some_and(|key| self.hash(key) == entry.hash),
+                "a key of slot {index} is indexed at the wrong position {}",
+                entry.position
+            );
+        }
+        let mut indexed = 0;
+        for (index, slot) in self.slots.iter().enumerate() {
+            for (position, key) in slot.keys.iter().enumerate() {
+                let entry = self.find(key);
+                assert!(
+                    entry.is_some_and(|entry| entry.slot == index && entry.position == position),
+                    "slot {index} lists a key that does not point to it"
+                );
+            }
+            indexed += slot.keys.len();
+            assert!(
+                slot.value.is_none() || !slot.keys.is_empty(),
+                "slot {index} holds a value no key points to"
+            );
+        }
+        assert_eq!(
+            indexed,
+            self.key_index.len(),
+            "slots list {} keys but the index holds {}",
+            indexed,
+            self.key_index.len()
+        );
+        for (link, _) in &self.soft_links {
+            assert!(
+                self.find_normalized(link).is_none(),
+                "a soft alias is also bound as a key"
+            );
+        }
+    }
+
+    /// Verifies the internal consistency of the map after a mutation, with the
+    /// `debug-invariants` feature in debug builds, and does nothing otherwise.
+    #[inline(always)]
+    fn check_mutation(&self) {
+        #[cfg(all(feature = "debug-invariants", debug_assertions))]
+        self.check_invariants();
+    }
+
+    /// Moves every group whose entry in `take` is `true` into a new map.
+    fn extract_groups(&mut self, take: &[bool]) -> Self {
+        let mut taken = MultiKeyMap::new();
+        let mut disabled = self.disabled_positions();
+        for (index, &take) in take.iter().enumerate() {
+            if take && self.slots[index].value.is_some() {
+                let keys = self.unlink_all(index);
+                if let Some(log) = self.hooks.log() {
+                    log.group_removed(&keys);
+                }
+                let taken_index = taken.push_group(keys, self.release(index));
+                for position in disabled.remove(&index).unwrap_or_default() {
+                    taken.disable_at(taken_index, position);
+                }
+            }
+        }
+        taken
+    }
+
+    /// Returns a new map with clones of every group whose entry in `take` is `true`.
+    fn clone_groups(&self, take: &[bool]) -> Self
+    where
+        K: Clone,
+        V: Clone,
+    {
+        let mut cloned = MultiKeyMap::new();
+        let mut disabled = self.disabled_positions();
+        for (index, value) in self.occupied() {
+            if take[index] {
+                let cloned_index =
+                    cloned.push_group(self.slots[index].keys.iter().cloned(), value.clone());
+                for position in disabled.remove(&index).unwrap_or_default() {
+                    cloned.disable_at(cloned_index, position);
+                }
+            }
+        }
+        cloned
+    }
+
+    /// Consumes the map and moves the keys of every group next to the positions of its disabled
+    /// keys and its value.
+    fn into_groups(self) -> impl Iterator<Item = (Vec<K>, Vec<usize>, V)> {
+        let mut disabled = self.disabled_positions();
+        self.slots
+            .into_iter()
+            .enumerate()
+            .filter_map(move |(index, slot)| {
+                let positions = disabled.remove(&index).unwrap_or_default();
+                slot.value
+                    .map(|value| (slot.keys.into_vec(), positions, value))
+            })
+    }
+
+    /// Adds a new group bound to all of `keys` and returns the index of its slot.
+    fn push_group(&mut self, keys: impl IntoIterator<Item = K>, value: V) -> usize {
+        let index = self.alloc(value);
+        for key in keys {
+            self.bind(key, index);
+        }
+        if let Some(log) = self.hooks.log() {
+            log.group_created(&self.slots[index]);
+        }
+        index
+    }
+
+    /// Disables the key at `position` in the keys of the slot at `index`.
+    fn disable_at(&mut self, index: usize, position: usize) {
+        let hash = self.hash(&self.slots[index].keys[position]);
+        self.key_index
+            .find_mut(hash, |entry| {
+                entry.slot == index && entry.position == position
+            })
+            .expect("listed key is indexed")
+            .disabled = true;
+    }
+
+    /// Adds a new group bound to all of `keys`, which must be non-empty and unbound.
+    ///
+    /// Fails with a description of the problem otherwise, numbering groups in slot order.
+    #[cfg(any(feature = "borsh", feature = "rkyv", feature = "serde"))]
+    fn push_checked_group(&mut self, keys: Vec<K>, value: V) -> Result<(), String> {
+        let group = self.len;
+        if keys.is_empty() {
+            return Err(format!("group {group} has no keys"));
+        }
+        let index = self.alloc(value);
+        for key in keys {
+            let error = match self.index_of(&key) {
+                None => {
+                    self.link(key, index);
+                    continue;
+                }
+                Some(other) if other == index => format!("a key appears twice in group {group}"),
+                Some(other) => {
+                    let other = self.occupied().take_while(|&(i, _)| i != other).count();
+                    format!("a key appears in both group {other} and group {group}")
+                }
+            };
+            self.unlink_all(index);
+            self.release(index);
+            return Err(error);
+        }
+        Ok(())
+    }
+
+    /// Returns the keys and value of every group, with the keys of each group sorted and the
+    /// groups sorted by their smallest key.
+    #[cfg(any(feature = "borsh", feature = "serde"))]
+    fn sorted_groups(&self) -> Vec<(Vec<&K>, &V)>
+    where
+        K: Ord,
+    {
+        let mut groups: Vec<(Vec<&K>, &V)> = self
+            .occupied()
+            .map(|(index, value)| {
+                let mut keys: Vec<&K> = self.slots[index].keys.iter().collect();
+                keys.sort_unstable();
+                (keys, value)
+            })
+            .collect();
+        groups.sort_unstable_by(|(a, _), (b, _)| a[0].cmp(b[0]));
+        groups
+    }
+
+    /// Removes every group whose entry in `keep` is `false`.
+    fn retain_groups(&mut self, keep: &[bool]) {
+        for (index, &keep) in keep.iter().enumerate() {
+            if !keep && self.slots[index].value.is_some() {
+                let keys = self.unlink_all(index);
+                if let (Some(log), false) = (self.hooks.log(), keys.is_empty()) {
+                    log.group_removed(&keys);
+                }
+                self.release(index);
+            }
+        }
+    }
+
+    /// Folds the values of every set into its root, then removes the groups no key points to.
+    ///
+    /// Values are combined in slot order, so `resolve` always receives the value of the lower
+    /// slot first. Keys are rebound to the root of their set.
+    fn merge_sets(&mut self, sets: &mut DisjointSets, mut resolve: impl FnMut(V, V) -> V) {
+        for index in 0..self.slots.len() {
+            let root = sets.find(index);
+            if root != index && self.slots[index].value.is_some() {
+                self.relocate(index, root);
+                let value = self.release(index);
+                self.touch(root);
+                let root_value = self.slots[root]
+                    .value
+                    .take()
+                    .expect("root precedes its set");
+                self.slots[root].value = Some(resolve(root_value, value));
+            }
+        }
+        let alive: Vec<bool> = self
+            .slots
+            .iter()
+            .map(|slot| !slot.keys.is_empty())
+            .collect();
+        self.retain_groups(&alive);
+    }
+
+    /// Consumes the map and converts the value of every slot, keeping the slots in place.
+    ///
+    /// Slots converted to `None` are vacated and their keys removed.
+    fn map_slots<U>(self, mut f: impl FnMut(&[K], V) -> Option<U>) -> MultiKeyMap<K, U> {
+        let mut map = MultiKeyMap {
+            key_index: self.key_index,
+            hasher: self.hasher,
+            slots: Slots::with_capacity(self.slots.len()),
+            free: self.free,
+            len: self.len,
+            next_generation: self.next_generation,
+            alias_limit: self.alias_limit,
+            counters: self.counters,
+            hooks: Hooks::default(),
+            revision: self.revision,
+            normalizer: self.normalizer,
+            soft_links: self.soft_links,
+            value_index: None,
+        };
+        for (index, slot) in self.slots.into_iter().enumerate() {
+            let mut generation = slot.generation;
+            let revision = slot.revision;
+            let mut keys = slot.keys;
+            let value = slot.value.and_then(|value| {
+                let mapped = f(&keys, value);
+                if mapped.is_none() {
+                    for (position, key) in keys.drain(..).enumerate() {
+                        let hash = map.hasher.hash_one(&key);
+                        map.key_index
+                            .remove(hash, |entry| {
+                                entry.slot == index && entry.position == position
+                            })
+                            .expect("listed key is indexed");
+                    }
+                    generation += 1;
+                    map.free.push(index);
+                    map.len -= 1;
+                }
+                mapped
+            });
+            map.slots.push(Slot {
+                generation,
+                value,
+                keys,
+                revision,
+            });
+        }
+        map
+    }
+
+    /// Binds `key` to the slot at `index`, removing the group it leaves if that group has no
+    /// other keys.
+    fn bind(&mut self, key: K, index: usize) {
+        if let Some((previous, old)) = self.unbind(&key) {
+            if previous != index {
+                self.log_unbound(&old, previous);
+                if self.slots[previous].keys.is_empty() {
+                    self.release(previous);
+                }
+            }
+        }
+        self.link(key, index);
+    }
+
+    /// Records `key`, just unbound from the slot at `index`, as removed from its group, or as
+    /// removing the group if it has no keys left.
+    fn log_unbound(&mut self, key: &K, index: usize) {
+        if let Some(log) = self.hooks.log() {
+            if self.slots[index].keys.is_empty() {
+                log.group_removed(std::slice::from_ref(key));
+            } else {
+                log.alias_removed(key, &self.slots[index]);
+            }
+        }
+    }
+
+    /// Fails with [`AliasLimitExceeded`] unless a group with `keys`, counting repeated keys once,
+    /// fits the alias limit.
+    fn check_group_size(&self, keys: &[K]) -> Result<(), AliasLimitExceeded> {
+        if let Some(limit) = self.alias_limit {
+            let normalized: Vec<K>;
+            let keys = match &self.normalizer {
+                Some(normalize) => {
+                    normalized = keys.iter().map(|key| normalize(key)).collect();
+                    &normalized
+                }
+                None => keys,
+            };
+            let mut seen = HashSet::new();
+            if keys.iter().filter(|&key| seen.insert(key)).count() > limit {
+                return Err(AliasLimitExceeded { limit });
+            }
+        }
+        Ok(())
+    }
+
+    /// Inserts a group with `keys`, as [`insert_with_keys`](Self::insert_with_keys) does without
+    /// checking the alias limit, and returns the index of its slot.
+    fn insert_group(&mut self, keys: Vec<K>, value: V) -> usize {
+        assert!(!keys.is_empty(), "a group needs at least one key");
+        let index = self.alloc(value);
+        for key in keys {
+            // Rebinding a repeated key would move it, and could take the primary from the first
+            if self.index_of(&key) != Some(index) {
+                self.bind_hooked(key, index);
+            }
+        }
+        self.counters.insert();
+        self.hooks.group_inserted(&self.slots[index]);
+        index
+    }
+
+    /// Binds `key` to the slot at `index` like [`bind`](Self::bind), reporting its removal from
+    /// another group to the hooks.
+    ///
+    /// Returns `true` if the key was not bound to the slot yet.
+    fn bind_hooked(&mut self, key: K, index: usize) -> bool {
+        let Some((previous, old)) = self.unbind(&key) else {
+            self.link(key, index);
+            return true;
+        };
+        if previous != index {
+            if self.slots[previous].keys.is_empty() {
+                let value = self.release(previous);
+                self.hooks.group_removed(std::slice::from_ref(&old), &value);
+            } else {
+                self.hooks.alias_removed(&old, &self.slots[previous]);
+            }
+        }
+        self.link(key, index);
+        previous != index
+    }
+
+    /// Adds `key`, which must not be bound yet, as an alias of the group at `index`, counting
+    /// and reporting it.
+    fn join_hooked(&mut self, key: K, index: usize) {
+        self.link(key, index);
+        self.counters.alias_inserts(1);
+        self.hooks.alias_added(&self.slots[index]);
+    }
+
+    /// Removes the group at `from`, dropping its value, and adds its keys as aliases of the group
+    /// at `to`.
+    fn merge_hooked(&mut self, from: usize, to: usize) {
+        let keys = self.unlink_all(from);
+        let value = self.release(from);
+        self.hooks.group_removed(&keys, &value);
+        for key in keys {
+            self.join_hooked(key, to);
+        }
+    }
+
+    /// Adds `key`, which must not be bound yet, to the keys of the slot at `index`.
+    fn link(&mut self, key: K, index: usize) {
+        let key = match &self.normalizer {
+            Some(normalize) => normalize(&key),
+            None => key,
+        };
+        self.touch(index);
+        let hash = self.hash(&key);
+        self.remove_soft_link(hash, &key);
+        let keys = &mut self.slots[index].keys;
+        let entry = KeyEntry {
+            hash,
+            slot: index,
+            position: keys.len(),
+            disabled: false,
+        };
+        keys.push(key);
+        self.key_index.insert_unique(entry);
+    }
+
+    /// Unbinds `key` and returns the index of its slot and the stored key.
+    ///
+    /// The slot is left in place even if it has no keys left.
+    fn unbind(&mut self, key: &K) -> Option<(usize, K)> {
+        let entry = *self.find(key)?;
+        Some((entry.slot, self.unlink_at(entry.slot, entry.position)))
+    }
+
+    /// Unbinds the key at `position` in the keys of the slot at `index` and returns it.
+    ///
+    /// The last key of the slot takes its position.
+    fn unlink_at(&mut self, index: usize, position: usize) -> K {
+        self.touch(index);
+        let hash = self.hash(&self.slots[index].keys[position]);
+        self.key_index
+            .remove(hash, |entry| {
+                entry.slot == index && entry.position == position
+            })
+            .expect("listed key is indexed");
+        let keys = &mut self.slots[index].keys;
+        let key = keys.swap_remove(position);
+        scrub::popped(keys);
+        if let Some(moved) = keys.get(position) {
+            let last = keys.len();
+            let hash = self.hasher.hash_one(moved);
+            self.key_index
+                .find_mut(hash, |entry| entry.slot == index && entry.position == last)
+                .expect("listed key is indexed")
+                .position = position;
+        }
+        key
+    }
+
+    /// Unbinds every key of the slot at `index` and returns them.
+    fn unlink_all(&mut self, index: usize) -> Vec<K> {
+        self.touch(index);
+        let keys = std::mem::take(&mut self.slots[index].keys);
+        for (position, key) in keys.iter().enumerate() {
+            self.key_index
+                .remove(self.hasher.hash_one(key), |entry| {
+                    entry.slot == index && entry.position == position
+                })
+                .expect("listed key is indexed");
+        }
+        keys.into_vec()
+    }
+
+    /// Moves every key of the slot at `from` to the slot at `to`, recording the group at `from`
+    /// as removed and its keys as added to the group at `to`.
+    fn relocate(&mut self, from: usize, to: usize) {
+        self.touch(from);
+        self.touch(to);
+        let mut keys = std::mem::take(&mut self.slots[from].keys);
+        let moved = keys.len();
+        if let (Some(log), false) = (self.hooks.log(), keys.is_empty()) {
+            log.group_removed(&keys);
+        }
+        let offset = self.slots[to].keys.len();
+        for (position, key) in keys.iter().enumerate() {
+            let entry = self
+                .key_index
+                .find_mut(self.hasher.hash_one(key), |entry| {
+                    entry.slot == from && entry.position == position
+                })
+                .expect("listed key is indexed");
+            entry.slot = to;
+            entry.position = offset + position;
+        }
+        self.slots[to].keys.append(&mut keys);
+        if let (Some(log), false) = (self.hooks.log(), moved == 0) {
+            log.aliases_added(&self.slots[to], moved);
+        }
+    }
+
+    /// Swaps the keys at positions `a` and `b` in the keys of the slot at `index`.
+    fn swap_keys(&mut self, index: usize, a: usize, b: usize) {
+        self.touch(index);
+        let keys = &self.slots[index].keys;
+        let (hash_a, hash_b) = (self.hash(&keys[a]), self.hash(&keys[b]));
+        // Park the entry of `a` on a position no key has, so the entries never coincide
+        let moves = [
+            (hash_a, a, usize::MAX),
+            (hash_b, b, a),
+            (hash_a, usize::MAX, b),
+        ];
+        for (hash, from, to) in moves {
+            self.key_index
+                .find_mut(hash, |entry| entry.slot == index && entry.position == from)
+                .expect("listed key is indexed")
+                .position = to;
+        }
+        self.slots[index].keys.swap(a, b);
+    }
+
+    /// Reserves capacity for at least `additional` more keys.
+    fn reserve_keys(&mut self, additional: usize) {
+        self.key_index.reserve(additional);
+    }
+
+    /// Returns the index entry of `key`.
+    fn find(&self, key: &K) -> Option<&KeyEntry> {
+        if let Some(normalize) = &self.normalizer {
+            return self.find_normalized(&normalize(key));
+        }
+        self.find_normalized(key)
+    }
+
+    /// Finds the index entry of `key`, which is already normalized.
+    fn find_normalized(&self, key: &K) -> Option<&KeyEntry> {
+        let hash = self.hash(key);
+        self.key_index.find(hash, |entry| {
+            entry.hash == hash && self.slots[entry.slot].keys[entry.position] == *key
+        })
+    }
+
+    /// Returns the index of the slot `key` points to.
+    fn index_of(&self, key: &K) -> Option<usize> {
+        self.find(key).map(|entry| entry.slot)
+    }
+
+    /// Returns the index of the slot a value lookup of `key` resolves to: the slot of the key,
+    /// unless it is disabled, or that of the key a soft alias leads to.
+    fn resolve_index(&self, key: &K) -> Option<usize> {
+        if let Some(entry) = self.find(key) {
+            return (!entry.disabled).then_some(entry.slot);
+        }
+        if self.soft_links.is_empty() {
+            return None;
+        }
+        let normalized: K;
+        let key = match &self.normalizer {
+            Some(normalize) => {
+                normalized = normalize(key);
+                &normalized
+            }
+            None => key,
+        };
+        self.soft_link_entry(key)
+            .and_then(|entry| (!entry.disabled).then_some(entry.slot))
+    }
+
+    /// Returns the index entry of the key the soft alias `link`, which is already normalized,
+    /// leads to, even if that key is disabled.
+    fn soft_link_entry(&self, link: &K) -> Option<&KeyEntry> {
+        let mut key = link;
+        // A chain longer than the number of soft aliases visits one of them twice: it is a cycle
+        for _ in 0..self.soft_links.len() {
+            key = self.soft_target(key)?;
+            if let Some(entry) = self.find_normalized(key) {
+                return Some(entry);
+            }
+        }
+        None
+    }
+
+    /// Removes the soft alias `key`, which is already normalized and has the given hash, as it
+    /// is about to be bound to a group.
+    fn remove_soft_link(&mut self, hash: u64, key: &K) {
+        if self.soft_links.is_empty() {
+            return;
+        }
+        if let Ok(link) = self.soft_links.find_entry(hash, |(link, _)| link == key) {
+            link.remove();
+        }
+    }
+
+    /// Returns the key the soft alias `link`, which is already normalized, points to.
+    fn soft_target(&self, link: &K) -> Option<&K> {
+        self.soft_links
+            .find(self.hash(link), |(other, _)| other == link)
+            .map(|(_, target)| target)
+    }
+
+    /// Sets whether `key` is disabled.
+    ///
+    /// Returns `true` if the key exists and was not in that state yet.
+    fn set_disabled(&mut self, key: &K, disabled: bool) -> bool {
+        let Some(&entry) = self.find(key) else {
+            return false;
+        };
+        if entry.disabled == disabled {
+            return false;
+        }
+        self.touch(entry.slot);
+        self.key_index
+            .find_mut(entry.hash, |other| {
+                other.slot == entry.slot && other.position == entry.position
+            })
+            .expect("found key is indexed")
+            .disabled = disabled;
+        self.check_mutation();
+        true
+    }
+
+    fn hash(&self, key: &K) -> u64 {
+        self.hasher.hash_one(key)
+    }
+
+    /// Returns `true` if `a` and `b` are the same key once normalized.
+    fn same_key(&self, a: &K, b: &K) -> bool {
+        match &self.normalizer {
+            Some(normalize) => normalize(a) == normalize(b),
+            None => a == b,
+        }
+    }
+
+    /// Collects clones of the keys pointing to a particular slot.
+    fn keys_of(&self, index: usize) -> Vec<K>
+    where
+        K: Clone,
+    {
+        self.slots[index].keys.to_vec()
+    }
+
+    /// Returns the id of the group in the slot at `index`.
+    fn id_at(&self, index: usize) -> GroupId {
+        GroupId {
+            index,
+            generation: self.slots[index].generation,
+        }
+    }
+
+    /// Returns the value index of `id`, or `None` if the id is stale.
+    fn resolve_id(&self, id: GroupId) -> Option<usize> {
+        self.slots
+            .get(id.index)
+            .filter(|slot| slot.generation == id.generation && slot.value.is_some())
+            .map(|_| id.index)
+    }
+
+    /// Counts the number of keys pointing to a particular slot.
+    fn count_references(&self, index: usize) -> usize {
+        self.slots[index].keys.len()
+    }
+
+    /// Returns `true` if the slot at `index` can gain `extra` keys within the alias limit.
+    fn has_room(&self, index: usize, extra: usize) -> bool {
+        self.alias_limit
+            .is_none_or(|limit| self.count_references(index) + extra <= limit)
+    }
+
+    /// Fails with [`AliasLimitExceeded`] unless the slot at `index` can gain `extra` keys.
+    fn check_room(&self, index: usize, extra: usize) -> Result<(), AliasLimitExceeded> {
+        match self.alias_limit {
+            Some(limit) if !self.has_room(index, extra) => Err(AliasLimitExceeded { limit }),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// How [`MultiKeyMap::map_keys`] resolves keys of different groups converting to the same key.
+pub enum CollisionPolicy<V> {
+    /// Abort the conversion with a [`KeyCollisionError`].
+    Error,
+    /// Keep the key bound to the group stored first and drop it from the other group.
+    KeepFirst,
+    /// Merge the colliding groups into one, combining their values with the given resolver.
+    ///
+    /// The resolver receives the value of the group stored first and then the other value.
+    Merge(Box<dyn FnMut(V, V) -> V>),
+}
+
+/// How [`MultiKeyMap::append`] resolves keys present in both maps.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MergePolicy {
+    /// Keep the key bound to its group in `self`; the incoming group loses the key.
+    KeepSelf,
+    /// Rebind the key to the incoming group; the group in `self` loses the key.
+    Replace,
+}
+
+/// Whether [`MultiKeyMap::try_extend`] inserts part of a batch when other pairs conflict.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExtendPolicy {
+    /// Insert every pair without a conflict and reject the others.
+    Partial,
+    /// Insert nothing if any pair conflicts.
+    Atomic,
+}
+
+/// The changes that turn one [`MultiKeyMap`] into another, as computed by
+/// [`MultiKeyMap::diff`] and applied by [`MultiKeyMap::apply`].
+///
+/// Groups that survive are identified by one of their keys.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChangeSet<K, V> {
+    /// A key of every group to remove along with all of its aliases.
+    pub removed_groups: Vec<K>,
+    /// Keys to remove from groups that survive.
+    pub removed_aliases: Vec<K>,
+    /// Aliases to add, as `(existing key, alias)` pairs.
+    pub added_aliases: Vec<(K, K)>,
+    /// New values for surviving groups, as `(key, value)` pairs.
+    pub replaced_values: Vec<(K, V)>,
+    /// New groups with all of their keys.
+    pub added_groups: Vec<(Vec<K>, V)>,
+    /// Keys to [disable](MultiKeyMap::disable_alias) once the other changes are made.
+    pub disabled_keys: Vec<K>,
+    /// Disabled keys to [enable](MultiKeyMap::enable_alias) again.
+    pub enabled_keys: Vec<K>,
+}
+
+impl<K, V> ChangeSet<K, V> {
+    /// Creates an empty `ChangeSet`.
+    pub fn new() -> Self {
+        ChangeSet {
+            removed_groups: Vec::new(),
+            removed_aliases: Vec::new(),
+            added_aliases: Vec::new(),
+            replaced_values: Vec::new(),
+            added_groups: Vec::new(),
+            disabled_keys: Vec::new(),
+            enabled_keys: Vec::new(),
+        }
+    }
+
+    /// Returns `true` if the `ChangeSet` contains no changes.
+    pub fn is_empty(&self) -> bool {
+        self.removed_groups.is_empty()
+            && self.removed_aliases.is_empty()
+            && self.added_aliases.is_empty()
+            && self.replaced_values.is_empty()
+            && self.added_groups.is_empty()
+            && self.disabled_keys.is_empty()
+            && self.enabled_keys.is_empty()
+    }
+}
+
+impl<K, V> Default for ChangeSet<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Disjoint sets over slots, used to merge groups. The root of a set is its smallest slot.
+struct DisjointSets {
+    parent: Vec<usize>,
+}
+
+impl DisjointSets {
+    fn new(len: usize) -> Self {
+        DisjointSets {
+            parent: (0..len).collect(),
+        }
+    }
+
+    fn find(&mut self, mut index: usize) -> usize {
+        while self.parent[index] != index {
+            self.parent[index] = self.parent[self.parent[index]];
+            index = self.parent[index];
+        }
+        index
+    }
+
+    fn union(&mut self, a: usize, b: usize) {
+        let (a, b) = (self.find(a), self.find(b));
+        self.parent[a.max(b)] = a.min(b);
+    }
+}
+
+impl<K, V> MultiKeyMap<K, V> {
+    /// Stores `value` in a vacant slot, or a new one if there is none, and returns its index.
+    fn alloc(&mut self, value: V) -> usize {
+        let index = match self.free.pop() {
+            Some(index) => {
+                self.slots[index].value = Some(value);
+                self.len += 1;
+                index
+            }
+            None => self.push_slot(value),
+        };
+        self.touch(index);
+        index
+    }
+
+    /// Stores `value` in a new slot after all existing ones and returns its index.
+    fn push_slot(&mut self, value: V) -> usize {
+        self.slots.push(Slot {
+            generation: self.next_generation,
+            value: Some(value),
+            keys: GroupKeys::new(),
+            revision: Revision::default(),
+        });
+        self.len += 1;
+        self.slots.len() - 1
+    }
+
+    /// Vacates the slot at `index` and returns its value.
+    fn release(&mut self, index: usize) -> V {
+        let value = self.slots[index]
+            .value
+            .take()
+            .expect("released slot is occupied");
+        self.vacate(index);
+        value
+    }
+
+    /// Vacates the slot at `index`, whose value was already taken out.
+    fn vacate(&mut self, index: usize) {
+        self.touch(index);
+        let slot = &mut self.slots[index];
+        scrub::vacated(&mut slot.value);
+        slot.generation += 1;
+        slot.keys.clear();
+        self.free.push(index);
+        self.len -= 1;
+    }
+
+    /// Records a modification of the slot at `index` in the map's history.
+    fn touch(&mut self, index: usize) {
+        self.revision = self.revision.next();
+        self.slots[index].revision = self.revision;
+        if let Some(values) = &mut self.value_index {
+            values.touch(index);
+        }
+    }
+
+    /// Returns a generation above that of every slot, for slots replacing all current ones.
+    fn fresh_generation(&self) -> u64 {
+        self.slots
+            .iter()
+            .map(|slot| slot.generation + 1)
+            .fold(self.next_generation, u64::max)
+    }
+
+    /// Returns the value of an occupied slot.
+    fn value(&self, index: usize) -> &V {
+        self.slots[index]
+            .value
+            .as_ref()
+            .expect("keys point to occupied slots")
+    }
+
+    /// Returns the value of an occupied slot mutably, recording it as modified.
+    fn value_mut(&mut self, index: usize) -> &mut V {
+        self.touch(index);
+        self.slots[index]
+            .value
+            .as_mut()
+            .expect("keys point to occupied slots")
+    }
+
+    /// Iterates over the occupied slots and their values.
+    fn occupied(&self) -> impl Iterator<Item = (usize, &V)> {
+        self.slots
+            .iter()
+            .enumerate()
+            .filter_map(|(index, slot)| slot.value.as_ref().map(|value| (index, value)))
+    }
+
+    /// Describes why the map cannot be serialized, if it cannot.
+    ///
+    /// The serialized forms only hold groups of keys, so they would silently lose soft aliases
+    /// and disabled keys.
+    #[cfg(any(feature = "borsh", feature = "rkyv", feature = "serde"))]
+    fn serialization_error(&self) -> Option<&'static str> {
+        if !self.soft_links.is_empty() {
+            Some("a map with soft aliases cannot be serialized")
+        } else if self.key_index.iter().any(|entry| entry.disabled) {
+            Some("a map with disabled keys cannot be serialized")
+        } else {
+            None
+        }
+    }
+
+    /// Returns the positions of the disabled keys of every slot having any, by slot index.
+    fn disabled_positions(&self) -> HashMap<usize, Vec<usize>> {
+        let mut disabled: HashMap<usize, Vec<usize>> = HashMap::new();
+        for entry in self.key_index.iter().filter(|entry| entry.disabled) {
+            disabled.entry(entry.slot).or_default().push(entry.position);
+        }
+        disabled
+    }
+}
+
+impl<K: Eq + Hash, V> Default for MultiKeyMap<K, V> {
+    /// Creates an empty `MultiKeyMap`.
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Debug, V: Debug> Debug for MultiKeyMap<K, V> {
+    /// Formats the value using the given formatter.
+    ///
+    /// This trait is used for debugging purposes.
+    ///
+    /// # Arguments
+    ///
+    /// * `f` - The formatter to use.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyMap;
+    ///
+    /// let mut map = MultiKeyMap::new();
+    /// map.insert("key1", "value1");
+    /// println!("{:?}", map);
+    /// ```
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut debug_struct = f.debug_struct("MultiKeyMap");
+        for slot in &self.slots {
+            if let Some(value) = &slot.value {
+                debug_struct.field(&format!("{:?}", slot.keys), value);
+            }
+        }
+        debug_struct.finish()
+    }
+}
+
+impl<K: Eq + Hash, V: PartialEq> PartialEq for MultiKeyMap<K, V> {
+    /// Compares two `MultiKeyMap` instances for equality.
+    ///
+    /// Two `MultiKeyMap` instances are considered equal if they have the same keys and values,
+    /// and each key in one map points to the same value as the corresponding key in the other map.
+    /// Both maps must also hold as many values and as many keys, so that equality is symmetric.
+    /// A key [disabled](Self::disable_alias) in one map must be disabled in the other as well,
+    /// and both maps must hold the same soft aliases.
+    ///
+    /// Equality does not compare how the keys are grouped: two groups with equal values can
+    /// split their keys differently and still compare equal. Use
+    /// [`same_grouping`](Self::same_grouping) on top of `==` to compare the groups too.
+    ///
+    /// # Arguments
+    ///
+    /// * `other` - The other `MultiKeyMap` to compare against.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyMap;
+    ///
+    /// let mut map1 = MultiKeyMap::new();
+    /// map1.insert("key1", "value1");
+    /// map1.insert_alias(&"key1", "alias1");
+    ///
+    /// let mut map2 = MultiKeyMap::new();
+    /// map2.insert("key1", "value1");
+    /// map2.insert_alias(&"key1", "alias1");
+    ///
+    /// assert_eq!(map1, map2);  // Should be true because both maps have the same keys and values.
+    ///
+    /// map2.remove_alias(&"alias1");
+    /// assert_ne!(map1, map2);  // Should be true because the alias has been removed from map2.
+    /// ```
+    ///
+    /// A map whose keys are all found in another map, with the same values, is only equal to it
+    /// if they have as many keys, whichever side is compared:
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyMap;
+    ///
+    /// let mut small = MultiKeyMap::new();
+    /// small.insert("a", 1);
+    /// let mut large = small.clone();
+    /// large.insert_alias(&"a", "b");
+    /// assert_ne!(small, large);
+    /// assert_ne!(large, small);
+    ///
+    /// // Disabling the alias in only one map makes them differ as well
+    /// let mut disabled = large.clone();
+    /// disabled.disable_alias(&"b");
+    /// assert_ne!(large, disabled);
+    /// assert_ne!(disabled, large);
+    /// ```
+    ///
+    /// Maps grouping the same keys differently compare equal if every key has the same value:
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyMap;
+    ///
+    /// let mut first = MultiKeyMap::new();
+    /// first.insert_with_keys(["a", "b"], 1).unwrap();
+    /// first.insert("c", 1);
+    /// let mut second = MultiKeyMap::new();
+    /// second.insert("a", 1);
+    /// second.insert_with_keys(["b", "c"], 1).unwrap();
+    /// assert_eq!(first, second);
+    /// assert!(!first.same_grouping(&second));
+    /// ```
+    fn eq(&self, other: &Self) -> bool {
+        // Check if both maps have the same number of values and keys
+        if self.len != other.len || self.key_count() != other.key_count() {
+            return false;
+        }
+        // Check if each key in `self` maps to the same value as the corresponding key in `other`,
+        // and is disabled there if it is disabled here
+        for entry in self.key_index.iter() {
+            let key = &self.slots[entry.slot].keys[entry.position];
+            match other.find(key) {
+                Some(other_entry)
+                    if other_entry.disabled == entry.disabled
+                        && self.value(entry.slot) == other.value(other_entry.slot) => {}
+                _ => return false,
+            }
+        }
+        self.soft_links.len() == other.soft_links.len()
+            && self
+                .soft_links
+                .iter()
+                .all(|(link, target)| other.soft_target(link) == Some(target))
+    }
 }
 
 impl<K: Eq + Hash, V: PartialEq> Eq for MultiKeyMap<K, V> {}
 
+impl<K: Eq + Hash, V> Extend<(K, V)> for MultiKeyMap<K, V> {
+    /// Inserts every key-value pair as a new group, in order, as [`insert`](Self::insert) does.
+    ///
+    /// A key that already exists, in the map or earlier in `iter`, moves to the group of its
+    /// later pair, and a group left without keys is dropped with its value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyMap;
+    ///
+    /// let mut map = MultiKeyMap::new();
+    /// map.insert("a", 1);
+    /// map.insert_alias(&"a", "b");
+    /// map.extend([("a", 2), ("c", 3), ("c", 4)]);
+    /// assert_eq!(map.len(), 3);
+    /// assert_eq!(map.get(&"a"), Some(&2));
+    /// assert_eq!(map.get(&"b"), Some(&1));
+    /// assert_eq!(map.get(&"c"), Some(&4));
+    /// ```
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        let _span = self.trace_bulk("extend");
+        for (key, value) in iter {
+            self.insert(key, value);
+        }
+    }
+}
+
+impl<K: Eq + Hash, V> Extend<(Vec<K>, V)> for MultiKeyMap<K, V> {
+    /// Inserts every group of keys and value, in order, as
+    /// [`insert_with_keys`](Self::insert_with_keys) does.
+    ///
+    /// A key that already exists, in the map or earlier in `iter`, moves to the later group, and
+    /// a group left without keys is dropped with its value.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a group has no keys or does not fit the [alias limit](Self::with_alias_limit).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyMap;
+    ///
+    /// let mut map = MultiKeyMap::new();
+    /// map.extend([(vec!["a", "b"], 1), (vec!["c"], 2), (vec!["b", "d"], 3)]);
+    /// assert_eq!(map.len(), 3);
+    /// assert!(map.group_matches(&"a", &["a"]));
+    /// assert!(map.group_matches(&"d", &["b", "d"]));
+    /// ```
+    fn extend<I: IntoIterator<Item = (Vec<K>, V)>>(&mut self, iter: I) {
+        let _span = self.trace_bulk("extend");
+        for (keys, value) in iter {
+            self.insert_with_keys(keys, value)
+                .expect("group fits the alias limit");
+        }
+    }
+}
+
+impl<K: Eq + Hash, V> FromIterator<(K, V)> for MultiKeyMap<K, V> {
+    /// Creates a map from key-value pairs, as [`extend`](Extend::extend) inserts them into an
+    /// empty map.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyMap;
+    ///
+    /// let map: MultiKeyMap<_, _> = [("a", 1), ("b", 2), ("a", 3)].into_iter().collect();
+    /// assert_eq!(map.len(), 2);
+    /// assert_eq!(map.get(&"a"), Some(&3));
+    /// ```
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut map = Self::new();
+        map.extend(iter);
+        map
+    }
+}
+
+impl<K: Eq + Hash, V> FromIterator<(Vec<K>, V)> for MultiKeyMap<K, V> {
+    /// Creates a map from groups of keys and values, as [`extend`](Extend::extend) inserts them
+    /// into an empty map.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a group has no keys.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyMap;
+    ///
+    /// let map: MultiKeyMap<_, _> = [(vec!["a", "b"], 1), (vec!["c"], 2)].into_iter().collect();
+    /// assert_eq!(map.len(), 2);
+    /// assert_eq!(map.get(&"b"), Some(&1));
+    /// ```
+    fn from_iter<I: IntoIterator<Item = (Vec<K>, V)>>(iter: I) -> Self {
+        let mut map = Self::new();
+        map.extend(iter);
+        map
+    }
+}
+
 impl<K: Eq + Hash + Clone + Debug, V: Clone + Debug> Clone for MultiKeyMap<K, V> {
     /// Creates a deep copy of the `MultiKeyMap`.
     ///
-    /// This method clones both the `key_map` and the `values` vector to produce a new `MultiKeyMap`
-    /// instance that is a copy of the original.
+    /// This method clones both the keys and the values to produce a new `MultiKeyMap` instance
+    /// that is a copy of the original.
     ///
     /// # Examples
     ///
@@ -469,10 +4942,21 @@ impl<K: Eq + Hash + Clone + Debug, V: Clone + Debug> Clone for MultiKeyMap<K, V>
     /// assert_eq!(original, clone);  // The original and clone should be equal.
     /// ```
     fn clone(&self) -> Self {
-        // Clone the values and the key_map
+        // Clone the slots and the index into them
         MultiKeyMap {
-            key_map: self.key_map.clone(),
-            values: self.values.clone(),
+            key_index: self.key_index.clone(),
+            hasher: self.hasher.clone(),
+            slots: self.slots.clone(),
+            free: self.free.clone(),
+            len: self.len,
+            next_generation: self.next_generation,
+            alias_limit: self.alias_limit,
+            counters: self.counters.clone(),
+            hooks: Hooks::default(),
+            revision: self.revision,
+            normalizer: self.normalizer.clone(),
+            soft_links: self.soft_links.clone(),
+            value_index: None,
         }
     }
 }
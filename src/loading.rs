@@ -0,0 +1,198 @@
+use crate::{AliasLimitExceeded, MultiKeyMap};
+use std::future::Future;
+use std::hash::Hash;
+
+impl<K: Eq + Hash, V> MultiKeyMap<K, V> {
+    /// Retrieves a mutable reference to the value of `key`, inserting the value returned by `f`
+    /// first if the key does not exist and `f` succeeds.
+    ///
+    /// `key` is looked up like [`get`](Self::get) does, following soft aliases and missing
+    /// disabled keys, which a miss binds to the loaded value as [`insert`](Self::insert)
+    /// would. `f` is only called on a miss. If it fails, its error is returned and the map is
+    /// left unchanged, without the key; `key` is dropped.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key to lookup, inserted with the loaded value on a miss.
+    /// * `f` - A function returning the value to insert, or why it could not be loaded.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error of `f`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyMap;
+    ///
+    /// let mut ports = MultiKeyMap::new();
+    /// ports.insert("http", 80);
+    /// ports.insert_alias(&"http", "www");
+    ///
+    /// // A hit does not call the loader
+    /// let port = ports.get_or_try_insert_with("www", || "x".parse::<u16>());
+    /// assert_eq!(port, Ok(&mut 80));
+    ///
+    /// // A miss inserts the loaded value
+    /// *ports.get_or_try_insert_with("ssh", || "22".parse::<u16>()).unwrap() += 2000;
+    /// assert_eq!(ports.get(&"ssh"), Some(&2022));
+    ///
+    /// // A soft alias hits the group of its target
+    /// ports.insert_soft_alias(&"ssh", "remote");
+    /// assert_eq!(ports.get_or_try_insert_with("remote", || "x".parse::<u16>()), Ok(&mut 2022));
+    ///
+    /// // A failed load leaves the map unchanged
+    /// assert!(ports.get_or_try_insert_with("ftp", || "twenty-one".parse::<u16>()).is_err());
+    /// assert!(!ports.contains_key(&"ftp"));
+    /// assert_eq!(ports.len(), 2);
+    ///
+    /// // A disabled key misses too
+    /// ports.disable_alias(&"www");
+    /// assert_eq!(ports.get_or_try_insert_with("www", || "8080".parse::<u16>()), Ok(&mut 8080));
+    /// assert!(!ports.are_aliases(&"http", &"www"));
+    /// ```
+    pub fn get_or_try_insert_with<E>(
+        &mut self,
+        key: K,
+        f: impl FnOnce() -> Result<V, E>,
+    ) -> Result<&mut V, E> {
+        let index = self.resolve_index(&key);
+        self.counters.lookup(index.is_some());
+        let index = match index {
+            Some(index) => index,
+            None => {
+                let index = self.insert_group(vec![key], f()?);
+                self.check_mutation();
+                index
+            }
+        };
+        Ok(self.value_mut(index))
+    }
+
+    /// Retrieves a mutable reference to the value of `key`, awaiting the value returned by `f`
+    /// and inserting it first if the key does not exist.
+    ///
+    /// `f` is only called and awaited on a miss. The map is not borrowed by the future of `f`, so
+    /// the loader may borrow anything else, and it is left untouched until the value is ready.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key to lookup, inserted with the loaded value on a miss.
+    /// * `f` - A function returning a future of the value to insert.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use futures::executor::block_on;
+    /// use multi_key_map::MultiKeyMap;
+    /// use std::cell::Cell;
+    ///
+    /// let loads = Cell::new(0);
+    /// let load = |id: u32| {
+    ///     let loads = &loads;
+    ///     async move {
+    ///         loads.set(loads.get() + 1);
+    ///         format!("user {id}")
+    ///     }
+    /// };
+    ///
+    /// let mut map = MultiKeyMap::new();
+    /// block_on(async {
+    ///     assert_eq!(map.get_or_insert_with_async(7, || load(7)).await, "user 7");
+    ///     map.get_or_insert_with_async(7, || load(7)).await.push('!');
+    ///     map.insert_alias(&7, 8);
+    ///     assert_eq!(map.get_or_insert_with_async(8, || load(8)).await, "user 7!");
+    /// });
+    /// assert_eq!(loads.get(), 1);
+    /// ```
+    pub async fn get_or_insert_with_async<F, Fut>(&mut self, key: K, f: F) -> &mut V
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = V>,
+    {
+        let index = self.resolve_index(&key);
+        self.counters.lookup(index.is_some());
+        let index = match index {
+            Some(index) => index,
+            None => {
+                let value = f().await;
+                let index = self.insert_group(vec![key], value);
+                self.check_mutation();
+                index
+            }
+        };
+        self.value_mut(index)
+    }
+
+    /// Retrieves a mutable reference to the value of `key`, awaiting the value and aliases
+    /// returned by `f` and inserting them first if the key does not exist.
+    ///
+    /// On a miss, the loaded value is inserted with `key` and the loaded aliases, as
+    /// [`insert_with_keys`](Self::insert_with_keys) does: aliases that exist are moved to the new
+    /// group. `f` is only called and awaited on a miss.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key to lookup, the primary key of the loaded group on a miss.
+    /// * `f` - A function returning a future of the value to insert and its aliases.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AliasLimitExceeded`], dropping the loaded value, if the loaded keys do not fit
+    /// the [alias limit](Self::with_alias_limit).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use futures::executor::block_on;
+    /// use multi_key_map::MultiKeyMap;
+    ///
+    /// let mut loads = 0;
+    /// let mut map = MultiKeyMap::with_alias_limit(3);
+    /// block_on(async {
+    ///     for key in ["en", "en-us", "en-gb", "en"] {
+    ///         let value = map
+    ///             .get_or_insert_with_aliases_async(key, || async {
+    ///                 loads += 1;
+    ///                 ("hello", vec!["en-us", "en-gb"])
+    ///             })
+    ///             .await
+    ///             .unwrap();
+    ///         assert_eq!(*value, "hello");
+    ///     }
+    ///     let error = map
+    ///         .get_or_insert_with_aliases_async("fr", || async { ("salut", vec!["a", "b", "c"]) })
+    ///         .await;
+    ///     assert!(error.is_err());
+    /// });
+    /// assert_eq!(loads, 1);
+    /// assert_eq!(map.primary_of(&"en-gb"), Some(&"en"));
+    /// assert!(!map.contains_key(&"fr"));
+    /// ```
+    pub async fn get_or_insert_with_aliases_async<F, Fut>(
+        &mut self,
+        key: K,
+        f: F,
+    ) -> Result<&mut V, AliasLimitExceeded>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = (V, Vec<K>)>,
+    {
+        let index = self.resolve_index(&key);
+        self.counters.lookup(index.is_some());
+        let index = match index {
+            Some(index) => index,
+            None => {
+                let (value, aliases) = f().await;
+                let mut keys = Vec::with_capacity(aliases.len() + 1);
+                keys.push(key);
+                keys.extend(aliases);
+                self.check_group_size(&keys)?;
+                let index = self.insert_group(keys, value);
+                self.check_mutation();
+                index
+            }
+        };
+        Ok(self.value_mut(index))
+    }
+}
@@ -0,0 +1,367 @@
+use crate::MultiKeyMap;
+use std::fmt::{self, Debug};
+use std::hash::Hash;
+
+#[cfg(feature = "concurrent")]
+use parking_lot::{RwLock, RwLockReadGuard, RwLockWriteGuard};
+#[cfg(not(feature = "concurrent"))]
+use std::sync::{PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+/// A `SyncMultiKeyMap` is a [`MultiKeyMap`] behind a read-write lock, which can be shared between
+/// threads as is.
+///
+/// Every method takes the lock for the duration of one operation on the map and releases it
+/// before returning, so no reference into the map ever escapes. Values are handed out either
+/// cloned, with [`get_cloned`](Self::get_cloned), or to a closure, with
+/// [`with_value`](Self::with_value).
+///
+/// Each call is atomic, but a sequence of calls is not: other threads may modify the map between
+/// them, so a key found by one call may be gone by the next. Use [`read`](Self::read) or
+/// [`write`](Self::write) to run several operations under one lock.
+///
+/// The lock is a `parking_lot` lock with the `concurrent` feature, and a [`std::sync::RwLock`]
+/// otherwise. As `parking_lot` locks are never poisoned, a panic while the lock is held does not
+/// poison the standard lock either: later calls see the map as the panicking thread left it.
+///
+/// # Examples
+///
+/// ```
+/// use multi_key_map::SyncMultiKeyMap;
+/// use std::sync::Arc;
+/// use std::thread;
+///
+/// let map = Arc::new(SyncMultiKeyMap::new());
+/// let handles: Vec<_> = (0..4)
+///     .map(|thread| {
+///         let map = Arc::clone(&map);
+///         thread::spawn(move || {
+///             for i in 0..100 {
+///                 let key = thread * 1000 + i;
+///                 map.insert(key, key * 2);
+///                 map.insert_alias(&key, key + 100_000);
+///                 if i % 2 == 1 {
+///                     map.remove(&(key - 1));
+///                 }
+///             }
+///         })
+///     })
+///     .collect();
+/// for handle in handles {
+///     handle.join().unwrap();
+/// }
+///
+/// assert_eq!(map.len(), 200);
+/// map.read(|map| {
+///     assert_eq!(map.keys().count(), 400);
+///     for &key in map.keys().filter(|&&key| key < 100_000) {
+///         assert_eq!(key % 2, 1);
+///         assert_eq!(map.aliases(&key), Some(vec![key, key + 100_000]));
+///         assert_eq!(map.get(&key), Some(&(key * 2)));
+///     }
+/// });
+/// ```
+pub struct SyncMultiKeyMap<K, V> {
+    map: RwLock<MultiKeyMap<K, V>>,
+}
+
+impl<K: Eq + Hash, V> SyncMultiKeyMap<K, V> {
+    /// Creates an empty `SyncMultiKeyMap`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::SyncMultiKeyMap;
+    ///
+    /// let map: SyncMultiKeyMap<&str, u32> = SyncMultiKeyMap::new();
+    /// assert!(map.is_empty());
+    /// ```
+    pub fn new() -> Self {
+        Self::from(MultiKeyMap::new())
+    }
+
+    /// Returns a clone of the value of `key`, or `None` if the key does not exist.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key to lookup.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::SyncMultiKeyMap;
+    ///
+    /// let map = SyncMultiKeyMap::new();
+    /// map.insert("key1", String::from("value1"));
+    /// map.insert_alias(&"key1", "alias1");
+    /// assert_eq!(map.get_cloned(&"alias1").as_deref(), Some("value1"));
+    /// assert_eq!(map.get_cloned(&"key2"), None);
+    /// ```
+    pub fn get_cloned(&self, key: &K) -> Option<V>
+    where
+        V: Clone,
+    {
+        self.read_lock().get(key).cloned()
+    }
+
+    /// Calls `f` with the value of `key` under the read lock and returns its result, or `None`
+    /// if the key does not exist.
+    ///
+    /// `f` should be short: writers wait for it to return.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key to lookup.
+    /// * `f` - The function to call with the value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::SyncMultiKeyMap;
+    ///
+    /// let map = SyncMultiKeyMap::new();
+    /// map.insert("key1", vec![1, 2, 3]);
+    /// assert_eq!(map.with_value(&"key1", |value| value.len()), Some(3));
+    /// assert_eq!(map.with_value(&"key2", |value| value.len()), None);
+    /// ```
+    pub fn with_value<R>(&self, key: &K, f: impl FnOnce(&V) -> R) -> Option<R> {
+        self.read_lock().get(key).map(f)
+    }
+
+    /// Inserts a value with the given key, as [`MultiKeyMap::insert`] does.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key to insert.
+    /// * `value` - The value to insert.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::SyncMultiKeyMap;
+    ///
+    /// let map = SyncMultiKeyMap::new();
+    /// map.insert("key1", "value1");
+    /// map.insert("key1", "value2");
+    /// assert_eq!(map.get_cloned(&"key1"), Some("value2"));
+    /// assert_eq!(map.len(), 1);
+    /// ```
+    pub fn insert(&self, key: K, value: V) {
+        self.write_lock().insert(key, value);
+    }
+
+    /// Adds a new alias key for the value of `key`, as [`MultiKeyMap::insert_alias`] does.
+    ///
+    /// Returns the reference count if the alias is successfully added.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The original key.
+    /// * `alias` - The alias key to add.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::SyncMultiKeyMap;
+    ///
+    /// let map = SyncMultiKeyMap::new();
+    /// map.insert("key1", "value1");
+    /// assert_eq!(map.insert_alias(&"key1", "alias1"), Some(2));
+    /// assert_eq!(map.insert_alias(&"key2", "alias2"), None);
+    /// ```
+    pub fn insert_alias(&self, key: &K, alias: K) -> Option<usize> {
+        self.write_lock().insert_alias(key, alias)
+    }
+
+    /// Removes the value of `key` and all of its keys, returning the value if the key existed.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - Any key of the value to remove.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::SyncMultiKeyMap;
+    ///
+    /// let map = SyncMultiKeyMap::new();
+    /// map.insert("key1", "value1");
+    /// map.insert_alias(&"key1", "alias1");
+    /// assert_eq!(map.remove(&"alias1"), Some("value1"));
+    /// assert_eq!(map.get_cloned(&"key1"), None);
+    /// assert_eq!(map.remove(&"key1"), None);
+    /// ```
+    pub fn remove(&self, key: &K) -> Option<V> {
+        self.write_lock().remove(key)
+    }
+
+    /// Returns every key of the value of `key`, or `None` if the key does not exist.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - Any key of the value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::SyncMultiKeyMap;
+    ///
+    /// let map = SyncMultiKeyMap::new();
+    /// map.insert("key1", "value1");
+    /// map.insert_alias(&"key1", "alias1");
+    /// assert_eq!(map.aliases(&"alias1"), Some(vec!["key1", "alias1"]));
+    /// assert_eq!(map.aliases(&"key2"), None);
+    /// ```
+    pub fn aliases(&self, key: &K) -> Option<Vec<K>>
+    where
+        K: Clone,
+    {
+        self.read_lock().aliases(key)
+    }
+
+    /// Returns the number of values in the map.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::SyncMultiKeyMap;
+    ///
+    /// let map = SyncMultiKeyMap::new();
+    /// map.insert("key1", "value1");
+    /// map.insert_alias(&"key1", "alias1");
+    /// assert_eq!(map.len(), 1);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.read_lock().len()
+    }
+
+    /// Returns `true` if the map contains no values.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::SyncMultiKeyMap;
+    ///
+    /// let map: SyncMultiKeyMap<&str, &str> = SyncMultiKeyMap::new();
+    /// assert!(map.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.read_lock().is_empty()
+    }
+
+    /// Calls `f` with the map under the read lock and returns its result.
+    ///
+    /// # Arguments
+    ///
+    /// * `f` - The function to call with the map.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::SyncMultiKeyMap;
+    ///
+    /// let map = SyncMultiKeyMap::new();
+    /// map.insert("key1", 1);
+    /// map.insert_alias(&"key1", "alias1");
+    /// map.insert("key2", 2);
+    /// assert_eq!(map.read(|map| map.keys().count()), 3);
+    /// ```
+    pub fn read<R>(&self, f: impl FnOnce(&MultiKeyMap<K, V>) -> R) -> R {
+        f(&self.read_lock())
+    }
+
+    /// Calls `f` with the map under the write lock and returns its result, so several
+    /// operations happen atomically.
+    ///
+    /// # Arguments
+    ///
+    /// * `f` - The function to call with the map.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::SyncMultiKeyMap;
+    ///
+    /// let map = SyncMultiKeyMap::new();
+    /// map.insert("key1", 1);
+    /// // No other thread can remove the key between the lookup and the alias
+    /// map.write(|map| {
+    ///     if map.get(&"key1") == Some(&1) {
+    ///         map.insert_alias(&"key1", "one");
+    ///     }
+    /// });
+    /// assert_eq!(map.get_cloned(&"one"), Some(1));
+    /// ```
+    pub fn write<R>(&self, f: impl FnOnce(&mut MultiKeyMap<K, V>) -> R) -> R {
+        f(&mut self.write_lock())
+    }
+
+    /// Consumes the lock and returns the map.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::SyncMultiKeyMap;
+    ///
+    /// let map = SyncMultiKeyMap::new();
+    /// map.insert("key1", "value1");
+    /// let map = map.into_inner();
+    /// assert_eq!(map.get(&"key1"), Some(&"value1"));
+    /// ```
+    pub fn into_inner(self) -> MultiKeyMap<K, V> {
+        #[cfg(feature = "concurrent")]
+        return self.map.into_inner();
+        #[cfg(not(feature = "concurrent"))]
+        return self
+            .map
+            .into_inner()
+            .unwrap_or_else(PoisonError::into_inner);
+    }
+}
+
+impl<K, V> SyncMultiKeyMap<K, V> {
+    fn read_lock(&self) -> RwLockReadGuard<'_, MultiKeyMap<K, V>> {
+        #[cfg(feature = "concurrent")]
+        return self.map.read();
+        #[cfg(not(feature = "concurrent"))]
+        return self.map.read().unwrap_or_else(PoisonError::into_inner);
+    }
+
+    fn write_lock(&self) -> RwLockWriteGuard<'_, MultiKeyMap<K, V>> {
+        #[cfg(feature = "concurrent")]
+        return self.map.write();
+        #[cfg(not(feature = "concurrent"))]
+        return self.map.write().unwrap_or_else(PoisonError::into_inner);
+    }
+}
+
+impl<K: Eq + Hash, V> Default for SyncMultiKeyMap<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K, V> From<MultiKeyMap<K, V>> for SyncMultiKeyMap<K, V> {
+    /// Puts `map` behind a lock.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::{MultiKeyMap, SyncMultiKeyMap};
+    ///
+    /// let mut map = MultiKeyMap::new();
+    /// map.insert("key1", "value1");
+    /// let map = SyncMultiKeyMap::from(map);
+    /// assert_eq!(map.len(), 1);
+    /// ```
+    fn from(map: MultiKeyMap<K, V>) -> Self {
+        SyncMultiKeyMap {
+            map: RwLock::new(map),
+        }
+    }
+}
+
+impl<K: Debug, V: Debug> Debug for SyncMultiKeyMap<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Debug::fmt(&*self.read_lock(), f)
+    }
+}
@@ -0,0 +1,217 @@
+#[cfg(feature = "persistent")]
+use crate::PersistentMultiKeyMap;
+use crate::{
+    BiMultiKeyMap, BoxedMultiKeyMap, FrozenMultiKeyMap, MultiKeyBTreeMap, MultiKeyIndexMap,
+    MultiKeyMap, MultiKeyMapView, UndoableMultiKeyMap,
+};
+use std::collections::{BTreeMap, HashMap};
+use std::hash::{BuildHasher, Hash};
+
+/// Read access to a map by key, so generic code can accept any map of this crate as well as the
+/// standard maps.
+///
+/// [`len`](Self::len) counts values: groups for the maps of this crate, and entries for the
+/// standard maps.
+///
+/// # Examples
+///
+/// ```
+/// use multi_key_map::{KeyLookup, MultiKeyMap};
+/// use std::collections::{BTreeMap, HashMap};
+///
+/// fn describe(ports: &impl KeyLookup<&'static str, u16>) -> String {
+///     match ports.get(&"http") {
+///         Some(port) => format!("http on {port} among {} services", ports.len()),
+///         None => String::from("no http"),
+///     }
+/// }
+///
+/// let mut multi = MultiKeyMap::new();
+/// multi.insert("http", 80);
+/// multi.insert_alias(&"http", "www");
+/// multi.insert("ssh", 22);
+/// assert_eq!(describe(&multi), "http on 80 among 2 services");
+///
+/// let hash = HashMap::from([("http", 8080), ("www", 8080), ("ssh", 22)]);
+/// assert_eq!(describe(&hash), "http on 8080 among 3 services");
+///
+/// let btree = BTreeMap::from([("ssh", 22)]);
+/// assert_eq!(describe(&btree), "no http");
+/// assert_eq!(describe(&multi.view(|key| *key != "http")), "no http");
+/// ```
+pub trait KeyLookup<K, V: ?Sized> {
+    /// Retrieves a reference to the value of `key`, or `None` if the key does not exist.
+    fn get(&self, key: &K) -> Option<&V>;
+
+    /// Returns `true` if the map contains a value for `key`.
+    fn contains_key(&self, key: &K) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// Returns the number of values in the map.
+    fn len(&self) -> usize;
+
+    /// Returns `true` if the map contains no values.
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<K: Eq + Hash, V, S: BuildHasher> KeyLookup<K, V> for HashMap<K, V, S> {
+    fn get(&self, key: &K) -> Option<&V> {
+        HashMap::get(self, key)
+    }
+
+    fn contains_key(&self, key: &K) -> bool {
+        HashMap::contains_key(self, key)
+    }
+
+    fn len(&self) -> usize {
+        HashMap::len(self)
+    }
+}
+
+impl<K: Ord, V> KeyLookup<K, V> for BTreeMap<K, V> {
+    fn get(&self, key: &K) -> Option<&V> {
+        BTreeMap::get(self, key)
+    }
+
+    fn contains_key(&self, key: &K) -> bool {
+        BTreeMap::contains_key(self, key)
+    }
+
+    fn len(&self) -> usize {
+        BTreeMap::len(self)
+    }
+}
+
+impl<K: Eq + Hash, V> KeyLookup<K, V> for MultiKeyMap<K, V> {
+    fn get(&self, key: &K) -> Option<&V> {
+        MultiKeyMap::get(self, key)
+    }
+
+    fn contains_key(&self, key: &K) -> bool {
+        MultiKeyMap::contains_key(self, key)
+    }
+
+    fn len(&self) -> usize {
+        MultiKeyMap::len(self)
+    }
+}
+
+impl<K: Eq + Hash, V> KeyLookup<K, V> for MultiKeyMapView<'_, K, V> {
+    fn get(&self, key: &K) -> Option<&V> {
+        MultiKeyMapView::get(self, key)
+    }
+
+    fn contains_key(&self, key: &K) -> bool {
+        MultiKeyMapView::contains_key(self, key)
+    }
+
+    fn len(&self) -> usize {
+        MultiKeyMapView::len(self)
+    }
+
+    fn is_empty(&self) -> bool {
+        MultiKeyMapView::is_empty(self)
+    }
+}
+
+impl<K: Eq + Hash, V> KeyLookup<K, V> for FrozenMultiKeyMap<K, V> {
+    fn get(&self, key: &K) -> Option<&V> {
+        FrozenMultiKeyMap::get(self, key)
+    }
+
+    fn contains_key(&self, key: &K) -> bool {
+        FrozenMultiKeyMap::contains_key(self, key)
+    }
+
+    fn len(&self) -> usize {
+        FrozenMultiKeyMap::len(self)
+    }
+}
+
+impl<K: Eq + Hash, V: Eq + Hash + Clone> KeyLookup<K, V> for BiMultiKeyMap<K, V> {
+    fn get(&self, key: &K) -> Option<&V> {
+        BiMultiKeyMap::get(self, key)
+    }
+
+    fn contains_key(&self, key: &K) -> bool {
+        BiMultiKeyMap::contains_key(self, key)
+    }
+
+    fn len(&self) -> usize {
+        BiMultiKeyMap::len(self)
+    }
+}
+
+impl<K: Eq + Hash, V: ?Sized> KeyLookup<K, V> for BoxedMultiKeyMap<K, V> {
+    fn get(&self, key: &K) -> Option<&V> {
+        BoxedMultiKeyMap::get(self, key)
+    }
+
+    fn contains_key(&self, key: &K) -> bool {
+        BoxedMultiKeyMap::contains_key(self, key)
+    }
+
+    fn len(&self) -> usize {
+        BoxedMultiKeyMap::len(self)
+    }
+}
+
+impl<K: Ord, V> KeyLookup<K, V> for MultiKeyBTreeMap<K, V> {
+    fn get(&self, key: &K) -> Option<&V> {
+        MultiKeyBTreeMap::get(self, key)
+    }
+
+    fn contains_key(&self, key: &K) -> bool {
+        MultiKeyBTreeMap::contains_key(self, key)
+    }
+
+    fn len(&self) -> usize {
+        MultiKeyBTreeMap::len(self)
+    }
+}
+
+impl<K: Eq + Hash, V> KeyLookup<K, V> for MultiKeyIndexMap<K, V> {
+    fn get(&self, key: &K) -> Option<&V> {
+        MultiKeyIndexMap::get(self, key)
+    }
+
+    fn contains_key(&self, key: &K) -> bool {
+        MultiKeyIndexMap::contains_key(self, key)
+    }
+
+    fn len(&self) -> usize {
+        MultiKeyIndexMap::len(self)
+    }
+}
+
+impl<K: Eq + Hash + Clone, V> KeyLookup<K, V> for UndoableMultiKeyMap<K, V> {
+    fn get(&self, key: &K) -> Option<&V> {
+        UndoableMultiKeyMap::get(self, key)
+    }
+
+    fn contains_key(&self, key: &K) -> bool {
+        UndoableMultiKeyMap::contains_key(self, key)
+    }
+
+    fn len(&self) -> usize {
+        UndoableMultiKeyMap::len(self)
+    }
+}
+
+#[cfg(feature = "persistent")]
+impl<K: Eq + Hash + Clone, V: Clone> KeyLookup<K, V> for PersistentMultiKeyMap<K, V> {
+    fn get(&self, key: &K) -> Option<&V> {
+        PersistentMultiKeyMap::get(self, key)
+    }
+
+    fn contains_key(&self, key: &K) -> bool {
+        PersistentMultiKeyMap::contains_key(self, key)
+    }
+
+    fn len(&self) -> usize {
+        PersistentMultiKeyMap::len(self)
+    }
+}
@@ -0,0 +1,450 @@
+use crate::{EvictionPolicy, GroupId, Lru, MultiKeyMap};
+use std::fmt::{self, Debug};
+use std::hash::Hash;
+
+/// A `MultiKeyLruMap` is a [`MultiKeyMap`] bounded to a number of groups, evicting the group
+/// picked by its [eviction policy](EvictionPolicy) when it grows beyond its capacity.
+///
+/// The policy is [`Lru`] by default, evicting the least recently used group. Use
+/// [`with_policy`](Self::with_policy) to pick another one, such as [`Lfu`](crate::Lfu).
+///
+/// Usage is tracked per group: accessing a value through any of its keys counts for the whole
+/// group, and eviction removes the group with all of its aliases at once. With [`Lru`], every
+/// access and eviction is `O(1)`.
+///
+/// # Examples
+///
+/// ```
+/// use multi_key_map::MultiKeyLruMap;
+///
+/// let mut cache = MultiKeyLruMap::new(2);
+/// cache.insert("a", 1);
+/// cache.insert_alias(&"a", "alias");
+/// cache.insert("b", 2);
+///
+/// // Accessing `a` through its alias protects the whole group
+/// assert_eq!(cache.get(&"alias"), Some(&1));
+/// assert_eq!(cache.insert("c", 3), Some((vec!["b"], 2)));
+///
+/// cache.get(&"c");
+/// let (mut keys, value) = cache.insert("d", 4).unwrap();
+/// keys.sort();
+/// assert_eq!((keys, value), (vec!["a", "alias"], 1));
+/// assert!(!cache.contains_key(&"alias"));
+/// assert_eq!(cache.len(), 2);
+/// ```
+pub struct MultiKeyLruMap<K, V, P = Lru> {
+    map: MultiKeyMap<K, V>,
+    /// Tracks the usage of every group.
+    policy: P,
+    capacity: usize,
+}
+
+impl<K: Eq + Hash, V> MultiKeyLruMap<K, V> {
+    /// Creates an empty `MultiKeyLruMap` holding at most `capacity` groups and evicting the least
+    /// recently used one.
+    ///
+    /// # Arguments
+    ///
+    /// * `capacity` - The maximum number of groups.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyLruMap;
+    ///
+    /// let cache: MultiKeyLruMap<&str, &str> = MultiKeyLruMap::new(16);
+    /// assert_eq!(cache.capacity(), 16);
+    /// ```
+    pub fn new(capacity: usize) -> Self {
+        Self::with_policy(capacity, Lru::new())
+    }
+}
+
+impl<K: Eq + Hash, V, P: EvictionPolicy> MultiKeyLruMap<K, V, P> {
+    /// Creates an empty `MultiKeyLruMap` holding at most `capacity` groups and evicting the one
+    /// picked by `policy`.
+    ///
+    /// # Arguments
+    ///
+    /// * `capacity` - The maximum number of groups.
+    /// * `policy` - The eviction policy, which must know of no group.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` is zero.
+    ///
+    /// # Examples
+    ///
+    /// The same accesses make [`Lru`] and [`Lfu`](crate::Lfu) evict different groups:
+    ///
+    /// ```
+    /// use multi_key_map::{EvictionPolicy, Lfu, Lru, MultiKeyLruMap};
+    ///
+    /// fn run<P: EvictionPolicy>(policy: P) -> Vec<(Vec<&'static str>, u32)> {
+    ///     let mut cache = MultiKeyLruMap::with_policy(2, policy);
+    ///     let mut evicted = Vec::new();
+    ///     cache.insert("a", 1);
+    ///     cache.insert_alias(&"a", "alias");
+    ///     cache.insert("b", 2);
+    ///     cache.get(&"alias");
+    ///     cache.get(&"a");
+    ///     cache.get(&"b");
+    ///     evicted.extend(cache.insert("c", 3));
+    ///     cache.get(&"c");
+    ///     evicted.extend(cache.insert("d", 4));
+    ///     evicted
+    /// }
+    ///
+    /// assert_eq!(run(Lru::new()), [(vec!["a", "alias"], 1), (vec!["b"], 2)]);
+    /// assert_eq!(run(Lfu::new()), [(vec!["b"], 2), (vec!["c"], 3)]);
+    /// ```
+    pub fn with_policy(capacity: usize, policy: P) -> Self {
+        assert!(capacity > 0, "an LRU map needs room for at least one group");
+        MultiKeyLruMap {
+            map: MultiKeyMap::new(),
+            policy,
+            capacity,
+        }
+    }
+
+    /// Retrieves a reference to a value by its key, recording an access to its group.
+    ///
+    /// Returns `None` if the key does not exist.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key to lookup.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyLruMap;
+    ///
+    /// let mut cache = MultiKeyLruMap::new(2);
+    /// cache.insert("a", 1);
+    /// cache.insert("b", 2);
+    /// assert_eq!(cache.get(&"a"), Some(&1));
+    /// cache.insert("c", 3);
+    /// assert!(cache.contains_key(&"a"));
+    /// assert!(!cache.contains_key(&"b"));
+    /// ```
+    pub fn get(&mut self, key: &K) -> Option<&V> {
+        let index = self.map.index_of(key)?;
+        self.policy.on_access(self.map.id_at(index));
+        Some(self.map.value(index))
+    }
+
+    /// Retrieves a mutable reference to a value by its key, recording an access to its group.
+    ///
+    /// Returns `None` if the key does not exist.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key to lookup.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyLruMap;
+    ///
+    /// let mut cache = MultiKeyLruMap::new(2);
+    /// cache.insert("a", 1);
+    /// *cache.get_mut(&"a").unwrap() += 1;
+    /// assert_eq!(cache.peek(&"a"), Some(&2));
+    /// ```
+    pub fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        let index = self.map.index_of(key)?;
+        self.policy.on_access(self.map.id_at(index));
+        Some(self.map.value_mut(index))
+    }
+
+    /// Retrieves a reference to a value by its key without recording an access to its group.
+    ///
+    /// Returns `None` if the key does not exist.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key to lookup.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyLruMap;
+    ///
+    /// let mut cache = MultiKeyLruMap::new(2);
+    /// cache.insert("a", 1);
+    /// cache.insert("b", 2);
+    /// assert_eq!(cache.peek(&"a"), Some(&1));
+    /// cache.insert("c", 3);
+    /// assert!(!cache.contains_key(&"a"));
+    /// ```
+    pub fn peek(&self, key: &K) -> Option<&V> {
+        self.map.get(key)
+    }
+
+    /// Inserts a value with the given key as a new group.
+    ///
+    /// If the key already exists, it is moved to the new value, and its old group is removed if
+    /// it has no other keys. Returns the keys and value of the group picked by the policy if it
+    /// was evicted to make room.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key to insert.
+    /// * `value` - The value to insert.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyLruMap;
+    ///
+    /// let mut cache = MultiKeyLruMap::new(1);
+    /// assert_eq!(cache.insert("a", 1), None);
+    /// assert_eq!(cache.insert("a", 2), None);
+    /// assert_eq!(cache.insert("b", 3), Some((vec!["a"], 2)));
+    /// ```
+    pub fn insert(&mut self, key: K, value: V) -> Option<(Vec<K>, V)> {
+        let previous = self.map.group_id(&key);
+        let index = self.map.alloc(value);
+        self.map.bind(key, index);
+        self.forget_if_vacant(previous);
+        // Pick the victim among the other groups, so the new one is never evicted right away
+        let evicted = if self.map.len() > self.capacity {
+            self.pop_lru()
+        } else {
+            None
+        };
+        self.policy.on_insert(self.map.id_at(index));
+        evicted
+    }
+
+    /// Adds a new alias key for the element at `key`, recording an access to its group.
+    ///
+    /// Returns the reference count if the alias is successfully added. An alias taken from
+    /// another group removes that group if it has no other keys.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The original key.
+    /// * `alias` - The alias key to add.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyLruMap;
+    ///
+    /// let mut cache = MultiKeyLruMap::new(2);
+    /// cache.insert("a", 1);
+    /// cache.insert("b", 2);
+    /// assert_eq!(cache.insert_alias(&"b", "a"), Some(2));
+    /// assert_eq!(cache.len(), 1);
+    /// assert_eq!(cache.insert_alias(&"c", "d"), None);
+    /// ```
+    pub fn insert_alias(&mut self, key: &K, alias: K) -> Option<usize> {
+        let index = self.map.index_of(key)?;
+        let previous = self.map.group_id(&alias);
+        let count = self.map.insert_alias(key, alias)?;
+        self.forget_if_vacant(previous);
+        self.policy.on_access(self.map.id_at(index));
+        Some(count)
+    }
+
+    /// Removes an alias key.
+    ///
+    /// Returns the reference count if the alias is successfully removed.
+    /// If the last alias is removed, the value is also removed.
+    ///
+    /// # Arguments
+    ///
+    /// * `alias` - The alias key to remove.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyLruMap;
+    ///
+    /// let mut cache = MultiKeyLruMap::new(2);
+    /// cache.insert("a", 1);
+    /// cache.insert_alias(&"a", "alias");
+    /// assert_eq!(cache.remove_alias(&"alias"), Some(1));
+    /// assert_eq!(cache.remove_alias(&"a"), Some(0));
+    /// assert!(cache.is_empty());
+    /// ```
+    pub fn remove_alias(&mut self, alias: &K) -> Option<usize> {
+        let id = self.map.group_id(alias)?;
+        let remaining = self.map.remove_alias(alias)?;
+        self.forget_if_vacant(Some(id));
+        Some(remaining)
+    }
+
+    /// Removes a value by its key and all its aliases.
+    ///
+    /// Returns the value if it was present.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key of the value to remove.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyLruMap;
+    ///
+    /// let mut cache = MultiKeyLruMap::new(2);
+    /// cache.insert("a", 1);
+    /// cache.insert_alias(&"a", "alias");
+    /// assert_eq!(cache.remove(&"alias"), Some(1));
+    /// assert!(!cache.contains_key(&"a"));
+    /// assert!(cache.is_empty());
+    /// ```
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        self.policy.on_remove(self.map.group_id(key)?);
+        self.map.remove(key)
+    }
+
+    /// Evicts the group picked by the policy, the least recently used one by default, and
+    /// returns its keys and value.
+    ///
+    /// Returns `None` if the map is empty.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the policy picks a group that is not in the map.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyLruMap;
+    ///
+    /// let mut cache = MultiKeyLruMap::new(2);
+    /// cache.insert("a", 1);
+    /// cache.insert("b", 2);
+    /// assert_eq!(cache.pop_lru(), Some((vec!["a"], 1)));
+    /// assert_eq!(cache.pop_lru(), Some((vec!["b"], 2)));
+    /// assert_eq!(cache.pop_lru(), None);
+    /// ```
+    pub fn pop_lru(&mut self) -> Option<(Vec<K>, V)> {
+        let victim = self.policy.pick_victim()?;
+        let index = self
+            .map
+            .resolve_id(victim)
+            .expect("the eviction policy picks a group of the map");
+        self.policy.on_remove(victim);
+        let keys = self.map.unlink_all(index);
+        Some((keys, self.map.release(index)))
+    }
+
+    /// Returns `true` if the map contains a value for the specified key, without recording an
+    /// access to its group.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key to check.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyLruMap;
+    ///
+    /// let mut cache = MultiKeyLruMap::new(2);
+    /// cache.insert("a", 1);
+    /// assert!(cache.contains_key(&"a"));
+    /// assert!(!cache.contains_key(&"b"));
+    /// ```
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.map.contains_key(key)
+    }
+
+    /// Returns the underlying map.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyLruMap;
+    ///
+    /// let mut cache = MultiKeyLruMap::new(2);
+    /// cache.insert("a", 1);
+    /// cache.insert_alias(&"a", "alias");
+    /// assert!(cache.as_map().are_aliases(&"a", &"alias"));
+    /// ```
+    pub fn as_map(&self) -> &MultiKeyMap<K, V> {
+        &self.map
+    }
+
+    /// Returns the maximum number of groups.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Returns the number of groups in the map.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyLruMap;
+    ///
+    /// let mut cache = MultiKeyLruMap::new(2);
+    /// cache.insert("a", 1);
+    /// cache.insert_alias(&"a", "alias");
+    /// assert_eq!(cache.len(), 1);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    /// Returns the number of keys in the map, counting every alias.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyLruMap;
+    ///
+    /// let mut cache = MultiKeyLruMap::new(2);
+    /// cache.insert("a", 1);
+    /// cache.insert_alias(&"a", "alias");
+    /// assert_eq!(cache.key_count(), 2);
+    /// ```
+    pub fn key_count(&self) -> usize {
+        self.map.key_count()
+    }
+
+    /// Returns `true` if the map contains no values.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyLruMap;
+    ///
+    /// let cache: MultiKeyLruMap<&str, &str> = MultiKeyLruMap::new(2);
+    /// assert!(cache.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    /// Makes the policy forget the group `previous` if it is no longer in the map.
+    fn forget_if_vacant(&mut self, previous: Option<GroupId>) {
+        if let Some(previous) = previous {
+            if self.map.resolve_id(previous).is_none() {
+                self.policy.on_remove(previous);
+            }
+        }
+    }
+}
+
+impl<K: Debug, V: Debug, P> Debug for MultiKeyLruMap<K, V, P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut debug_struct = f.debug_struct("MultiKeyLruMap");
+        for slot in &self.map.slots {
+            if let Some(value) = &slot.value {
+                debug_struct.field(&format!("{:?}", slot.keys), value);
+            }
+        }
+        debug_struct.finish()
+    }
+}
@@ -0,0 +1,148 @@
+use crate::MultiKeyMap;
+use std::hash::Hash;
+
+impl<K: Eq + Hash, T> MultiKeyMap<K, Vec<T>> {
+    /// Pushes `value` onto the values of the group of `key`, inserting a group for `key` if it
+    /// does not exist.
+    ///
+    /// Finding the group takes a single lookup, whether it exists or not. A disabled key is
+    /// moved to a new group, as [`insert`](Self::insert) would move it.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - Any key of the group.
+    /// * `value` - The value to push.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyMap;
+    ///
+    /// let mut logs = MultiKeyMap::new();
+    /// logs.push_value(&"host-7", "booted");
+    /// logs.insert_alias(&"host-7", "10.0.0.7");
+    /// logs.insert_alias(&"host-7", "web-frontend");
+    ///
+    /// logs.push_value(&"10.0.0.7", "connection refused");
+    /// logs.push_value(&"web-frontend", "deployed v2");
+    /// assert_eq!(
+    ///     logs.values_of(&"host-7"),
+    ///     Some(&["booted", "connection refused", "deployed v2"][..])
+    /// );
+    /// assert_eq!(logs.len(), 1);
+    /// ```
+    pub fn push_value(&mut self, key: &K, value: T)
+    where
+        K: Clone,
+    {
+        self.extend_or_insert(key, Some(value));
+    }
+
+    /// Appends `values` to the values of the group of `key`, inserting a group for `key` if it
+    /// does not exist.
+    ///
+    /// Finding the group takes a single lookup, whether it exists or not. Nothing is inserted
+    /// if `key` does not exist and `values` is empty.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - Any key of the group.
+    /// * `values` - The values to append, in order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyMap;
+    ///
+    /// let mut events = MultiKeyMap::new();
+    /// events.append_values(&"order-1", [1, 2]);
+    /// events.insert_alias(&"order-1", "invoice-9");
+    /// events.append_values(&"invoice-9", vec![3, 4]);
+    /// assert_eq!(events.values_of(&"order-1"), Some(&[1, 2, 3, 4][..]));
+    ///
+    /// events.append_values(&"order-2", []);
+    /// assert!(!events.contains_key(&"order-2"));
+    /// ```
+    pub fn append_values(&mut self, key: &K, values: impl IntoIterator<Item = T>)
+    where
+        K: Clone,
+    {
+        self.extend_or_insert(key, values);
+    }
+
+    /// Returns the values of the group of `key`.
+    ///
+    /// Returns `None` if the key does not exist.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - Any key of the group.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyMap;
+    ///
+    /// let mut map = MultiKeyMap::new();
+    /// map.insert_with_keys(["a", "b"], vec![1, 2]).unwrap();
+    /// assert_eq!(map.values_of(&"b"), Some(&[1, 2][..]));
+    /// assert_eq!(map.values_of(&"c"), None);
+    /// ```
+    pub fn values_of(&self, key: &K) -> Option<&[T]> {
+        self.get(key).map(Vec::as_slice)
+    }
+
+    /// Removes the last value of the group of `key` and returns it.
+    ///
+    /// The group stays in the map once its last value is popped; [`remove`](Self::remove)
+    /// removes it. Returns `None` if the key does not exist or its group has no values.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - Any key of the group.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyMap;
+    ///
+    /// let mut map = MultiKeyMap::new();
+    /// map.append_values(&"a", ["x", "y"]);
+    /// map.insert_alias(&"a", "b");
+    /// assert_eq!(map.pop_value(&"b"), Some("y"));
+    /// assert_eq!(map.pop_value(&"a"), Some("x"));
+    /// assert_eq!(map.pop_value(&"a"), None);
+    /// assert_eq!(map.values_of(&"b"), Some(&[][..]));
+    /// assert_eq!(map.pop_value(&"c"), None);
+    /// ```
+    pub fn pop_value(&mut self, key: &K) -> Option<T> {
+        self.get_mut(key)?.pop()
+    }
+
+    /// Extends the values of the group of `key` with `values`, or inserts them as a new group
+    /// bound to `key` if it is not enabled.
+    fn extend_or_insert(&mut self, key: &K, values: impl IntoIterator<Item = T>)
+    where
+        K: Clone,
+    {
+        let found = self.find(key).copied();
+        if let Some(entry) = found.filter(|entry| !entry.disabled) {
+            self.value_mut(entry.slot).extend(values);
+            return;
+        }
+        let values: Vec<T> = values.into_iter().collect();
+        if values.is_empty() {
+            return;
+        }
+        if found.is_some() {
+            self.insert(key.clone(), values);
+            return;
+        }
+        let index = self.alloc(values);
+        self.link(key.clone(), index);
+        self.counters.insert();
+        self.hooks.group_inserted(&self.slots[index]);
+        self.trace_key("insert", &self.slots[index].keys[0], 1);
+        self.check_mutation();
+    }
+}
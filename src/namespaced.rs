@@ -0,0 +1,499 @@
+use crate::MultiKeyMap;
+use std::collections::HashMap;
+use std::fmt::{self, Debug};
+use std::hash::Hash;
+
+/// A `NamespacedMultiKeyMap` is a [`MultiKeyMap`] whose aliases can belong to a namespace, such
+/// as the kind of identifier they are.
+///
+/// Every key is in at most one namespace. The keys of each namespace are indexed per group, so
+/// [`aliases_in`](Self::aliases_in) and [`remove_namespace`](Self::remove_namespace) only visit
+/// the keys of that namespace, not all aliases of the group.
+///
+/// # Examples
+///
+/// ```
+/// use multi_key_map::NamespacedMultiKeyMap;
+///
+/// #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+/// enum Kind {
+///     Email,
+///     Username,
+/// }
+///
+/// let mut users = NamespacedMultiKeyMap::new();
+/// users.insert("user-1", "Ada");
+/// users.insert_alias_in(&"user-1", Kind::Email, "ada@example.com");
+/// users.insert_alias_in(&"user-1", Kind::Email, "ada@work.example");
+/// users.insert_alias_in(&"user-1", Kind::Username, "ada");
+///
+/// let mut emails = users.aliases_in(&"ada", &Kind::Email);
+/// emails.sort();
+/// assert_eq!(emails, vec![&"ada@example.com", &"ada@work.example"]);
+/// assert_eq!(users.namespace_of(&"ada"), Some(&Kind::Username));
+///
+/// assert_eq!(users.remove_namespace(&"user-1", &Kind::Email), 2);
+/// assert!(!users.contains_key(&"ada@example.com"));
+/// assert_eq!(users.get(&"ada"), Some(&"Ada"));
+/// ```
+pub struct NamespacedMultiKeyMap<K, N, V> {
+    map: MultiKeyMap<K, V>,
+    /// The namespace of every key that has one.
+    namespaces: HashMap<K, N>,
+    /// The keys of every namespace within each group, indexed like the slots of `map`.
+    members: Vec<HashMap<N, Vec<K>>>,
+}
+
+impl<K: Eq + Hash + Clone, N: Eq + Hash + Clone, V> NamespacedMultiKeyMap<K, N, V> {
+    /// Creates an empty `NamespacedMultiKeyMap`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::NamespacedMultiKeyMap;
+    ///
+    /// let map: NamespacedMultiKeyMap<&str, &str, u32> = NamespacedMultiKeyMap::new();
+    /// ```
+    pub fn new() -> Self {
+        NamespacedMultiKeyMap {
+            map: MultiKeyMap::new(),
+            namespaces: HashMap::new(),
+            members: Vec::new(),
+        }
+    }
+
+    /// Retrieves a reference to a value by its key.
+    ///
+    /// Returns `None` if the key does not exist.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key to lookup.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::NamespacedMultiKeyMap;
+    ///
+    /// let mut map = NamespacedMultiKeyMap::new();
+    /// map.insert("key1", "value1");
+    /// map.insert_alias_in(&"key1", "kind", "alias1");
+    /// assert_eq!(map.get(&"alias1"), Some(&"value1"));
+    /// assert_eq!(map.get(&"key2"), None);
+    /// ```
+    pub fn get(&self, key: &K) -> Option<&V> {
+        self.map.get(key)
+    }
+
+    /// Retrieves a mutable reference to a value by its key.
+    ///
+    /// Returns `None` if the key does not exist.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key to lookup.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::NamespacedMultiKeyMap;
+    ///
+    /// let mut map = NamespacedMultiKeyMap::<_, &str, _>::new();
+    /// map.insert("key1", 1);
+    /// *map.get_mut(&"key1").unwrap() += 1;
+    /// assert_eq!(map.get(&"key1"), Some(&2));
+    /// ```
+    pub fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        self.map.get_mut(key)
+    }
+
+    /// Inserts a value with the given key, which is in no namespace.
+    ///
+    /// If the key already exists, it is moved to the new value and leaves its namespace, and its
+    /// old group is removed if it has no other keys.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key to insert.
+    /// * `value` - The value to insert.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::NamespacedMultiKeyMap;
+    ///
+    /// let mut map = NamespacedMultiKeyMap::new();
+    /// map.insert("key1", "value1");
+    /// map.insert_alias_in(&"key1", "kind", "alias1");
+    /// map.insert("alias1", "value2");
+    /// assert_eq!(map.namespace_of(&"alias1"), None);
+    /// assert!(map.aliases_in(&"key1", &"kind").is_empty());
+    /// ```
+    pub fn insert(&mut self, key: K, value: V) {
+        let previous = self.leave_namespace(&key);
+        let index = self.map.alloc(value);
+        self.map.bind(key, index);
+        self.forget_if_vacant(previous);
+        self.members_mut(index).clear();
+    }
+
+    /// Adds a new alias key in no namespace for the element at `key`.
+    ///
+    /// Returns the reference count if the alias is successfully added.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The original key.
+    /// * `alias` - The alias key to add.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::NamespacedMultiKeyMap;
+    ///
+    /// let mut map = NamespacedMultiKeyMap::<_, &str, _>::new();
+    /// map.insert("key1", "value1");
+    /// assert_eq!(map.insert_alias(&"key1", "alias1"), Some(2));
+    /// assert_eq!(map.namespace_of(&"alias1"), None);
+    /// ```
+    pub fn insert_alias(&mut self, key: &K, alias: K) -> Option<usize> {
+        self.bind_alias(key, None, alias)
+    }
+
+    /// Adds a new alias key in `namespace` for the element at `key`.
+    ///
+    /// Returns the reference count if the alias is successfully added. An alias that already
+    /// exists moves to this group and namespace.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The original key.
+    /// * `namespace` - The namespace of the alias.
+    /// * `alias` - The alias key to add.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::NamespacedMultiKeyMap;
+    ///
+    /// let mut map = NamespacedMultiKeyMap::new();
+    /// map.insert("key1", "value1");
+    /// assert_eq!(map.insert_alias_in(&"key1", "email", "a@example.com"), Some(2));
+    /// assert_eq!(map.insert_alias_in(&"key1", "legacy", "a@example.com"), Some(2));
+    /// assert_eq!(map.namespace_of(&"a@example.com"), Some(&"legacy"));
+    /// assert_eq!(map.insert_alias_in(&"key2", "email", "b@example.com"), None);
+    /// ```
+    pub fn insert_alias_in(&mut self, key: &K, namespace: N, alias: K) -> Option<usize> {
+        self.bind_alias(key, Some(namespace), alias)
+    }
+
+    /// Returns the keys in `namespace` of the group `key` belongs to, in no particular order.
+    ///
+    /// Returns an empty vector if the key does not exist or the group has no key in the
+    /// namespace.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - A key of the group.
+    /// * `namespace` - The namespace to list.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::NamespacedMultiKeyMap;
+    ///
+    /// let mut map = NamespacedMultiKeyMap::new();
+    /// map.insert("key1", "value1");
+    /// map.insert_alias_in(&"key1", "email", "a@example.com");
+    /// map.insert_alias_in(&"key1", "username", "a");
+    /// assert_eq!(map.aliases_in(&"a", &"email"), vec![&"a@example.com"]);
+    /// assert!(map.aliases_in(&"key1", &"phone").is_empty());
+    /// assert!(map.aliases_in(&"key2", &"email").is_empty());
+    /// ```
+    pub fn aliases_in(&self, key: &K, namespace: &N) -> Vec<&K> {
+        self.map
+            .index_of(key)
+            .and_then(|index| self.members.get(index)?.get(namespace))
+            .map_or_else(Vec::new, |keys| keys.iter().collect())
+    }
+
+    /// Returns the namespace of `key`.
+    ///
+    /// Returns `None` if the key does not exist or is in no namespace.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key to check.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::NamespacedMultiKeyMap;
+    ///
+    /// let mut map = NamespacedMultiKeyMap::new();
+    /// map.insert("key1", "value1");
+    /// map.insert_alias_in(&"key1", "email", "a@example.com");
+    /// assert_eq!(map.namespace_of(&"a@example.com"), Some(&"email"));
+    /// assert_eq!(map.namespace_of(&"key1"), None);
+    /// ```
+    pub fn namespace_of(&self, key: &K) -> Option<&N> {
+        self.namespaces.get(key)
+    }
+
+    /// Removes an alias key.
+    ///
+    /// Returns the reference count if the alias is successfully removed.
+    /// If the last alias is removed, the value is also removed.
+    ///
+    /// # Arguments
+    ///
+    /// * `alias` - The alias key to remove.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::NamespacedMultiKeyMap;
+    ///
+    /// let mut map = NamespacedMultiKeyMap::new();
+    /// map.insert("key1", "value1");
+    /// map.insert_alias_in(&"key1", "email", "a@example.com");
+    /// assert_eq!(map.remove_alias(&"a@example.com"), Some(1));
+    /// assert!(map.aliases_in(&"key1", &"email").is_empty());
+    /// assert_eq!(map.remove_alias(&"key1"), Some(0));
+    /// assert!(map.is_empty());
+    /// ```
+    pub fn remove_alias(&mut self, alias: &K) -> Option<usize> {
+        let index = self.leave_namespace(alias)?;
+        let remaining = self.map.remove_alias(alias)?;
+        self.forget_if_vacant(Some(index));
+        Some(remaining)
+    }
+
+    /// Removes every key in `namespace` from the group `key` belongs to and returns how many
+    /// were removed.
+    ///
+    /// If they were the last keys of the group, the group is removed along with its value.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - A key of the group.
+    /// * `namespace` - The namespace to strip.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::NamespacedMultiKeyMap;
+    ///
+    /// let mut map = NamespacedMultiKeyMap::new();
+    /// map.insert("key1", "value1");
+    /// map.insert_alias_in(&"key1", "email", "a@example.com");
+    /// map.insert_alias_in(&"key1", "email", "b@example.com");
+    /// map.insert_alias_in(&"key1", "username", "a");
+    ///
+    /// // Only namespaced keys are left once `key1` is gone
+    /// map.remove_alias(&"key1");
+    /// assert_eq!(map.remove_namespace(&"a", &"username"), 1);
+    /// assert_eq!(map.get(&"a@example.com"), Some(&"value1"));
+    /// assert_eq!(map.remove_namespace(&"a@example.com", &"email"), 2);
+    /// assert!(map.is_empty());
+    /// assert_eq!(map.remove_namespace(&"a@example.com", &"email"), 0);
+    /// ```
+    pub fn remove_namespace(&mut self, key: &K, namespace: &N) -> usize {
+        let Some(index) = self.map.index_of(key) else {
+            return 0;
+        };
+        let Some(keys) = self.members_mut(index).remove(namespace) else {
+            return 0;
+        };
+        for key in &keys {
+            self.namespaces.remove(key);
+            self.map.remove_alias(key);
+        }
+        self.forget_if_vacant(Some(index));
+        keys.len()
+    }
+
+    /// Removes a value by its key and all its aliases.
+    ///
+    /// Returns the value if it was present.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key of the value to remove.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::NamespacedMultiKeyMap;
+    ///
+    /// let mut map = NamespacedMultiKeyMap::new();
+    /// map.insert("key1", "value1");
+    /// map.insert_alias_in(&"key1", "email", "a@example.com");
+    /// assert_eq!(map.remove(&"a@example.com"), Some("value1"));
+    /// assert_eq!(map.namespace_of(&"a@example.com"), None);
+    /// assert!(map.is_empty());
+    /// ```
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        let index = self.map.index_of(key)?;
+        let members = std::mem::take(self.members_mut(index));
+        for key in members.into_values().flatten() {
+            self.namespaces.remove(&key);
+        }
+        self.map.remove(key)
+    }
+
+    /// Returns `true` if the map contains a value for the specified key.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key to check.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::NamespacedMultiKeyMap;
+    ///
+    /// let mut map = NamespacedMultiKeyMap::<_, &str, _>::new();
+    /// map.insert("key1", "value1");
+    /// assert!(map.contains_key(&"key1"));
+    /// assert!(!map.contains_key(&"key2"));
+    /// ```
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.map.contains_key(key)
+    }
+
+    /// Returns the underlying map.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::NamespacedMultiKeyMap;
+    ///
+    /// let mut map = NamespacedMultiKeyMap::new();
+    /// map.insert("key1", "value1");
+    /// map.insert_alias_in(&"key1", "email", "a@example.com");
+    /// assert!(map.as_map().are_aliases(&"key1", &"a@example.com"));
+    /// ```
+    pub fn as_map(&self) -> &MultiKeyMap<K, V> {
+        &self.map
+    }
+
+    /// Returns the number of groups in the map.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::NamespacedMultiKeyMap;
+    ///
+    /// let mut map = NamespacedMultiKeyMap::new();
+    /// map.insert("key1", "value1");
+    /// map.insert_alias_in(&"key1", "email", "a@example.com");
+    /// assert_eq!(map.len(), 1);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    /// Returns the number of keys in the map, counting every alias.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::NamespacedMultiKeyMap;
+    ///
+    /// let mut map = NamespacedMultiKeyMap::new();
+    /// map.insert("key1", "value1");
+    /// map.insert_alias_in(&"key1", "email", "a@example.com");
+    /// assert_eq!(map.key_count(), 2);
+    /// ```
+    pub fn key_count(&self) -> usize {
+        self.map.key_count()
+    }
+
+    /// Returns `true` if the map contains no values.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::NamespacedMultiKeyMap;
+    ///
+    /// let map: NamespacedMultiKeyMap<&str, &str, u32> = NamespacedMultiKeyMap::new();
+    /// assert!(map.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    /// Binds `alias` to the group of `key` in `namespace`.
+    fn bind_alias(&mut self, key: &K, namespace: Option<N>, alias: K) -> Option<usize> {
+        if key == &alias {
+            // Do not allow aliasing the same key
+            return None;
+        }
+        let index = self.map.index_of(key)?;
+        let previous = self.leave_namespace(&alias);
+        if previous != Some(index) {
+            self.map.bind(alias.clone(), index);
+            self.forget_if_vacant(previous);
+        }
+        if let Some(namespace) = namespace {
+            self.namespaces.insert(alias.clone(), namespace.clone());
+            self.members_mut(index)
+                .entry(namespace)
+                .or_default()
+                .push(alias);
+        }
+        Some(self.map.count_references(index))
+    }
+
+    /// Takes `key` out of its namespace, if it has one, and returns the slot of its group.
+    fn leave_namespace(&mut self, key: &K) -> Option<usize> {
+        let index = self.map.index_of(key)?;
+        if let Some(namespace) = self.namespaces.remove(key) {
+            let members = self.members_mut(index);
+            let keys = members
+                .get_mut(&namespace)
+                .expect("namespaced key is a member");
+            keys.swap_remove(
+                keys.iter()
+                    .position(|k| k == key)
+                    .expect("member is listed"),
+            );
+            if keys.is_empty() {
+                members.remove(&namespace);
+            }
+        }
+        Some(index)
+    }
+
+    /// Drops the namespaces of the slot `previous` if it no longer holds a value.
+    fn forget_if_vacant(&mut self, previous: Option<usize>) {
+        if let Some(previous) = previous {
+            if self.map.slots[previous].value.is_none() {
+                self.members_mut(previous).clear();
+            }
+        }
+    }
+
+    fn members_mut(&mut self, index: usize) -> &mut HashMap<N, Vec<K>> {
+        if self.members.len() <= index {
+            self.members.resize_with(index + 1, HashMap::new);
+        }
+        &mut self.members[index]
+    }
+}
+
+impl<K: Eq + Hash + Clone, N: Eq + Hash + Clone, V> Default for NamespacedMultiKeyMap<K, N, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Debug, N: Debug, V: Debug> Debug for NamespacedMultiKeyMap<K, N, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("NamespacedMultiKeyMap")
+            .field("map", &self.map)
+            .field("namespaces", &self.namespaces)
+            .finish()
+    }
+}
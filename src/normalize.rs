@@ -0,0 +1,116 @@
+use crate::MultiKeyMap;
+use std::hash::Hash;
+use std::sync::Arc;
+
+/// A function mapping every key to its canonical form, shared by clones of a map.
+pub(crate) type Normalizer<K> = Arc<dyn Fn(&K) -> K + Send + Sync>;
+
+impl<K: Eq + Hash, V> MultiKeyMap<K, V> {
+    /// Creates an empty `MultiKeyMap` normalizing every key with `normalize`.
+    ///
+    /// See [`set_key_normalizer`](Self::set_key_normalizer).
+    ///
+    /// # Arguments
+    ///
+    /// * `normalize` - The function returning the canonical form of a key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyMap;
+    ///
+    /// let mut map = MultiKeyMap::with_key_normalizer(|key: &String| key.trim().to_string());
+    /// map.insert(" a ".to_string(), 1);
+    /// assert_eq!(map.get(&"a".to_string()), Some(&1));
+    /// assert_eq!(map.get(&"a\n".to_string()), Some(&1));
+    /// ```
+    pub fn with_key_normalizer(normalize: impl Fn(&K) -> K + Send + Sync + 'static) -> Self {
+        let mut map = Self::new();
+        map.set_key_normalizer(normalize);
+        map
+    }
+
+    /// Sets the function normalizing keys, so that keys with the same canonical form are the same
+    /// key.
+    ///
+    /// Every key is normalized before it is stored, and every key passed to a lookup is
+    /// normalized before it is searched for, whatever the method. The map stores the normalized
+    /// keys, so they are what [`aliases`](Self::aliases) and [`keys`](Self::keys) return, and a key
+    /// normalizing to an existing key behaves exactly as that key would: inserting it moves the
+    /// existing key, and aliasing a key to itself in another spelling is refused. Clones of the
+    /// map share the normalizer, while maps with converted keys, as returned by
+    /// [`map_keys`](Self::map_keys), have none.
+    ///
+    /// `normalize` must be idempotent: normalizing a normalized key must return it unchanged.
+    /// Lookups then cost a call to it on top of the hashing.
+    ///
+    /// The keys already in the map are normalized again, in storage order, as if every key was
+    /// bound to its group once more: a key normalizing to a key of a later group moves to that
+    /// group, and groups left without keys are dropped. The hooks are not called for these moves.
+    ///
+    /// # Arguments
+    ///
+    /// * `normalize` - The function returning the canonical form of a key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyMap;
+    ///
+    /// let mut map = MultiKeyMap::new();
+    /// map.insert(" Berlin", 1);
+    /// map.insert("Paris ", 2);
+    /// map.insert_alias(&"Paris ", "paris");
+    /// map.set_key_normalizer(|key: &&str| key.trim());
+    /// assert_eq!(map.get(&"Berlin"), Some(&1));
+    /// assert_eq!(map.aliases(&"  Paris  "), Some(vec!["Paris", "paris"]));
+    ///
+    /// // Aliases added in either spelling behave the same
+    /// assert_eq!(map.insert_alias(&" Berlin ", "BER"), Some(2));
+    /// assert_eq!(map.insert_alias(&"Berlin", " BER "), Some(2));
+    /// assert_eq!(map.insert_alias(&"Berlin", " Berlin"), None);
+    /// assert_eq!(map.aliases(&"BER"), Some(vec!["Berlin", "BER"]));
+    ///
+    /// // Inserting a key in another spelling moves the existing key
+    /// map.insert(" paris", 3);
+    /// assert_eq!(map.get(&"Paris"), Some(&2));
+    /// assert_eq!(map.get(&"paris"), Some(&3));
+    /// assert_eq!(map.remove(&"paris "), Some(3));
+    /// assert_eq!(map.len(), 2);
+    /// ```
+    pub fn set_key_normalizer(&mut self, normalize: impl Fn(&K) -> K + Send + Sync + 'static) {
+        self.normalizer = Some(Arc::new(normalize));
+        let occupied: Vec<usize> = self.occupied().map(|(index, _)| index).collect();
+        // Unbind every key first, so no key is found under its former spelling
+        let keys: Vec<Vec<K>> = occupied
+            .iter()
+            .map(|&index| self.unlink_all(index))
+            .collect();
+        // Respelled keys may collide, so the change log sees every group recreated rather than
+        // the moves of the colliding keys
+        let mut log = self.hooks.changes.take();
+        if let Some(log) = &mut log {
+            for keys in &keys {
+                log.group_removed(keys);
+            }
+        }
+        for (&index, keys) in occupied.iter().zip(keys) {
+            for key in keys {
+                self.bind(key, index);
+            }
+        }
+        if let Some(log) = &mut log {
+            for &index in &occupied {
+                if self.slots[index].value.is_some() {
+                    log.group_created(&self.slots[index]);
+                }
+            }
+        }
+        self.hooks.changes = log;
+        let links: Vec<(K, K)> = self.soft_links.drain().collect();
+        for (link, target) in links {
+            self.set_soft_link(link, target);
+        }
+        self.check_mutation();
+    }
+}
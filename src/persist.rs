@@ -0,0 +1,208 @@
+use crate::MultiKeyMap;
+use borsh::{BorshDeserialize, BorshSerialize};
+use std::error::Error;
+use std::fmt::{self, Display};
+use std::hash::Hash;
+use std::io::{self, Read, Write};
+
+/// The bytes every file written by [`MultiKeyMap::write_to`] starts with.
+const MAGIC: [u8; 4] = *b"MKMP";
+
+/// The version of the format written by [`MultiKeyMap::write_to`].
+const VERSION: u16 = 1;
+
+/// The length of the header: magic bytes, version, group and key counts, and payload length.
+const HEADER_LEN: usize = 4 + 2 + 8 + 8 + 8;
+
+/// The length of the trailing checksum.
+const CHECKSUM_LEN: usize = 8;
+
+impl<K: Eq + Hash, V> MultiKeyMap<K, V> {
+    /// Writes the map in a self-describing binary format that [`read_from`](Self::read_from)
+    /// loads back.
+    ///
+    /// The map is encoded with its [`BorshSerialize`] implementation and framed by a header
+    /// holding magic bytes, the format version, the number of groups and keys, and the length
+    /// of the encoding, followed by a checksum of everything before it.
+    ///
+    /// This method is only available with the `borsh` feature.
+    ///
+    /// # Arguments
+    ///
+    /// * `writer` - The writer to write to.
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of `writer` and of encoding the keys and values, and an
+    /// [`io::ErrorKind::InvalidInput`] error if the map has soft aliases or disabled keys, which
+    /// the format cannot hold.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyMap;
+    ///
+    /// let mut map = MultiKeyMap::new();
+    /// map.insert("one".to_string(), 1u32);
+    /// map.insert_alias(&"one".to_string(), "uno".to_string());
+    ///
+    /// let mut bytes = Vec::new();
+    /// map.write_to(&mut bytes).unwrap();
+    /// assert!(bytes.starts_with(b"MKMP"));
+    /// ```
+    pub fn write_to<W: Write>(&self, mut writer: W) -> io::Result<()>
+    where
+        K: Ord + BorshSerialize,
+        V: BorshSerialize,
+    {
+        let payload = borsh::to_vec(self)?;
+        let mut bytes = Vec::with_capacity(HEADER_LEN + payload.len() + CHECKSUM_LEN);
+        bytes.extend_from_slice(&MAGIC);
+        bytes.extend_from_slice(&VERSION.to_le_bytes());
+        bytes.extend_from_slice(&(self.len() as u64).to_le_bytes());
+        bytes.extend_from_slice(&(self.key_count() as u64).to_le_bytes());
+        bytes.extend_from_slice(&(payload.len() as u64).to_le_bytes());
+        bytes.extend_from_slice(&payload);
+        bytes.extend_from_slice(&checksum(&bytes).to_le_bytes());
+        writer.write_all(&bytes)
+    }
+
+    /// Reads a map written by [`write_to`](Self::write_to).
+    ///
+    /// The input is checked in order: its magic bytes, its format version, its length, its
+    /// checksum, and finally its contents against the counts in the header. Input written by a
+    /// later format version is rejected rather than misread.
+    ///
+    /// This method is only available with the `borsh` feature.
+    ///
+    /// # Arguments
+    ///
+    /// * `reader` - The reader to read from, which is read to its end.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`LoadError`] describing the first check the input fails.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::{LoadError, MultiKeyMap};
+    ///
+    /// let mut map = MultiKeyMap::new();
+    /// map.insert("one".to_string(), 1u32);
+    /// map.insert_alias(&"one".to_string(), "uno".to_string());
+    /// map.insert("two".to_string(), 2);
+    ///
+    /// let mut bytes = Vec::new();
+    /// map.write_to(&mut bytes).unwrap();
+    /// let loaded = MultiKeyMap::<String, u32>::read_from(&bytes[..]).unwrap();
+    /// assert_eq!(loaded, map);
+    ///
+    /// let truncated = MultiKeyMap::<String, u32>::read_from(&bytes[..bytes.len() - 1]);
+    /// assert!(matches!(truncated, Err(LoadError::Truncated)));
+    ///
+    /// let mut corrupted = bytes.clone();
+    /// corrupted[35] ^= 1;
+    /// let corrupted = MultiKeyMap::<String, u32>::read_from(&corrupted[..]);
+    /// assert!(matches!(corrupted, Err(LoadError::ChecksumMismatch)));
+    ///
+    /// let mut future = bytes.clone();
+    /// future[4] = 9;
+    /// let future = MultiKeyMap::<String, u32>::read_from(&future[..]);
+    /// assert!(matches!(future, Err(LoadError::UnsupportedVersion(9))));
+    ///
+    /// let other = MultiKeyMap::<String, u32>::read_from(&b"PNG\0 not a map"[..]);
+    /// assert!(matches!(other, Err(LoadError::NotAMap)));
+    /// ```
+    pub fn read_from<R: Read>(mut reader: R) -> Result<Self, LoadError>
+    where
+        K: BorshDeserialize,
+        V: BorshDeserialize,
+    {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).map_err(LoadError::Io)?;
+        if bytes.len() < MAGIC.len() + 2 || bytes[..MAGIC.len()] != MAGIC {
+            return Err(LoadError::NotAMap);
+        }
+        match u16::from_le_bytes([bytes[4], bytes[5]]) {
+            VERSION => {}
+            version => return Err(LoadError::UnsupportedVersion(version)),
+        }
+        if bytes.len() < HEADER_LEN + CHECKSUM_LEN {
+            return Err(LoadError::Truncated);
+        }
+        let field = |at: usize| u64::from_le_bytes(bytes[at..at + 8].try_into().unwrap());
+        let (groups, keys, payload_len) = (field(6), field(14), field(22));
+        let end = bytes.len() - CHECKSUM_LEN;
+        match (HEADER_LEN as u64).checked_add(payload_len) {
+            Some(expected) if expected == end as u64 => {}
+            Some(expected) if expected > end as u64 => return Err(LoadError::Truncated),
+            _ => return Err(LoadError::Corrupt(io::ErrorKind::InvalidData.into())),
+        }
+        if checksum(&bytes[..end]) != field(end) {
+            return Err(LoadError::ChecksumMismatch);
+        }
+        let map: Self = borsh::from_slice(&bytes[HEADER_LEN..end]).map_err(LoadError::Corrupt)?;
+        if map.len() as u64 != groups || map.key_count() as u64 != keys {
+            return Err(LoadError::Corrupt(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "the contents do not match the counts in the header",
+            )));
+        }
+        Ok(map)
+    }
+}
+
+/// The FNV-1a hash of `bytes`.
+fn checksum(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+/// The error returned by [`MultiKeyMap::read_from`].
+///
+/// This type is only available with the `borsh` feature.
+#[derive(Debug)]
+pub enum LoadError {
+    /// Reading the input failed.
+    Io(io::Error),
+    /// The input does not start with the magic bytes of a saved map.
+    NotAMap,
+    /// The input was written by a format version this version of the crate cannot read.
+    UnsupportedVersion(u16),
+    /// The input ends before the length given in its header.
+    Truncated,
+    /// The input does not match its checksum.
+    ChecksumMismatch,
+    /// The input passed its checksum but does not hold a valid map, as when it was written
+    /// with different key or value types.
+    Corrupt(io::Error),
+}
+
+impl Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LoadError::Io(error) => write!(f, "failed to read the map: {error}"),
+            LoadError::NotAMap => f.write_str("the input is not a saved map"),
+            LoadError::UnsupportedVersion(version) => {
+                write!(
+                    f,
+                    "unsupported format version {version}, expected {VERSION}"
+                )
+            }
+            LoadError::Truncated => f.write_str("the input is truncated"),
+            LoadError::ChecksumMismatch => f.write_str("the input does not match its checksum"),
+            LoadError::Corrupt(error) => write!(f, "the input holds an invalid map: {error}"),
+        }
+    }
+}
+
+impl Error for LoadError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            LoadError::Io(error) | LoadError::Corrupt(error) => Some(error),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,463 @@
+use im::HashMap;
+use std::fmt::{self, Debug};
+use std::hash::Hash;
+
+/// A `PersistentMultiKeyMap` allows multiple keys to point to a single value, and is cloned in
+/// constant time.
+///
+/// Both the alias index and the groups are kept in hash array mapped tries whose nodes are
+/// shared between clones. A mutation copies only the nodes on the path it changes, and the
+/// group it touches, so the clones never affect each other. This suits keeping many versions
+/// of a map alive at once, for instance to roll back a transaction.
+///
+/// This type is only available with the `persistent` feature.
+///
+/// # Examples
+///
+/// ```
+/// use multi_key_map::PersistentMultiKeyMap;
+///
+/// let mut map = PersistentMultiKeyMap::new();
+/// map.insert("key1", 1);
+/// map.insert_alias(&"key1", "alias1");
+///
+/// let mut snapshot = map.clone();
+/// map.insert_alias(&"key1", "alias2");
+/// map.remove_alias(&"alias1");
+/// *map.get_mut(&"key1").unwrap() = 2;
+/// snapshot.insert("key2", 3);
+/// snapshot.insert_alias(&"key2", "alias2");
+///
+/// assert_eq!(map.get(&"alias2"), Some(&2));
+/// assert!(!map.contains_key(&"alias1"));
+/// assert!(!map.contains_key(&"key2"));
+/// assert_eq!(snapshot.get(&"key1"), Some(&1));
+/// assert!(snapshot.are_aliases(&"key1", &"alias1"));
+/// assert!(snapshot.are_aliases(&"key2", &"alias2"));
+/// assert_eq!((map.len(), map.key_count()), (1, 2));
+/// assert_eq!((snapshot.len(), snapshot.key_count()), (2, 4));
+/// ```
+pub struct PersistentMultiKeyMap<K, V> {
+    /// The group of every key.
+    key_map: HashMap<K, u64>,
+    groups: HashMap<u64, Group<K, V>>,
+    next_id: u64,
+}
+
+#[derive(Clone)]
+struct Group<K, V> {
+    keys: Vec<K>,
+    value: V,
+}
+
+impl<K: Eq + Hash + Clone, V: Clone> PersistentMultiKeyMap<K, V> {
+    /// Creates an empty `PersistentMultiKeyMap`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::PersistentMultiKeyMap;
+    ///
+    /// let map: PersistentMultiKeyMap<&str, &str> = PersistentMultiKeyMap::new();
+    /// ```
+    pub fn new() -> Self {
+        PersistentMultiKeyMap {
+            key_map: HashMap::new(),
+            groups: HashMap::new(),
+            next_id: 0,
+        }
+    }
+
+    /// Retrieves a reference to a value by its key.
+    ///
+    /// Returns `None` if the key does not exist.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key to lookup.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::PersistentMultiKeyMap;
+    ///
+    /// let mut map = PersistentMultiKeyMap::new();
+    /// map.insert("key1", "value1");
+    /// assert_eq!(map.get(&"key1"), Some(&"value1"));
+    /// assert_eq!(map.get(&"key2"), None);
+    /// ```
+    pub fn get(&self, key: &K) -> Option<&V> {
+        let id = self.key_map.get(key)?;
+        Some(&self.groups[id].value)
+    }
+
+    /// Retrieves a mutable reference to a value by its key.
+    ///
+    /// Returns `None` if the key does not exist. The group is copied first if a clone of the map
+    /// still shares it.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key to lookup.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::PersistentMultiKeyMap;
+    ///
+    /// let mut map = PersistentMultiKeyMap::new();
+    /// map.insert("key1", "value1");
+    /// map.insert_alias(&"key1", "alias1");
+    /// let snapshot = map.clone();
+    /// if let Some(value) = map.get_mut(&"alias1") {
+    ///     *value = "value2";
+    /// }
+    /// assert_eq!(map.get(&"key1"), Some(&"value2"));
+    /// assert_eq!(snapshot.get(&"key1"), Some(&"value1"));
+    /// ```
+    pub fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        let id = self.key_map.get(key)?;
+        Some(&mut self.groups.get_mut(id).expect("routed group exists").value)
+    }
+
+    /// Inserts a value with the given key.
+    ///
+    /// If the key already exists, it is moved to the new value, and its old group is removed if
+    /// it has no other keys.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key to insert.
+    /// * `value` - The value to insert.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::PersistentMultiKeyMap;
+    ///
+    /// let mut map = PersistentMultiKeyMap::new();
+    /// map.insert("key1", "value1");
+    /// map.insert("key1", "value2");
+    /// assert_eq!(map.get(&"key1"), Some(&"value2"));
+    /// assert_eq!(map.len(), 1);
+    /// ```
+    pub fn insert(&mut self, key: K, value: V) {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.groups.insert(
+            id,
+            Group {
+                keys: Vec::new(),
+                value,
+            },
+        );
+        self.bind(key, id);
+    }
+
+    /// Adds a new alias key for the element at `key`.
+    ///
+    /// Returns the reference count if the alias is successfully added.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The original key.
+    /// * `alias` - The alias key to add.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::PersistentMultiKeyMap;
+    ///
+    /// let mut map = PersistentMultiKeyMap::new();
+    /// map.insert("key1", "value1");
+    /// assert_eq!(map.insert_alias(&"key1", "alias1"), Some(2));
+    /// assert_eq!(map.insert_alias(&"key1", "key1"), None);
+    /// assert_eq!(map.insert_alias(&"key2", "alias2"), None);
+    /// ```
+    pub fn insert_alias(&mut self, key: &K, alias: K) -> Option<usize> {
+        if key == &alias {
+            // Do not allow aliasing the same key
+            return None;
+        }
+        let id = *self.key_map.get(key)?;
+        self.bind(alias, id);
+        Some(self.groups[&id].keys.len())
+    }
+
+    /// Removes an alias key.
+    ///
+    /// Returns the reference count if the alias is successfully removed.
+    /// If the last alias is removed, the value is also removed.
+    ///
+    /// # Arguments
+    ///
+    /// * `alias` - The alias key to remove.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::PersistentMultiKeyMap;
+    ///
+    /// let mut map = PersistentMultiKeyMap::new();
+    /// map.insert("key1", "value1");
+    /// map.insert_alias(&"key1", "alias1");
+    /// assert_eq!(map.remove_alias(&"alias1"), Some(1));
+    /// assert_eq!(map.remove_alias(&"key1"), Some(0));
+    /// assert!(map.is_empty());
+    /// ```
+    pub fn remove_alias(&mut self, alias: &K) -> Option<usize> {
+        let id = self.key_map.remove(alias)?;
+        Some(self.unlink(id, alias))
+    }
+
+    /// Removes a value by its key and all its aliases.
+    ///
+    /// Returns the value if it was present.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key of the value to remove.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::PersistentMultiKeyMap;
+    ///
+    /// let mut map = PersistentMultiKeyMap::new();
+    /// map.insert("key1", "value1");
+    /// map.insert_alias(&"key1", "alias1");
+    /// let snapshot = map.clone();
+    /// assert_eq!(map.remove(&"alias1"), Some("value1"));
+    /// assert!(!map.contains_key(&"key1"));
+    /// assert!(map.is_empty());
+    /// assert_eq!(snapshot.get(&"alias1"), Some(&"value1"));
+    /// ```
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        let id = *self.key_map.get(key)?;
+        let group = self.groups.remove(&id).expect("routed group exists");
+        for key in &group.keys {
+            self.key_map.remove(key);
+        }
+        Some(group.value)
+    }
+
+    /// Returns the keys of the group `key` belongs to, in no particular order.
+    ///
+    /// Returns `None` if the key does not exist.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - A key of the group.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::PersistentMultiKeyMap;
+    ///
+    /// let mut map = PersistentMultiKeyMap::new();
+    /// map.insert("a", 1);
+    /// map.insert_alias(&"a", "b");
+    /// map.insert("c", 2);
+    /// let mut aliases = map.aliases(&"b").unwrap().to_vec();
+    /// aliases.sort();
+    /// assert_eq!(aliases, vec!["a", "b"]);
+    /// assert_eq!(map.aliases(&"c"), Some(&["c"][..]));
+    /// assert_eq!(map.aliases(&"d"), None);
+    /// ```
+    pub fn aliases(&self, key: &K) -> Option<&[K]> {
+        let id = self.key_map.get(key)?;
+        Some(&self.groups[id].keys)
+    }
+
+    /// Checks if two keys are aliases of each other.
+    ///
+    /// # Arguments
+    ///
+    /// * `key1` - The first key.
+    /// * `key2` - The second key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::PersistentMultiKeyMap;
+    ///
+    /// let mut map = PersistentMultiKeyMap::new();
+    /// map.insert("key1", "value1");
+    /// map.insert_alias(&"key1", "alias1");
+    /// map.insert("key2", "value1");
+    /// assert!(map.are_aliases(&"key1", &"alias1"));
+    /// assert!(!map.are_aliases(&"key1", &"key2"));
+    /// assert!(!map.are_aliases(&"key1", &"key3"));
+    /// ```
+    pub fn are_aliases(&self, key1: &K, key2: &K) -> bool {
+        match (self.key_map.get(key1), self.key_map.get(key2)) {
+            (Some(id1), Some(id2)) => id1 == id2,
+            _ => false,
+        }
+    }
+
+    /// Returns `true` if the map contains a value for the specified key.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key to check.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::PersistentMultiKeyMap;
+    ///
+    /// let mut map = PersistentMultiKeyMap::new();
+    /// map.insert("key1", "value1");
+    /// assert!(map.contains_key(&"key1"));
+    /// assert!(!map.contains_key(&"key2"));
+    /// ```
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.key_map.contains_key(key)
+    }
+
+    /// Returns an iterator over all keys, in no particular order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::PersistentMultiKeyMap;
+    ///
+    /// let mut map = PersistentMultiKeyMap::new();
+    /// map.insert("a", 1);
+    /// map.insert_alias(&"a", "b");
+    /// let mut keys: Vec<_> = map.keys().copied().collect();
+    /// keys.sort();
+    /// assert_eq!(keys, vec!["a", "b"]);
+    /// ```
+    pub fn keys(&self) -> impl Iterator<Item = &K> + '_ {
+        self.key_map.keys()
+    }
+
+    /// Returns an iterator over the groups, each given as its keys and its value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::PersistentMultiKeyMap;
+    ///
+    /// let mut map = PersistentMultiKeyMap::new();
+    /// map.insert("a", 1);
+    /// map.insert_alias(&"a", "b");
+    /// map.insert("c", 2);
+    /// let mut groups: Vec<_> = map.groups().map(|(keys, value)| (keys.len(), *value)).collect();
+    /// groups.sort();
+    /// assert_eq!(groups, vec![(1, 2), (2, 1)]);
+    /// ```
+    pub fn groups(&self) -> impl Iterator<Item = (&[K], &V)> + '_ {
+        self.groups
+            .values()
+            .map(|group| (&group.keys[..], &group.value))
+    }
+
+    /// Returns the number of groups in the map.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::PersistentMultiKeyMap;
+    ///
+    /// let mut map = PersistentMultiKeyMap::new();
+    /// map.insert("key1", "value1");
+    /// map.insert_alias(&"key1", "alias1");
+    /// assert_eq!(map.len(), 1);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.groups.len()
+    }
+
+    /// Returns the number of keys in the map, counting every alias.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::PersistentMultiKeyMap;
+    ///
+    /// let mut map = PersistentMultiKeyMap::new();
+    /// map.insert("key1", "value1");
+    /// map.insert_alias(&"key1", "alias1");
+    /// assert_eq!(map.key_count(), 2);
+    /// ```
+    pub fn key_count(&self) -> usize {
+        self.key_map.len()
+    }
+
+    /// Returns `true` if the map contains no values.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::PersistentMultiKeyMap;
+    ///
+    /// let mut map = PersistentMultiKeyMap::new();
+    /// assert!(map.is_empty());
+    /// map.insert("key1", "value1");
+    /// assert!(!map.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.groups.is_empty()
+    }
+
+    /// Binds `key` to the group `id`, removing the group it leaves if that group has no other
+    /// keys.
+    fn bind(&mut self, key: K, id: u64) {
+        match self.key_map.insert(key.clone(), id) {
+            Some(previous) if previous == id => return,
+            Some(previous) => {
+                self.unlink(previous, &key);
+            }
+            None => {}
+        }
+        self.groups
+            .get_mut(&id)
+            .expect("bound group exists")
+            .keys
+            .push(key);
+    }
+
+    /// Removes `key` from the keys of the group `id`, removing the group once it has no keys.
+    /// Returns the number of remaining keys.
+    fn unlink(&mut self, id: u64, key: &K) -> usize {
+        let keys = &mut self.groups.get_mut(&id).expect("routed group exists").keys;
+        if let Some(position) = keys.iter().position(|k| k == key) {
+            keys.swap_remove(position);
+        }
+        let remaining = keys.len();
+        if remaining == 0 {
+            self.groups.remove(&id);
+        }
+        remaining
+    }
+}
+
+impl<K: Eq + Hash + Clone, V: Clone> Default for PersistentMultiKeyMap<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Debug, V: Debug> Debug for PersistentMultiKeyMap<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut debug_struct = f.debug_struct("PersistentMultiKeyMap");
+        for group in self.groups.values() {
+            debug_struct.field(&format!("{:?}", group.keys), &group.value);
+        }
+        debug_struct.finish()
+    }
+}
+
+impl<K: Clone, V: Clone> Clone for PersistentMultiKeyMap<K, V> {
+    /// Returns a copy of the map sharing all of its storage, in constant time.
+    fn clone(&self) -> Self {
+        PersistentMultiKeyMap {
+            key_map: self.key_map.clone(),
+            groups: self.groups.clone(),
+            next_id: self.next_id,
+        }
+    }
+}
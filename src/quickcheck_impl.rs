@@ -0,0 +1,208 @@
+use crate::MultiKeyMap;
+use quickcheck::{Arbitrary, Gen};
+use std::fmt::Debug;
+use std::hash::Hash;
+use std::rc::Rc;
+
+/// The largest number of keys of a generated group with aliases.
+const MAX_GROUP_SIZE: usize = 4;
+
+impl<K, V> Arbitrary for MultiKeyMap<K, V>
+where
+    K: Arbitrary + Eq + Hash + Debug,
+    V: Arbitrary + Debug,
+{
+    /// Generates a map of up to [`Gen::size`] groups, about half of them with a single key and
+    /// the others with two to four keys.
+    ///
+    /// Generated keys that already belong to a group are skipped, so groups may come out
+    /// smaller, and may not be inserted at all, when keys are drawn from a small domain.
+    ///
+    /// This implementation is only available with the `quickcheck` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyMap;
+    /// use quickcheck::{Arbitrary, Gen, QuickCheck, TestResult};
+    ///
+    /// // Removing a group by any of its keys removes every one of them
+    /// fn remove_drops_every_alias(map: MultiKeyMap<u16, u8>, pick: usize) -> TestResult {
+    ///     let keys: Vec<u16> = map.keys().copied().collect();
+    ///     if keys.is_empty() {
+    ///         return TestResult::discard();
+    ///     }
+    ///     let key = keys[pick % keys.len()];
+    ///     let aliases = map.aliases(&key).unwrap();
+    ///     let mut map = map;
+    ///     map.remove(&key);
+    ///     TestResult::from_bool(aliases.iter().all(|alias| !map.contains_key(alias)))
+    /// }
+    ///
+    /// QuickCheck::new()
+    ///     .tests(200)
+    ///     .quickcheck(remove_drops_every_alias as fn(MultiKeyMap<u16, u8>, usize) -> TestResult);
+    ///
+    /// // Generated maps mix singleton groups with groups of aliases
+    /// let mut gen = Gen::new(50);
+    /// let mut sizes = Vec::new();
+    /// for _ in 0..20 {
+    ///     sizes.extend(MultiKeyMap::<u64, u8>::arbitrary(&mut gen).group_sizes());
+    /// }
+    /// assert!(sizes.contains(&1) && sizes.iter().any(|&size| size > 1));
+    /// assert!(sizes.iter().all(|&size| size <= 4));
+    /// ```
+    fn arbitrary(g: &mut Gen) -> Self {
+        let mut map = MultiKeyMap::new();
+        let groups = usize::arbitrary(g) % (g.size() + 1);
+        for _ in 0..groups {
+            let size = if bool::arbitrary(g) {
+                1
+            } else {
+                2 + usize::arbitrary(g) % (MAX_GROUP_SIZE - 1)
+            };
+            let keys: Vec<K> = (0..size).map(|_| K::arbitrary(g)).collect();
+            let mut value = Some(V::arbitrary(g));
+            let mut primary: Option<K> = None;
+            for key in keys {
+                if map.contains_key(&key) {
+                    continue;
+                }
+                match &primary {
+                    Some(primary) => {
+                        map.insert_alias(primary, key);
+                    }
+                    None => {
+                        primary = Some(key.clone());
+                        map.insert(key, value.take().expect("value is inserted once"));
+                    }
+                }
+            }
+        }
+        map
+    }
+
+    /// Yields smaller maps, the boldest first: the empty map, each half of the groups, the map
+    /// without one of its groups, without one of the keys of a group with aliases, and then with
+    /// a single key or value shrunk.
+    ///
+    /// Every candidate keeps the alias limit and key normalizer of the map. A failing property
+    /// thus reduces to a map of as few groups and keys as still fail it, with keys and values
+    /// as small as their own shrinking allows.
+    ///
+    /// This implementation is only available with the `quickcheck` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyMap;
+    /// use quickcheck::Arbitrary;
+    ///
+    /// // A property failing for any map with a group of three keys or more
+    /// let fails = |map: &MultiKeyMap<u32, u32>| map.group_sizes().any(|size| size >= 3);
+    ///
+    /// let mut map = MultiKeyMap::new();
+    /// for i in 0..20 {
+    ///     let base = i * 100 + 10;
+    ///     map.insert_with_keys([base, base + 1, base + 2, base + 3], i).unwrap();
+    /// }
+    /// map.insert(5000, 5000);
+    /// assert!(fails(&map));
+    ///
+    /// // Keep taking the first smaller map that still fails, as quickcheck does
+    /// while let Some(smaller) = map.shrink().find(|candidate| fails(candidate)) {
+    ///     map = smaller;
+    /// }
+    /// assert_eq!(map.len(), 1);
+    /// assert_eq!(map.key_count(), 3);
+    /// assert_eq!(map.get(&0), Some(&0));
+    /// assert!(map.group_matches(&0, &[0, 1, 2]));
+    /// ```
+    fn shrink(&self) -> Box<dyn Iterator<Item = Self>> {
+        let map = Rc::new(self.clone());
+        let groups: Vec<&[K]> = self
+            .slots
+            .iter()
+            .filter(|slot| slot.value.is_some())
+            .map(|slot| slot.keys.as_slice())
+            .collect();
+        let primaries: Rc<Vec<K>> = Rc::new(groups.iter().map(|keys| keys[0].clone()).collect());
+        let aliased: Vec<K> = groups
+            .iter()
+            .filter(|keys| keys.len() > 1)
+            .flat_map(|keys| keys.iter().cloned())
+            .collect();
+        let keys: Vec<K> = groups
+            .iter()
+            .flat_map(|keys| keys.iter().cloned())
+            .collect();
+
+        let count = primaries.len();
+        let mut drops = Vec::new();
+        if count > 1 {
+            drops.push(0..count);
+        }
+        if count > 2 {
+            drops.push(0..count / 2);
+            drops.push(count / 2..count);
+        }
+        drops.extend((0..count).map(|group| group..group + 1));
+        let without_groups = {
+            let (map, primaries) = (Rc::clone(&map), Rc::clone(&primaries));
+            drops.into_iter().map(move |drop| {
+                let mut smaller = (*map).clone();
+                for primary in &primaries[drop] {
+                    smaller.remove(primary);
+                }
+                smaller
+            })
+        };
+
+        let without_aliases = {
+            let map = Rc::clone(&map);
+            aliased.into_iter().map(move |alias| {
+                let mut smaller = (*map).clone();
+                smaller.remove_alias(&alias);
+                smaller
+            })
+        };
+
+        let with_smaller_keys = {
+            let map = Rc::clone(&map);
+            keys.into_iter().flat_map(move |key| {
+                let map = Rc::clone(&map);
+                key.shrink().filter_map(move |new| {
+                    let mut smaller = (*map).clone();
+                    smaller
+                        .rekey_group(&key, |old| {
+                            if *old == key {
+                                new.clone()
+                            } else {
+                                old.clone()
+                            }
+                        })
+                        .ok()?;
+                    // A normalizer may turn the smaller key back into the original one
+                    (!smaller.contains_key(&key)).then_some(smaller)
+                })
+            })
+        };
+
+        let with_smaller_values = (0..count).flat_map(move |group| {
+            let (map, primary) = (Rc::clone(&map), primaries[group].clone());
+            let value = map.get(&primary).expect("listed group is occupied").clone();
+            value.shrink().map(move |value| {
+                let mut smaller = (*map).clone();
+                smaller.replace(&primary, value);
+                smaller
+            })
+        });
+
+        Box::new(
+            without_groups
+                .chain(without_aliases)
+                .chain(with_smaller_keys)
+                .chain(with_smaller_values),
+        )
+    }
+}
@@ -0,0 +1,184 @@
+use crate::{MergePolicy, MultiKeyMap};
+use rayon::iter::{FromParallelIterator, IntoParallelIterator, ParallelExtend, ParallelIterator};
+use std::hash::Hash;
+
+/// Builds a map from `par_iter`, extending a partial map per thread and appending each partial
+/// map to the one built from the items before it.
+///
+/// Appending with [`MergePolicy::Replace`] lets later items take shared keys, as extending a
+/// single map in order does.
+fn build<K, V, T>(
+    par_iter: impl IntoParallelIterator<Item = T>,
+    empty: &(impl Fn() -> MultiKeyMap<K, V> + Sync),
+) -> MultiKeyMap<K, V>
+where
+    K: Eq + Hash + Send,
+    V: Send,
+    T: Send,
+    MultiKeyMap<K, V>: Extend<T>,
+{
+    par_iter
+        .into_par_iter()
+        .fold(empty, |mut map, item| {
+            map.extend(Some(item));
+            map
+        })
+        .reduce(empty, |mut left, right| {
+            left.append(right, MergePolicy::Replace);
+            left
+        })
+}
+
+impl<K: Eq + Hash, V> MultiKeyMap<K, V> {
+    /// Returns an empty map with the alias limit and key normalizer of `self`.
+    fn empty_like(&self) -> impl Fn() -> Self + Sync
+    where
+        K: Send + Sync,
+        V: Send,
+    {
+        let alias_limit = self.alias_limit;
+        let normalizer = self.normalizer.clone();
+        move || {
+            let mut map = Self::new();
+            map.alias_limit = alias_limit;
+            map.normalizer = normalizer.clone();
+            map
+        }
+    }
+}
+
+impl<K: Eq + Hash + Send, V: Send> FromParallelIterator<(K, V)> for MultiKeyMap<K, V> {
+    /// Creates a map from key-value pairs in parallel.
+    ///
+    /// Each thread collects its pairs into a partial map, and the partial maps are then moved
+    /// into one with [`append`](MultiKeyMap::append). For an iterator with a defined order, such
+    /// as that of a vector or a range, the result is the map [`collect`](Iterator::collect) builds
+    /// sequentially: a key repeated in several pairs belongs to the last of them.
+    ///
+    /// This implementation is only available with the `rayon` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyMap;
+    /// use rayon::prelude::*;
+    ///
+    /// let pairs: Vec<(u32, u32)> = (0..10_000).map(|i| (i % 1000, i)).collect();
+    /// let parallel: MultiKeyMap<_, _> = pairs.par_iter().copied().collect();
+    /// let sequential: MultiKeyMap<_, _> = pairs.iter().copied().collect();
+    /// assert_eq!(parallel, sequential);
+    /// assert_eq!(parallel.len(), 1000);
+    /// assert_eq!(parallel.get(&7), Some(&9007));
+    /// ```
+    fn from_par_iter<I: IntoParallelIterator<Item = (K, V)>>(par_iter: I) -> Self {
+        build(par_iter, &Self::new)
+    }
+}
+
+impl<K: Eq + Hash + Send, V: Send> FromParallelIterator<(Vec<K>, V)> for MultiKeyMap<K, V> {
+    /// Creates a map from groups of keys and values in parallel.
+    ///
+    /// Partial maps are built and merged as for key-value pairs, so for an iterator with a
+    /// defined order the result is the map [`collect`](Iterator::collect) builds sequentially:
+    /// a key repeated in several groups belongs to the last of them.
+    ///
+    /// This implementation is only available with the `rayon` feature.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a group has no keys.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyMap;
+    /// use rayon::prelude::*;
+    ///
+    /// let groups: Vec<(Vec<u32>, u32)> =
+    ///     (0..5000).map(|i| (vec![i, i / 2 + 10_000], i)).collect();
+    /// let parallel: MultiKeyMap<_, _> = groups.clone().into_par_iter().collect();
+    /// let sequential: MultiKeyMap<_, _> = groups.into_iter().collect();
+    /// assert_eq!(parallel, sequential);
+    /// assert!(parallel.group_matches(&11, &[11, 10_005]));
+    /// assert!(parallel.group_matches(&10, &[10]));
+    /// ```
+    fn from_par_iter<I: IntoParallelIterator<Item = (Vec<K>, V)>>(par_iter: I) -> Self {
+        build(par_iter, &Self::new)
+    }
+}
+
+impl<K: Eq + Hash + Send + Sync, V: Send> ParallelExtend<(K, V)> for MultiKeyMap<K, V> {
+    /// Inserts key-value pairs in parallel.
+    ///
+    /// The pairs are collected into partial maps in parallel, sharing the key normalizer of
+    /// `self`, and the result is moved into `self` with [`append`](MultiKeyMap::append). For an
+    /// iterator with a defined order, `self` ends up as [`extend`](Extend::extend) leaves it:
+    /// keys of the pairs move out of the groups of `self`, and a key repeated in several pairs
+    /// belongs to the last of them. Like `append`, this does not report the changes to hooks.
+    ///
+    /// This implementation is only available with the `rayon` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyMap;
+    /// use rayon::prelude::*;
+    ///
+    /// let mut parallel = MultiKeyMap::new();
+    /// parallel.insert_with_keys([1, 2, 5000], 0).unwrap();
+    /// let mut sequential = parallel.clone();
+    ///
+    /// let pairs: Vec<(u32, u32)> = (2..10_000).map(|i| (i % 3000, i)).collect();
+    /// parallel.par_extend(pairs.par_iter().copied());
+    /// sequential.extend(pairs);
+    /// assert_eq!(parallel, sequential);
+    /// assert!(parallel.group_matches(&5000, &[5000]));
+    /// assert_eq!(parallel.get(&5000), Some(&0));
+    /// assert_eq!(parallel.get(&2), Some(&9002));
+    /// ```
+    fn par_extend<I: IntoParallelIterator<Item = (K, V)>>(&mut self, par_iter: I) {
+        let other = build(par_iter, &self.empty_like());
+        self.append(other, MergePolicy::Replace);
+    }
+}
+
+impl<K: Eq + Hash + Send + Sync, V: Send> ParallelExtend<(Vec<K>, V)> for MultiKeyMap<K, V> {
+    /// Inserts groups of keys and values in parallel.
+    ///
+    /// The groups are collected into partial maps in parallel, sharing the alias limit and key
+    /// normalizer of `self`, and the result is moved into `self` with
+    /// [`append`](MultiKeyMap::append). For an iterator with a defined order, `self` ends up as
+    /// [`extend`](Extend::extend) leaves it: keys of the groups move out of the groups of
+    /// `self`, and a key repeated in several groups belongs to the last of them. Like `append`,
+    /// this does not report the changes to hooks.
+    ///
+    /// This implementation is only available with the `rayon` feature.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a group has no keys or does not fit the
+    /// [alias limit](MultiKeyMap::with_alias_limit).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyMap;
+    /// use rayon::prelude::*;
+    /// use std::panic::{self, AssertUnwindSafe};
+    ///
+    /// let mut map = MultiKeyMap::with_alias_limit(2);
+    /// map.insert("a", 0);
+    /// map.par_extend(vec![(vec!["a", "b"], 1), (vec!["c"], 2)]);
+    /// assert_eq!(map.len(), 2);
+    /// assert!(map.group_matches(&"b", &["a", "b"]));
+    ///
+    /// let result = panic::catch_unwind(AssertUnwindSafe(|| {
+    ///     map.par_extend(vec![(vec!["d", "e", "f"], 3)]);
+    /// }));
+    /// assert!(result.is_err());
+    /// ```
+    fn par_extend<I: IntoParallelIterator<Item = (Vec<K>, V)>>(&mut self, par_iter: I) {
+        let other = build(par_iter, &self.empty_like());
+        self.append(other, MergePolicy::Replace);
+    }
+}
@@ -0,0 +1,156 @@
+use crate::MultiKeyMap;
+use std::fmt::{self, Display};
+use std::hash::Hash;
+
+/// Summary statistics of the shape of a [`MultiKeyMap`], returned by [`MultiKeyMap::report`].
+///
+/// Its [`Display`] output is a human-readable summary over several lines, suitable for logs.
+///
+/// # Examples
+///
+/// ```
+/// use multi_key_map::MapReport;
+///
+/// let report = MapReport {
+///     groups: 3,
+///     keys: 6,
+///     singleton_groups: 1,
+///     min_group_size: Some(1),
+///     max_group_size: Some(3),
+///     mean_group_size: 2.0,
+///     slots: 4,
+///     slot_capacity: 4,
+///     key_capacity: 7,
+/// };
+/// assert_eq!(
+///     report.to_string(),
+///     "groups: 3 (1 singleton)\n\
+///      keys: 6\n\
+///      keys per group: min 1, max 3, mean 2.00\n\
+///      slots: 3 of 4 occupied, capacity 4 (75.0% used)\n\
+///      key index: 6 of 7 (85.7% used)"
+/// );
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MapReport {
+    /// The number of groups.
+    pub groups: usize,
+    /// The number of keys, counting every alias.
+    pub keys: usize,
+    /// The number of groups with a single key.
+    pub singleton_groups: usize,
+    /// The number of keys in the smallest group, or `None` if the map is empty.
+    pub min_group_size: Option<usize>,
+    /// The number of keys in the largest group, or `None` if the map is empty.
+    pub max_group_size: Option<usize>,
+    /// The mean number of keys per group, or `0.0` if the map is empty.
+    pub mean_group_size: f64,
+    /// The number of slots allocated for groups, occupied or vacant.
+    pub slots: usize,
+    /// The number of slots the map can hold without reallocating.
+    pub slot_capacity: usize,
+    /// The number of keys the key index can hold without reallocating.
+    pub key_capacity: usize,
+}
+
+impl Display for MapReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "groups: {} ({} singleton)",
+            self.groups, self.singleton_groups
+        )?;
+        writeln!(f, "keys: {}", self.keys)?;
+        match (self.min_group_size, self.max_group_size) {
+            (Some(min), Some(max)) => writeln!(
+                f,
+                "keys per group: min {min}, max {max}, mean {:.2}",
+                self.mean_group_size
+            )?,
+            _ => writeln!(f, "keys per group: none")?,
+        }
+        write!(
+            f,
+            "slots: {} of {} occupied, capacity {}",
+            self.groups, self.slots, self.slot_capacity
+        )?;
+        write_usage(f, self.groups, self.slot_capacity)?;
+        write!(f, "\nkey index: {} of {}", self.keys, self.key_capacity)?;
+        write_usage(f, self.keys, self.key_capacity)
+    }
+}
+
+/// Writes the share of `capacity` that `used` takes, unless the capacity is zero.
+fn write_usage(f: &mut fmt::Formatter<'_>, used: usize, capacity: usize) -> fmt::Result {
+    if capacity == 0 {
+        return Ok(());
+    }
+    write!(f, " ({:.1}% used)", used as f64 * 100.0 / capacity as f64)
+}
+
+impl<K: Eq + Hash, V> MultiKeyMap<K, V> {
+    /// Gathers summary statistics of the shape of the map in a single pass over its slots.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyMap;
+    ///
+    /// let mut map = MultiKeyMap::new();
+    /// map.insert_with_keys(["a", "a1", "a2"], 1).unwrap();
+    /// map.insert_with_keys(["b", "b1"], 2).unwrap();
+    /// map.insert("c", 3);
+    /// map.insert("d", 4);
+    ///
+    /// let report = map.report();
+    /// assert_eq!((report.groups, report.keys, report.singleton_groups), (4, 7, 2));
+    /// assert_eq!((report.min_group_size, report.max_group_size), (Some(1), Some(3)));
+    /// assert_eq!(report.mean_group_size, 1.75);
+    /// assert_eq!(report.slots, 4);
+    /// assert!(report.slot_capacity >= 4 && report.key_capacity >= 7);
+    ///
+    /// // Removals leave vacant slots behind
+    /// map.remove(&"a");
+    /// map.remove_alias(&"b1");
+    /// let report = map.report();
+    /// assert_eq!((report.groups, report.keys, report.singleton_groups), (3, 3, 3));
+    /// assert_eq!((report.min_group_size, report.max_group_size), (Some(1), Some(1)));
+    /// assert_eq!(report.mean_group_size, 1.0);
+    /// assert_eq!(report.slots, 4);
+    /// assert!(report.to_string().starts_with("groups: 3 (3 singleton)\nkeys: 3\n"));
+    ///
+    /// map.clear();
+    /// let report = map.report();
+    /// assert_eq!((report.min_group_size, report.mean_group_size), (None, 0.0));
+    /// assert!(report.to_string().contains("keys per group: none"));
+    /// ```
+    pub fn report(&self) -> MapReport {
+        let mut singleton_groups = 0;
+        let mut min_group_size: Option<usize> = None;
+        let mut max_group_size: Option<usize> = None;
+        for slot in self.slots.iter().filter(|slot| slot.value.is_some()) {
+            let size = slot.keys.len();
+            if size == 1 {
+                singleton_groups += 1;
+            }
+            min_group_size = Some(min_group_size.map_or(size, |min| min.min(size)));
+            max_group_size = Some(max_group_size.map_or(size, |max| max.max(size)));
+        }
+        let keys = self.key_count();
+        MapReport {
+            groups: self.len,
+            keys,
+            singleton_groups,
+            min_group_size,
+            max_group_size,
+            mean_group_size: if self.len == 0 {
+                0.0
+            } else {
+                keys as f64 / self.len as f64
+            },
+            slots: self.slots.len(),
+            slot_capacity: self.values_capacity(),
+            key_capacity: self.keys_capacity(),
+        }
+    }
+}
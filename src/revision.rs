@@ -0,0 +1,125 @@
+#[cfg(feature = "generations")]
+use crate::MultiKeyMap;
+#[cfg(feature = "generations")]
+use std::hash::Hash;
+
+/// A point in the modification history of a [`MultiKeyMap`].
+///
+/// With the `generations` feature the map counts its modifications and every slot keeps the count
+/// at its last modification. Without it this is an empty type whose methods do nothing.
+#[derive(Debug, Default, Clone, Copy)]
+pub(crate) struct Revision {
+    #[cfg(feature = "generations")]
+    count: u64,
+}
+
+impl Revision {
+    /// Returns the revision of a new map.
+    pub(crate) const fn new() -> Self {
+        Revision {
+            #[cfg(feature = "generations")]
+            count: 0,
+        }
+    }
+
+    /// Returns the revision following this one.
+    #[inline(always)]
+    pub(crate) fn next(self) -> Self {
+        Revision {
+            #[cfg(feature = "generations")]
+            count: self.count + 1,
+        }
+    }
+}
+
+#[cfg(feature = "generations")]
+impl<K: Eq + Hash, V> MultiKeyMap<K, V> {
+    /// Returns the generation of the group of `key`, or `None` if the key does not exist.
+    ///
+    /// The generation of a group is the [map generation](Self::map_generation) of its last
+    /// change: its creation, a mutable access to its value, as through
+    /// [`get_mut`](Self::get_mut) or [`replace`](Self::replace), or a change to its keys. It
+    /// therefore only grows, and a key moved to another group never finds the generation it had
+    /// in its previous group. Lookups and compaction leave it unchanged.
+    ///
+    /// This method is only available with the `generations` feature.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - Any key of the group.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyMap;
+    ///
+    /// let mut map = MultiKeyMap::new();
+    /// map.insert("a", 1);
+    /// map.insert("b", 2);
+    /// let mut last = map.generation(&"a").unwrap();
+    /// let mut changed = |map: &MultiKeyMap<&str, u32>| {
+    ///     let generation = map.generation(&"a").unwrap();
+    ///     let changed = generation > last;
+    ///     last = generation;
+    ///     changed
+    /// };
+    ///
+    /// map.get(&"a");
+    /// map.are_aliases(&"a", &"b");
+    /// map.insert("c", 3);
+    /// map.compact();
+    /// assert!(!changed(&map));
+    ///
+    /// *map.get_mut(&"a").unwrap() += 1;
+    /// assert!(changed(&map));
+    /// map.replace(&"a", 5);
+    /// assert!(changed(&map));
+    /// map.insert_alias(&"a", "a1");
+    /// assert!(changed(&map));
+    /// map.set_primary(&"a1");
+    /// assert!(changed(&map));
+    /// map.insert_alias(&"b", "a1");
+    /// assert!(changed(&map));
+    /// map.retain(|_, value| *value > 0);
+    /// assert!(changed(&map));
+    ///
+    /// let before = map.generation(&"b").unwrap();
+    /// map.remove(&"b");
+    /// map.insert("b", 2);
+    /// assert!(map.generation(&"b").unwrap() > before);
+    /// assert_eq!(map.generation(&"missing"), None);
+    /// ```
+    pub fn generation(&self, key: &K) -> Option<u64> {
+        let index = self.index_of(key)?;
+        Some(self.slots[index].revision.count)
+    }
+
+    /// Returns the generation of the map, which grows with every modification of the map.
+    ///
+    /// This method is only available with the `generations` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyMap;
+    ///
+    /// let mut map = MultiKeyMap::new();
+    /// assert_eq!(map.map_generation(), 0);
+    /// map.insert("a", 1);
+    /// let generation = map.map_generation();
+    /// assert!(generation > 0);
+    ///
+    /// map.get(&"a");
+    /// map.remove_alias(&"missing");
+    /// assert_eq!(map.map_generation(), generation);
+    ///
+    /// map.remove(&"a");
+    /// assert!(map.map_generation() > generation);
+    /// let generation = map.map_generation();
+    /// map.clear();
+    /// assert!(map.map_generation() > generation);
+    /// ```
+    pub fn map_generation(&self) -> u64 {
+        self.revision.count
+    }
+}
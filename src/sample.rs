@@ -0,0 +1,157 @@
+use crate::MultiKeyMap;
+use rand::Rng;
+use std::hash::Hash;
+
+impl<K: Eq + Hash, V> MultiKeyMap<K, V> {
+    /// Picks a random group, uniformly over groups, and returns its keys and value.
+    ///
+    /// Every value is equally likely to be picked regardless of how many aliases it has. Vacant
+    /// slots left by removals are skipped by drawing again, so a pick takes constant time as
+    /// long as most slots are occupied; [`compact`](Self::compact) restores that after many
+    /// removals.
+    ///
+    /// Returns `None` if the map is empty.
+    ///
+    /// This method is only available with the `rand` feature.
+    ///
+    /// # Arguments
+    ///
+    /// * `rng` - The random number generator to draw from.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyMap;
+    /// use rand::rngs::SmallRng;
+    /// use rand::SeedableRng;
+    ///
+    /// let mut rng = SmallRng::seed_from_u64(7);
+    /// let mut map = MultiKeyMap::new();
+    /// map.insert("a", 1);
+    /// map.insert("b", 2);
+    /// for alias in ["b1", "b2", "b3", "b4", "b5", "b6", "b7", "b8"] {
+    ///     map.insert_alias(&"b", alias);
+    /// }
+    ///
+    /// // `b` has nine keys but is picked as often as `a`
+    /// let draws = 10_000;
+    /// let a = (0..draws)
+    ///     .filter(|_| map.random_group(&mut rng).unwrap().1 == &1)
+    ///     .count();
+    /// assert!((4_500..5_500).contains(&a), "picked `a` {a} times");
+    ///
+    /// let (keys, value) = map.random_group(&mut rng).unwrap();
+    /// assert_eq!(keys.len(), if *value == 1 { 1 } else { 9 });
+    ///
+    /// map.clear();
+    /// assert_eq!(map.random_group(&mut rng), None);
+    /// ```
+    pub fn random_group<R: Rng + ?Sized>(&self, rng: &mut R) -> Option<(Vec<&K>, &V)> {
+        let index = self.random_slot(rng)?;
+        let slot = &self.slots[index];
+        Some((slot.keys.iter().collect(), self.value(index)))
+    }
+
+    /// Picks a random key by first picking a group uniformly, then one of its keys uniformly.
+    ///
+    /// Keys of heavily aliased groups are therefore less likely to be picked than keys of groups
+    /// with few aliases. Use [`random_key_weighted`](Self::random_key_weighted) to pick
+    /// uniformly over keys instead.
+    ///
+    /// Returns `None` if the map is empty.
+    ///
+    /// This method is only available with the `rand` feature.
+    ///
+    /// # Arguments
+    ///
+    /// * `rng` - The random number generator to draw from.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyMap;
+    /// use rand::rngs::SmallRng;
+    /// use rand::SeedableRng;
+    ///
+    /// let mut rng = SmallRng::seed_from_u64(7);
+    /// let mut map = MultiKeyMap::new();
+    /// map.insert("a", 1);
+    /// map.insert("b", 2);
+    /// map.insert_alias(&"b", "b1");
+    ///
+    /// let draws = 10_000;
+    /// let a = (0..draws)
+    ///     .filter(|_| map.random_key(&mut rng) == Some(&"a"))
+    ///     .count();
+    /// assert!((4_500..5_500).contains(&a), "picked `a` {a} times");
+    /// ```
+    pub fn random_key<R: Rng + ?Sized>(&self, rng: &mut R) -> Option<&K> {
+        let keys = &self.slots[self.random_slot(rng)?].keys;
+        Some(&keys[rng.random_range(0..keys.len())])
+    }
+
+    /// Picks a random key uniformly over all keys.
+    ///
+    /// A group is picked with probability proportional to its number of keys. Unlike
+    /// [`random_key`](Self::random_key), this walks the slots to find the picked key, which
+    /// takes `O(n)` time.
+    ///
+    /// Returns `None` if the map is empty.
+    ///
+    /// This method is only available with the `rand` feature.
+    ///
+    /// # Arguments
+    ///
+    /// * `rng` - The random number generator to draw from.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyMap;
+    /// use rand::rngs::SmallRng;
+    /// use rand::SeedableRng;
+    ///
+    /// let mut rng = SmallRng::seed_from_u64(7);
+    /// let mut map = MultiKeyMap::new();
+    /// map.insert("a", 1);
+    /// map.insert("b", 2);
+    /// for alias in ["b1", "b2", "b3", "b4", "b5", "b6", "b7", "b8"] {
+    ///     map.insert_alias(&"b", alias);
+    /// }
+    ///
+    /// // `a` is one key out of ten
+    /// let draws = 10_000;
+    /// let a = (0..draws)
+    ///     .filter(|_| map.random_key_weighted(&mut rng) == Some(&"a"))
+    ///     .count();
+    /// assert!((700..1_300).contains(&a), "picked `a` {a} times");
+    /// ```
+    pub fn random_key_weighted<R: Rng + ?Sized>(&self, rng: &mut R) -> Option<&K> {
+        if self.is_empty() {
+            return None;
+        }
+        let mut target = rng.random_range(0..self.key_count());
+        self.slots
+            .iter()
+            .find_map(|slot| match slot.keys.get(target) {
+                Some(key) => Some(key),
+                None => {
+                    target -= slot.keys.len();
+                    None
+                }
+            })
+    }
+
+    /// Draws slot indices until one is occupied and returns it, or `None` if the map is empty.
+    fn random_slot<R: Rng + ?Sized>(&self, rng: &mut R) -> Option<usize> {
+        if self.is_empty() {
+            return None;
+        }
+        loop {
+            let index = rng.random_range(0..self.slots.len());
+            if self.slots[index].value.is_some() {
+                return Some(index);
+            }
+        }
+    }
+}
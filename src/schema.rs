@@ -0,0 +1,68 @@
+use crate::MultiKeyMap;
+use schemars::{json_schema, JsonSchema, Schema, SchemaGenerator};
+use std::borrow::Cow;
+
+/// Describes the representation written by the `serde` implementation: an array of groups,
+/// each a two-element array of its non-empty, duplicate-free list of keys and its value.
+///
+/// This implementation is only available with the `schemars` feature.
+///
+/// # Examples
+///
+/// ```
+/// use multi_key_map::MultiKeyMap;
+///
+/// let mut map = MultiKeyMap::new();
+/// map.insert("a".to_string(), 1u32);
+/// map.insert_alias(&"a".to_string(), "alias".to_string());
+/// map.insert("b".to_string(), 2);
+///
+/// let schema = serde_json::to_value(schemars::schema_for!(MultiKeyMap<String, u32>)).unwrap();
+/// let validator = jsonschema::validator_for(&schema).unwrap();
+/// assert!(validator.is_valid(&serde_json::to_value(&map).unwrap()));
+///
+/// // Neither an empty list of keys nor a repeated key is valid
+/// assert!(!validator.is_valid(&serde_json::json!([[[], 1]])));
+/// assert!(!validator.is_valid(&serde_json::json!([[["a", "a"], 1]])));
+/// assert!(!validator.is_valid(&serde_json::json!({ "a": 1 })));
+/// ```
+impl<K: JsonSchema, V: JsonSchema> JsonSchema for MultiKeyMap<K, V> {
+    fn schema_name() -> Cow<'static, str> {
+        format!(
+            "MultiKeyMap_of_{}_and_{}",
+            K::schema_name(),
+            V::schema_name()
+        )
+        .into()
+    }
+
+    fn schema_id() -> Cow<'static, str> {
+        format!(
+            "multi_key_map::MultiKeyMap<{}, {}>",
+            K::schema_id(),
+            V::schema_id()
+        )
+        .into()
+    }
+
+    fn json_schema(generator: &mut SchemaGenerator) -> Schema {
+        json_schema!({
+            "description": "Groups of keys sharing a value, each given as its keys and its value.",
+            "type": "array",
+            "items": {
+                "type": "array",
+                "prefixItems": [
+                    {
+                        "type": "array",
+                        "items": generator.subschema_for::<K>(),
+                        "minItems": 1,
+                        "uniqueItems": true,
+                    },
+                    generator.subschema_for::<V>(),
+                ],
+                "minItems": 2,
+                "maxItems": 2,
+            },
+        })
+    }
+}
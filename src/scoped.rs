@@ -0,0 +1,399 @@
+use crate::{MergePolicy, MultiKeyMap};
+use std::fmt::{self, Debug};
+use std::hash::Hash;
+
+/// A `ScopedMultiKeyMap` is a stack of [`MultiKeyMap`] scopes, where lookups fall back from the
+/// innermost scope to the outer ones and writes go to the innermost scope.
+///
+/// There is always at least one scope, the outermost, which cannot be popped.
+///
+/// # Shadowing
+///
+/// Every key is resolved in the innermost scope that binds it, independently of its aliases.
+/// Binding a key in an inner scope therefore shadows only that key: if it is an alias in an
+/// outer scope, its other aliases there still resolve to the outer value, and the shadowed key
+/// is no longer an alias of them. Popping the inner scope restores the outer binding.
+///
+/// # Examples
+///
+/// ```
+/// use multi_key_map::ScopedMultiKeyMap;
+///
+/// let mut map = ScopedMultiKeyMap::new();
+/// map.insert("x", 1);
+/// map.insert_alias(&"x", "y");
+///
+/// map.push_scope();
+/// // Outer bindings and their aliases are visible from the inner scope
+/// assert_eq!(map.get(&"y"), Some(&1));
+/// assert!(map.are_aliases(&"x", &"y"));
+///
+/// // Shadowing `y` leaves `x` bound to the outer value
+/// map.insert("y", 2);
+/// map.insert_alias(&"y", "z");
+/// assert_eq!(map.get(&"x"), Some(&1));
+/// assert_eq!(map.get(&"y"), Some(&2));
+/// assert!(!map.are_aliases(&"x", &"y"));
+/// assert!(map.are_aliases(&"y", &"z"));
+///
+/// map.pop_scope();
+/// assert_eq!(map.get(&"y"), Some(&1));
+/// assert!(map.are_aliases(&"x", &"y"));
+/// assert_eq!(map.get(&"z"), None);
+/// ```
+pub struct ScopedMultiKeyMap<K, V> {
+    /// The scopes, from the outermost to the innermost. Never empty.
+    scopes: Vec<MultiKeyMap<K, V>>,
+}
+
+impl<K: Eq + Hash, V> ScopedMultiKeyMap<K, V> {
+    /// Creates a `ScopedMultiKeyMap` with a single, empty scope.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::ScopedMultiKeyMap;
+    ///
+    /// let map: ScopedMultiKeyMap<&str, &str> = ScopedMultiKeyMap::new();
+    /// assert_eq!(map.depth(), 1);
+    /// ```
+    pub fn new() -> Self {
+        ScopedMultiKeyMap {
+            scopes: vec![MultiKeyMap::new()],
+        }
+    }
+
+    /// Opens a new, empty innermost scope.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::ScopedMultiKeyMap;
+    ///
+    /// let mut map: ScopedMultiKeyMap<&str, &str> = ScopedMultiKeyMap::new();
+    /// map.push_scope();
+    /// assert_eq!(map.depth(), 2);
+    /// ```
+    pub fn push_scope(&mut self) {
+        self.scopes.push(MultiKeyMap::new());
+    }
+
+    /// Closes the innermost scope and returns its bindings.
+    ///
+    /// Returns `None` and leaves the map unchanged if only the outermost scope is left.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::ScopedMultiKeyMap;
+    ///
+    /// let mut map = ScopedMultiKeyMap::new();
+    /// map.insert("x", 1);
+    /// map.push_scope();
+    /// map.insert("x", 2);
+    /// map.insert("y", 3);
+    ///
+    /// let scope = map.pop_scope().unwrap();
+    /// assert_eq!(scope.get(&"y"), Some(&3));
+    /// assert_eq!(map.get(&"x"), Some(&1));
+    /// assert_eq!(map.get(&"y"), None);
+    /// assert!(map.pop_scope().is_none());
+    /// ```
+    pub fn pop_scope(&mut self) -> Option<MultiKeyMap<K, V>> {
+        if self.scopes.len() == 1 {
+            return None;
+        }
+        self.scopes.pop()
+    }
+
+    /// Returns the number of open scopes, counting the outermost.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::ScopedMultiKeyMap;
+    ///
+    /// let mut map: ScopedMultiKeyMap<&str, &str> = ScopedMultiKeyMap::new();
+    /// map.push_scope();
+    /// map.push_scope();
+    /// map.pop_scope();
+    /// assert_eq!(map.depth(), 2);
+    /// ```
+    pub fn depth(&self) -> usize {
+        self.scopes.len()
+    }
+
+    /// Retrieves a reference to the value `key` resolves to, searching the scopes from the
+    /// innermost outward.
+    ///
+    /// Returns `None` if no scope binds the key.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key to lookup.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::ScopedMultiKeyMap;
+    ///
+    /// let mut map = ScopedMultiKeyMap::new();
+    /// map.insert("outer", 1);
+    /// map.push_scope();
+    /// map.insert("inner", 2);
+    /// assert_eq!(map.get(&"outer"), Some(&1));
+    /// assert_eq!(map.get(&"inner"), Some(&2));
+    /// assert_eq!(map.get(&"missing"), None);
+    /// ```
+    pub fn get(&self, key: &K) -> Option<&V> {
+        self.scopes.iter().rev().find_map(|scope| scope.get(key))
+    }
+
+    /// Retrieves a mutable reference to the value `key` resolves to, searching the scopes from
+    /// the innermost outward.
+    ///
+    /// The value is modified in the scope that binds it, which may be an outer one.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key to lookup.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::ScopedMultiKeyMap;
+    ///
+    /// let mut map = ScopedMultiKeyMap::new();
+    /// map.insert("outer", 1);
+    /// map.push_scope();
+    /// *map.get_mut(&"outer").unwrap() = 2;
+    /// map.pop_scope();
+    /// assert_eq!(map.get(&"outer"), Some(&2));
+    /// ```
+    pub fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        self.scopes
+            .iter_mut()
+            .rev()
+            .find_map(|scope| scope.get_mut(key))
+    }
+
+    /// Returns the depth of the innermost scope binding `key`, where the outermost scope has
+    /// depth 1.
+    ///
+    /// Returns `None` if no scope binds the key.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key to lookup.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::ScopedMultiKeyMap;
+    ///
+    /// let mut map = ScopedMultiKeyMap::new();
+    /// map.insert("x", 1);
+    /// map.push_scope();
+    /// map.insert("y", 2);
+    /// assert_eq!(map.scope_of(&"x"), Some(1));
+    /// assert_eq!(map.scope_of(&"y"), Some(2));
+    /// assert_eq!(map.scope_of(&"z"), None);
+    /// ```
+    pub fn scope_of(&self, key: &K) -> Option<usize> {
+        self.scopes
+            .iter()
+            .rposition(|scope| scope.contains_key(key))
+            .map(|index| index + 1)
+    }
+
+    /// Inserts a value with the given key into the innermost scope.
+    ///
+    /// Bindings of the key in outer scopes are shadowed, not modified.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key to insert.
+    /// * `value` - The value to insert.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::ScopedMultiKeyMap;
+    ///
+    /// let mut map = ScopedMultiKeyMap::new();
+    /// map.insert("x", 1);
+    /// map.push_scope();
+    /// map.insert("x", 2);
+    /// assert_eq!(map.get(&"x"), Some(&2));
+    /// ```
+    pub fn insert(&mut self, key: K, value: V) {
+        self.innermost().insert(key, value);
+    }
+
+    /// Adds a new alias key for the element at `key` in the innermost scope.
+    ///
+    /// Returns the reference count if the alias is successfully added. `key` must be bound in
+    /// the innermost scope itself: values of outer scopes cannot gain aliases from an inner
+    /// scope, so `None` is returned for them.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The original key.
+    /// * `alias` - The alias key to add.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::ScopedMultiKeyMap;
+    ///
+    /// let mut map = ScopedMultiKeyMap::new();
+    /// map.insert("x", 1);
+    /// map.push_scope();
+    /// assert_eq!(map.insert_alias(&"x", "y"), None);
+    /// map.insert("z", 2);
+    /// assert_eq!(map.insert_alias(&"z", "w"), Some(2));
+    /// ```
+    pub fn insert_alias(&mut self, key: &K, alias: K) -> Option<usize> {
+        self.innermost().insert_alias(key, alias)
+    }
+
+    /// Removes an alias key from the innermost scope.
+    ///
+    /// Returns the reference count if the alias is successfully removed. A binding of the key
+    /// in an outer scope becomes visible again.
+    ///
+    /// # Arguments
+    ///
+    /// * `alias` - The alias key to remove.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::ScopedMultiKeyMap;
+    ///
+    /// let mut map = ScopedMultiKeyMap::new();
+    /// map.insert("x", 1);
+    /// map.push_scope();
+    /// map.insert("x", 2);
+    /// assert_eq!(map.remove_alias(&"x"), Some(0));
+    /// assert_eq!(map.get(&"x"), Some(&1));
+    /// assert_eq!(map.remove_alias(&"x"), None);
+    /// ```
+    pub fn remove_alias(&mut self, alias: &K) -> Option<usize> {
+        self.innermost().remove_alias(alias)
+    }
+
+    /// Returns `true` if any scope binds `key`.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key to check.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::ScopedMultiKeyMap;
+    ///
+    /// let mut map = ScopedMultiKeyMap::new();
+    /// map.insert("x", 1);
+    /// map.push_scope();
+    /// assert!(map.contains_key(&"x"));
+    /// assert!(!map.contains_key(&"y"));
+    /// ```
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.scope_of(key).is_some()
+    }
+
+    /// Checks if two keys resolve to the same value, that is if the innermost scope binding
+    /// either of them binds both as aliases.
+    ///
+    /// # Arguments
+    ///
+    /// * `key1` - The first key.
+    /// * `key2` - The second key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::ScopedMultiKeyMap;
+    ///
+    /// let mut map = ScopedMultiKeyMap::new();
+    /// map.insert("x", 1);
+    /// map.insert_alias(&"x", "y");
+    /// map.push_scope();
+    /// assert!(map.are_aliases(&"x", &"y"));
+    /// map.insert("y", 2);
+    /// assert!(!map.are_aliases(&"x", &"y"));
+    /// ```
+    pub fn are_aliases(&self, key1: &K, key2: &K) -> bool {
+        match (self.scope_of(key1), self.scope_of(key2)) {
+            (Some(scope1), Some(scope2)) if scope1 == scope2 => {
+                self.scopes[scope1 - 1].are_aliases(key1, key2)
+            }
+            _ => false,
+        }
+    }
+
+    /// Collapses the scopes into a single map holding the bindings visible from the innermost
+    /// scope.
+    ///
+    /// Inner bindings shadow outer ones key by key, as described in
+    /// [Shadowing](Self#shadowing): a shadowed key leaves its outer group, and an outer value
+    /// whose keys are all shadowed is dropped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::ScopedMultiKeyMap;
+    ///
+    /// let mut map = ScopedMultiKeyMap::new();
+    /// map.insert("x", 1);
+    /// map.insert_alias(&"x", "y");
+    /// map.insert("dropped", 2);
+    /// map.push_scope();
+    /// map.insert("y", 3);
+    /// map.insert("dropped", 4);
+    /// map.insert_alias(&"dropped", "z");
+    ///
+    /// let flat = map.flatten();
+    /// assert_eq!(flat.len(), 3);
+    /// assert_eq!(flat.get(&"x"), Some(&1));
+    /// assert_eq!(flat.get(&"y"), Some(&3));
+    /// assert!(flat.group_matches(&"x", &["x"]));
+    /// assert!(flat.group_matches(&"dropped", &["dropped", "z"]));
+    /// ```
+    pub fn flatten(self) -> MultiKeyMap<K, V> {
+        let mut scopes = self.scopes.into_iter();
+        let mut flat = scopes.next().expect("the outermost scope is never popped");
+        for scope in scopes {
+            flat.append(scope, MergePolicy::Replace);
+        }
+        flat
+    }
+
+    fn innermost(&mut self) -> &mut MultiKeyMap<K, V> {
+        self.scopes
+            .last_mut()
+            .expect("the outermost scope is never popped")
+    }
+}
+
+impl<K: Eq + Hash, V> Default for ScopedMultiKeyMap<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Debug, V: Debug> Debug for ScopedMultiKeyMap<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(&self.scopes).finish()
+    }
+}
+
+impl<K: Eq + Hash + Clone + Debug, V: Clone + Debug> Clone for ScopedMultiKeyMap<K, V> {
+    fn clone(&self) -> Self {
+        ScopedMultiKeyMap {
+            scopes: self.scopes.clone(),
+        }
+    }
+}
@@ -0,0 +1,204 @@
+use crate::small::GroupKeys;
+#[cfg(feature = "zeroize")]
+use crate::MultiKeyMap;
+#[cfg(feature = "zeroize")]
+use std::hash::Hash;
+#[cfg(feature = "zeroize")]
+use std::mem::MaybeUninit;
+#[cfg(feature = "zeroize")]
+use zeroize::{Zeroize, ZeroizeOnDrop};
+
+/// Overwrites the bytes a value was moved out of, leaving `None` behind.
+///
+/// Moving a value out of a slot leaves a stale copy of its inline bytes until the slot is reused.
+/// With the `zeroize` feature they are zeroed; without it this does nothing.
+#[cfg(feature = "zeroize")]
+pub(crate) fn vacated<T>(value: &mut Option<T>) {
+    debug_assert!(value.is_none(), "only vacated values are scrubbed");
+    let raw = (value as *mut Option<T>).cast::<MaybeUninit<Option<T>>>();
+    // SAFETY: `None` owns nothing, so overwriting it leaks nothing, and it is written back before
+    // the place can be read again.
+    unsafe {
+        (*raw).zeroize();
+        raw.cast::<Option<T>>().write(None);
+    }
+}
+
+/// Overwrites the key of `keys` that was just moved out of its last position.
+///
+/// `swap_remove` leaves a stale copy of the moved key past the end of the keys, whether they are
+/// stored inline or on the heap. With the `zeroize` feature it is zeroed; without it this does
+/// nothing.
+#[cfg(feature = "zeroize")]
+pub(crate) fn popped<T>(keys: &mut GroupKeys<T>) {
+    if keys.len() == keys.capacity() {
+        return;
+    }
+    // SAFETY: the position past the last key is within the storage of `keys`, and no longer
+    // holds a key `keys` owns, so overwriting it drops nothing.
+    let stale = unsafe { &mut *keys.as_mut_ptr().add(keys.len()).cast::<MaybeUninit<T>>() };
+    stale.zeroize();
+}
+
+#[cfg(not(feature = "zeroize"))]
+#[inline(always)]
+pub(crate) fn vacated<T>(_value: &mut Option<T>) {}
+
+#[cfg(not(feature = "zeroize"))]
+#[inline(always)]
+pub(crate) fn popped<T>(_keys: &mut GroupKeys<T>) {}
+
+/// Zeroizes every value, then removes all keys and values.
+///
+/// Keys are dropped without being zeroized, unless they zeroize themselves on drop.
+///
+/// This is the only place a map zeroizes values that merely implement [`Zeroize`], such as
+/// [`String`]: [`clear`](MultiKeyMap::clear), [`remove`](MultiKeyMap::remove) and dropping the
+/// map release them as they are. To have every path zeroize them, wrap the values in
+/// [`Zeroizing`](zeroize::Zeroizing), so that the map is [`ZeroizeOnDrop`].
+///
+/// This implementation is only available with the `zeroize` feature.
+///
+/// # Examples
+///
+/// ```
+/// use multi_key_map::MultiKeyMap;
+/// use std::cell::Cell;
+/// use std::rc::Rc;
+/// use zeroize::Zeroize;
+///
+/// struct Token(Rc<Cell<u32>>);
+///
+/// impl Zeroize for Token {
+///     fn zeroize(&mut self) {
+///         self.0.set(self.0.get() + 1);
+///     }
+/// }
+///
+/// let zeroized = Rc::new(Cell::new(0));
+/// let mut tokens = MultiKeyMap::new();
+/// for name in ["a", "b", "c"] {
+///     tokens.insert(name, Token(zeroized.clone()));
+/// }
+/// tokens.insert_alias(&"a", "alias");
+///
+/// // Without a wrapper, removing and clearing leave the values as they are
+/// drop(tokens.remove(&"a"));
+/// tokens.clear();
+/// assert_eq!(zeroized.get(), 0);
+///
+/// for name in ["d", "e"] {
+///     tokens.insert(name, Token(zeroized.clone()));
+/// }
+/// tokens.zeroize();
+/// assert_eq!(zeroized.get(), 2);
+/// assert!(tokens.is_empty());
+/// ```
+#[cfg(feature = "zeroize")]
+impl<K: Eq + Hash, V: Zeroize> Zeroize for MultiKeyMap<K, V> {
+    fn zeroize(&mut self) {
+        for slot in &mut self.slots {
+            if let Some(value) = &mut slot.value {
+                value.zeroize();
+            }
+        }
+        self.clear();
+    }
+}
+
+/// A map of values that zeroize themselves on drop, such as
+/// [`Zeroizing`](zeroize::Zeroizing) values, zeroizes every value it drops or hands out.
+///
+/// Values are dropped in place by [`clear`](MultiKeyMap::clear) and when the map is dropped, and
+/// the bytes left behind by values moved out of the map by [`remove`](MultiKeyMap::remove) or
+/// [`remove_alias`](MultiKeyMap::remove_alias) are zeroed. Likewise, removing an alias zeroes
+/// the stale copy of the key moved into its place.
+///
+/// Values that implement [`Zeroize`] but do not zeroize themselves on drop, such as [`String`],
+/// are not zeroized by any of these paths: callers must wrap them in
+/// [`Zeroizing`](zeroize::Zeroizing), or call [`zeroize`](Zeroize::zeroize) on the map before
+/// clearing or dropping it. The map also moves its values when its storage grows, leaving copies
+/// of their inline bytes behind; wrapped values such as `Zeroizing<String>` keep their contents
+/// on the heap, where they are not moved.
+///
+/// This implementation is only available with the `zeroize` feature.
+///
+/// # Examples
+///
+/// ```
+/// use multi_key_map::MultiKeyMap;
+/// use std::cell::Cell;
+/// use std::rc::Rc;
+/// use zeroize::{Zeroize, ZeroizeOnDrop};
+///
+/// struct Token(Rc<Cell<u32>>);
+///
+/// impl Zeroize for Token {
+///     fn zeroize(&mut self) {
+///         self.0.set(self.0.get() + 1);
+///     }
+/// }
+///
+/// impl Drop for Token {
+///     fn drop(&mut self) {
+///         self.zeroize();
+///     }
+/// }
+///
+/// impl ZeroizeOnDrop for Token {}
+///
+/// let zeroized = Rc::new(Cell::new(0));
+/// let mut tokens = MultiKeyMap::new();
+/// for name in ["a", "b", "c", "d"] {
+///     tokens.insert(name, Token(zeroized.clone()));
+/// }
+/// tokens.insert_alias(&"a", "alias");
+///
+/// drop(tokens.remove(&"alias"));
+/// assert_eq!(zeroized.get(), 1);
+/// tokens.remove_alias(&"b");
+/// assert_eq!(zeroized.get(), 2);
+/// tokens.clear();
+/// assert_eq!(zeroized.get(), 4);
+///
+/// tokens.insert("e", Token(zeroized.clone()));
+/// drop(tokens);
+/// assert_eq!(zeroized.get(), 5);
+/// ```
+///
+/// Values that only implement [`Zeroize`] are zeroized on every path once wrapped:
+///
+/// ```
+/// use multi_key_map::MultiKeyMap;
+/// use std::cell::Cell;
+/// use std::rc::Rc;
+/// use zeroize::{Zeroize, Zeroizing};
+///
+/// struct Token(Rc<Cell<u32>>);
+///
+/// impl Zeroize for Token {
+///     fn zeroize(&mut self) {
+///         self.0.set(self.0.get() + 1);
+///     }
+/// }
+///
+/// let zeroized = Rc::new(Cell::new(0));
+/// let mut tokens = MultiKeyMap::new();
+/// for name in ["a", "b", "c", "d"] {
+///     tokens.insert(name, Zeroizing::new(Token(zeroized.clone())));
+/// }
+/// tokens.insert_alias(&"a", "alias");
+///
+/// drop(tokens.remove(&"alias"));
+/// assert_eq!(zeroized.get(), 1);
+/// tokens.remove_alias(&"b");
+/// assert_eq!(zeroized.get(), 2);
+/// tokens.clear();
+/// assert_eq!(zeroized.get(), 4);
+///
+/// tokens.insert("e", Zeroizing::new(Token(zeroized.clone())));
+/// drop(tokens);
+/// assert_eq!(zeroized.get(), 5);
+/// ```
+#[cfg(feature = "zeroize")]
+impl<K, V: ZeroizeOnDrop> ZeroizeOnDrop for MultiKeyMap<K, V> {}
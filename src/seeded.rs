@@ -0,0 +1,163 @@
+use crate::MultiKeyMap;
+use std::hash::{BuildHasher, DefaultHasher, Hash, Hasher, RandomState};
+use std::sync::OnceLock;
+
+/// A [`BuildHasher`] whose hashes depend only on its seed, so that every process using the same
+/// seed computes the same hashes.
+///
+/// Hashes are computed with the [`DefaultHasher`] of the standard library, keyed with fixed keys
+/// and fed the seed first. They are reproducible across runs of the same build, but not
+/// necessarily across Rust versions, since the algorithm of `DefaultHasher` is unspecified.
+///
+/// # Security
+///
+/// [`RandomState`] picks random keys so that no one can predict which keys collide. With a
+/// known seed anyone can, and a party choosing the keys of a map can make every key land in the
+/// same bucket, turning each lookup into a linear scan. Use this only with trusted keys, as in
+/// tests and reproducible tooling, never for keys from untrusted input.
+///
+/// # Examples
+///
+/// ```
+/// use multi_key_map::DeterministicState;
+/// use std::hash::BuildHasher;
+///
+/// let a = DeterministicState::with_seed(7);
+/// let b = DeterministicState::with_seed(7);
+/// assert_eq!(a.hash_one("key"), b.hash_one("key"));
+///
+/// // Different seeds give unrelated hashes
+/// let c = DeterministicState::with_seed(8);
+/// let differing = (0..100).filter(|i| a.hash_one(i) != c.hash_one(i)).count();
+/// assert!(differing > 95);
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct DeterministicState {
+    seed: u64,
+}
+
+impl DeterministicState {
+    /// Creates a `DeterministicState` with the fixed seed `0`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::DeterministicState;
+    ///
+    /// assert_eq!(DeterministicState::new(), DeterministicState::with_seed(0));
+    /// ```
+    pub fn new() -> Self {
+        Self::with_seed(0)
+    }
+
+    /// Creates a `DeterministicState` with the given seed.
+    ///
+    /// # Arguments
+    ///
+    /// * `seed` - The seed every hash depends on.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::DeterministicState;
+    ///
+    /// assert_eq!(DeterministicState::with_seed(42).seed(), 42);
+    /// ```
+    pub fn with_seed(seed: u64) -> Self {
+        DeterministicState { seed }
+    }
+
+    /// Returns the seed of the state.
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+}
+
+impl BuildHasher for DeterministicState {
+    type Hasher = DefaultHasher;
+
+    fn build_hasher(&self) -> DefaultHasher {
+        let mut hasher = DefaultHasher::new();
+        hasher.write_u64(self.seed);
+        hasher
+    }
+}
+
+/// The hasher state of a [`MultiKeyMap`]: random by default, or seeded through
+/// [`MultiKeyMap::with_seed`].
+///
+/// The random state is only created when the first key is hashed, since creating it is not
+/// possible in a const context such as [`MultiKeyMap::new`].
+#[derive(Debug, Clone)]
+pub(crate) enum MapState {
+    Random(OnceLock<RandomState>),
+    Deterministic(DeterministicState),
+}
+
+impl MapState {
+    pub(crate) const fn new() -> Self {
+        MapState::Random(OnceLock::new())
+    }
+}
+
+impl Default for MapState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl BuildHasher for MapState {
+    type Hasher = DefaultHasher;
+
+    fn build_hasher(&self) -> DefaultHasher {
+        match self {
+            MapState::Random(state) => state.get_or_init(RandomState::new).build_hasher(),
+            MapState::Deterministic(state) => state.build_hasher(),
+        }
+    }
+}
+
+impl<K: Eq + Hash, V> MultiKeyMap<K, V> {
+    /// Creates an empty `MultiKeyMap` hashing its keys with a [`DeterministicState`] seeded with
+    /// `seed`.
+    ///
+    /// The order of [`keys`](Self::keys) and of the [`Debug`](std::fmt::Debug) output follows the
+    /// slots of the groups, and so only depends on the operations applied to the map, whatever
+    /// its hasher. The seed makes everything else about the key index reproducible as well, such
+    /// as where each key is stored and which keys collide, so that two processes building the
+    /// same map with the same seed lay it out identically.
+    ///
+    /// Read the [security notes](DeterministicState#security) first: a known seed exposes the map
+    /// to collision attacks on untrusted keys.
+    ///
+    /// # Arguments
+    ///
+    /// * `seed` - The seed of the hasher.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyMap;
+    ///
+    /// let build = |seed| {
+    ///     let mut map = MultiKeyMap::with_seed(seed);
+    ///     for i in 0..100 {
+    ///         map.insert(i, i * 2);
+    ///         map.insert_alias(&i, i + 1000);
+    ///     }
+    ///     for i in (0..100).step_by(3) {
+    ///         map.remove(&i);
+    ///     }
+    ///     map
+    /// };
+    /// let (a, b) = (build(5), build(5));
+    /// assert!(a.keys().eq(b.keys()));
+    /// assert_eq!(format!("{a:?}"), format!("{b:?}"));
+    /// assert_eq!(a.get(&1001), Some(&2));
+    /// ```
+    pub fn with_seed(seed: u64) -> Self {
+        let mut map = Self::new();
+        map.hasher = MapState::Deterministic(DeterministicState::with_seed(seed));
+        map
+    }
+}
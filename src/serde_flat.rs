@@ -0,0 +1,116 @@
+//! Serialization of a [`MultiKeyMap`] as a plain map with one entry per key, for use with
+//! `#[serde(with = "multi_key_map::serde_flat")]`.
+//!
+//! Serializing writes every group once per alias, repeating its value. Deserializing regroups
+//! keys whose values compare equal, so a map round-trips with its alias groups intact as long as
+//! different groups hold different values. The search for an equal value is linear in the number
+//! of groups. Use [`serde_flat_ungrouped`](crate::serde_flat_ungrouped) to keep every key in a
+//! group of its own instead.
+//!
+//! A key appearing twice in the input is rejected. Serializing a map with
+//! [soft aliases](MultiKeyMap::insert_soft_alias) or [disabled keys](MultiKeyMap::disable_alias)
+//! fails, as they would come back as plain aliases.
+//!
+//! This module is only available with the `serde` feature.
+//!
+//! # Examples
+//!
+//! ```
+//! use multi_key_map::MultiKeyMap;
+//! use serde::{Deserialize, Serialize};
+//!
+//! #[derive(Serialize, Deserialize)]
+//! struct Registry {
+//!     #[serde(with = "multi_key_map::serde_flat")]
+//!     ports: MultiKeyMap<String, u16>,
+//! }
+//!
+//! let mut ports = MultiKeyMap::new();
+//! ports.insert("http".to_string(), 80);
+//! ports.insert_alias(&"http".to_string(), "www".to_string());
+//! ports.insert("ssh".to_string(), 22);
+//!
+//! let json = serde_json::to_value(Registry { ports }).unwrap();
+//! assert_eq!(
+//!     json,
+//!     serde_json::json!({ "ports": { "http": 80, "www": 80, "ssh": 22 } })
+//! );
+//!
+//! let json = r#"{ "ports": { "http": 80, "ssh": 22, "www": 80, "alt": 8080 } }"#;
+//! let registry: Registry = serde_json::from_str(json).unwrap();
+//! assert_eq!(registry.ports.len(), 3);
+//! assert!(registry.ports.are_aliases(&"http".to_string(), &"www".to_string()));
+//! assert!(!registry.ports.are_aliases(&"http".to_string(), &"alt".to_string()));
+//!
+//! let json = r#"{ "ports": { "http": 80, "http": 8080 } }"#;
+//! assert!(serde_json::from_str::<Registry>(json).is_err());
+//! ```
+
+use crate::MultiKeyMap;
+use serde::de::{self, Deserialize, Deserializer, MapAccess, Visitor};
+use serde::ser::{self, Serialize, SerializeMap, Serializer};
+use std::fmt;
+use std::hash::Hash;
+
+/// Serializes `map` as a map with an entry for every key.
+pub fn serialize<K, V, S>(map: &MultiKeyMap<K, V>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    K: Serialize,
+    V: Serialize,
+    S: Serializer,
+{
+    if let Some(error) = map.serialization_error() {
+        return Err(ser::Error::custom(error));
+    }
+    let mut entries = serializer.serialize_map(Some(map.key_index.len()))?;
+    for slot in &map.slots {
+        if let Some(value) = &slot.value {
+            for key in &slot.keys {
+                entries.serialize_entry(key, value)?;
+            }
+        }
+    }
+    entries.end()
+}
+
+/// Deserializes a map with an entry for every key, grouping keys whose values are equal.
+pub fn deserialize<'de, K, V, D>(deserializer: D) -> Result<MultiKeyMap<K, V>, D::Error>
+where
+    K: Eq + Hash + Deserialize<'de>,
+    V: Eq + Hash + Deserialize<'de>,
+    D: Deserializer<'de>,
+{
+    deserializer.deserialize_map(FlatVisitor {
+        insert: |map, key, value| {
+            map.insert_or_alias(key, value);
+        },
+    })
+}
+
+/// Collects the entries of a flat map, adding each one with `insert`.
+pub(crate) struct FlatVisitor<K, V> {
+    pub(crate) insert: fn(&mut MultiKeyMap<K, V>, K, V),
+}
+
+impl<'de, K, V> Visitor<'de> for FlatVisitor<K, V>
+where
+    K: Eq + Hash + Deserialize<'de>,
+    V: Deserialize<'de>,
+{
+    type Value = MultiKeyMap<K, V>;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a map")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut entries: A) -> Result<Self::Value, A::Error> {
+        let mut map = MultiKeyMap::new();
+        while let Some((key, value)) = entries.next_entry::<K, V>()? {
+            if map.contains_key(&key) {
+                return Err(de::Error::custom("a key appears twice"));
+            }
+            (self.insert)(&mut map, key, value);
+        }
+        Ok(map)
+    }
+}
@@ -0,0 +1,52 @@
+//! Serialization of a [`MultiKeyMap`] as a plain map with one entry per key, for use with
+//! `#[serde(with = "multi_key_map::serde_flat_ungrouped")]`.
+//!
+//! Serializing is the same as with [`serde_flat`](crate::serde_flat), but deserializing puts
+//! every key in a group of its own, even if its value equals that of another key, so values need
+//! not implement `PartialEq`.
+//!
+//! A key appearing twice in the input is rejected.
+//!
+//! This module is only available with the `serde` feature.
+//!
+//! # Examples
+//!
+//! ```
+//! use multi_key_map::MultiKeyMap;
+//! use serde::{Deserialize, Serialize};
+//!
+//! #[derive(Serialize, Deserialize)]
+//! struct Registry {
+//!     #[serde(with = "multi_key_map::serde_flat_ungrouped")]
+//!     ports: MultiKeyMap<String, u16>,
+//! }
+//!
+//! let json = r#"{ "ports": { "http": 80, "www": 80, "ssh": 22 } }"#;
+//! let registry: Registry = serde_json::from_str(json).unwrap();
+//! assert_eq!(registry.ports.len(), 3);
+//! assert!(!registry.ports.are_aliases(&"http".to_string(), &"www".to_string()));
+//!
+//! let mut json = serde_json::to_value(&registry).unwrap();
+//! json["ports"]["alt"] = 8080.into();
+//! let registry: Registry = serde_json::from_value(json).unwrap();
+//! assert_eq!(registry.ports.get(&"alt".to_string()), Some(&8080));
+//! ```
+
+use crate::serde_flat::FlatVisitor;
+use crate::MultiKeyMap;
+use serde::de::{Deserialize, Deserializer};
+use std::hash::Hash;
+
+pub use crate::serde_flat::serialize;
+
+/// Deserializes a map with an entry for every key, each in a group of its own.
+pub fn deserialize<'de, K, V, D>(deserializer: D) -> Result<MultiKeyMap<K, V>, D::Error>
+where
+    K: Eq + Hash + Deserialize<'de>,
+    V: Deserialize<'de>,
+    D: Deserializer<'de>,
+{
+    deserializer.deserialize_map(FlatVisitor {
+        insert: MultiKeyMap::insert,
+    })
+}
@@ -0,0 +1,269 @@
+use crate::MultiKeyMap;
+use serde::de::{self, Deserialize, Deserializer, SeqAccess, Visitor};
+use serde::ser::{self, Serialize, SerializeSeq, Serializer};
+use std::fmt;
+use std::hash::Hash;
+use std::marker::PhantomData;
+
+/// Serializes the map as a sequence of `(keys, value)` groups, so every value is written once
+/// along with all of its aliases.
+///
+/// The groups are written in storage order, and the first key of each group is its
+/// [primary key](MultiKeyMap::primary_of).
+///
+/// The representation only uses sequences and tuples whose lengths are known upfront, so it suits
+/// non-self-describing formats such as bincode or postcard as well as self-describing ones. It is
+/// part of the stable interface of the crate: the bytes written for a map do not change between
+/// versions.
+///
+/// The representation has no room for [soft aliases](MultiKeyMap::insert_soft_alias) or
+/// [disabled keys](MultiKeyMap::disable_alias), so serializing a map holding any fails with an
+/// error rather than losing them.
+///
+/// This implementation is only available with the `serde` feature.
+///
+/// # Examples
+///
+/// ```
+/// use multi_key_map::MultiKeyMap;
+///
+/// let mut map = MultiKeyMap::new();
+/// map.insert("a", 1);
+/// map.insert_alias(&"a", "alias");
+/// map.insert("b", 2);
+///
+/// let json = serde_json::to_string(&map).unwrap();
+/// assert_eq!(json, r#"[[["a","alias"],1],[["b"],2]]"#);
+///
+/// map.insert_soft_alias(&"b", "latest");
+/// let error = serde_json::to_string(&map).unwrap_err();
+/// assert_eq!(error.to_string(), "a map with soft aliases cannot be serialized");
+///
+/// map.remove_soft_alias(&"latest");
+/// map.disable_alias(&"alias");
+/// let error = serde_json::to_string(&map).unwrap_err();
+/// assert_eq!(error.to_string(), "a map with disabled keys cannot be serialized");
+/// ```
+///
+/// With bincode, every sequence is prefixed by its length and the tuples are written field by
+/// field:
+///
+/// ```
+/// use multi_key_map::MultiKeyMap;
+///
+/// let mut map: MultiKeyMap<u16, u32> = MultiKeyMap::new();
+/// map.insert(1, 7);
+/// map.insert_alias(&1, 2);
+///
+/// let bytes = bincode::serialize(&map).unwrap();
+/// #[rustfmt::skip]
+/// assert_eq!(bytes, [
+///     1, 0, 0, 0, 0, 0, 0, 0, // one group
+///     2, 0, 0, 0, 0, 0, 0, 0, // with two keys
+///     1, 0,
+///     2, 0,
+///     7, 0, 0, 0,             // and its value
+/// ]);
+/// ```
+impl<K: Serialize, V: Serialize> Serialize for MultiKeyMap<K, V> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if let Some(error) = self.serialization_error() {
+            return Err(ser::Error::custom(error));
+        }
+        let mut seq = serializer.serialize_seq(Some(self.len))?;
+        for slot in &self.slots {
+            if let Some(value) = &slot.value {
+                seq.serialize_element(&(&slot.keys[..], value))?;
+            }
+        }
+        seq.end()
+    }
+}
+
+/// Deserializes a map from a sequence of `(keys, value)` groups, as written by its
+/// [`Serialize`] implementation.
+///
+/// Every group must have at least one key, and no key may appear twice, whether in the same
+/// group or in different ones. Input breaking either rule is rejected with an error naming the
+/// offending groups.
+///
+/// This implementation is only available with the `serde` feature.
+///
+/// # Examples
+///
+/// ```
+/// use multi_key_map::MultiKeyMap;
+///
+/// let mut map = MultiKeyMap::new();
+/// map.insert(1, "one");
+/// map.insert_alias(&1, 10);
+/// map.insert_alias(&1, 100);
+/// map.insert(2, "two");
+///
+/// let json = serde_json::to_string(&map).unwrap();
+/// let restored: MultiKeyMap<u32, String> = serde_json::from_str(&json).unwrap();
+/// assert_eq!(restored.len(), 2);
+/// assert!(restored.are_aliases(&1, &100));
+/// assert_eq!(restored.primary_of(&10), Some(&1));
+/// assert_eq!(restored.get(&2).map(String::as_str), Some("two"));
+///
+/// let empty = serde_json::from_str::<MultiKeyMap<u32, u32>>("[[[1], 1], [[], 2]]");
+/// assert_eq!(
+///     empty.unwrap_err().to_string(),
+///     "group 1 has no keys at line 1 column 19"
+/// );
+///
+/// let shared = serde_json::from_str::<MultiKeyMap<u32, u32>>("[[[1, 2], 1], [[3, 2], 2]]");
+/// assert_eq!(
+///     shared.unwrap_err().to_string(),
+///     "a key appears in both group 0 and group 1 at line 1 column 26"
+/// );
+///
+/// let repeated = serde_json::from_str::<MultiKeyMap<u32, u32>>("[[[1, 1], 1]]");
+/// assert_eq!(
+///     repeated.unwrap_err().to_string(),
+///     "a key appears twice in group 0 at line 1 column 13"
+/// );
+/// ```
+///
+/// Maps round-trip through non-self-describing formats too:
+///
+/// ```
+/// use multi_key_map::MultiKeyMap;
+/// use serde::{Deserialize, Serialize};
+///
+/// #[derive(Debug, PartialEq, Serialize, Deserialize)]
+/// struct User {
+///     name: String,
+///     age: u8,
+/// }
+///
+/// let mut map = MultiKeyMap::new();
+/// map.insert(1u64, User { name: "ada".into(), age: 36 });
+/// map.insert_alias(&1, 100);
+/// map.insert(2, User { name: "alan".into(), age: 41 });
+/// map.remove(&2);
+/// map.insert(3, User { name: "grace".into(), age: 85 });
+///
+/// let bytes = bincode::serialize(&map).unwrap();
+/// let restored: MultiKeyMap<u64, User> = bincode::deserialize(&bytes).unwrap();
+/// assert_eq!(restored, map);
+/// assert_eq!(restored.primary_of(&100), Some(&1));
+///
+/// let bytes = postcard::to_allocvec(&map).unwrap();
+/// let restored: MultiKeyMap<u64, User> = postcard::from_bytes(&bytes).unwrap();
+/// assert_eq!(restored, map);
+/// assert_eq!(restored.get(&3).map(|user| user.age), Some(85));
+///
+/// let shared = postcard::to_allocvec(&vec![(vec![1u64], 1u32), (vec![1], 2)]).unwrap();
+/// assert!(postcard::from_bytes::<MultiKeyMap<u64, u32>>(&shared).is_err());
+/// ```
+impl<'de, K, V> Deserialize<'de> for MultiKeyMap<K, V>
+where
+    K: Eq + Hash + Deserialize<'de>,
+    V: Deserialize<'de>,
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_seq(GroupsVisitor(PhantomData))
+    }
+}
+
+struct GroupsVisitor<K, V>(PhantomData<(K, V)>);
+
+impl<'de, K, V> Visitor<'de> for GroupsVisitor<K, V>
+where
+    K: Eq + Hash + Deserialize<'de>,
+    V: Deserialize<'de>,
+{
+    type Value = MultiKeyMap<K, V>;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a sequence of (keys, value) groups")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut map = MultiKeyMap::new();
+        while let Some((keys, value)) = seq.next_element::<(Vec<K>, V)>()? {
+            map.push_checked_group(keys, value)
+                .map_err(de::Error::custom)?;
+        }
+        Ok(map)
+    }
+}
+
+impl<K: Eq + Hash, V> MultiKeyMap<K, V> {
+    /// Serializes the map like its [`Serialize`] implementation, but in an order that only
+    /// depends on the contents of the map.
+    ///
+    /// The keys of each group are sorted, and the groups are sorted by their smallest key. Equal
+    /// maps therefore serialize identically, however they were built, which keeps serialized
+    /// snapshots diffable. As the smallest key of every group comes first, it becomes the
+    /// [primary key](Self::primary_of) of its group when deserialized.
+    ///
+    /// It can be used with `#[serde(serialize_with = "MultiKeyMap::serialize_canonical")]` on
+    /// struct fields.
+    ///
+    /// This method is only available with the `serde` feature.
+    ///
+    /// # Arguments
+    ///
+    /// * `serializer` - The serializer to write to.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the map has soft aliases or disabled keys, as its [`Serialize`]
+    /// implementation does, and the errors of `serializer`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyMap;
+    ///
+    /// fn canonical_json(map: &MultiKeyMap<&str, u32>) -> String {
+    ///     let mut json = Vec::new();
+    ///     map.serialize_canonical(&mut serde_json::Serializer::new(&mut json))
+    ///         .unwrap();
+    ///     String::from_utf8(json).unwrap()
+    /// }
+    ///
+    /// let mut first = MultiKeyMap::new();
+    /// first.insert("b", 2);
+    /// first.insert_alias(&"b", "z");
+    /// first.insert("a", 1);
+    /// first.insert_alias(&"a", "y");
+    /// first.insert_alias(&"a", "x");
+    ///
+    /// // Built in another order, with removals shuffling the storage
+    /// let mut second = MultiKeyMap::new();
+    /// second.insert("c", 3);
+    /// second.insert("x", 1);
+    /// second.insert_alias(&"x", "a");
+    /// second.insert("z", 0);
+    /// second.remove(&"c");
+    /// second.insert("y", 1);
+    /// second.remove(&"y");
+    /// second.insert_alias(&"a", "y");
+    /// second.insert("b", 2);
+    /// second.insert_alias(&"b", "z");
+    ///
+    /// assert_eq!(first, second);
+    /// assert_ne!(serde_json::to_string(&first).unwrap(), serde_json::to_string(&second).unwrap());
+    /// assert_eq!(canonical_json(&first), canonical_json(&second));
+    /// assert_eq!(canonical_json(&first), r#"[[["a","x","y"],1],[["b","z"],2]]"#);
+    /// ```
+    pub fn serialize_canonical<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        K: Ord + Serialize,
+        V: Serialize,
+    {
+        if let Some(error) = self.serialization_error() {
+            return Err(ser::Error::custom(error));
+        }
+        let groups = self.sorted_groups();
+        let mut seq = serializer.serialize_seq(Some(groups.len()))?;
+        for group in &groups {
+            seq.serialize_element(group)?;
+        }
+        seq.end()
+    }
+}
@@ -0,0 +1,332 @@
+use crate::MultiKeyMap;
+use std::fmt::{self, Debug};
+use std::hash::Hash;
+
+/// A `MultiKeySet` tracks which keys name the same thing, without a payload.
+///
+/// Keys are partitioned into sets. [`alias`](Self::alias) puts two keys into the same set,
+/// merging their sets if both already exist, so aliasing is transitive.
+pub struct MultiKeySet<K> {
+    map: MultiKeyMap<K, ()>,
+}
+
+impl<K: Eq + Hash> MultiKeySet<K> {
+    /// Creates an empty `MultiKeySet`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeySet;
+    ///
+    /// let set: MultiKeySet<&str> = MultiKeySet::new();
+    /// ```
+    pub fn new() -> Self {
+        MultiKeySet {
+            map: MultiKeyMap::new(),
+        }
+    }
+
+    /// Inserts `key` as a set of its own.
+    ///
+    /// Returns `false` and leaves the set unchanged if `key` is already present.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key to insert.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeySet;
+    ///
+    /// let mut set = MultiKeySet::new();
+    /// assert!(set.insert("a"));
+    /// assert!(!set.insert("a"));
+    /// assert_eq!(set.len(), 1);
+    /// ```
+    pub fn insert(&mut self, key: K) -> bool {
+        if self.map.contains_key(&key) {
+            return false;
+        }
+        self.map.insert(key, ());
+        true
+    }
+
+    /// Puts `a` and `b` into the same set and returns the size of that set.
+    ///
+    /// Keys that are not present yet are added. If `a` and `b` are in different sets, the two
+    /// sets are merged.
+    ///
+    /// # Arguments
+    ///
+    /// * `a` - A key of the set.
+    /// * `b` - Another key of the set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeySet;
+    ///
+    /// let mut set = MultiKeySet::new();
+    /// assert_eq!(set.alias("a", "b"), 2);
+    /// assert_eq!(set.alias("c", "d"), 2);
+    /// assert_eq!(set.alias("b", "e"), 3);
+    /// assert_eq!(set.len(), 2);
+    ///
+    /// // Bridging the two sets merges them, so aliasing is transitive
+    /// assert_eq!(set.alias("e", "d"), 5);
+    /// assert_eq!(set.len(), 1);
+    /// assert!(set.same_set(&"a", &"c"));
+    /// assert_eq!(set.alias("a", "c"), 5);
+    /// ```
+    pub fn alias(&mut self, a: K, b: K) -> usize {
+        if a == b {
+            self.insert(a);
+            return self.set_of(&b).map_or(0, <[K]>::len);
+        }
+        let map = &mut self.map;
+        let index = match (map.index_of(&a), map.index_of(&b)) {
+            (Some(first), Some(second)) if first == second => first,
+            (Some(first), Some(second)) => {
+                // Keep the lower slot, as merges of the map do
+                let (root, merged) = (first.min(second), first.max(second));
+                map.relocate(merged, root);
+                map.release(merged);
+                root
+            }
+            (Some(index), None) => {
+                map.link(b, index);
+                index
+            }
+            (None, Some(index)) => {
+                map.link(a, index);
+                index
+            }
+            (None, None) => {
+                let index = map.alloc(());
+                map.link(a, index);
+                map.link(b, index);
+                index
+            }
+        };
+        map.count_references(index)
+    }
+
+    /// Returns `true` if `a` and `b` are in the same set.
+    ///
+    /// # Arguments
+    ///
+    /// * `a` - A key to check.
+    /// * `b` - Another key to check.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeySet;
+    ///
+    /// let mut set = MultiKeySet::new();
+    /// set.alias("a", "b");
+    /// set.insert("c");
+    /// assert!(set.same_set(&"a", &"b"));
+    /// assert!(!set.same_set(&"a", &"c"));
+    /// assert!(!set.same_set(&"a", &"z"));
+    /// ```
+    pub fn same_set(&self, a: &K, b: &K) -> bool {
+        self.map.are_aliases(a, b)
+    }
+
+    /// Returns the keys of the set containing `key`, in no particular order.
+    ///
+    /// Returns `None` if the key does not exist.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - A key of the set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeySet;
+    ///
+    /// let mut set = MultiKeySet::new();
+    /// set.alias("a", "b");
+    /// let mut keys = set.set_of(&"b").unwrap().to_vec();
+    /// keys.sort();
+    /// assert_eq!(keys, vec!["a", "b"]);
+    /// assert_eq!(set.set_of(&"z"), None);
+    /// ```
+    pub fn set_of(&self, key: &K) -> Option<&[K]> {
+        let index = self.map.index_of(key)?;
+        Some(&self.map.slots[index].keys)
+    }
+
+    /// Returns an iterator over the sets, each given as its keys.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeySet;
+    ///
+    /// let mut set = MultiKeySet::new();
+    /// set.alias("a", "b");
+    /// set.insert("c");
+    /// let mut sizes: Vec<_> = set.sets().map(<[_]>::len).collect();
+    /// sizes.sort();
+    /// assert_eq!(sizes, vec![1, 2]);
+    /// ```
+    pub fn sets(&self) -> impl Iterator<Item = &[K]> + '_ {
+        self.map
+            .slots
+            .iter()
+            .filter(|slot| slot.value.is_some())
+            .map(|slot| slot.keys.as_slice())
+    }
+
+    /// Removes `key` from its set.
+    ///
+    /// The other keys of the set stay together; a set is only dropped once its last key is
+    /// removed. Returns `false` if the key does not exist.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key to remove.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeySet;
+    ///
+    /// let mut set = MultiKeySet::new();
+    /// set.alias("a", "b");
+    /// set.alias("b", "c");
+    /// assert!(set.remove(&"b"));
+    /// assert!(!set.remove(&"b"));
+    /// assert!(set.same_set(&"a", &"c"));
+    /// assert_eq!(set.len(), 1);
+    ///
+    /// set.remove(&"a");
+    /// set.remove(&"c");
+    /// assert!(set.is_empty());
+    /// ```
+    pub fn remove(&mut self, key: &K) -> bool {
+        self.map.remove_alias(key).is_some()
+    }
+
+    /// Returns `true` if the set contains `key`.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key to check.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeySet;
+    ///
+    /// let mut set = MultiKeySet::new();
+    /// set.insert("a");
+    /// assert!(set.contains(&"a"));
+    /// assert!(!set.contains(&"b"));
+    /// ```
+    pub fn contains(&self, key: &K) -> bool {
+        self.map.contains_key(key)
+    }
+
+    /// Returns the number of sets.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeySet;
+    ///
+    /// let mut set = MultiKeySet::new();
+    /// set.alias("a", "b");
+    /// set.insert("c");
+    /// assert_eq!(set.len(), 2);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    /// Returns the number of keys across all sets.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeySet;
+    ///
+    /// let mut set = MultiKeySet::new();
+    /// set.alias("a", "b");
+    /// set.insert("c");
+    /// assert_eq!(set.key_count(), 3);
+    /// ```
+    pub fn key_count(&self) -> usize {
+        self.map.key_count()
+    }
+
+    /// Returns `true` if the set contains no keys.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeySet;
+    ///
+    /// let set: MultiKeySet<&str> = MultiKeySet::new();
+    /// assert!(set.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    /// Returns an iterator over all keys, in no particular order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeySet;
+    ///
+    /// let mut set = MultiKeySet::new();
+    /// set.alias("a", "b");
+    /// assert_eq!(set.keys().count(), 2);
+    /// ```
+    pub fn keys(&self) -> impl Iterator<Item = &K> + '_ {
+        self.map.keys()
+    }
+}
+
+impl<K: Eq + Hash> Default for MultiKeySet<K> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Debug> Debug for MultiKeySet<K> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set()
+            .entries(
+                self.map
+                    .slots
+                    .iter()
+                    .filter(|slot| slot.value.is_some())
+                    .map(|slot| &slot.keys),
+            )
+            .finish()
+    }
+}
+
+impl<K: Eq + Hash> PartialEq for MultiKeySet<K> {
+    /// Two sets are equal if they partition the same keys the same way.
+    fn eq(&self, other: &Self) -> bool {
+        self.map.same_grouping(&other.map)
+    }
+}
+
+impl<K: Eq + Hash> Eq for MultiKeySet<K> {}
+
+impl<K: Eq + Hash + Clone + Debug> Clone for MultiKeySet<K> {
+    fn clone(&self) -> Self {
+        MultiKeySet {
+            map: self.map.clone(),
+        }
+    }
+}
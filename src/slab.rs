@@ -0,0 +1,101 @@
+/// Reference-counted value storage for the map variants that keep their keys elsewhere.
+///
+/// Values never move, so removals leave the indices of the others valid.
+#[derive(Clone)]
+pub(crate) struct Slab<V> {
+    entries: Vec<Entry<V>>,
+    /// Indices of the vacant entries, reused by later insertions.
+    free: Vec<usize>,
+    /// Number of occupied entries.
+    len: usize,
+}
+
+#[derive(Clone)]
+struct Entry<V> {
+    value: Option<V>,
+    /// Number of keys pointing to the entry.
+    ref_count: usize,
+}
+
+impl<V> Slab<V> {
+    pub(crate) fn new() -> Self {
+        Slab {
+            entries: Vec::new(),
+            free: Vec::new(),
+            len: 0,
+        }
+    }
+
+    /// Stores `value` with no references and returns its index.
+    pub(crate) fn insert(&mut self, value: V) -> usize {
+        self.len += 1;
+        let entry = Entry {
+            value: Some(value),
+            ref_count: 0,
+        };
+        match self.free.pop() {
+            Some(index) => {
+                self.entries[index] = entry;
+                index
+            }
+            None => {
+                self.entries.push(entry);
+                self.entries.len() - 1
+            }
+        }
+    }
+
+    /// Vacates the entry at `index` and returns its value.
+    pub(crate) fn remove(&mut self, index: usize) -> V {
+        let entry = &mut self.entries[index];
+        let value = entry.value.take().expect("removed entry is occupied");
+        entry.ref_count = 0;
+        self.free.push(index);
+        self.len -= 1;
+        value
+    }
+
+    pub(crate) fn get(&self, index: usize) -> &V {
+        self.entries[index]
+            .value
+            .as_ref()
+            .expect("keys only point to occupied entries")
+    }
+
+    pub(crate) fn get_mut(&mut self, index: usize) -> &mut V {
+        self.entries[index]
+            .value
+            .as_mut()
+            .expect("keys only point to occupied entries")
+    }
+
+    /// Records a new key pointing to the entry at `index`.
+    pub(crate) fn add_ref(&mut self, index: usize) {
+        self.entries[index].ref_count += 1;
+    }
+
+    /// Records that a key no longer points to the entry at `index`, removing the entry once no
+    /// key does. Returns the number of remaining references.
+    pub(crate) fn drop_ref(&mut self, index: usize) -> usize {
+        let entry = &mut self.entries[index];
+        entry.ref_count -= 1;
+        let remaining = entry.ref_count;
+        if remaining == 0 {
+            self.remove(index);
+        }
+        remaining
+    }
+
+    pub(crate) fn ref_count(&self, index: usize) -> usize {
+        self.entries[index].ref_count
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns a bound above every index handed out so far.
+    pub(crate) fn index_bound(&self) -> usize {
+        self.entries.len()
+    }
+}
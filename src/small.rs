@@ -0,0 +1,208 @@
+use crate::{KeyEntry, Slot};
+use hashbrown::HashTable;
+use smallvec::{Array, SmallVec};
+
+/// The number of keys a map indexes inline, searching them linearly, before moving its index to
+/// a hash table.
+pub(crate) const INLINE_KEYS: usize = 4;
+
+/// The number of groups a map stores inline before moving its slots to the heap.
+pub(crate) const INLINE_GROUPS: usize = 2;
+
+/// The number of keys a group stores inline before moving them to the heap.
+pub(crate) const INLINE_GROUP_KEYS: usize = 2;
+
+/// The slots of a map, stored inline while there are at most [`INLINE_GROUPS`] of them.
+pub(crate) type Slots<K, V> = SmallVec<[Slot<K, V>; INLINE_GROUPS]>;
+
+/// The keys of a group, stored inline while there are at most [`INLINE_GROUP_KEYS`] of them.
+pub(crate) type GroupKeys<K> = SmallVec<[K; INLINE_GROUP_KEYS]>;
+
+/// Shrinks the capacity of `items` as much as possible, keeping room for at least `min` items,
+/// and moves them back inline if they fit.
+pub(crate) fn shrink_to<A: Array>(items: &mut SmallVec<A>, min: usize) {
+    let capacity = items.len().max(min);
+    if capacity < items.capacity() {
+        items.grow(capacity);
+    }
+}
+
+/// The index of the keys of a map.
+///
+/// Up to [`INLINE_KEYS`] entries are kept inline and searched linearly, so small maps index their
+/// keys without allocating. Inserting past that moves the entries to a hash table, and removals
+/// move them back inline once at most half of [`INLINE_KEYS`] remain, so that a map hovering
+/// around the threshold does not reallocate on every insertion.
+#[derive(Clone)]
+pub(crate) enum KeyIndex {
+    Inline(SmallVec<[KeyEntry; INLINE_KEYS]>),
+    Table(HashTable<KeyEntry>),
+}
+
+impl KeyIndex {
+    pub(crate) const fn new() -> Self {
+        KeyIndex::Inline(SmallVec::new_const())
+    }
+
+    pub(crate) fn with_capacity(capacity: usize) -> Self {
+        if capacity <= INLINE_KEYS {
+            KeyIndex::new()
+        } else {
+            KeyIndex::Table(HashTable::with_capacity(capacity))
+        }
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        match self {
+            KeyIndex::Inline(entries) => entries.len(),
+            KeyIndex::Table(table) => table.len(),
+        }
+    }
+
+    pub(crate) fn capacity(&self) -> usize {
+        match self {
+            KeyIndex::Inline(_) => INLINE_KEYS,
+            KeyIndex::Table(table) => table.capacity(),
+        }
+    }
+
+    /// Removes every entry, moving the index back inline.
+    pub(crate) fn clear(&mut self) {
+        *self = KeyIndex::new();
+    }
+
+    /// Returns the entry with the given hash that `eq` accepts.
+    pub(crate) fn find(
+        &self,
+        hash: u64,
+        mut eq: impl FnMut(&KeyEntry) -> bool,
+    ) -> Option<&KeyEntry> {
+        match self {
+            KeyIndex::Inline(entries) => {
+                entries.iter().find(|entry| entry.hash == hash && eq(entry))
+            }
+            KeyIndex::Table(table) => table.find(hash, eq),
+        }
+    }
+
+    /// Returns the entry with the given hash that `eq` accepts, mutably.
+    pub(crate) fn find_mut(
+        &mut self,
+        hash: u64,
+        mut eq: impl FnMut(&KeyEntry) -> bool,
+    ) -> Option<&mut KeyEntry> {
+        match self {
+            KeyIndex::Inline(entries) => entries
+                .iter_mut()
+                .find(|entry| entry.hash == hash && eq(entry)),
+            KeyIndex::Table(table) => table.find_mut(hash, eq),
+        }
+    }
+
+    /// Removes the entry with the given hash that `eq` accepts and returns it.
+    pub(crate) fn remove(
+        &mut self,
+        hash: u64,
+        mut eq: impl FnMut(&KeyEntry) -> bool,
+    ) -> Option<KeyEntry> {
+        let removed = match self {
+            KeyIndex::Inline(entries) => {
+                let position = entries
+                    .iter()
+                    .position(|entry| entry.hash == hash && eq(entry))?;
+                return Some(entries.swap_remove(position));
+            }
+            KeyIndex::Table(table) => table.find_entry(hash, eq).ok()?.remove().0,
+        };
+        if self.len() <= INLINE_KEYS / 2 {
+            self.move_inline();
+        }
+        Some(removed)
+    }
+
+    /// Inserts `entry`, which no other entry locates the same key as.
+    pub(crate) fn insert_unique(&mut self, entry: KeyEntry) {
+        if let KeyIndex::Inline(entries) = self {
+            if entries.len() < INLINE_KEYS {
+                entries.push(entry);
+                return;
+            }
+            self.move_to_table(INLINE_KEYS + 1);
+        }
+        if let KeyIndex::Table(table) = self {
+            table.insert_unique(entry.hash, entry, |entry| entry.hash);
+        }
+    }
+
+    /// Reserves room for at least `additional` more entries.
+    pub(crate) fn reserve(&mut self, additional: usize) {
+        let needed = self.len() + additional;
+        match self {
+            KeyIndex::Inline(_) if needed > INLINE_KEYS => self.move_to_table(needed),
+            KeyIndex::Inline(_) => {}
+            KeyIndex::Table(table) => table.reserve(additional, |entry| entry.hash),
+        }
+    }
+
+    /// Shrinks the capacity as much as possible, keeping room for at least `min` entries, and
+    /// moves the index back inline if they fit.
+    pub(crate) fn shrink_to(&mut self, min: usize) {
+        if let KeyIndex::Table(table) = self {
+            if table.len().max(min) <= INLINE_KEYS {
+                self.move_inline();
+            } else {
+                table.shrink_to(min, |entry| entry.hash);
+            }
+        }
+    }
+
+    pub(crate) fn iter(&self) -> impl Iterator<Item = &KeyEntry> {
+        let (inline, table) = match self {
+            KeyIndex::Inline(entries) => (Some(entries.iter()), None),
+            KeyIndex::Table(table) => (None, Some(table.iter())),
+        };
+        inline
+            .into_iter()
+            .flatten()
+            .chain(table.into_iter().flatten())
+    }
+
+    pub(crate) fn iter_mut(&mut self) -> impl Iterator<Item = &mut KeyEntry> {
+        let (inline, table) = match self {
+            KeyIndex::Inline(entries) => (Some(entries.iter_mut()), None),
+            KeyIndex::Table(table) => (None, Some(table.iter_mut())),
+        };
+        inline
+            .into_iter()
+            .flatten()
+            .chain(table.into_iter().flatten())
+    }
+
+    /// Returns the entries that may have the given hash.
+    pub(crate) fn iter_hash(&self, hash: u64) -> impl Iterator<Item = &KeyEntry> {
+        let (inline, table) = match self {
+            KeyIndex::Inline(entries) => (Some(entries.iter()), None),
+            KeyIndex::Table(table) => (None, Some(table.iter_hash(hash))),
+        };
+        let inline = inline
+            .into_iter()
+            .flatten()
+            .filter(move |entry| entry.hash == hash);
+        inline.chain(table.into_iter().flatten())
+    }
+
+    /// Moves the entries to a hash table with room for at least `capacity` entries.
+    fn move_to_table(&mut self, capacity: usize) {
+        let mut table = HashTable::with_capacity(capacity);
+        for entry in self.iter() {
+            table.insert_unique(entry.hash, *entry, |entry| entry.hash);
+        }
+        *self = KeyIndex::Table(table);
+    }
+
+    /// Moves the entries inline. There must be at most [`INLINE_KEYS`] of them.
+    fn move_inline(&mut self) {
+        debug_assert!(self.len() <= INLINE_KEYS, "inline entries fit");
+        *self = KeyIndex::Inline(self.iter().copied().collect());
+    }
+}
@@ -0,0 +1,178 @@
+use crate::MultiKeyMap;
+use std::hash::{BuildHasher, Hash};
+
+impl<K: Eq + Hash, V> MultiKeyMap<K, V> {
+    /// Adds `link` as a soft alias of `target`: a key pointing to another key rather than to a
+    /// group.
+    ///
+    /// Unlike an alias added with [`insert_alias`](Self::insert_alias), a soft alias follows
+    /// its target: binding `target` to another group, as [`insert`](Self::insert) does,
+    /// re-points every soft alias of it at once. A soft alias may point to another soft alias,
+    /// and to a key that does not exist yet.
+    ///
+    /// Soft aliases are resolved by value lookups, that is [`get`](Self::get),
+    /// [`get_mut`](Self::get_mut), [`get_with_aliases`](Self::get_with_aliases),
+    /// [`replace`](Self::replace), [`modify_many`](Self::modify_many),
+    /// [`contains_key`](Self::contains_key), [`group_id`](Self::group_id) and loaders such as
+    /// [`get_or_try_insert_with`](Self::get_or_try_insert_with). These miss if the chain of soft
+    /// aliases ends at a key that does not exist or is disabled, or loops back on itself. The
+    /// other methods only see the keys bound to groups: [`aliases`](Self::aliases) does not list
+    /// soft aliases, and [`remove`](Self::remove) does not remove them. Removing the target
+    /// leaves its soft aliases dangling, so that they resolve again once the target is inserted
+    /// again; [`remove_soft_alias`](Self::remove_soft_alias) removes a soft alias itself.
+    ///
+    /// Binding `link` to a group later replaces the soft alias. Soft aliases are kept by clones,
+    /// [`map_keys`](Self::map_keys) and [`freeze`](Self::freeze), while the serialized forms of
+    /// the map cannot hold them, so serializing a map with soft aliases fails.
+    ///
+    /// Returns `false`, leaving the map unchanged, if `link` is already bound to a group.
+    /// Otherwise returns `true`, replacing the target of `link` if it was already a soft alias.
+    ///
+    /// # Arguments
+    ///
+    /// * `target` - The key the soft alias points to.
+    /// * `link` - The soft alias to add.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyMap;
+    ///
+    /// let mut map = MultiKeyMap::new();
+    /// map.insert("v1.2.0", "release notes of 1.2.0");
+    /// assert!(map.insert_soft_alias(&"v1.2.0", "latest"));
+    /// assert!(map.insert_soft_alias(&"latest", "stable"));
+    /// assert_eq!(map.get(&"stable"), Some(&"release notes of 1.2.0"));
+    /// assert!(!map.insert_soft_alias(&"latest", "v1.2.0"));
+    ///
+    /// // Re-pointing the target re-points the whole chain
+    /// map.insert("v1.3.0", "release notes of 1.3.0");
+    /// assert!(map.insert_soft_alias(&"v1.3.0", "latest"));
+    /// assert_eq!(map.get(&"stable"), Some(&"release notes of 1.3.0"));
+    ///
+    /// // So does binding the target key itself to another group
+    /// map.insert("v1.3.0", "revised release notes of 1.3.0");
+    /// assert_eq!(map.get(&"latest"), Some(&"revised release notes of 1.3.0"));
+    ///
+    /// // Removing the target leaves the soft aliases dangling until it comes back
+    /// map.remove(&"v1.3.0");
+    /// assert!(!map.contains_key(&"stable"));
+    /// assert_eq!(map.soft_alias_target(&"stable"), Some(&"latest"));
+    /// map.insert("v1.3.0", "final release notes of 1.3.0");
+    /// assert_eq!(map.get(&"stable"), Some(&"final release notes of 1.3.0"));
+    ///
+    /// // Cycles miss instead of looping forever
+    /// map.insert_soft_alias(&"b", "a");
+    /// map.insert_soft_alias(&"c", "b");
+    /// map.insert_soft_alias(&"a", "c");
+    /// assert_eq!(map.get(&"a"), None);
+    /// assert!(!map.contains_key(&"c"));
+    ///
+    /// // Binding a soft alias to a group replaces it
+    /// map.insert_alias(&"v1.2.0", "a");
+    /// assert_eq!(map.soft_alias_target(&"a"), None);
+    /// assert_eq!(map.get(&"b"), Some(&"release notes of 1.2.0"));
+    /// map.check_invariants();
+    /// ```
+    pub fn insert_soft_alias(&mut self, target: &K, link: K) -> bool
+    where
+        K: Clone,
+    {
+        let inserted = self.set_soft_link(link, target.clone());
+        if inserted {
+            self.check_mutation();
+        }
+        inserted
+    }
+
+    /// Removes the soft alias `link` and returns the key it pointed to.
+    ///
+    /// Returns `None` if `link` is not a soft alias. Keys bound to groups are left alone.
+    ///
+    /// # Arguments
+    ///
+    /// * `link` - The soft alias to remove.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyMap;
+    ///
+    /// let mut map = MultiKeyMap::new();
+    /// map.insert("key", 1);
+    /// map.insert_soft_alias(&"key", "link");
+    /// assert_eq!(map.remove_soft_alias(&"link"), Some("key"));
+    /// assert_eq!(map.get(&"link"), None);
+    /// assert_eq!(map.remove_soft_alias(&"key"), None);
+    /// assert_eq!(map.get(&"key"), Some(&1));
+    /// ```
+    pub fn remove_soft_alias(&mut self, link: &K) -> Option<K> {
+        let normalized: K;
+        let link = match &self.normalizer {
+            Some(normalize) => {
+                normalized = normalize(link);
+                &normalized
+            }
+            None => link,
+        };
+        let hash = self.hash(link);
+        let entry = self
+            .soft_links
+            .find_entry(hash, |(other, _)| other == link)
+            .ok()?;
+        let ((_, target), _) = entry.remove();
+        self.revision = self.revision.next();
+        self.check_mutation();
+        Some(target)
+    }
+
+    /// Returns the key the soft alias `link` directly points to.
+    ///
+    /// Returns `None` if `link` is not a soft alias.
+    ///
+    /// # Arguments
+    ///
+    /// * `link` - The soft alias to look up.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyMap;
+    ///
+    /// let mut map: MultiKeyMap<&str, u32> = MultiKeyMap::new();
+    /// map.insert_soft_alias(&"target", "first");
+    /// map.insert_soft_alias(&"first", "second");
+    /// assert_eq!(map.soft_alias_target(&"second"), Some(&"first"));
+    /// assert_eq!(map.soft_alias_target(&"target"), None);
+    /// ```
+    pub fn soft_alias_target(&self, link: &K) -> Option<&K> {
+        match &self.normalizer {
+            Some(normalize) => self.soft_target(&normalize(link)),
+            None => self.soft_target(link),
+        }
+    }
+
+    /// Points the soft alias `link` to `target`, normalizing both.
+    ///
+    /// Returns `false` if `link` is bound to a group.
+    pub(crate) fn set_soft_link(&mut self, link: K, target: K) -> bool {
+        let (link, target) = match &self.normalizer {
+            Some(normalize) => (normalize(&link), normalize(&target)),
+            None => (link, target),
+        };
+        if self.find_normalized(&link).is_some() {
+            return false;
+        }
+        let hash = self.hash(&link);
+        match self.soft_links.find_mut(hash, |(other, _)| *other == link) {
+            Some(entry) => entry.1 = target,
+            None => {
+                let hasher = &self.hasher;
+                self.soft_links
+                    .insert_unique(hash, (link, target), |(link, _)| hasher.hash_one(link));
+            }
+        }
+        self.revision = self.revision.next();
+        true
+    }
+}
@@ -0,0 +1,495 @@
+use std::cmp::Ordering;
+use std::fmt::{self, Debug};
+
+/// A read-only multi-key map with `&'static str` keys, built at compile time by
+/// [`static_multi_key_map!`](crate::static_multi_key_map).
+///
+/// The groups, their values and an index of every key sorted by key are all computed by the
+/// compiler and stored in the binary, so the map costs nothing at startup and lookups never
+/// allocate: [`get`](Self::get), [`aliases`](Self::aliases) and
+/// [`are_aliases`](Self::are_aliases) binary search the sorted keys. A key listed in two groups
+/// fails to compile.
+///
+/// # Examples
+///
+/// ```
+/// use multi_key_map::{static_multi_key_map, MultiKeyMap};
+/// use std::alloc::{GlobalAlloc, Layout, System};
+/// use std::sync::atomic::{AtomicUsize, Ordering};
+///
+/// struct Counting;
+///
+/// static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+///
+/// unsafe impl GlobalAlloc for Counting {
+///     unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+///         ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+///         unsafe { System.alloc(layout) }
+///     }
+///
+///     unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+///         unsafe { System.dealloc(ptr, layout) }
+///     }
+/// }
+///
+/// #[global_allocator]
+/// static GLOBAL: Counting = Counting;
+///
+/// // 300 groups of three keys: "k<n>", "a<n>" and "n<n>" for every n below 300
+/// macro_rules! table {
+///     ($($tens:literal)*) => {
+///         static_multi_key_map! {
+///             static TABLE: StaticMultiKeyMap<u32> = {
+///                 $(
+///                     [concat!("k", $tens, 0), concat!("a", $tens, 0), concat!("n", $tens, 0)]
+///                         => $tens * 10,
+///                     [concat!("k", $tens, 1), concat!("a", $tens, 1), concat!("n", $tens, 1)]
+///                         => $tens * 10 + 1,
+///                     [concat!("k", $tens, 2), concat!("a", $tens, 2), concat!("n", $tens, 2)]
+///                         => $tens * 10 + 2,
+///                     [concat!("k", $tens, 3), concat!("a", $tens, 3), concat!("n", $tens, 3)]
+///                         => $tens * 10 + 3,
+///                     [concat!("k", $tens, 4), concat!("a", $tens, 4), concat!("n", $tens, 4)]
+///                         => $tens * 10 + 4,
+///                     [concat!("k", $tens, 5), concat!("a", $tens, 5), concat!("n", $tens, 5)]
+///                         => $tens * 10 + 5,
+///                     [concat!("k", $tens, 6), concat!("a", $tens, 6), concat!("n", $tens, 6)]
+///                         => $tens * 10 + 6,
+///                     [concat!("k", $tens, 7), concat!("a", $tens, 7), concat!("n", $tens, 7)]
+///                         => $tens * 10 + 7,
+///                     [concat!("k", $tens, 8), concat!("a", $tens, 8), concat!("n", $tens, 8)]
+///                         => $tens * 10 + 8,
+///                     [concat!("k", $tens, 9), concat!("a", $tens, 9), concat!("n", $tens, 9)]
+///                         => $tens * 10 + 9,
+///                 )*
+///             };
+///         }
+///     };
+/// }
+///
+/// table!(0 1 2 3 4 5 6 7 8 9 10 11 12 13 14 15 16 17 18 19 20 21 22 23 24 25 26 27 28 29);
+/// assert_eq!((TABLE.len(), TABLE.key_count()), (300, 900));
+///
+/// // The same data built at runtime
+/// let mut map = MultiKeyMap::new();
+/// let mut probes = Vec::new();
+/// for n in 0..300u32 {
+///     let (tens, units) = (n / 10, n % 10);
+///     let keys = ["k", "a", "n"].map(|prefix| format!("{prefix}{tens}{units}"));
+///     probes.extend(keys.iter().cloned());
+///     probes.push(format!("x{n}"));
+///     map.insert_with_keys(keys, n).unwrap();
+/// }
+///
+/// for key in &probes {
+///     let allocations = ALLOCATIONS.load(Ordering::Relaxed);
+///     let value = TABLE.get(key);
+///     let aliases = TABLE.aliases(key);
+///     let paired = TABLE.are_aliases(key, "k7");
+///     assert_eq!(ALLOCATIONS.load(Ordering::Relaxed), allocations);
+///
+///     assert_eq!(value, map.get(key));
+///     assert_eq!(aliases.map(|keys| keys.join(" ")), map.aliases(key).map(|keys| keys.join(" ")));
+///     assert_eq!(paired, map.are_aliases(key, &"k7".to_string()));
+/// }
+/// ```
+pub struct StaticMultiKeyMap<V: 'static> {
+    groups: &'static [&'static [&'static str]],
+    values: &'static [V],
+    /// Every key with the index of its group, sorted by key.
+    index: &'static [(&'static str, usize)],
+}
+
+impl<V> StaticMultiKeyMap<V> {
+    /// Assembles a map from the output of [`sorted_index`](Self::sorted_index).
+    ///
+    /// Used by [`static_multi_key_map!`](crate::static_multi_key_map); not part of the API.
+    #[doc(hidden)]
+    pub const fn from_parts(
+        groups: &'static [&'static [&'static str]],
+        values: &'static [V],
+        index: &'static [(&'static str, usize)],
+    ) -> Self {
+        assert!(groups.len() == values.len(), "every group has a value");
+        StaticMultiKeyMap {
+            groups,
+            values,
+            index,
+        }
+    }
+
+    /// Returns the number of keys of `groups`.
+    ///
+    /// Used by [`static_multi_key_map!`](crate::static_multi_key_map); not part of the API.
+    #[doc(hidden)]
+    pub const fn count_keys(groups: &[&[&str]]) -> usize {
+        let mut count = 0;
+        let mut group = 0;
+        while group < groups.len() {
+            count += groups[group].len();
+            group += 1;
+        }
+        count
+    }
+
+    /// Lists every key of `groups`, of which there are `N`, with the index of its group, sorted
+    /// by key.
+    ///
+    /// Used by [`static_multi_key_map!`](crate::static_multi_key_map); not part of the API.
+    ///
+    /// # Panics
+    ///
+    /// Panics, failing the compilation of a static map, if a key belongs to more than one
+    /// group or appears twice in a group.
+    #[doc(hidden)]
+    pub const fn sorted_index<const N: usize>(
+        groups: &[&'static [&'static str]],
+    ) -> [(&'static str, usize); N] {
+        let mut index = [("", 0); N];
+        let mut position = 0;
+        let mut group = 0;
+        while group < groups.len() {
+            let mut key = 0;
+            while key < groups[group].len() {
+                index[position] = (groups[group][key], group);
+                position += 1;
+                key += 1;
+            }
+            group += 1;
+        }
+        assert!(position == N, "the index has room for every key");
+
+        // Heapsort, since a const fn cannot call `sort`
+        let mut end = N / 2;
+        while end > 0 {
+            end -= 1;
+            sift_down(&mut index, end, N);
+        }
+        let mut end = N;
+        while end > 1 {
+            end -= 1;
+            let first = index[0];
+            index[0] = index[end];
+            index[end] = first;
+            sift_down(&mut index, 0, end);
+        }
+
+        let mut position = 1;
+        while position < N {
+            if matches!(
+                compare(index[position - 1].0, index[position].0),
+                Ordering::Equal
+            ) {
+                panic!("a key of a static multi-key map is listed twice");
+            }
+            position += 1;
+        }
+        index
+    }
+
+    /// Returns the value of the group `key` belongs to.
+    ///
+    /// Returns `None` if the key does not exist.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - Any key of the group.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::static_multi_key_map;
+    ///
+    /// static_multi_key_map! {
+    ///     static COUNTRIES: StaticMultiKeyMap<&str> = {
+    ///         ["DE", "DEU", "Germany", "Deutschland"] => "Germany",
+    ///         ["FR", "FRA", "France"] => "France",
+    ///     };
+    /// }
+    ///
+    /// assert_eq!(COUNTRIES.get("Deutschland"), Some(&"Germany"));
+    /// assert_eq!(COUNTRIES.get("FRA"), Some(&"France"));
+    /// assert_eq!(COUNTRIES.get("IT"), None);
+    /// ```
+    pub fn get(&self, key: &str) -> Option<&V> {
+        self.group_of(key).map(|group| &self.values[group])
+    }
+
+    /// Returns the keys of the group `key` belongs to, in the order they were listed.
+    ///
+    /// Returns `None` if the key does not exist.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - Any key of the group.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::static_multi_key_map;
+    ///
+    /// static_multi_key_map! {
+    ///     static COUNTRIES: StaticMultiKeyMap<u16> = {
+    ///         ["NL", "NLD", "Netherlands", "Holland"] => 528,
+    ///     };
+    /// }
+    ///
+    /// let keys = ["NL", "NLD", "Netherlands", "Holland"];
+    /// assert_eq!(COUNTRIES.aliases("Holland"), Some(&keys[..]));
+    /// assert_eq!(COUNTRIES.aliases("BE"), None);
+    /// ```
+    pub fn aliases(&self, key: &str) -> Option<&'static [&'static str]> {
+        self.group_of(key).map(|group| self.groups[group])
+    }
+
+    /// Checks if two keys are aliases of each other.
+    ///
+    /// # Arguments
+    ///
+    /// * `key1` - The first key.
+    /// * `key2` - The second key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::static_multi_key_map;
+    ///
+    /// static_multi_key_map! {
+    ///     static COUNTRIES: StaticMultiKeyMap<()> = {
+    ///         ["GB", "GBR", "United Kingdom", "UK"] => (),
+    ///         ["IE", "IRL", "Ireland"] => (),
+    ///     };
+    /// }
+    ///
+    /// assert!(COUNTRIES.are_aliases("UK", "GBR"));
+    /// assert!(!COUNTRIES.are_aliases("UK", "IE"));
+    /// assert!(!COUNTRIES.are_aliases("UK", "Wales"));
+    /// ```
+    pub fn are_aliases(&self, key1: &str, key2: &str) -> bool {
+        match (self.group_of(key1), self.group_of(key2)) {
+            (Some(group1), Some(group2)) => group1 == group2,
+            _ => false,
+        }
+    }
+
+    /// Returns `true` if the map contains a value for the specified key.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key to check.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::static_multi_key_map;
+    ///
+    /// static_multi_key_map! {
+    ///     static CODES: StaticMultiKeyMap<u16> = {
+    ///         ["ES", "ESP", "Spain"] => 724,
+    ///     };
+    /// }
+    ///
+    /// assert!(CODES.contains_key("ESP"));
+    /// assert!(!CODES.contains_key("esp"));
+    /// ```
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.group_of(key).is_some()
+    }
+
+    /// Returns an iterator over the groups in the order they were listed, each given as its
+    /// keys and its value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::static_multi_key_map;
+    ///
+    /// static_multi_key_map! {
+    ///     static CODES: StaticMultiKeyMap<u16> = {
+    ///         ["PT", "PRT"] => 620,
+    ///         ["AT", "AUT", "Austria"] => 40,
+    ///     };
+    /// }
+    ///
+    /// let groups: Vec<_> = CODES.groups().map(|(keys, value)| (keys[0], *value)).collect();
+    /// assert_eq!(groups, vec![("PT", 620), ("AT", 40)]);
+    /// ```
+    pub fn groups(&self) -> impl Iterator<Item = (&'static [&'static str], &V)> + '_ {
+        self.groups.iter().copied().zip(self.values)
+    }
+
+    /// Returns the number of groups in the map.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::static_multi_key_map;
+    ///
+    /// static_multi_key_map! {
+    ///     static CODES: StaticMultiKeyMap<u16> = {
+    ///         ["PL", "POL"] => 616,
+    ///         ["CZ", "CZE", "Czechia"] => 203,
+    ///     };
+    /// }
+    ///
+    /// assert_eq!(CODES.len(), 2);
+    /// ```
+    pub const fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Returns the number of keys in the map, counting every alias.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::static_multi_key_map;
+    ///
+    /// static_multi_key_map! {
+    ///     static CODES: StaticMultiKeyMap<u16> = {
+    ///         ["PL", "POL"] => 616,
+    ///         ["CZ", "CZE", "Czechia"] => 203,
+    ///     };
+    /// }
+    ///
+    /// assert_eq!(CODES.key_count(), 5);
+    /// ```
+    pub const fn key_count(&self) -> usize {
+        self.index.len()
+    }
+
+    /// Returns `true` if the map contains no values.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::static_multi_key_map;
+    ///
+    /// static_multi_key_map! {
+    ///     static EMPTY: StaticMultiKeyMap<u16> = {};
+    /// }
+    ///
+    /// assert!(EMPTY.is_empty());
+    /// assert_eq!(EMPTY.get("anything"), None);
+    /// ```
+    pub const fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Returns the group `key` belongs to.
+    fn group_of(&self, key: &str) -> Option<usize> {
+        self.index
+            .binary_search_by(|&(probe, _)| probe.cmp(key))
+            .ok()
+            .map(|position| self.index[position].1)
+    }
+}
+
+impl<V: Debug> Debug for StaticMultiKeyMap<V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut debug_struct = f.debug_struct("StaticMultiKeyMap");
+        for (keys, value) in self.groups() {
+            debug_struct.field(&format!("{:?}", keys), value);
+        }
+        debug_struct.finish()
+    }
+}
+
+/// Restores the max-heap order of `index[..end]` below `root`, by key.
+const fn sift_down(index: &mut [(&str, usize)], mut root: usize, end: usize) {
+    loop {
+        let mut child = 2 * root + 1;
+        if child >= end {
+            return;
+        }
+        if child + 1 < end && matches!(compare(index[child].0, index[child + 1].0), Ordering::Less)
+        {
+            child += 1;
+        }
+        if !matches!(compare(index[root].0, index[child].0), Ordering::Less) {
+            return;
+        }
+        let parent = index[root];
+        index[root] = index[child];
+        index[child] = parent;
+        root = child;
+    }
+}
+
+/// Compares two keys byte by byte, as [`str::cmp`] does.
+const fn compare(a: &str, b: &str) -> Ordering {
+    let (a, b) = (a.as_bytes(), b.as_bytes());
+    let mut position = 0;
+    while position < a.len() && position < b.len() {
+        if a[position] != b[position] {
+            return if a[position] < b[position] {
+                Ordering::Less
+            } else {
+                Ordering::Greater
+            };
+        }
+        position += 1;
+    }
+    if a.len() < b.len() {
+        Ordering::Less
+    } else if a.len() > b.len() {
+        Ordering::Greater
+    } else {
+        Ordering::Equal
+    }
+}
+
+/// Declares a [`StaticMultiKeyMap`](crate::StaticMultiKeyMap) built entirely at compile time.
+///
+/// Each group lists its `&'static str` keys in brackets, followed by `=>` and its value. Keys
+/// and values may be any constant expressions, such as literals, `concat!` or constants. A key
+/// listed more than once fails the compilation with a constant evaluation error.
+///
+/// # Examples
+///
+/// ```
+/// use multi_key_map::static_multi_key_map;
+///
+/// const GERMANY: u16 = 276;
+///
+/// static_multi_key_map! {
+///     /// ISO 3166 numeric codes, by alpha-2 code, alpha-3 code and name.
+///     pub static COUNTRIES: StaticMultiKeyMap<u16> = {
+///         ["DE", "DEU", "Germany"] => GERMANY,
+///         ["CH", "CHE", "Switzerland", concat!("Swiss ", "Confederation")] => 756,
+///     };
+/// }
+///
+/// assert_eq!(COUNTRIES.get("Swiss Confederation"), Some(&756));
+/// assert_eq!(COUNTRIES.get("DEU"), Some(&276));
+/// ```
+///
+/// ```compile_fail
+/// use multi_key_map::static_multi_key_map;
+///
+/// static_multi_key_map! {
+///     static COUNTRIES: StaticMultiKeyMap<u16> = {
+///         ["AT", "AUT"] => 40,
+///         ["AU", "AUS", "AUT"] => 36,
+///     };
+/// }
+/// # fn main() { COUNTRIES.get("AT"); }
+/// ```
+#[macro_export]
+macro_rules! static_multi_key_map {
+    (
+        $(#[$attr:meta])*
+        $vis:vis static $name:ident: StaticMultiKeyMap<$value:ty> = {
+            $([$($key:expr),+ $(,)?] => $group_value:expr),* $(,)?
+        };
+    ) => {
+        $(#[$attr])*
+        $vis static $name: $crate::StaticMultiKeyMap<$value> = {
+            const GROUPS: &[&[&str]] = &[$(&[$($key),+]),*];
+            const VALUES: &[$value] = &[$($group_value),*];
+            const INDEX: [(&str, usize); $crate::StaticMultiKeyMap::<$value>::count_keys(GROUPS)] =
+                $crate::StaticMultiKeyMap::<$value>::sorted_index(GROUPS);
+            $crate::StaticMultiKeyMap::from_parts(GROUPS, VALUES, &INDEX)
+        };
+    };
+}
@@ -0,0 +1,133 @@
+#[cfg(feature = "stats")]
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Operation counters of a [`MultiKeyMap`](crate::MultiKeyMap).
+///
+/// With the `stats` feature the counters are relaxed atomics, so lookups through `&self` can
+/// count and the map stays `Sync`. Without it this is an empty type whose methods do nothing.
+#[derive(Default)]
+pub(crate) struct Counters {
+    #[cfg(feature = "stats")]
+    hits: AtomicU64,
+    #[cfg(feature = "stats")]
+    misses: AtomicU64,
+    #[cfg(feature = "stats")]
+    inserts: AtomicU64,
+    #[cfg(feature = "stats")]
+    alias_inserts: AtomicU64,
+    #[cfg(feature = "stats")]
+    removals: AtomicU64,
+}
+
+impl Counters {
+    pub(crate) const fn new() -> Self {
+        Counters {
+            #[cfg(feature = "stats")]
+            hits: AtomicU64::new(0),
+            #[cfg(feature = "stats")]
+            misses: AtomicU64::new(0),
+            #[cfg(feature = "stats")]
+            inserts: AtomicU64::new(0),
+            #[cfg(feature = "stats")]
+            alias_inserts: AtomicU64::new(0),
+            #[cfg(feature = "stats")]
+            removals: AtomicU64::new(0),
+        }
+    }
+}
+
+#[cfg(feature = "stats")]
+impl Counters {
+    /// Records a lookup, which hit if `found`.
+    pub(crate) fn lookup(&self, found: bool) {
+        let counter = if found { &self.hits } else { &self.misses };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn insert(&self) {
+        self.inserts.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Records `count` aliases added to existing groups.
+    pub(crate) fn alias_inserts(&self, count: usize) {
+        self.alias_inserts
+            .fetch_add(count as u64, Ordering::Relaxed);
+    }
+
+    pub(crate) fn removal(&self) {
+        self.removals.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn snapshot(&self) -> MapStats {
+        let hits = self.hits.load(Ordering::Relaxed);
+        let misses = self.misses.load(Ordering::Relaxed);
+        MapStats {
+            lookups: hits + misses,
+            hits,
+            misses,
+            inserts: self.inserts.load(Ordering::Relaxed),
+            alias_inserts: self.alias_inserts.load(Ordering::Relaxed),
+            removals: self.removals.load(Ordering::Relaxed),
+        }
+    }
+}
+
+#[cfg(not(feature = "stats"))]
+impl Counters {
+    #[inline(always)]
+    pub(crate) fn lookup(&self, _found: bool) {}
+
+    #[inline(always)]
+    pub(crate) fn insert(&self) {}
+
+    #[inline(always)]
+    pub(crate) fn alias_inserts(&self, _count: usize) {}
+
+    #[inline(always)]
+    pub(crate) fn removal(&self) {}
+}
+
+impl Clone for Counters {
+    fn clone(&self) -> Self {
+        #[cfg(feature = "stats")]
+        {
+            let stats = self.snapshot();
+            Counters {
+                hits: AtomicU64::new(stats.hits),
+                misses: AtomicU64::new(stats.misses),
+                inserts: AtomicU64::new(stats.inserts),
+                alias_inserts: AtomicU64::new(stats.alias_inserts),
+                removals: AtomicU64::new(stats.removals),
+            }
+        }
+        #[cfg(not(feature = "stats"))]
+        Counters {}
+    }
+}
+
+/// A snapshot of the operation counters of a [`MultiKeyMap`](crate::MultiKeyMap), returned by
+/// [`MultiKeyMap::stats`](crate::MultiKeyMap::stats).
+///
+/// Only methods retrieving a value by key, such as [`get`](crate::MultiKeyMap::get) and
+/// [`get_mut`](crate::MultiKeyMap::get_mut), count as lookups; key checks such as
+/// [`contains_key`](crate::MultiKeyMap::contains_key) do not. Counters only move on operations
+/// that take effect: an alias that could not be added or a removal of a missing key is not
+/// counted.
+///
+/// This type is only available with the `stats` feature.
+#[cfg(feature = "stats")]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MapStats {
+    /// The number of lookups, `hits + misses`.
+    pub lookups: u64,
+    /// The number of lookups that found a value.
+    pub hits: u64,
+    /// The number of lookups that found no value.
+    pub misses: u64,
+    /// The number of groups created by inserts.
+    pub inserts: u64,
+    /// The number of aliases added to existing groups.
+    pub alias_inserts: u64,
+    /// The number of keys or groups removed by `remove_alias` and `remove`.
+    pub removals: u64,
+}
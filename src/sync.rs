@@ -0,0 +1,332 @@
+//! Read-optimized handles to a [`MultiKeyMap`] shared between threads.
+//!
+//! A single [`WriteHandle`] owns a private copy of the map and applies operations to it, and
+//! [`publish`](WriteHandle::publish) atomically replaces the snapshot seen by every
+//! [`ReadHandle`]. Readers never take a lock: each read loads the current snapshot, a
+//! [`FrozenMultiKeyMap`], which is never modified. A reader therefore sees either the state of
+//! one publish or the state of the next, never a mix of both.
+//!
+//! Publishing copies the whole map, so it suits workloads with rare, batched writes.
+//!
+//! This module is only available with the `sync` feature.
+//!
+//! # Examples
+//!
+//! Readers observing a writer that publishes batches of operations:
+//!
+//! ```
+//! use multi_key_map::sync;
+//! use std::sync::atomic::{AtomicBool, Ordering};
+//! use std::sync::Arc;
+//! use std::thread;
+//!
+//! let (mut writer, reader) = sync::new();
+//! let done = Arc::new(AtomicBool::new(false));
+//! let readers: Vec<_> = (0..4)
+//!     .map(|_| {
+//!         let reader = reader.clone();
+//!         let done = Arc::clone(&done);
+//!         thread::spawn(move || {
+//!             while !done.load(Ordering::Relaxed) {
+//!                 // Every published state holds the groups `first..first + len`, each with a
+//!                 // key `n`, an alias `-n` and the value `n`
+//!                 let snapshot = reader.snapshot();
+//!                 assert!(snapshot.len() <= 10);
+//!                 assert_eq!(snapshot.key_count(), 2 * snapshot.len());
+//!                 let first = snapshot.groups().map(|(_, &value)| value).min().unwrap_or(0);
+//!                 for n in first..first + snapshot.len() as i64 {
+//!                     assert_eq!(snapshot.get(&n), Some(&n));
+//!                     assert!(snapshot.are_aliases(&n, &-n));
+//!                 }
+//!             }
+//!         })
+//!     })
+//!     .collect();
+//!
+//! for n in 1..=2_000i64 {
+//!     writer.insert(n, n);
+//!     writer.insert_alias(&n, -n);
+//!     writer.remove(&(n - 10));
+//!     writer.publish();
+//! }
+//! done.store(true, Ordering::Relaxed);
+//! for reader in readers {
+//!     reader.join().unwrap();
+//! }
+//! assert_eq!(reader.get(&2_000), Some(2_000));
+//! assert_eq!(reader.snapshot().len(), 10);
+//! ```
+
+use crate::{FrozenMultiKeyMap, MultiKeyMap};
+use arc_swap::ArcSwap;
+use std::fmt::{self, Debug};
+use std::hash::Hash;
+use std::sync::Arc;
+
+/// Creates an empty map, returning its writer and a first reader.
+///
+/// # Examples
+///
+/// ```
+/// use multi_key_map::sync;
+///
+/// let (mut writer, reader) = sync::new();
+/// writer.insert("key1", "value1");
+/// assert_eq!(reader.get(&"key1"), None);
+/// writer.publish();
+/// assert_eq!(reader.get(&"key1"), Some("value1"));
+/// ```
+pub fn new<K, V>() -> (WriteHandle<K, V>, ReadHandle<K, V>)
+where
+    K: Eq + Hash + Clone,
+    V: Clone,
+{
+    from_map(MultiKeyMap::new())
+}
+
+/// Shares an existing map, returning its writer and a first reader.
+///
+/// The map is published right away.
+///
+/// # Arguments
+///
+/// * `map` - The initial contents.
+///
+/// # Examples
+///
+/// ```
+/// use multi_key_map::{sync, MultiKeyMap};
+///
+/// let mut map = MultiKeyMap::new();
+/// map.insert("key1", "value1");
+/// let (_writer, reader) = sync::from_map(map);
+/// assert_eq!(reader.get(&"key1"), Some("value1"));
+/// ```
+pub fn from_map<K, V>(map: MultiKeyMap<K, V>) -> (WriteHandle<K, V>, ReadHandle<K, V>)
+where
+    K: Eq + Hash + Clone,
+    V: Clone,
+{
+    let shared = Arc::new(ArcSwap::from_pointee(snapshot_of(&map)));
+    let reader = ReadHandle {
+        shared: Arc::clone(&shared),
+    };
+    (WriteHandle { map, shared }, reader)
+}
+
+/// The single writer of a shared map.
+///
+/// Operations only change the writer's own copy until [`publish`](Self::publish) is called.
+pub struct WriteHandle<K, V> {
+    map: MultiKeyMap<K, V>,
+    shared: Arc<ArcSwap<FrozenMultiKeyMap<K, V>>>,
+}
+
+impl<K: Eq + Hash + Clone, V: Clone> WriteHandle<K, V> {
+    /// Inserts a value with the given key, as [`MultiKeyMap::insert`] does.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key to insert.
+    /// * `value` - The value to insert.
+    pub fn insert(&mut self, key: K, value: V) {
+        self.map.insert(key, value);
+    }
+
+    /// Adds a new alias key for the element at `key`, as [`MultiKeyMap::insert_alias`] does.
+    ///
+    /// Returns the reference count if the alias is successfully added.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The original key.
+    /// * `alias` - The alias key to add.
+    pub fn insert_alias(&mut self, key: &K, alias: K) -> Option<usize> {
+        self.map.insert_alias(key, alias)
+    }
+
+    /// Removes an alias key, as [`MultiKeyMap::remove_alias`] does.
+    ///
+    /// Returns the reference count if the alias is successfully removed.
+    ///
+    /// # Arguments
+    ///
+    /// * `alias` - The alias key to remove.
+    pub fn remove_alias(&mut self, alias: &K) -> Option<usize> {
+        self.map.remove_alias(alias)
+    }
+
+    /// Removes a value by its key and all its aliases, as [`MultiKeyMap::remove`] does.
+    ///
+    /// Returns the value if it was present.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key of the value to remove.
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        self.map.remove(key)
+    }
+
+    /// Returns the writer's copy of the map, including the operations not yet published.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::sync;
+    ///
+    /// let (mut writer, reader) = sync::new();
+    /// writer.insert("key1", "value1");
+    /// assert_eq!(writer.map().get(&"key1"), Some(&"value1"));
+    /// assert!(reader.snapshot().is_empty());
+    /// ```
+    pub fn map(&self) -> &MultiKeyMap<K, V> {
+        &self.map
+    }
+
+    /// Makes every operation applied so far visible to the readers, atomically.
+    ///
+    /// Readers already holding a snapshot keep it until they load a new one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::sync;
+    ///
+    /// let (mut writer, reader) = sync::new();
+    /// writer.insert("key1", "value1");
+    /// let before = reader.snapshot();
+    /// writer.insert_alias(&"key1", "alias1");
+    /// writer.publish();
+    /// assert!(reader.snapshot().are_aliases(&"key1", &"alias1"));
+    /// assert!(before.is_empty());
+    /// ```
+    pub fn publish(&mut self) {
+        self.shared.store(Arc::new(snapshot_of(&self.map)));
+    }
+
+    /// Returns a new reader of the published map.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::sync;
+    ///
+    /// let (mut writer, _) = sync::new();
+    /// writer.insert("key1", "value1");
+    /// writer.publish();
+    /// assert_eq!(writer.reader().get(&"key1"), Some("value1"));
+    /// ```
+    pub fn reader(&self) -> ReadHandle<K, V> {
+        ReadHandle {
+            shared: Arc::clone(&self.shared),
+        }
+    }
+}
+
+impl<K: Debug, V: Debug> Debug for WriteHandle<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WriteHandle")
+            .field("map", &self.map)
+            .finish_non_exhaustive()
+    }
+}
+
+/// A reader of a shared map, seeing the state of the last publish.
+///
+/// Reads never block, and cloning a reader is cheap.
+pub struct ReadHandle<K, V> {
+    shared: Arc<ArcSwap<FrozenMultiKeyMap<K, V>>>,
+}
+
+impl<K: Eq + Hash, V> ReadHandle<K, V> {
+    /// Returns the last published snapshot.
+    ///
+    /// Use a single snapshot for several lookups that must agree with each other: later publishes
+    /// do not affect it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::sync;
+    ///
+    /// let (mut writer, reader) = sync::new();
+    /// writer.insert("key1", "value1");
+    /// writer.insert_alias(&"key1", "alias1");
+    /// writer.publish();
+    /// let snapshot = reader.snapshot();
+    /// writer.remove(&"key1");
+    /// writer.publish();
+    /// assert!(snapshot.are_aliases(&"key1", &"alias1"));
+    /// assert!(reader.snapshot().is_empty());
+    /// ```
+    pub fn snapshot(&self) -> FrozenMultiKeyMap<K, V> {
+        FrozenMultiKeyMap::clone(&self.shared.load())
+    }
+
+    /// Retrieves a copy of the value `key` points to in the last published snapshot.
+    ///
+    /// Returns `None` if the key does not exist.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key to lookup.
+    pub fn get(&self, key: &K) -> Option<V>
+    where
+        V: Clone,
+    {
+        self.shared.load().get(key).cloned()
+    }
+
+    /// Returns `true` if the last published snapshot contains a value for the specified key.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key to check.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::sync;
+    ///
+    /// let (mut writer, reader) = sync::new();
+    /// writer.insert("key1", "value1");
+    /// writer.publish();
+    /// assert!(reader.contains_key(&"key1"));
+    /// assert!(!reader.contains_key(&"key2"));
+    /// ```
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.shared.load().contains_key(key)
+    }
+}
+
+impl<K, V> Clone for ReadHandle<K, V> {
+    fn clone(&self) -> Self {
+        ReadHandle {
+            shared: Arc::clone(&self.shared),
+        }
+    }
+}
+
+impl<K: Debug, V: Debug> Debug for ReadHandle<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("ReadHandle")
+            .field(&*self.shared.load())
+            .finish()
+    }
+}
+
+/// Copies `map` into a new snapshot.
+fn snapshot_of<K, V>(map: &MultiKeyMap<K, V>) -> FrozenMultiKeyMap<K, V>
+where
+    K: Eq + Hash + Clone,
+    V: Clone,
+{
+    let mut disabled = map.disabled_positions();
+    FrozenMultiKeyMap::from_groups(
+        map.occupied().map(|(index, value)| {
+            let positions = disabled.remove(&index).unwrap_or_default();
+            (map.slots[index].keys.to_vec(), positions, value.clone())
+        }),
+        map.soft_links.iter().cloned(),
+    )
+}
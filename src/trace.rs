@@ -0,0 +1,167 @@
+use crate::MultiKeyMap;
+#[cfg(feature = "tracing")]
+use std::fmt::{self, Debug, Write};
+use std::hash::Hash;
+
+/// Writes the representation of a key into a traced event.
+#[cfg(feature = "tracing")]
+pub(crate) type KeyWriter<K> = fn(&K, &mut dyn Write) -> fmt::Result;
+
+/// The number of bytes past which traced key representations are cut.
+#[cfg(feature = "tracing")]
+const MAX_KEY_LEN: usize = 64;
+
+/// The span of a bulk operation of a [`MultiKeyMap`], exited when dropped.
+///
+/// With the `tracing` feature this holds an entered span. Without it this is an empty type.
+pub(crate) struct BulkSpan {
+    #[cfg(feature = "tracing")]
+    _span: tracing::span::EnteredSpan,
+}
+
+#[cfg(feature = "tracing")]
+impl<K: Eq + Hash + Debug, V> MultiKeyMap<K, V> {
+    /// Includes the [`Debug`] representation of keys in the events the map emits.
+    ///
+    /// With the `tracing` feature, [`insert`](Self::insert), [`insert_alias`](Self::insert_alias),
+    /// [`remove_alias`](Self::remove_alias), [`remove`](Self::remove) and [`clear`](Self::clear)
+    /// emit debug-level events with the target `multi_key_map`, recording the resulting number
+    /// of keys of the group as `group_size`, and bulk operations such as
+    /// [`extend`](Extend::extend) and [`append`](Self::append) run in a debug-level `bulk` span.
+    /// Events only record the key once this is called, since `insert` and the others do not
+    /// require keys to implement `Debug`. Representations longer than 64 bytes are cut and end
+    /// with `...`. Like hooks, this setting is not cloned along with the map.
+    ///
+    /// This method is only available with the `tracing` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyMap;
+    /// use std::io::Write;
+    /// use std::sync::{Arc, Mutex};
+    ///
+    /// #[derive(Clone, Default)]
+    /// struct Capture(Arc<Mutex<Vec<u8>>>);
+    ///
+    /// impl Write for Capture {
+    ///     fn write(&mut self, bytes: &[u8]) -> std::io::Result<usize> {
+    ///         self.0.lock().unwrap().write(bytes)
+    ///     }
+    ///
+    ///     fn flush(&mut self) -> std::io::Result<()> {
+    ///         Ok(())
+    ///     }
+    /// }
+    ///
+    /// let capture = Capture::default();
+    /// let writer = capture.clone();
+    /// let subscriber = tracing_subscriber::fmt()
+    ///     .with_max_level(tracing::Level::DEBUG)
+    ///     .with_writer(move || writer.clone())
+    ///     .without_time()
+    ///     .with_ansi(false)
+    ///     .with_target(false)
+    ///     .finish();
+    ///
+    /// tracing::subscriber::with_default(subscriber, || {
+    ///     let mut map = MultiKeyMap::new();
+    ///     map.insert("untraced".to_string(), 0);
+    ///     map.trace_keys();
+    ///     map.insert("a".to_string(), 1);
+    ///     map.insert_alias(&"a".to_string(), "b".to_string());
+    ///     map.insert_alias(&"a".to_string(), "x".repeat(100));
+    ///     map.remove_alias(&"b".to_string());
+    ///     map.extend([("c".to_string(), 2)]);
+    ///     map.remove(&"a".to_string());
+    ///     map.clear();
+    /// });
+    ///
+    /// let output = String::from_utf8(capture.0.lock().unwrap().clone()).unwrap();
+    /// let long = format!("\"{}...", "x".repeat(63));
+    /// let expected = [
+    ///     "DEBUG insert group_size=1".to_string(),
+    ///     "DEBUG insert key=\"a\" group_size=1".to_string(),
+    ///     "DEBUG insert_alias key=\"b\" group_size=2".to_string(),
+    ///     format!("DEBUG insert_alias key={long} group_size=3"),
+    ///     "DEBUG remove_alias key=\"b\" group_size=2".to_string(),
+    ///     "DEBUG bulk{operation=\"extend\"}: insert key=\"c\" group_size=1".to_string(),
+    ///     "DEBUG remove key=\"a\" group_size=0".to_string(),
+    ///     "DEBUG clear groups=2".to_string(),
+    /// ];
+    /// assert_eq!(output.lines().collect::<Vec<_>>(), expected);
+    /// ```
+    pub fn trace_keys(&mut self) {
+        self.hooks.key_writer = Some(|key, out| write!(out, "{key:?}"));
+    }
+}
+
+#[cfg(feature = "tracing")]
+impl<K: Eq + Hash, V> MultiKeyMap<K, V> {
+    /// Emits the event of `operation` on `key`, which left its group with `group_size` keys.
+    pub(crate) fn trace_key(&self, operation: &'static str, key: &K, group_size: usize) {
+        if !tracing::enabled!(target: "multi_key_map", tracing::Level::DEBUG) {
+            return;
+        }
+        let key = self.hooks.key_writer.map(|write_key| {
+            let mut out = Truncated(String::new());
+            if write_key(key, &mut out).is_err() {
+                out.0.push_str("...");
+            }
+            out.0
+        });
+        tracing::debug!(
+            target: "multi_key_map",
+            key = key.as_deref().map(tracing::field::display),
+            group_size,
+            "{operation}"
+        );
+    }
+
+    /// Emits the event of clearing the map of `groups` groups.
+    pub(crate) fn trace_clear(&self, groups: usize) {
+        tracing::debug!(target: "multi_key_map", groups, "clear");
+    }
+
+    /// Enters the span of the bulk operation `operation`.
+    pub(crate) fn trace_bulk(&self, operation: &'static str) -> BulkSpan {
+        BulkSpan {
+            _span: tracing::debug_span!(target: "multi_key_map", "bulk", operation).entered(),
+        }
+    }
+}
+
+#[cfg(not(feature = "tracing"))]
+impl<K: Eq + Hash, V> MultiKeyMap<K, V> {
+    #[inline(always)]
+    pub(crate) fn trace_key(&self, _operation: &'static str, _key: &K, _group_size: usize) {}
+
+    #[inline(always)]
+    pub(crate) fn trace_clear(&self, _groups: usize) {}
+
+    #[inline(always)]
+    pub(crate) fn trace_bulk(&self, _operation: &'static str) -> BulkSpan {
+        BulkSpan {}
+    }
+}
+
+/// Collects a representation, failing once it grows past [`MAX_KEY_LEN`] bytes.
+#[cfg(feature = "tracing")]
+struct Truncated(String);
+
+#[cfg(feature = "tracing")]
+impl Write for Truncated {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let room = MAX_KEY_LEN - self.0.len();
+        if s.len() <= room {
+            self.0.push_str(s);
+            return Ok(());
+        }
+        let mut end = room;
+        while !s.is_char_boundary(end) {
+            end -= 1;
+        }
+        self.0.push_str(&s[..end]);
+        Err(fmt::Error)
+    }
+}
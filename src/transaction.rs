@@ -0,0 +1,195 @@
+use crate::normalize::Normalizer;
+use crate::revision::Revision;
+use crate::seeded::MapState;
+use crate::small::{KeyIndex, Slots};
+use crate::MultiKeyMap;
+use hashbrown::HashTable;
+use std::hash::Hash;
+
+/// The state of a map before a transaction, restored if the transaction fails.
+struct Snapshot<K, V> {
+    key_index: KeyIndex,
+    hasher: MapState,
+    slots: Slots<K, V>,
+    free: Vec<usize>,
+    len: usize,
+    next_generation: u64,
+    alias_limit: Option<usize>,
+    revision: Revision,
+    normalizer: Option<Normalizer<K>>,
+    soft_links: HashTable<(K, K)>,
+    /// The number of changes recorded, if the map tracks changes.
+    changes: Option<usize>,
+}
+
+impl<K: Eq + Hash + Clone, V: Clone> MultiKeyMap<K, V> {
+    /// Applies the mutations of `f` to the map as a whole, or not at all.
+    ///
+    /// If `f` returns `Err`, the map is restored to its exact state before the call: the same
+    /// groups in the same storage, so [`GroupId`](crate::GroupId)s issued before the call resolve
+    /// as they did, the same [soft aliases](Self::insert_soft_alias), the same alias limit and
+    /// the same [key normalizer](Self::set_key_normalizer). The map is copied before calling
+    /// `f`, so a transaction costs a [`clone`](Clone::clone) of the map.
+    ///
+    /// Changes made by a failed transaction are dropped from the
+    /// [change log](Self::track_changes), but [hooks](Self::set_on_group_inserted) have already
+    /// been called for them. The statistics of the `stats` feature count the operations of failed
+    /// transactions too.
+    ///
+    /// Transactions nest: a failed inner transaction only undoes its own mutations, and a failed
+    /// outer transaction undoes those of the inner ones as well.
+    ///
+    /// If `f` panics, the map is not restored and keeps the mutations made before the panic.
+    ///
+    /// # Arguments
+    ///
+    /// * `f` - A function mutating the map, which fails by returning `Err`.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error returned by `f`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyMap;
+    ///
+    /// let mut map = MultiKeyMap::new();
+    /// map.insert("old", 0);
+    /// map.insert("kept", 1);
+    /// let kept = map.group_id(&"kept").unwrap();
+    ///
+    /// let created = map.transaction(|map| {
+    ///     map.insert("new", 2);
+    ///     for alias in ["n1", "n2", "n3"] {
+    ///         map.try_insert_alias(&"new", alias).map_err(|error| error.to_string())?;
+    ///     }
+    ///     map.remove(&"old").ok_or_else(|| "missing old key".to_string())
+    /// });
+    /// assert_eq!(created, Ok(0));
+    /// assert_eq!(map.get(&"n3"), Some(&2));
+    /// assert!(!map.contains_key(&"old"));
+    ///
+    /// let before = map.clone();
+    /// let debug = format!("{map:?}");
+    /// let failed = map.transaction(|map| {
+    ///     map.remove(&"kept");
+    ///     map.insert_alias(&"new", "n4");
+    ///     map.set_alias_limit(Some(1));
+    ///     map.remove(&"old").ok_or("missing old key")
+    /// });
+    /// assert_eq!(failed, Err("missing old key"));
+    /// assert_eq!(map, before);
+    /// assert_eq!(format!("{map:?}"), debug);
+    /// assert_eq!(map.alias_limit(), None);
+    /// assert_eq!(map.get_by_id(kept), Some(&1));
+    /// ```
+    ///
+    /// Soft aliases are rolled back too, whether the transaction added them or replaced them:
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyMap;
+    ///
+    /// let mut map = MultiKeyMap::new();
+    /// map.insert("target", 1);
+    /// map.insert_soft_alias(&"target", "displaced");
+    /// let before = map.clone();
+    /// let failed: Result<(), ()> = map.transaction(|map| {
+    ///     map.insert_soft_alias(&"target", "added");
+    ///     map.insert("displaced", 2);
+    ///     Err(())
+    /// });
+    /// assert!(failed.is_err());
+    /// assert_eq!(map, before);
+    /// assert_eq!(map.get(&"displaced"), Some(&1));
+    /// assert_eq!(map.get(&"added"), None);
+    /// ```
+    ///
+    /// A failed transaction takes back the key normalizer it installed, along with the keys it
+    /// normalized:
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyMap;
+    ///
+    /// let mut map = MultiKeyMap::new();
+    /// map.insert("A".to_string(), 1);
+    /// map.insert_alias(&"A".to_string(), "b".to_string());
+    /// let before = map.clone();
+    /// let failed: Result<(), ()> = map.transaction(|map| {
+    ///     map.set_key_normalizer(|key: &String| key.to_lowercase());
+    ///     assert_eq!(map.get(&"a".to_string()), Some(&1));
+    ///     Err(())
+    /// });
+    /// assert!(failed.is_err());
+    /// assert_eq!(map, before);
+    /// assert_eq!(map.get(&"A".to_string()), Some(&1));
+    /// assert_eq!(map.get(&"a".to_string()), None);
+    /// map.check_invariants();
+    /// ```
+    ///
+    /// Nested transactions:
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyMap;
+    ///
+    /// let mut map = MultiKeyMap::new();
+    /// let outer: Result<(), ()> = map.transaction(|map| {
+    ///     map.insert("a", 1);
+    ///     let inner: Result<(), ()> = map.transaction(|map| {
+    ///         map.insert("b", 2);
+    ///         Err(())
+    ///     });
+    ///     assert!(inner.is_err() && !map.contains_key(&"b"));
+    ///     map.transaction(|map| {
+    ///         map.insert("c", 3);
+    ///         Ok::<_, ()>(())
+    ///     })?;
+    ///     assert_eq!(map.len(), 2);
+    ///     Err(())
+    /// });
+    /// assert!(outer.is_err());
+    /// assert!(map.is_empty());
+    /// ```
+    pub fn transaction<T, E>(
+        &mut self,
+        f: impl FnOnce(&mut MultiKeyMap<K, V>) -> Result<T, E>,
+    ) -> Result<T, E> {
+        let snapshot = Snapshot {
+            key_index: self.key_index.clone(),
+            hasher: self.hasher.clone(),
+            slots: self.slots.clone(),
+            free: self.free.clone(),
+            len: self.len,
+            next_generation: self.next_generation,
+            alias_limit: self.alias_limit,
+            revision: self.revision,
+            normalizer: self.normalizer.clone(),
+            soft_links: self.soft_links.clone(),
+            changes: self.hooks.changes.as_ref().map(|log| log.len()),
+        };
+        let result = f(self);
+        if result.is_err() {
+            self.restore(snapshot);
+        }
+        result
+    }
+
+    /// Puts the map back in the state held by `snapshot`.
+    fn restore(&mut self, snapshot: Snapshot<K, V>) {
+        self.key_index = snapshot.key_index;
+        self.hasher = snapshot.hasher;
+        self.slots = snapshot.slots;
+        self.free = snapshot.free;
+        self.len = snapshot.len;
+        self.next_generation = snapshot.next_generation;
+        self.alias_limit = snapshot.alias_limit;
+        self.revision = snapshot.revision;
+        self.normalizer = snapshot.normalizer;
+        self.soft_links = snapshot.soft_links;
+        self.value_index = None;
+        if let (Some(len), Some(log)) = (snapshot.changes, &mut self.hooks.changes) {
+            log.truncate(len);
+        }
+        self.check_mutation();
+    }
+}
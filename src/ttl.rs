@@ -0,0 +1,696 @@
+use crate::MultiKeyMap;
+use std::collections::HashMap;
+use std::fmt::{self, Debug};
+use std::hash::Hash;
+use std::time::{Duration, Instant};
+
+/// A source of the current time for [`MultiKeyTtlMap`].
+///
+/// Implement it to control time in tests instead of sleeping.
+pub trait Clock {
+    /// Returns the current instant.
+    fn now(&self) -> Instant;
+}
+
+/// The [`Clock`] reading the system's monotonic clock.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+/// A `MultiKeyTtlMap` is a [`MultiKeyMap`] whose groups can expire after a time to live.
+///
+/// Expiry applies to a whole group: once its deadline is reached, every alias of the group
+/// misses. Expired groups are dropped lazily, so lookups treat them as absent but they keep
+/// their memory until [`purge_expired`](Self::purge_expired) removes them, or until a write
+/// reuses their keys. A group expires at the instant its deadline is reached, not after it.
+///
+/// Aliases added with [`insert_alias_with_ttl`](Self::insert_alias_with_ttl) also expire on
+/// their own, leaving the rest of their group in place. Expired aliases miss lookups and are
+/// removed by [`purge_expired_aliases`](Self::purge_expired_aliases), which drops the groups left
+/// without keys along with their values.
+///
+/// # Examples
+///
+/// ```
+/// use multi_key_map::{Clock, MultiKeyTtlMap};
+/// use std::cell::Cell;
+/// use std::rc::Rc;
+/// use std::time::{Duration, Instant};
+///
+/// #[derive(Clone)]
+/// struct MockClock(Rc<Cell<Instant>>);
+///
+/// impl Clock for MockClock {
+///     fn now(&self) -> Instant {
+///         self.0.get()
+///     }
+/// }
+///
+/// let start = Instant::now();
+/// let clock = MockClock(Rc::new(Cell::new(start)));
+/// let mut map = MultiKeyTtlMap::with_clock(clock.clone());
+/// map.insert_with_ttl("session", 1, Duration::from_secs(10));
+/// map.insert_alias(&"session", "token");
+/// map.insert("forever", 2);
+///
+/// clock.0.set(start + Duration::from_secs(9));
+/// assert_eq!(map.get(&"token"), Some(&1));
+///
+/// // The deadline itself is already expired, for every alias
+/// clock.0.set(start + Duration::from_secs(10));
+/// assert_eq!(map.get(&"session"), None);
+/// assert!(!map.contains_key(&"token"));
+/// assert_eq!(map.get(&"forever"), Some(&2));
+///
+/// let mut purged = map.purge_expired();
+/// purged[0].0.sort();
+/// assert_eq!(purged, vec![(vec!["session", "token"], 1)]);
+/// assert_eq!(map.len(), 1);
+/// ```
+pub struct MultiKeyTtlMap<K, V, C = SystemClock> {
+    map: MultiKeyMap<K, V>,
+    /// The deadline of every slot, indexed like the slots. Vacant slots hold stale deadlines,
+    /// which are overwritten when the slot is reused.
+    deadlines: Vec<Option<Instant>>,
+    /// The deadline of every alias that expires on its own.
+    alias_deadlines: HashMap<K, Instant>,
+    clock: C,
+}
+
+impl<K: Eq + Hash, V> MultiKeyTtlMap<K, V> {
+    /// Creates an empty `MultiKeyTtlMap` reading the system clock.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyTtlMap;
+    ///
+    /// let map: MultiKeyTtlMap<&str, &str> = MultiKeyTtlMap::new();
+    /// ```
+    pub fn new() -> Self {
+        Self::with_clock(SystemClock)
+    }
+}
+
+impl<K: Eq + Hash, V, C: Clock> MultiKeyTtlMap<K, V, C> {
+    /// Creates an empty `MultiKeyTtlMap` reading `clock`.
+    ///
+    /// # Arguments
+    ///
+    /// * `clock` - The source of the current time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::{MultiKeyTtlMap, SystemClock};
+    ///
+    /// let map: MultiKeyTtlMap<&str, &str> = MultiKeyTtlMap::with_clock(SystemClock);
+    /// ```
+    pub fn with_clock(clock: C) -> Self {
+        MultiKeyTtlMap {
+            map: MultiKeyMap::new(),
+            deadlines: Vec::new(),
+            alias_deadlines: HashMap::new(),
+            clock,
+        }
+    }
+
+    /// Retrieves a reference to a value by its key.
+    ///
+    /// Returns `None` if the key does not exist, has expired, or its group has expired.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key to lookup.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyTtlMap;
+    /// use std::time::Duration;
+    ///
+    /// let mut map = MultiKeyTtlMap::new();
+    /// map.insert_with_ttl("key1", "value1", Duration::from_secs(60));
+    /// map.insert_with_ttl("key2", "value2", Duration::ZERO);
+    /// assert_eq!(map.get(&"key1"), Some(&"value1"));
+    /// assert_eq!(map.get(&"key2"), None);
+    /// ```
+    pub fn get(&self, key: &K) -> Option<&V> {
+        let index = self.live_index(key)?;
+        Some(self.map.value(index))
+    }
+
+    /// Retrieves a mutable reference to a value by its key.
+    ///
+    /// Returns `None` if the key does not exist, has expired, or its group has expired.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key to lookup.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyTtlMap;
+    ///
+    /// let mut map = MultiKeyTtlMap::new();
+    /// map.insert("key1", 1);
+    /// *map.get_mut(&"key1").unwrap() += 1;
+    /// assert_eq!(map.get(&"key1"), Some(&2));
+    /// ```
+    pub fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        let index = self.live_index(key)?;
+        Some(self.map.value_mut(index))
+    }
+
+    /// Inserts a value with the given key, without expiry.
+    ///
+    /// If the key already exists, it is moved to the new value, and its old group is removed if
+    /// it has no other keys.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key to insert.
+    /// * `value` - The value to insert.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyTtlMap;
+    ///
+    /// let mut map = MultiKeyTtlMap::new();
+    /// map.insert("key1", "value1");
+    /// assert_eq!(map.get(&"key1"), Some(&"value1"));
+    /// ```
+    pub fn insert(&mut self, key: K, value: V) {
+        self.insert_with_deadline(key, value, None);
+    }
+
+    /// Inserts a value with the given key, expiring once `ttl` has elapsed.
+    ///
+    /// If the key already exists, it is moved to the new value, and its old group is removed if
+    /// it has no other keys.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key to insert.
+    /// * `value` - The value to insert.
+    /// * `ttl` - How long the group lives.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyTtlMap;
+    /// use std::time::Duration;
+    ///
+    /// let mut map = MultiKeyTtlMap::new();
+    /// map.insert_with_ttl("key1", "value1", Duration::from_secs(60));
+    /// assert!(map.contains_key(&"key1"));
+    /// ```
+    pub fn insert_with_ttl(&mut self, key: K, value: V, ttl: Duration) {
+        let deadline = self.clock.now() + ttl;
+        self.insert_with_deadline(key, value, Some(deadline));
+    }
+
+    /// Adds a new alias key for the element at `key`.
+    ///
+    /// Returns the reference count if the alias is successfully added. The alias shares the
+    /// deadline of its group, and no longer expires on its own if it was added with
+    /// [`insert_alias_with_ttl`](Self::insert_alias_with_ttl). Returns `None` if `key` or its
+    /// group has expired.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The original key.
+    /// * `alias` - The alias key to add.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyTtlMap;
+    /// use std::time::Duration;
+    ///
+    /// let mut map = MultiKeyTtlMap::new();
+    /// map.insert("key1", "value1");
+    /// map.insert_with_ttl("key2", "value2", Duration::ZERO);
+    /// assert_eq!(map.insert_alias(&"key1", "alias1"), Some(2));
+    /// assert_eq!(map.insert_alias(&"key2", "alias2"), None);
+    /// ```
+    pub fn insert_alias(&mut self, key: &K, alias: K) -> Option<usize> {
+        self.live_index(key)?;
+        self.alias_deadlines.remove(&alias);
+        self.map.insert_alias(key, alias)
+    }
+
+    /// Adds a new alias key for the element at `key`, expiring on its own once `ttl` has
+    /// elapsed.
+    ///
+    /// The alias also expires with its group, whichever deadline comes first. Once expired, it
+    /// misses lookups while the other keys of the group keep resolving, until
+    /// [`purge_expired_aliases`](Self::purge_expired_aliases) removes it. If the alias already
+    /// belongs to the group, only its deadline is replaced.
+    ///
+    /// Returns the reference count if the alias is successfully added. Returns `None` if `key`
+    /// or its group has expired.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The original key.
+    /// * `alias` - The alias key to add.
+    /// * `ttl` - How long the alias lives.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::{Clock, MultiKeyTtlMap};
+    /// use std::cell::Cell;
+    /// use std::rc::Rc;
+    /// use std::time::{Duration, Instant};
+    ///
+    /// struct MockClock(Rc<Cell<Instant>>);
+    ///
+    /// impl Clock for MockClock {
+    ///     fn now(&self) -> Instant {
+    ///         self.0.get()
+    ///     }
+    /// }
+    ///
+    /// let start = Instant::now();
+    /// let now = Rc::new(Cell::new(start));
+    /// let mut map = MultiKeyTtlMap::with_clock(MockClock(Rc::clone(&now)));
+    /// map.insert("record-17", "report.pdf");
+    /// let day = Duration::from_secs(24 * 3600);
+    /// assert_eq!(map.insert_alias_with_ttl(&"record-17", "share-link", day), Some(2));
+    /// assert_eq!(map.insert_alias_with_ttl(&"record-17", "preview", 2 * day), Some(3));
+    ///
+    /// // The share link expires on its own, the record and the preview link remain
+    /// now.set(start + day);
+    /// assert_eq!(map.get(&"share-link"), None);
+    /// assert!(!map.contains_key(&"share-link"));
+    /// assert_eq!(map.get(&"record-17"), Some(&"report.pdf"));
+    /// assert_eq!(map.get(&"preview"), Some(&"report.pdf"));
+    /// assert_eq!(map.insert_alias(&"share-link", "other"), None);
+    ///
+    /// assert_eq!(map.purge_expired_aliases(), vec!["share-link"]);
+    /// assert_eq!(map.remove_alias(&"record-17"), Some(1));
+    ///
+    /// // Once its last key expires, the purge drops the value
+    /// now.set(start + 2 * day);
+    /// assert_eq!(map.get(&"preview"), None);
+    /// assert_eq!(map.len(), 1);
+    /// assert_eq!(map.purge_expired_aliases(), vec!["preview"]);
+    /// assert!(map.is_empty());
+    /// ```
+    pub fn insert_alias_with_ttl(&mut self, key: &K, alias: K, ttl: Duration) -> Option<usize>
+    where
+        K: Clone,
+    {
+        self.live_index(key)?;
+        let deadline = self.clock.now() + ttl;
+        let count = self.map.insert_alias(key, alias.clone())?;
+        self.alias_deadlines.insert(alias, deadline);
+        Some(count)
+    }
+
+    /// Makes the group of `key` expire once `ttl` has elapsed from now, replacing any previous
+    /// deadline.
+    ///
+    /// Returns `false` if the key does not exist or its group has already expired.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - A key of the group.
+    /// * `ttl` - How long the group lives from now.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyTtlMap;
+    /// use std::time::Duration;
+    ///
+    /// let mut map = MultiKeyTtlMap::new();
+    /// map.insert("key1", "value1");
+    /// map.insert_alias(&"key1", "alias1");
+    /// assert!(map.set_ttl(&"alias1", Duration::ZERO));
+    /// assert_eq!(map.get(&"key1"), None);
+    /// assert!(!map.set_ttl(&"key1", Duration::from_secs(60)));
+    /// ```
+    pub fn set_ttl(&mut self, key: &K, ttl: Duration) -> bool {
+        let Some(index) = self.live_index(key) else {
+            return false;
+        };
+        self.deadlines[index] = Some(self.clock.now() + ttl);
+        true
+    }
+
+    /// Removes the deadline of the group of `key`, so that it never expires.
+    ///
+    /// Returns `false` if the key does not exist or its group has already expired.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - A key of the group.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyTtlMap;
+    /// use std::time::Duration;
+    ///
+    /// let mut map = MultiKeyTtlMap::new();
+    /// map.insert_with_ttl("key1", "value1", Duration::from_secs(60));
+    /// assert!(map.persist(&"key1"));
+    /// assert_eq!(map.time_to_live(&"key1"), None);
+    /// ```
+    pub fn persist(&mut self, key: &K) -> bool {
+        let Some(index) = self.live_index(key) else {
+            return false;
+        };
+        self.deadlines[index] = None;
+        true
+    }
+
+    /// Returns how long the group of `key` has left to live.
+    ///
+    /// Returns `None` if the key does not exist, has expired, or its group has expired or has no
+    /// deadline. The deadline of the key itself, if it expires on its own, is not taken into
+    /// account.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - A key of the group.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyTtlMap;
+    /// use std::time::Duration;
+    ///
+    /// let mut map = MultiKeyTtlMap::new();
+    /// map.insert_with_ttl("key1", "value1", Duration::from_secs(60));
+    /// map.insert("key2", "value2");
+    /// assert!(map.time_to_live(&"key1").unwrap() <= Duration::from_secs(60));
+    /// assert_eq!(map.time_to_live(&"key2"), None);
+    /// ```
+    pub fn time_to_live(&self, key: &K) -> Option<Duration> {
+        let index = self.live_index(key)?;
+        let deadline = self.deadlines[index]?;
+        Some(deadline.duration_since(self.clock.now()))
+    }
+
+    /// Removes an alias key.
+    ///
+    /// Returns the reference count if the alias is successfully removed.
+    /// If the last alias is removed, the value is also removed. Returns `None` if the alias or
+    /// its group has expired.
+    ///
+    /// # Arguments
+    ///
+    /// * `alias` - The alias key to remove.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyTtlMap;
+    ///
+    /// let mut map = MultiKeyTtlMap::new();
+    /// map.insert("key1", "value1");
+    /// map.insert_alias(&"key1", "alias1");
+    /// assert_eq!(map.remove_alias(&"alias1"), Some(1));
+    /// assert_eq!(map.remove_alias(&"key1"), Some(0));
+    /// assert!(map.is_empty());
+    /// ```
+    pub fn remove_alias(&mut self, alias: &K) -> Option<usize> {
+        self.live_index(alias)?;
+        self.alias_deadlines.remove(alias);
+        self.map.remove_alias(alias)
+    }
+
+    /// Removes a value by its key and all its aliases.
+    ///
+    /// Returns the value if it was present and its group had not expired. An expired group is
+    /// removed all the same, but an expired key leaves its group in place and returns `None`.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key of the value to remove.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyTtlMap;
+    /// use std::time::Duration;
+    ///
+    /// let mut map = MultiKeyTtlMap::new();
+    /// map.insert("key1", "value1");
+    /// map.insert_with_ttl("key2", "value2", Duration::ZERO);
+    /// assert_eq!(map.remove(&"key1"), Some("value1"));
+    /// assert_eq!(map.remove(&"key2"), None);
+    /// assert!(map.is_empty());
+    /// ```
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        if self.alias_expired(key) {
+            return None;
+        }
+        let live = self.live_index(key).is_some();
+        let index = self.map.index_of(key)?;
+        self.forget_alias_deadlines(index);
+        let value = self.map.remove(key)?;
+        live.then_some(value)
+    }
+
+    /// Removes every expired group and returns their keys and values, the earliest deadline
+    /// first.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::{Clock, MultiKeyTtlMap};
+    /// use std::cell::Cell;
+    /// use std::rc::Rc;
+    /// use std::time::{Duration, Instant};
+    ///
+    /// struct MockClock(Rc<Cell<Instant>>);
+    ///
+    /// impl Clock for MockClock {
+    ///     fn now(&self) -> Instant {
+    ///         self.0.get()
+    ///     }
+    /// }
+    ///
+    /// let start = Instant::now();
+    /// let now = Rc::new(Cell::new(start));
+    /// let mut map = MultiKeyTtlMap::with_clock(MockClock(Rc::clone(&now)));
+    /// map.insert_with_ttl("c", 3, Duration::from_secs(3));
+    /// map.insert_with_ttl("a", 1, Duration::from_secs(1));
+    /// map.insert_with_ttl("d", 4, Duration::from_secs(4));
+    /// map.insert_with_ttl("b", 2, Duration::from_secs(2));
+    /// map.insert("e", 5);
+    ///
+    /// now.set(start + Duration::from_secs(3));
+    /// assert_eq!(
+    ///     map.purge_expired(),
+    ///     vec![(vec!["a"], 1), (vec!["b"], 2), (vec!["c"], 3)]
+    /// );
+    /// assert!(map.purge_expired().is_empty());
+    /// assert_eq!(map.len(), 2);
+    ///
+    /// now.set(start + Duration::from_secs(3600));
+    /// assert_eq!(map.purge_expired(), vec![(vec!["d"], 4)]);
+    /// assert_eq!(map.get(&"e"), Some(&5));
+    /// ```
+    pub fn purge_expired(&mut self) -> Vec<(Vec<K>, V)> {
+        let now = self.clock.now();
+        let mut expired: Vec<(Instant, usize)> = self
+            .map
+            .occupied()
+            .filter_map(|(index, _)| self.deadlines[index].map(|deadline| (deadline, index)))
+            .filter(|&(deadline, _)| deadline <= now)
+            .collect();
+        expired.sort_unstable();
+        expired
+            .into_iter()
+            .map(|(_, index)| {
+                self.forget_alias_deadlines(index);
+                let keys = self.map.unlink_all(index);
+                (keys, self.map.release(index))
+            })
+            .collect()
+    }
+
+    /// Removes every expired alias and returns them, the earliest deadline first.
+    ///
+    /// A group whose keys have all expired is removed with its value. The other groups keep
+    /// their remaining keys.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::{Clock, MultiKeyTtlMap};
+    /// use std::cell::Cell;
+    /// use std::rc::Rc;
+    /// use std::time::{Duration, Instant};
+    ///
+    /// struct MockClock(Rc<Cell<Instant>>);
+    ///
+    /// impl Clock for MockClock {
+    ///     fn now(&self) -> Instant {
+    ///         self.0.get()
+    ///     }
+    /// }
+    ///
+    /// let start = Instant::now();
+    /// let now = Rc::new(Cell::new(start));
+    /// let mut map = MultiKeyTtlMap::with_clock(MockClock(Rc::clone(&now)));
+    /// map.insert("id", 1);
+    /// map.insert_alias_with_ttl(&"id", "b", Duration::from_secs(2));
+    /// map.insert_alias_with_ttl(&"id", "a", Duration::from_secs(1));
+    /// map.insert_alias_with_ttl(&"id", "c", Duration::from_secs(3));
+    ///
+    /// now.set(start + Duration::from_secs(2));
+    /// assert_eq!(map.purge_expired_aliases(), vec!["a", "b"]);
+    /// assert!(map.purge_expired_aliases().is_empty());
+    ///
+    /// // The group survives its last expiring alias through its permanent key
+    /// now.set(start + Duration::from_secs(3));
+    /// assert_eq!(map.purge_expired_aliases(), vec!["c"]);
+    /// assert_eq!(map.get(&"id"), Some(&1));
+    /// ```
+    pub fn purge_expired_aliases(&mut self) -> Vec<K> {
+        let now = self.clock.now();
+        let mut expired: Vec<(Instant, K)> = self
+            .alias_deadlines
+            .extract_if(|_, deadline| *deadline <= now)
+            .map(|(alias, deadline)| (deadline, alias))
+            .collect();
+        expired.sort_unstable_by_key(|&(deadline, _)| deadline);
+        expired
+            .into_iter()
+            .map(|(_, alias)| {
+                let (index, _) = self.map.unbind(&alias).expect("expiring alias is bound");
+                if self.map.slots[index].keys.is_empty() {
+                    self.map.release(index);
+                }
+                alias
+            })
+            .collect()
+    }
+
+    /// Returns `true` if the map contains a value for the specified key and its group has not
+    /// expired.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key to check.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyTtlMap;
+    /// use std::time::Duration;
+    ///
+    /// let mut map = MultiKeyTtlMap::new();
+    /// map.insert("key1", "value1");
+    /// map.insert_with_ttl("key2", "value2", Duration::ZERO);
+    /// assert!(map.contains_key(&"key1"));
+    /// assert!(!map.contains_key(&"key2"));
+    /// ```
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.live_index(key).is_some()
+    }
+
+    /// Returns the number of groups in the map, including expired groups not purged yet.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyTtlMap;
+    /// use std::time::Duration;
+    ///
+    /// let mut map = MultiKeyTtlMap::new();
+    /// map.insert("key1", "value1");
+    /// map.insert_with_ttl("key2", "value2", Duration::ZERO);
+    /// assert_eq!(map.len(), 2);
+    /// map.purge_expired();
+    /// assert_eq!(map.len(), 1);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    /// Returns `true` if the map contains no values, counting expired groups not purged yet.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyTtlMap;
+    ///
+    /// let map: MultiKeyTtlMap<&str, &str> = MultiKeyTtlMap::new();
+    /// assert!(map.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    /// Returns the slot of `key` if neither the key nor its group has expired.
+    fn live_index(&self, key: &K) -> Option<usize> {
+        if self.alias_expired(key) {
+            return None;
+        }
+        let index = self.map.index_of(key)?;
+        match self.deadlines[index] {
+            Some(deadline) if deadline <= self.clock.now() => None,
+            _ => Some(index),
+        }
+    }
+
+    /// Returns `true` if `key` expires on its own and its deadline has been reached.
+    fn alias_expired(&self, key: &K) -> bool {
+        self.alias_deadlines
+            .get(key)
+            .is_some_and(|&deadline| deadline <= self.clock.now())
+    }
+
+    /// Drops the deadlines of the keys of the group at `index`, which is being removed.
+    fn forget_alias_deadlines(&mut self, index: usize) {
+        if !self.alias_deadlines.is_empty() {
+            for key in &self.map.slots[index].keys {
+                self.alias_deadlines.remove(key);
+            }
+        }
+    }
+
+    fn insert_with_deadline(&mut self, key: K, value: V, deadline: Option<Instant>) {
+        self.alias_deadlines.remove(&key);
+        let index = self.map.alloc(value);
+        self.map.bind(key, index);
+        if self.deadlines.len() <= index {
+            self.deadlines.resize(index + 1, None);
+        }
+        self.deadlines[index] = deadline;
+    }
+}
+
+impl<K: Eq + Hash, V> Default for MultiKeyTtlMap<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Debug, V: Debug, C> Debug for MultiKeyTtlMap<K, V, C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut debug_struct = f.debug_struct("MultiKeyTtlMap");
+        for (index, slot) in self.map.slots.iter().enumerate() {
+            if let Some(value) = &slot.value {
+                let keys = format!("{:?}", slot.keys);
+                debug_struct.field(&keys, &(value, self.deadlines[index]));
+            }
+        }
+        debug_struct.finish()
+    }
+}
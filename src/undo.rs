@@ -0,0 +1,562 @@
+use crate::MultiKeyMap;
+use std::collections::VecDeque;
+use std::fmt::{self, Debug};
+use std::hash::Hash;
+
+/// An `UndoableMultiKeyMap` is a [`MultiKeyMap`] that records its edits so that they can be
+/// undone and redone.
+///
+/// Every edit made through the wrapper is recorded as the steps that revert it, capturing
+/// whatever the edit discards: removing a group keeps all of its keys and its value, so undoing
+/// the removal restores the whole group. Undoing restores the keys of every group in their
+/// original order, so primary keys come back too. Making a new edit discards the edits that could
+/// be redone.
+///
+/// # Examples
+///
+/// ```
+/// use multi_key_map::{MultiKeyMap, UndoableMultiKeyMap};
+///
+/// let mut original = MultiKeyMap::new();
+/// original.insert("en", "hello");
+/// original.insert_alias(&"en", "en-us");
+/// original.insert("fr", "bonjour");
+///
+/// let mut map = UndoableMultiKeyMap::from(original.clone());
+/// map.insert("de", "hallo");
+/// map.insert_alias(&"de", "de-at");
+/// map.insert_alias(&"de", "en-us");
+/// map.replace(&"fr", "salut");
+/// map.remove_alias(&"de");
+/// map.remove(&"en");
+/// map.insert("fr", "coucou");
+/// let edited = map.as_map().clone();
+/// assert_eq!(map.history_len(), 7);
+///
+/// while map.undo() {}
+/// assert_eq!(*map.as_map(), original);
+/// assert!(map.as_map().same_grouping(&original));
+/// assert_eq!(map.as_map().primary_of(&"en-us"), Some(&"en"));
+///
+/// while map.redo() {}
+/// assert_eq!(*map.as_map(), edited);
+/// assert!(map.as_map().same_grouping(&edited));
+/// assert_eq!(map.history_len(), 7);
+/// ```
+pub struct UndoableMultiKeyMap<K, V> {
+    map: MultiKeyMap<K, V>,
+    /// The steps reverting every undoable edit, the latest edit last.
+    undo: VecDeque<Vec<Step<K, V>>>,
+    /// The steps replaying every undone edit, the latest undone edit last.
+    redo: Vec<Vec<Step<K, V>>>,
+    /// The most edits kept for undoing.
+    history_limit: Option<usize>,
+}
+
+/// An elementary change to a map. Applying a step returns the step reverting it.
+enum Step<K, V> {
+    /// Create a group with `keys`, in that order, holding `value`.
+    Insert { keys: Vec<K>, value: V },
+    /// Remove the group of `key`.
+    Remove { key: K },
+    /// Add `alias` at `position` in the keys of the group of `group`.
+    AddAlias { group: K, alias: K, position: usize },
+    /// Remove `alias` from its group, which has other keys.
+    RemoveAlias { alias: K },
+    /// Replace the value of the group of `key` with `value`.
+    Replace { key: K, value: V },
+}
+
+impl<K: Eq + Hash + Clone, V> Step<K, V> {
+    /// Applies the step to `map`, which must be in the state the step was recorded for, and
+    /// returns the step reverting it.
+    fn apply(self, map: &mut MultiKeyMap<K, V>) -> Self {
+        match self {
+            Step::Insert { keys, value } => {
+                let key = keys[0].clone();
+                let index = map.alloc(value);
+                for key in keys {
+                    map.link(key, index);
+                }
+                Step::Remove { key }
+            }
+            Step::Remove { key } => {
+                let index = map.index_of(&key).expect("removed key is bound");
+                let keys = map.unlink_all(index);
+                let value = map.release(index);
+                Step::Insert { keys, value }
+            }
+            Step::AddAlias {
+                group,
+                alias,
+                position,
+            } => {
+                let index = map.index_of(&group).expect("group key is bound");
+                map.link(alias.clone(), index);
+                let last = map.count_references(index) - 1;
+                if position < last {
+                    map.swap_keys(index, position, last);
+                }
+                Step::RemoveAlias { alias }
+            }
+            Step::RemoveAlias { alias } => {
+                let position = map.find(&alias).expect("removed alias is bound").position;
+                let (index, alias) = map.unbind(&alias).expect("removed alias is bound");
+                Step::AddAlias {
+                    group: map.slots[index].keys[0].clone(),
+                    alias,
+                    position,
+                }
+            }
+            Step::Replace { key, value } => {
+                let index = map.index_of(&key).expect("replaced key is bound");
+                let value = std::mem::replace(map.value_mut(index), value);
+                Step::Replace { key, value }
+            }
+        }
+    }
+}
+
+impl<K: Eq + Hash + Clone, V> UndoableMultiKeyMap<K, V> {
+    /// Creates an empty `UndoableMultiKeyMap` keeping every edit for undoing.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::UndoableMultiKeyMap;
+    ///
+    /// let map: UndoableMultiKeyMap<&str, u32> = UndoableMultiKeyMap::new();
+    /// assert_eq!(map.history_len(), 0);
+    /// ```
+    pub fn new() -> Self {
+        UndoableMultiKeyMap::from(MultiKeyMap::new())
+    }
+
+    /// Creates an empty `UndoableMultiKeyMap` keeping at most `limit` edits for undoing.
+    ///
+    /// Once `limit` edits are recorded, every new edit forgets the oldest one.
+    ///
+    /// # Arguments
+    ///
+    /// * `limit` - The most edits that can be undone.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::UndoableMultiKeyMap;
+    ///
+    /// let mut map = UndoableMultiKeyMap::with_history_limit(2);
+    /// map.insert("a", 1);
+    /// map.insert("b", 2);
+    /// map.insert("c", 3);
+    /// assert_eq!(map.history_len(), 2);
+    ///
+    /// while map.undo() {}
+    /// assert_eq!(map.get(&"a"), Some(&1));
+    /// assert_eq!(map.len(), 1);
+    /// ```
+    pub fn with_history_limit(limit: usize) -> Self {
+        let mut map = Self::new();
+        map.history_limit = Some(limit);
+        map
+    }
+
+    /// Retrieves a reference to a value by its key.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key to lookup.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::UndoableMultiKeyMap;
+    ///
+    /// let mut map = UndoableMultiKeyMap::new();
+    /// map.insert("key1", "value1");
+    /// assert_eq!(map.get(&"key1"), Some(&"value1"));
+    /// ```
+    pub fn get(&self, key: &K) -> Option<&V> {
+        self.map.get(key)
+    }
+
+    /// Inserts a value with the given key, as [`MultiKeyMap::insert`] does.
+    ///
+    /// Undoing the insertion removes the new group and puts `key` back in its previous group, if
+    /// any.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key to insert.
+    /// * `value` - The value to insert.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::UndoableMultiKeyMap;
+    ///
+    /// let mut map = UndoableMultiKeyMap::new();
+    /// map.insert("key1", "value1");
+    /// map.insert_alias(&"key1", "alias1");
+    /// map.insert("alias1", "value2");
+    ///
+    /// map.undo();
+    /// assert!(map.as_map().are_aliases(&"key1", &"alias1"));
+    /// ```
+    pub fn insert(&mut self, key: K, value: V) {
+        let detach = self.detach(&key);
+        let insert = Step::Insert {
+            keys: vec![key],
+            value,
+        };
+        self.record(detach.into_iter().chain([insert]).collect());
+    }
+
+    /// Adds a new alias key for the element at `key`, as [`MultiKeyMap::insert_alias`] does.
+    ///
+    /// Returns the reference count if the alias is successfully added. Nothing is recorded if
+    /// the map is left unchanged.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The original key.
+    /// * `alias` - The alias key to add.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::UndoableMultiKeyMap;
+    ///
+    /// let mut map = UndoableMultiKeyMap::new();
+    /// map.insert("key1", "value1");
+    /// assert_eq!(map.insert_alias(&"key1", "alias1"), Some(2));
+    /// assert_eq!(map.insert_alias(&"key1", "alias1"), Some(2));
+    /// assert_eq!(map.insert_alias(&"key2", "alias2"), None);
+    /// assert_eq!(map.history_len(), 2);
+    /// ```
+    pub fn insert_alias(&mut self, key: &K, alias: K) -> Option<usize> {
+        if key == &alias {
+            return None;
+        }
+        let index = self.map.index_of(key)?;
+        let position = self.map.count_references(index);
+        if self.map.index_of(&alias) == Some(index) {
+            return Some(position);
+        }
+        if !self.map.has_room(index, 1) {
+            return None;
+        }
+        let detach = self.detach(&alias);
+        let add = Step::AddAlias {
+            group: key.clone(),
+            alias,
+            position,
+        };
+        self.record(detach.into_iter().chain([add]).collect());
+        Some(position + 1)
+    }
+
+    /// Removes an alias, as [`MultiKeyMap::remove_alias`] does.
+    ///
+    /// Returns the remaining reference count, or `None` if the alias does not exist. Undoing the
+    /// removal restores the alias, and its group if it was the last key.
+    ///
+    /// # Arguments
+    ///
+    /// * `alias` - The alias key to remove.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::UndoableMultiKeyMap;
+    ///
+    /// let mut map = UndoableMultiKeyMap::new();
+    /// map.insert("key1", "value1");
+    /// map.insert_alias(&"key1", "alias1");
+    /// assert_eq!(map.remove_alias(&"key1"), Some(1));
+    /// assert_eq!(map.remove_alias(&"alias1"), Some(0));
+    ///
+    /// map.undo();
+    /// map.undo();
+    /// assert_eq!(map.as_map().primary_of(&"alias1"), Some(&"key1"));
+    /// ```
+    pub fn remove_alias(&mut self, alias: &K) -> Option<usize> {
+        let step = self.detach(alias)?;
+        let remaining = self.map.alias_count(alias)? - 1;
+        self.record(vec![step]);
+        Some(remaining)
+    }
+
+    /// Removes the group of `key` with all of its keys, as [`MultiKeyMap::remove`] does.
+    ///
+    /// Returns a clone of the removed value, whose original is kept for undoing the removal.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - Any key of the group to remove.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::UndoableMultiKeyMap;
+    ///
+    /// let mut map = UndoableMultiKeyMap::new();
+    /// map.insert("key1", "value1");
+    /// map.insert_alias(&"key1", "alias1");
+    /// assert_eq!(map.remove(&"alias1"), Some("value1"));
+    /// assert!(map.is_empty());
+    ///
+    /// map.undo();
+    /// assert!(map.as_map().are_aliases(&"key1", &"alias1"));
+    /// ```
+    pub fn remove(&mut self, key: &K) -> Option<V>
+    where
+        V: Clone,
+    {
+        let value = self.map.get(key)?.clone();
+        self.record(vec![Step::Remove { key: key.clone() }]);
+        Some(value)
+    }
+
+    /// Replaces the value of the group of `key`, as [`MultiKeyMap::replace`] does.
+    ///
+    /// Returns a clone of the previous value, whose original is kept for undoing the replacement.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - Any key of the group.
+    /// * `value` - The new value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::UndoableMultiKeyMap;
+    ///
+    /// let mut map = UndoableMultiKeyMap::new();
+    /// map.insert("key1", "value1");
+    /// assert_eq!(map.replace(&"key1", "value2"), Some("value1"));
+    ///
+    /// map.undo();
+    /// assert_eq!(map.get(&"key1"), Some(&"value1"));
+    /// ```
+    pub fn replace(&mut self, key: &K, value: V) -> Option<V>
+    where
+        V: Clone,
+    {
+        let previous = self.map.get(key)?.clone();
+        self.record(vec![Step::Replace {
+            key: key.clone(),
+            value,
+        }]);
+        Some(previous)
+    }
+
+    /// Reverts the latest edit not undone yet.
+    ///
+    /// Returns `false` if there is no edit to undo.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::UndoableMultiKeyMap;
+    ///
+    /// let mut map = UndoableMultiKeyMap::new();
+    /// map.insert("key1", "value1");
+    /// assert!(map.undo());
+    /// assert!(map.is_empty());
+    /// assert!(!map.undo());
+    /// ```
+    pub fn undo(&mut self) -> bool {
+        let Some(steps) = self.undo.pop_back() else {
+            return false;
+        };
+        let steps = Self::apply(&mut self.map, steps);
+        self.redo.push(steps);
+        true
+    }
+
+    /// Replays the latest undone edit.
+    ///
+    /// Returns `false` if there is no edit to redo, as after a new edit.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::UndoableMultiKeyMap;
+    ///
+    /// let mut map = UndoableMultiKeyMap::new();
+    /// map.insert("key1", "value1");
+    /// map.insert("key2", "value2");
+    /// map.undo();
+    /// assert!(map.redo());
+    /// assert_eq!(map.get(&"key2"), Some(&"value2"));
+    /// assert!(!map.redo());
+    ///
+    /// map.undo();
+    /// map.insert("key3", "value3");
+    /// assert!(!map.redo());
+    /// ```
+    pub fn redo(&mut self) -> bool {
+        let Some(steps) = self.redo.pop() else {
+            return false;
+        };
+        let steps = Self::apply(&mut self.map, steps);
+        self.push_undo(steps);
+        true
+    }
+
+    /// Returns the number of edits that can be undone.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::UndoableMultiKeyMap;
+    ///
+    /// let mut map = UndoableMultiKeyMap::new();
+    /// map.insert("key1", "value1");
+    /// map.insert("key2", "value2");
+    /// map.undo();
+    /// assert_eq!(map.history_len(), 1);
+    /// ```
+    pub fn history_len(&self) -> usize {
+        self.undo.len()
+    }
+
+    /// Returns `true` if the map contains the given key.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key to check.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::UndoableMultiKeyMap;
+    ///
+    /// let mut map = UndoableMultiKeyMap::new();
+    /// map.insert("key1", "value1");
+    /// assert!(map.contains_key(&"key1"));
+    /// ```
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.map.contains_key(key)
+    }
+
+    /// Returns the number of groups in the map.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::UndoableMultiKeyMap;
+    ///
+    /// let mut map = UndoableMultiKeyMap::new();
+    /// map.insert("key1", "value1");
+    /// map.insert_alias(&"key1", "alias1");
+    /// assert_eq!(map.len(), 1);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    /// Returns `true` if the map contains no groups.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::UndoableMultiKeyMap;
+    ///
+    /// let map: UndoableMultiKeyMap<&str, &str> = UndoableMultiKeyMap::new();
+    /// assert!(map.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    /// Returns the underlying map.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::UndoableMultiKeyMap;
+    ///
+    /// let mut map = UndoableMultiKeyMap::new();
+    /// map.insert("key1", "value1");
+    /// map.insert_alias(&"key1", "alias1");
+    /// assert!(map.as_map().are_aliases(&"key1", &"alias1"));
+    /// ```
+    pub fn as_map(&self) -> &MultiKeyMap<K, V> {
+        &self.map
+    }
+
+    /// Consumes the map, returning the underlying map and dropping the history.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::UndoableMultiKeyMap;
+    ///
+    /// let mut map = UndoableMultiKeyMap::new();
+    /// map.insert("key1", "value1");
+    /// let inner = map.into_map();
+    /// assert_eq!(inner.get(&"key1"), Some(&"value1"));
+    /// ```
+    pub fn into_map(self) -> MultiKeyMap<K, V> {
+        self.map
+    }
+
+    /// Returns the step taking `key` out of its group, if it has one.
+    fn detach(&self, key: &K) -> Option<Step<K, V>> {
+        match self.map.alias_count(key)? {
+            1 => Some(Step::Remove { key: key.clone() }),
+            _ => Some(Step::RemoveAlias { alias: key.clone() }),
+        }
+    }
+
+    /// Applies the steps of a new edit and records it, forgetting the edits to redo.
+    fn record(&mut self, steps: Vec<Step<K, V>>) {
+        let steps = Self::apply(&mut self.map, steps);
+        self.redo.clear();
+        self.push_undo(steps);
+    }
+
+    /// Records the steps reverting an edit, forgetting the oldest edit beyond the limit.
+    fn push_undo(&mut self, steps: Vec<Step<K, V>>) {
+        self.undo.push_back(steps);
+        if self
+            .history_limit
+            .is_some_and(|limit| self.undo.len() > limit)
+        {
+            self.undo.pop_front();
+        }
+    }
+
+    /// Applies `steps` in order and returns the steps reverting them.
+    fn apply(map: &mut MultiKeyMap<K, V>, steps: Vec<Step<K, V>>) -> Vec<Step<K, V>> {
+        let mut inverse: Vec<_> = steps.into_iter().map(|step| step.apply(map)).collect();
+        inverse.reverse();
+        inverse
+    }
+}
+
+impl<K: Eq + Hash + Clone, V> Default for UndoableMultiKeyMap<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K, V> From<MultiKeyMap<K, V>> for UndoableMultiKeyMap<K, V> {
+    /// Wraps `map` with an empty history, keeping every edit for undoing.
+    fn from(map: MultiKeyMap<K, V>) -> Self {
+        UndoableMultiKeyMap {
+            map,
+            undo: VecDeque::new(),
+            redo: Vec::new(),
+            history_limit: None,
+        }
+    }
+}
+
+impl<K: Debug, V: Debug> Debug for UndoableMultiKeyMap<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.map.fmt(f)
+    }
+}
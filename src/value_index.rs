@@ -0,0 +1,95 @@
+use crate::small::Slots;
+use hashbrown::HashTable;
+
+/// Where a slot stands in the [`ValueIndex`].
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum SlotHash {
+    /// The value of the slot is indexed under this hash.
+    Hashed(u64),
+    /// The slot changed since it was last indexed, and is listed as stale.
+    Stale,
+    /// The slot was vacant when it was last indexed.
+    Vacant,
+}
+
+/// An index of the values of a map by their hash, used by
+/// [`insert_or_alias`](crate::MultiKeyMap::insert_or_alias) to find equal values.
+///
+/// Maintaining the index needs no hashing: every modification of a slot marks it stale, and the
+/// stale slots are hashed again on the next [`refresh`](Self::refresh), where values are known to
+/// be hashable. The map only builds the index once it is first needed.
+#[derive(Clone)]
+pub(crate) struct ValueIndex {
+    /// The indices of the occupied slots that are not stale, by the hash of their value.
+    table: HashTable<usize>,
+    /// The state of every slot, up to the slots added since the last refresh.
+    hashes: Vec<SlotHash>,
+    /// The slots changed since the last refresh, each listed once.
+    stale: Vec<usize>,
+}
+
+impl ValueIndex {
+    pub(crate) fn new() -> Self {
+        ValueIndex {
+            table: HashTable::new(),
+            hashes: Vec::new(),
+            stale: Vec::new(),
+        }
+    }
+
+    /// Marks the slot at `index` as changed, taking its value out of the index.
+    pub(crate) fn touch(&mut self, index: usize) {
+        // Slots added since the last refresh are indexed by it anyway
+        let Some(state) = self.hashes.get_mut(index) else {
+            return;
+        };
+        match *state {
+            SlotHash::Hashed(hash) => {
+                let Ok(entry) = self.table.find_entry(hash, |&other| other == index) else {
+                    unreachable!("hashed slots are indexed");
+                };
+                entry.remove();
+            }
+            SlotHash::Vacant => {}
+            SlotHash::Stale => return,
+        }
+        *state = SlotHash::Stale;
+        self.stale.push(index);
+    }
+
+    /// Indexes the values of the stale slots and of the slots added since the last refresh,
+    /// hashing them with `hash`.
+    pub(crate) fn refresh<K, V>(&mut self, slots: &Slots<K, V>, hash: impl Fn(&V) -> u64) {
+        let added = self.hashes.len()..slots.len();
+        self.hashes
+            .resize(slots.len().max(self.hashes.len()), SlotHash::Stale);
+        self.stale.extend(added);
+        let ValueIndex {
+            table,
+            hashes,
+            stale,
+        } = self;
+        for index in stale.drain(..) {
+            hashes[index] = match slots.get(index).and_then(|slot| slot.value.as_ref()) {
+                Some(value) => {
+                    let hash = hash(value);
+                    table.insert_unique(hash, index, |&other| match hashes[other] {
+                        SlotHash::Hashed(hash) => hash,
+                        _ => unreachable!("indexed slots are hashed"),
+                    });
+                    SlotHash::Hashed(hash)
+                }
+                None => SlotHash::Vacant,
+            };
+        }
+    }
+
+    /// Returns the indices of the slots whose value has the given hash, as of the last
+    /// [`refresh`](Self::refresh).
+    pub(crate) fn find(&self, hash: u64) -> impl Iterator<Item = usize> + '_ {
+        self.table
+            .iter_hash(hash)
+            .copied()
+            .filter(move |&index| self.hashes[index] == SlotHash::Hashed(hash))
+    }
+}
@@ -0,0 +1,225 @@
+use crate::MultiKeyMap;
+use std::fmt::{self, Debug};
+use std::hash::Hash;
+
+/// A read-only view of a [`MultiKeyMap`] restricted to the keys passing a filter, as returned by
+/// [`MultiKeyMap::view`].
+///
+/// Keys failing the filter are invisible: looking them up finds nothing, and they are left out
+/// of the aliases and keys returned. A group is visible as long as one of its keys is, so a group
+/// none of whose keys pass the filter is invisible entirely. Nothing is cloned, and every method
+/// evaluates the filter as it goes, so the view always agrees with the map it borrows.
+///
+/// # Examples
+///
+/// ```
+/// use multi_key_map::MultiKeyMap;
+///
+/// let mut map = MultiKeyMap::new();
+/// map.insert("plugin:a", 1);
+/// map.insert_alias(&"plugin:a", "core:a");
+/// map.insert("core:b", 2);
+///
+/// let view = map.view(|key| key.starts_with("plugin:"));
+/// assert_eq!(view.get(&"plugin:a"), Some(&1));
+/// // Aliases failing the filter are invisible, even in a visible group
+/// assert_eq!(view.get(&"core:a"), None);
+/// assert_eq!(view.aliases(&"plugin:a"), Some(vec![&"plugin:a"]));
+/// // So are groups without any visible key
+/// assert!(!view.contains_key(&"core:b"));
+/// assert_eq!(view.len(), 1);
+/// ```
+pub struct MultiKeyMapView<'a, K, V> {
+    map: &'a MultiKeyMap<K, V>,
+    filter: Box<dyn Fn(&K) -> bool + 'a>,
+}
+
+impl<K: Eq + Hash, V> MultiKeyMap<K, V> {
+    /// Returns a read-only view of the map restricted to the keys for which `filter` returns
+    /// `true`.
+    ///
+    /// See [`MultiKeyMapView`] for the visibility rules.
+    ///
+    /// # Arguments
+    ///
+    /// * `filter` - The predicate selecting the visible keys.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyMap;
+    ///
+    /// let mut map = MultiKeyMap::new();
+    /// map.insert(1, "one");
+    /// map.insert(2, "two");
+    /// let odd = map.view(|key| key % 2 == 1);
+    /// assert_eq!(odd.get(&1), Some(&"one"));
+    /// assert_eq!(odd.get(&2), None);
+    /// ```
+    pub fn view<'a>(&'a self, filter: impl Fn(&K) -> bool + 'a) -> MultiKeyMapView<'a, K, V> {
+        MultiKeyMapView {
+            map: self,
+            filter: Box::new(filter),
+        }
+    }
+}
+
+impl<'a, K: Eq + Hash, V> MultiKeyMapView<'a, K, V> {
+    /// Retrieves a reference to the value of `key`, or `None` if the key does not exist or is
+    /// not visible.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key to lookup.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyMap;
+    ///
+    /// let mut map = MultiKeyMap::new();
+    /// map.insert("visible", 1);
+    /// map.insert_alias(&"visible", "hidden");
+    /// let view = map.view(|key| *key != "hidden");
+    /// assert_eq!(view.get(&"visible"), Some(&1));
+    /// assert_eq!(view.get(&"hidden"), None);
+    /// ```
+    pub fn get(&self, key: &K) -> Option<&'a V> {
+        if !(self.filter)(key) {
+            return None;
+        }
+        self.map.get(key)
+    }
+
+    /// Returns `true` if `key` exists and is visible.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key to check.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyMap;
+    ///
+    /// let mut map = MultiKeyMap::new();
+    /// map.insert("visible", 1);
+    /// map.insert("hidden", 2);
+    /// let view = map.view(|key| *key != "hidden");
+    /// assert!(view.contains_key(&"visible"));
+    /// assert!(!view.contains_key(&"hidden"));
+    /// assert!(!view.contains_key(&"missing"));
+    /// ```
+    pub fn contains_key(&self, key: &K) -> bool {
+        (self.filter)(key) && self.map.contains_key(key)
+    }
+
+    /// Returns the visible keys of the group of `key`, or `None` if the key does not exist or is
+    /// not visible.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - Any visible key of the group.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyMap;
+    ///
+    /// let mut map = MultiKeyMap::new();
+    /// map.insert("ns:a", 1);
+    /// map.insert_alias(&"ns:a", "a");
+    /// map.insert_alias(&"ns:a", "ns:alpha");
+    /// let view = map.view(|key| key.starts_with("ns:"));
+    /// assert_eq!(view.aliases(&"ns:alpha"), Some(vec![&"ns:a", &"ns:alpha"]));
+    /// assert_eq!(view.aliases(&"a"), None);
+    /// ```
+    pub fn aliases(&self, key: &K) -> Option<Vec<&'a K>> {
+        if !(self.filter)(key) {
+            return None;
+        }
+        let index = self.map.index_of(key)?;
+        Some(
+            self.map.slots[index]
+                .keys
+                .iter()
+                .filter(|key| (self.filter)(key))
+                .collect(),
+        )
+    }
+
+    /// Returns an iterator over the visible keys.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyMap;
+    ///
+    /// let mut map = MultiKeyMap::new();
+    /// map.insert(1, "one");
+    /// map.insert_alias(&1, 10);
+    /// map.insert(2, "two");
+    /// let view = map.view(|key| *key < 10);
+    /// let mut keys: Vec<_> = view.keys().collect();
+    /// keys.sort();
+    /// assert_eq!(keys, [&1, &2]);
+    /// ```
+    pub fn keys(&self) -> impl Iterator<Item = &'a K> + '_ {
+        self.map.keys().filter(|key| (self.filter)(key))
+    }
+
+    /// Returns the number of visible groups.
+    ///
+    /// This evaluates the filter on the keys of every group, until one passes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyMap;
+    ///
+    /// let mut map = MultiKeyMap::new();
+    /// map.insert("a", 1);
+    /// map.insert_alias(&"a", "b");
+    /// map.insert("c", 2);
+    /// map.insert("d", 3);
+    /// let view = map.view(|key| *key != "d");
+    /// assert_eq!(view.len(), 2);
+    /// assert_eq!(map.view(|key| *key == "b").len(), 1);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.map
+            .slots
+            .iter()
+            .filter(|slot| slot.keys.iter().any(|key| (self.filter)(key)))
+            .count()
+    }
+
+    /// Returns `true` if no group is visible.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyMap;
+    ///
+    /// let mut map = MultiKeyMap::new();
+    /// map.insert("a", 1);
+    /// assert!(map.view(|key| *key == "b").is_empty());
+    /// assert!(!map.view(|key| *key == "a").is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        !self.map.keys().any(|key| (self.filter)(key))
+    }
+}
+
+impl<K: Debug, V: Debug> Debug for MultiKeyMapView<'_, K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut debug_struct = f.debug_struct("MultiKeyMapView");
+        for slot in &self.map.slots {
+            let keys: Vec<&K> = slot.keys.iter().filter(|key| (self.filter)(key)).collect();
+            if let (Some(value), false) = (&slot.value, keys.is_empty()) {
+                debug_struct.field(&format!("{keys:?}"), value);
+            }
+        }
+        debug_struct.finish()
+    }
+}
@@ -0,0 +1,70 @@
+use crate::MultiKeyMap;
+use std::hash::Hash;
+use std::sync::{Arc, Weak};
+
+impl<K: Eq + Hash, T> MultiKeyMap<K, Weak<T>> {
+    /// Retrieves the value of `key` as a strong reference, if it is still alive.
+    ///
+    /// Returns `None` if the key does not exist or every strong reference to its value has been
+    /// dropped. A dead group stays in the map until [`prune_dead`](Self::prune_dead) removes it.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key to lookup.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyMap;
+    /// use std::sync::Arc;
+    ///
+    /// let window = Arc::new("main window");
+    /// let mut registry = MultiKeyMap::new();
+    /// registry.insert_with_keys(["main", "window-1"], Arc::downgrade(&window)).unwrap();
+    /// assert_eq!(registry.get_upgraded(&"window-1").as_deref(), Some(&"main window"));
+    ///
+    /// drop(window);
+    /// assert_eq!(registry.get_upgraded(&"main"), None);
+    /// assert_eq!(registry.get_upgraded(&"missing"), None);
+    /// assert_eq!(registry.len(), 1);
+    /// ```
+    pub fn get_upgraded(&self, key: &K) -> Option<Arc<T>> {
+        self.get(key)?.upgrade()
+    }
+
+    /// Removes every group whose value is no longer alive, with all of its keys, and returns the
+    /// number of groups removed.
+    ///
+    /// Groups with a live value are left untouched.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multi_key_map::MultiKeyMap;
+    /// use std::sync::Arc;
+    ///
+    /// let (a, b, c) = (Arc::new(1), Arc::new(2), Arc::new(3));
+    /// let mut registry = MultiKeyMap::new();
+    /// registry.insert_with_keys(["a", "first"], Arc::downgrade(&a)).unwrap();
+    /// registry.insert_with_keys(["b", "second", "even"], Arc::downgrade(&b)).unwrap();
+    /// registry.insert("c", Arc::downgrade(&c));
+    ///
+    /// drop(b);
+    /// drop(c);
+    /// assert_eq!(registry.prune_dead(), 2);
+    /// assert_eq!(registry.len(), 1);
+    /// assert_eq!(registry.key_count(), 2);
+    /// assert!(!registry.contains_key(&"even"));
+    /// assert_eq!(registry.get_upgraded(&"first"), Some(a.clone()));
+    ///
+    /// assert_eq!(registry.prune_dead(), 0);
+    /// drop(a);
+    /// assert_eq!(registry.prune_dead(), 1);
+    /// assert!(registry.is_empty());
+    /// ```
+    pub fn prune_dead(&mut self) -> usize {
+        let before = self.len;
+        self.retain(|_, value| value.strong_count() > 0);
+        before - self.len
+    }
+}
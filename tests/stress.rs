@@ -0,0 +1,128 @@
+use multi_key_map::MultiKeyMap;
+use std::collections::{HashMap, HashSet};
+
+/// Alias churn does not scan the other keys, so half a million operations stay cheap.
+#[test]
+fn alias_churn() {
+    let mut map = MultiKeyMap::new();
+    for key in 0..1000 {
+        map.insert(key, key);
+    }
+    for round in 0..500_000 {
+        let alias = 1000 + round % 16;
+        if round % 32 < 16 {
+            assert_eq!(map.insert_alias(&0, alias), Some(2 + round % 16));
+        } else {
+            assert_eq!(map.remove_alias(&alias), Some(16 - round % 16));
+        }
+    }
+    assert_eq!(map.alias_count(&0), Some(1));
+    map.check_invariants();
+}
+
+/// Churns the map, checking it against a model mapping each key to its group and value, then
+/// compacts it.
+#[test]
+fn compact_after_churn() {
+    let mut map = MultiKeyMap::new();
+    let mut model: HashMap<u32, (u32, u32)> = HashMap::new();
+    let mut state = 0x2545_f491_u32;
+    let mut next = move || {
+        state ^= state << 13;
+        state ^= state >> 17;
+        state ^= state << 5;
+        state
+    };
+    for round in 0..5000 {
+        let (key, other) = (next() % 64, next() % 64);
+        match next() % 4 {
+            0 => {
+                map.insert(key, round);
+                model.insert(key, (round, round));
+            }
+            1 => {
+                if let Some(&entry) = model.get(&other).filter(|_| key != other) {
+                    map.insert_alias(&other, key);
+                    model.insert(key, entry);
+                }
+            }
+            2 => {
+                map.remove_alias(&key);
+                model.remove(&key);
+            }
+            _ => {
+                let entry = model.get(&key).copied();
+                assert_eq!(map.remove(&key), entry.map(|(_, value)| value));
+                model.retain(|_, (group, _)| Some(*group) != entry.map(|(group, _)| group));
+            }
+        }
+        map.check_invariants();
+    }
+
+    let groups: HashSet<u32> = model.values().map(|&(group, _)| group).collect();
+    let id = model.keys().next().and_then(|key| map.group_id(key));
+    map.compact();
+    map.check_invariants();
+    assert_eq!(map.len(), groups.len());
+    assert_eq!(map.key_count(), model.len());
+    for (key, (_, value)) in &model {
+        assert_eq!(map.get(key), Some(value));
+    }
+    if let Some(id) = id {
+        assert_eq!(map.get_by_id(id), None);
+    }
+}
+
+/// A plain model of a map: the group of each key, and the value of each group.
+struct Model {
+    groups: HashMap<u32, u32>,
+    values: HashMap<u32, u32>,
+}
+
+fn check(map: &MultiKeyMap<u32, u32>, model: &Model) {
+    for key in 0..20 {
+        let expected = model.groups.get(&key).map(|group| &model.values[group]);
+        assert_eq!(map.get(&key), expected);
+    }
+    assert_eq!(map.key_count(), model.groups.len());
+    assert_eq!(map.len(), model.values.len());
+    map.check_invariants();
+}
+
+/// Grows a map past its inline sizes and back, checking it against a model at every step.
+#[test]
+fn small_map_transitions() {
+    let mut map = MultiKeyMap::new();
+    let mut model = Model {
+        groups: HashMap::new(),
+        values: HashMap::new(),
+    };
+    let mut capacities = Vec::new();
+    for round in 0..3 {
+        // Grow past the inline sizes, a group of two keys at a time
+        for group in (0..16).step_by(2) {
+            map.insert(group, round);
+            map.insert_alias(&group, group + 1);
+            *map.get_mut(&(group + 1)).unwrap() += group;
+            model.groups.extend([(group, group), (group + 1, group)]);
+            model.values.insert(group, round + group);
+            capacities.push(map.keys_capacity());
+            check(&map, &model);
+        }
+        // Then remove back below them, one key at a time
+        for group in (0..16).step_by(2) {
+            assert_eq!(map.remove_alias(&group), Some(1));
+            model.groups.remove(&group);
+            check(&map, &model);
+            assert_eq!(map.remove(&(group + 1)), Some(round + group));
+            model.groups.remove(&(group + 1));
+            model.values.remove(&group);
+            capacities.push(map.keys_capacity());
+            check(&map, &model);
+        }
+        assert_eq!(map.keys_capacity(), 4);
+        map.shrink_to_fit();
+        assert_eq!(map.values_capacity(), 2);
+    }
+    assert!(capacities.contains(&4) && capacities.iter().any(|&capacity| capacity > 4));
+}